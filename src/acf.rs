@@ -1,9 +1,18 @@
 use anyhow::Result;
 use num::Float;
 
-use std::cmp;
-use std::convert::From;
-use std::ops::{Add, AddAssign, Div};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp;
+use core::convert::From;
+use core::ops::{Add, AddAssign, Div};
+
+use crate::error::{self, ConditioningError, ConditioningKind};
+use crate::util;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 /// Calculate the auto-correlation function of a time series of length n.
 ///
@@ -21,7 +30,7 @@ use std::ops::{Add, AddAssign, Div};
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let ac = acf::acf(&x, Some(2), false).unwrap();
 /// assert!((ac[0] - 1.0).abs() < 1.0e-7);
 /// assert!((ac[1] - 0.25).abs() < 1.0e-7);
@@ -32,6 +41,41 @@ pub fn acf<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
     max_lag: Option<usize>,
     covariance: bool,
 ) -> Result<Vec<T>> {
+    let mut y: Vec<T> = Vec::new();
+    acf_into(x, max_lag, covariance, &mut y)?;
+    Ok(y)
+}
+
+/// Calculate the auto-correlation function of a time series of length n into an
+/// existing buffer. Same as `acf`, but reuses the allocation of `y`, which is useful
+/// when calculating the ACF of many short series in a loop.
+///
+/// # Arguments
+///
+/// * `&x` - Reference to input vector slice of length n.
+/// * `max_lag` - Calculate ACF for this maximum lag. Defaults to n-1.
+/// * `covariance` - If true, returns auto-covariances. If false, returns auto-correlations.
+/// * `&mut y` - Output buffer. Will be resized to length max_lag+1.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let mut buf = Vec::new();
+/// for x in [[1.0_f64, 1.2, 1.4, 1.6], [1.6, 1.4, 1.2, 1.0]].iter() {
+///     acf::acf_into(x, Some(2), false, &mut buf).unwrap();
+///     assert!((buf[1] - 0.25).abs() < 1.0e-7);
+/// }
+/// ```
+pub fn acf_into<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    x: &[T],
+    max_lag: Option<usize>,
+    covariance: bool,
+    y: &mut Vec<T>,
+) -> Result<()> {
+    if x.is_empty() {
+        anyhow::bail!("Time series must not be empty");
+    }
     let max_lag = match max_lag {
         // if upper bound for max_lag is n-1
         Some(max_lag) => cmp::min(max_lag, x.len() - 1),
@@ -46,15 +90,13 @@ pub fn acf<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
     let sum_x: T = x.iter().fold(sum, |sum, &xi| sum + xi);
     let mean_x: T = sum_x / len_x;
 
-    //let mut y: Vec<T> = Vec::with_capacity(max_lag);
-    let mut y: Vec<T> = vec![From::from(0.0); m];
+    y.clear();
+    y.resize(m, From::from(0.0));
+
+    lagged_sums(x, mean_x, y);
 
     for t in 0..m {
-        for i in 0..len_x_usize - t {
-            let xi = x[i] - mean_x;
-            let xi_t = x[i + t] - mean_x;
-            y[t] += (xi * xi_t) / len_x;
-        }
+        y[t] = y[t] / len_x;
         // we need y[0] to calculate the correlations, so we set it to 1.0 at the end
         if !covariance && t > 0 {
             y[t] = y[t] / y[0];
@@ -63,9 +105,378 @@ pub fn acf<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
     if !covariance {
         y[0] = From::from(1.0);
     }
+    Ok(())
+}
+
+/// Calculate the auto-correlation function of a time series of length n, computing
+/// the lags in parallel. Each lag is accumulated exactly as in `acf`, so the results are
+/// bit-identical to `acf` regardless of the number of threads. Only enabled with
+/// feature `rayon`.
+///
+/// # Arguments
+///
+/// * `&x` - Reference to input vector slice of length n.
+/// * `max_lag` - Calculate ACF for this maximum lag. Defaults to n-1.
+/// * `covariance` - If true, returns auto-covariances. If false, returns auto-correlations.
+///
+/// # Returns
+///
+/// * Output vector of length max_lag+1.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// assert_eq!(acf::acf_par(&x, Some(2), false).unwrap(), acf::acf(&x, Some(2), false).unwrap());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn acf_par<T: Float + From<u32> + From<f64> + Copy + AddAssign + Send + Sync>(
+    x: &[T],
+    max_lag: Option<usize>,
+    covariance: bool,
+) -> Result<Vec<T>> {
+    if x.is_empty() {
+        anyhow::bail!("Time series must not be empty");
+    }
+    let max_lag = match max_lag {
+        // if upper bound for max_lag is n-1
+        Some(max_lag) => cmp::min(max_lag, x.len() - 1),
+        None => x.len() - 1,
+    };
+
+    let len_x_usize = x.len();
+    let len_x: T = From::from(len_x_usize as u32);
+    let sum: T = From::from(0.0);
+
+    let sum_x: T = x.iter().fold(sum, |sum, &xi| sum + xi);
+    let mean_x: T = sum_x / len_x;
+
+    let mut y: Vec<T> = (0..max_lag + 1)
+        .into_par_iter()
+        .map(|t| lagged_sum(x, mean_x, t) / len_x)
+        .collect();
+
+    if !covariance {
+        let cov0 = y[0];
+        for v in y.iter_mut().skip(1) {
+            *v = *v / cov0;
+        }
+        y[0] = From::from(1.0);
+    }
     Ok(y)
 }
 
+/// Calculate the auto-correlation function of a time series of length n only at the
+/// given lags. This is much cheaper than `acf` when few, large lags are of interest,
+/// e.g. when screening daily data for yearly seasonality.
+///
+/// # Arguments
+///
+/// * `&x` - Reference to input vector slice of length n.
+/// * `&lags` - Lags to calculate the ACF for, in any order. Each lag must be smaller than n.
+/// * `covariance` - If true, returns auto-covariances. If false, returns auto-correlations.
+///
+/// # Returns
+///
+/// * Output vector with one value per requested lag.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let ac = acf::acf_lags(&x, &[2, 1], false).unwrap();
+/// assert!((ac[0] - (-0.3)).abs() < 1.0e-7);
+/// assert!((ac[1] - 0.25).abs() < 1.0e-7);
+/// ```
+pub fn acf_lags<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    x: &[T],
+    lags: &[usize],
+    covariance: bool,
+) -> Result<Vec<T>> {
+    if let Some(&lag) = lags.iter().find(|&&lag| lag >= x.len()) {
+        anyhow::bail!(
+            "Lag {} is too large for a series of length {}",
+            lag,
+            x.len()
+        );
+    }
+
+    let len_x_usize = x.len();
+    let len_x: T = From::from(len_x_usize as u32);
+    let sum: T = From::from(0.0);
+
+    let sum_x: T = x.iter().fold(sum, |sum, &xi| sum + xi);
+    let mean_x: T = sum_x / len_x;
+
+    let cov = |t: usize| lagged_sum(x, mean_x, t) / len_x;
+
+    if covariance {
+        return Ok(lags.iter().map(|&t| cov(t)).collect());
+    }
+
+    // same as in acf, the correlation at lag zero is set to exactly 1.0
+    let cov0 = cov(0);
+    let y = lags
+        .iter()
+        .map(|&t| {
+            if t == 0 {
+                From::from(1.0)
+            } else {
+                cov(t) / cov0
+            }
+        })
+        .collect();
+    Ok(y)
+}
+
+/// Streaming auto-covariance/auto-correlation calculation. Values are pushed one at a
+/// time, and the ACF of all values seen so far can be queried at any point. Memory use
+/// only depends on `max_lag`, not on the length of the series.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let mut online = acf::OnlineAcf::new(2);
+/// online.extend(x.iter().copied());
+/// let ac = online.acf(false).unwrap();
+/// assert!((ac[1] - 0.25).abs() < 1.0e-7);
+/// assert!((ac[2] - (-0.3)).abs() < 1.0e-7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct OnlineAcf<T> {
+    max_lag: usize,
+    n: usize,
+    // all values are shifted by the first value to reduce cancellation
+    shift: T,
+    sum: T,
+    // the first max_lag (shifted) values
+    head: Vec<T>,
+    // the last max_lag (shifted) values, most recent last
+    tail: VecDeque<T>,
+    // cross[k] = sum_t x_t * x_{t-k}
+    cross: Vec<T>,
+}
+
+impl<T: Float + AddAssign> OnlineAcf<T> {
+    /// Create an accumulator for lags `0..=max_lag`.
+    pub fn new(max_lag: usize) -> Self {
+        OnlineAcf {
+            max_lag,
+            n: 0,
+            shift: T::zero(),
+            sum: T::zero(),
+            head: Vec::with_capacity(max_lag),
+            tail: VecDeque::with_capacity(max_lag + 1),
+            cross: vec![T::zero(); max_lag + 1],
+        }
+    }
+
+    /// Number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// True if no values have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Add the next value of the series.
+    pub fn push(&mut self, x: T) {
+        if self.n == 0 {
+            self.shift = x;
+        }
+        let x = x - self.shift;
+
+        self.cross[0] += x * x;
+        for (k, prev) in self.tail.iter().rev().enumerate() {
+            self.cross[k + 1] += x * *prev;
+        }
+
+        if self.head.len() < self.max_lag {
+            self.head.push(x);
+        }
+        if self.max_lag > 0 {
+            if self.tail.len() == self.max_lag {
+                self.tail.pop_front();
+            }
+            self.tail.push_back(x);
+        }
+        self.sum += x;
+        self.n += 1;
+    }
+
+    /// Calculate the ACF of the values pushed so far. Gives the same result as `acf`
+    /// up to rounding errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `covariance` - If true, returns auto-covariances. If false, returns auto-correlations.
+    ///
+    /// # Returns
+    ///
+    /// * Output vector of length min(max_lag, n-1)+1.
+    pub fn acf(&self, covariance: bool) -> Result<Vec<T>> {
+        if self.n == 0 {
+            anyhow::bail!("No values pushed yet");
+        }
+        let m = cmp::min(self.max_lag, self.n - 1) + 1;
+        let n = T::from(self.n).unwrap();
+        let mean = self.sum / n;
+
+        let mut y: Vec<T> = Vec::with_capacity(m);
+        let mut sum_head = T::zero();
+        let mut sum_tail = T::zero();
+        for k in 0..m {
+            if k > 0 {
+                sum_head += self.head[k - 1];
+                sum_tail += self.tail[self.tail.len() - k];
+            }
+            // sum_{t<n-k} x_t and sum_{t>=k} x_t
+            let a = self.sum - sum_tail;
+            let b = self.sum - sum_head;
+            let n_k = T::from(self.n - k).unwrap();
+            let c = (self.cross[k] - mean * (a + b) + n_k * mean * mean) / n;
+            y.push(c);
+        }
+
+        if !covariance {
+            let cov0 = y[0];
+            for v in y.iter_mut().skip(1) {
+                *v = *v / cov0;
+            }
+            y[0] = T::one();
+        }
+        Ok(y)
+    }
+}
+
+impl<T: Float + AddAssign> Extend<T> for OnlineAcf<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+/// Number of values per data block in the lag product sums. Together with the lag tile,
+/// this keeps the accessed part of the series in the L1/L2 cache for long series.
+const ACF_BLOCK: usize = 2048;
+
+/// Number of lags processed per pass over a data block.
+const ACF_LAG_TILE: usize = 256;
+
+/// Add the sums of lagged products `sum_i (x[i] - mean) * (x[i + t] - mean)` for the lags
+/// `t` in `0..y.len()` to `y`. The loops run over tiles of lags and blocks of data, so each
+/// block is reused for many lags while it is in cache. For every lag, the block sums are
+/// added in ascending order, exactly as in `lagged_sum`.
+fn lagged_sums<T: Float + AddAssign>(x: &[T], mean: T, y: &mut [T]) {
+    let n = x.len();
+    let m = y.len();
+    for t0 in (0..m).step_by(ACF_LAG_TILE) {
+        let t1 = cmp::min(t0 + ACF_LAG_TILE, m);
+        for i0 in (0..n - t0).step_by(ACF_BLOCK) {
+            for t in t0..t1 {
+                let end = cmp::min(i0 + ACF_BLOCK, n - t);
+                if i0 >= end {
+                    // larger lags have even fewer products
+                    break;
+                }
+                y[t] += lagged_dot(&x[i0..end], &x[i0 + t..end + t], mean);
+            }
+        }
+    }
+}
+
+/// Sum of lagged products for a single lag `t`, see `lagged_sums`.
+fn lagged_sum<T: Float + AddAssign>(x: &[T], mean: T, t: usize) -> T {
+    let n = x.len();
+    let mut sum = T::zero();
+    for i0 in (0..n - t).step_by(ACF_BLOCK) {
+        let end = cmp::min(i0 + ACF_BLOCK, n - t);
+        sum += lagged_dot(&x[i0..end], &x[i0 + t..end + t], mean);
+    }
+    sum
+}
+
+/// Sum of the products `(a[i] - mean) * (b[i] - mean)`. The products are accumulated in
+/// four independent lanes so the compiler can keep them in vector registers; the lanes
+/// are combined in a fixed order, so results do not depend on the target.
+fn lagged_dot<T: Float + AddAssign>(a: &[T], b: &[T], mean: T) -> T {
+    let zero = T::zero();
+    let mut acc = [zero; 4];
+
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+
+    for (ca, cb) in a_chunks.zip(b_chunks) {
+        acc[0] += (ca[0] - mean) * (cb[0] - mean);
+        acc[1] += (ca[1] - mean) * (cb[1] - mean);
+        acc[2] += (ca[2] - mean) * (cb[2] - mean);
+        acc[3] += (ca[3] - mean) * (cb[3] - mean);
+    }
+
+    let mut sum = (acc[0] + acc[1]) + (acc[2] + acc[3]);
+    for (&ai, &bi) in a_rem.iter().zip(b_rem) {
+        sum += (ai - mean) * (bi - mean);
+    }
+    sum
+}
+
+/// Reusable buffers for `ar_into` and `ar_dl_rho_cov_into`.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let mut ws = acf::ArWorkspace::new();
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let _var = acf::ar_into(&x, Some(2), &mut ws).unwrap();
+/// assert!((ws.coef()[0] - 0.3466667).abs() < 1.0e-7);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ArWorkspace<T> {
+    rho: Vec<T>,
+    phi: Vec<T>,
+    prev: Vec<T>,
+}
+
+impl<T: Copy> ArWorkspace<T> {
+    /// Create an empty workspace. Buffers grow on first use.
+    pub fn new() -> Self {
+        ArWorkspace {
+            rho: Vec::new(),
+            phi: Vec::new(),
+            prev: Vec::new(),
+        }
+    }
+
+    /// Create a workspace with buffers preallocated for models up to `order`.
+    pub fn with_capacity(order: usize) -> Self {
+        ArWorkspace {
+            rho: Vec::with_capacity(order + 2),
+            phi: Vec::with_capacity(order),
+            prev: Vec::with_capacity(order),
+        }
+    }
+
+    /// AR coefficients of the last call to `ar_into` or `ar_dl_rho_cov_into`.
+    pub fn coef(&self) -> &[T] {
+        &self.phi
+    }
+
+    /// Auto-correlations calculated by the last call to `ar_into`.
+    pub fn rho(&self) -> &[T] {
+        &self.rho
+    }
+}
+
 /// Calculate the auto-regressive coefficients of a time series of length n.
 /// If you already calculated the auto-correlation coefficients (ACF), consider
 /// using `ar_rho` instead.
@@ -83,7 +494,7 @@ pub fn acf<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let (ar, _var) = acf::ar(&x, Some(2)).unwrap();
 /// assert!((ar[0] - 0.3466667).abs() < 1.0e-7);
 /// assert!((ar[1] - -0.3866667).abs() < 1.0e-7);
@@ -98,9 +509,454 @@ pub fn ar<T: Float + From<u32> + From<f64> + Into<f64> + Copy + AddAssign>(
     ar_dl_rho_cov(&rho, cov0, order)
 }
 
+/// Calculate the auto-regressive coefficients of a time series of length n using
+/// preallocated buffers. Same as `ar`, but the coefficients are stored in the workspace
+/// and can be read via `ws.coef()`.
+///
+/// # Arguments
+///
+/// * `&x` - Reference to input vector slice of length n.
+/// * `order` - Order of the AR model.
+/// * `&mut ws` - Workspace that holds the auto-correlations and coefficients.
+///
+/// # Returns
+///
+/// * Estimated variance.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let mut ws = acf::ArWorkspace::with_capacity(2);
+/// acf::ar_into(&x, Some(2), &mut ws).unwrap();
+/// assert!((ws.coef()[0] - 0.3466667).abs() < 1.0e-7);
+/// assert!((ws.coef()[1] - -0.3866667).abs() < 1.0e-7);
+/// ```
+pub fn ar_into<T: Float + From<u32> + From<f64> + Copy + AddAssign>(
+    x: &[T],
+    order: Option<usize>,
+    ws: &mut ArWorkspace<T>,
+) -> Result<T> {
+    let max_lag = order.map(|order| order + 1);
+
+    // calculate the covariances once and derive the correlations from them
+    let mut rho = core::mem::take(&mut ws.rho);
+    acf_into(x, max_lag, true, &mut rho)?;
+    let cov0 = rho[0];
+    for r in rho.iter_mut().skip(1) {
+        *r = *r / cov0;
+    }
+    rho[0] = From::from(1.0);
+
+    let var = ar_dl_rho_cov_into(&rho, cov0, order, ws);
+    ws.rho = rho;
+    var
+}
+
+/// Calculate the auto-regressive coefficients of a time series of length n, given
+/// the auto-correlation coefficients rho. Solves the Toeplitz Yule-Walker system with the
+/// Levinson recursion in O(order^2) operations, without requiring LAPACK. Gives the same
+/// coefficients as `ar_lapack_rho`. Does not estimate the condition number; use
+/// `ar_coef_rho_checked` to detect ill-conditioned systems.
+///
+/// # Arguments
+///
+/// * `&rho` - Reference to auto-correlation coefficients rho.
+/// * `order` - Order of the AR model.
+///
+/// # Returns
+///
+/// * Output vector of length order containing the AR coefficients. Fails with a
+///   `ConditioningError` if the auto-correlation matrix is not positive-definite.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let rho = acf::acf(&x, None, false).unwrap();
+/// let ar = acf::ar_coef_rho(&rho, Some(2)).unwrap();
+/// assert!((ar[0] - 0.3466667).abs() < 1.0e-7);
+/// assert!((ar[1] - -0.3866667).abs() < 1.0e-7);
+/// ```
+pub fn ar_coef_rho<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    rho: &[T],
+    order: Option<usize>,
+) -> Result<Vec<T>> {
+    levinson_rho(rho, order)
+}
+
+/// AR coefficients with the estimated condition number of the Yule-Walker system, see
+/// `ar_coef_rho_checked`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArSolution<T> {
+    /// AR coefficients.
+    pub coef: Vec<T>,
+    /// Estimated 1-norm condition number of the auto-correlation matrix.
+    pub condition: f64,
+    /// Set, with kind `IllConditioned`, if `condition` exceeds
+    /// `error::condition_threshold` for `T`.
+    pub warning: Option<ConditioningError>,
+}
+
+impl<T: Float> ArSolution<T> {
+    fn new(coef: Vec<T>, condition: f64) -> Self {
+        let order = coef.len();
+        let warning = if condition > error::condition_threshold::<T>() {
+            Some(ConditioningError {
+                kind: ConditioningKind::IllConditioned,
+                info: None,
+                condition,
+                order,
+            })
+        } else {
+            None
+        };
+        ArSolution {
+            coef,
+            condition,
+            warning,
+        }
+    }
+}
+
+/// Same as `ar_coef_rho`, but also returns an estimate of the condition number of the
+/// auto-correlation matrix and a warning if it is ill-conditioned. The estimate costs
+/// a few more O(order^2) Levinson solves.
+///
+/// # Arguments
+///
+/// * `&rho` - Reference to auto-correlation coefficients rho.
+/// * `order` - Order of the AR model.
+///
+/// # Returns
+///
+/// * AR coefficients with condition estimate. Fails with a `ConditioningError` if the
+///   auto-correlation matrix is not positive-definite.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// use arima::error::ConditioningKind;
+/// let ok = acf::ar_coef_rho_checked(&[1.0_f64, 0.5, 0.25], Some(2)).unwrap();
+/// assert!(ok.warning.is_none());
+/// // AR(1) auto-correlations close to a unit root
+/// let phi = 1.0_f64 - 1.0e-9;
+/// let rho = [1.0, phi, phi * phi];
+/// let bad = acf::ar_coef_rho_checked(&rho, Some(2)).unwrap();
+/// assert!(bad.condition > 1.0e8);
+/// assert_eq!(bad.warning.unwrap().kind, ConditioningKind::IllConditioned);
+/// ```
+pub fn ar_coef_rho_checked<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    rho: &[T],
+    order: Option<usize>,
+) -> Result<ArSolution<T>> {
+    let coef = levinson_rho(rho, order)?;
+    let condition = toeplitz_condition(&rho[..coef.len()]);
+    Ok(ArSolution::new(coef, condition))
+}
+
+/// Yule-Walker coefficients by the Levinson recursion, failing with the order of the
+/// first leading minor that is not positive-definite.
+fn levinson_rho<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    rho: &[T],
+    order: Option<usize>,
+) -> Result<Vec<T>> {
+    if rho.is_empty() {
+        anyhow::bail!("Need at least one auto-correlation");
+    }
+    let mut ws = ArWorkspace::new();
+    // with cov0 = 1, the variance is the ratio of the prediction error variance to the
+    // variance of the series, which is positive iff the matrix is positive-definite
+    let ratio = ar_dl_rho_cov_into(rho, From::from(1.0), order, &mut ws)?;
+    if ratio.is_nan() || ratio <= From::from(0.0) || ws.phi.iter().any(|p| !p.is_finite()) {
+        // find the first recursion step that fails: after step i, the ratio is
+        // det(R_{i+1}) / det(R_i) for the Toeplitz matrices R_k of rho_0..rho_{k-1}
+        let max_order = match order {
+            Some(order) => cmp::min(order, rho.len() - 1),
+            None => rho.len() - 1,
+        };
+        let mut failed = max_order;
+        for i in 1..max_order + 1 {
+            let ratio = ar_dl_rho_cov_into(rho, From::from(1.0), Some(i), &mut ws)?;
+            if ratio.is_nan() || ratio <= From::from(0.0) || ws.phi.iter().any(|p| !p.is_finite()) {
+                failed = i;
+                break;
+            }
+        }
+        anyhow::bail!(ConditioningError {
+            kind: ConditioningKind::NotPositiveDefinite,
+            info: None,
+            condition: toeplitz_condition(&rho[..failed + 1]),
+            order: failed + 1,
+        });
+    }
+    Ok(ws.phi)
+}
+
+/// Solve `R x = b` for the symmetric Toeplitz matrix `R` with first row `r` by the
+/// Levinson recursion (Golub and Van Loan, Algorithm 4.7.2). Requires all leading
+/// minors of `R` to be nonsingular, but not positive-definite.
+fn toeplitz_solve<T: Float>(r: &[T], b: &[T]) -> Vec<T> {
+    let n = b.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    // scale to a unit diagonal
+    let t: Vec<T> = r[1..n].iter().map(|v| *v / r[0]).collect();
+    let mut x = vec![b[0] / r[0]];
+    let mut y = Vec::with_capacity(n);
+    if n > 1 {
+        y.push(-t[0]);
+    }
+    let mut alpha = y.first().copied().unwrap_or_else(T::zero);
+    let mut beta = T::one();
+    for k in 1..n {
+        beta = (T::one() - alpha * alpha) * beta;
+        let dot = (0..k).fold(T::zero(), |acc, i| acc + t[i] * x[k - 1 - i]);
+        let mu = (b[k] / r[0] - dot) / beta;
+        let v: Vec<T> = (0..k).map(|i| x[i] + mu * y[k - 1 - i]).collect();
+        x = v;
+        x.push(mu);
+        if k < n - 1 {
+            let dot = (0..k).fold(T::zero(), |acc, i| acc + t[i] * y[k - 1 - i]);
+            alpha = -(t[k] + dot) / beta;
+            let z: Vec<T> = (0..k).map(|i| y[i] + alpha * y[k - 1 - i]).collect();
+            y = z;
+            y.push(alpha);
+        }
+    }
+    x
+}
+
+/// Estimated 1-norm condition number of the symmetric Toeplitz matrix with first row
+/// `r`. Infinite if the matrix is singular.
+fn toeplitz_condition<T: Float>(r: &[T]) -> f64 {
+    let n = r.len();
+    if n == 0 {
+        return 1.0;
+    }
+    let norm = (0..n)
+        .map(|j| (0..n).fold(T::zero(), |acc, i| acc + r[i.abs_diff(j)].abs()))
+        .fold(T::zero(), T::max);
+    let solve = |b: &[T]| toeplitz_solve(r, b);
+    let condition = (norm * util::inverse_norm1(n, solve, solve))
+        .to_f64()
+        .unwrap_or(f64::NAN);
+    if condition.is_finite() {
+        condition
+    } else {
+        f64::INFINITY
+    }
+}
+
+#[cfg(feature = "lapack")]
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Floating point types with LAPACK routines in their own precision: `sposv`, `spocon`
+/// and `sgels` for `f32`, `dposv`, `dpocon` and `dgels` for `f64`. The trait is sealed,
+/// and the methods panic if the slices are shorter than the dimensions passed to
+/// LAPACK. Only enabled with feature `lapack`.
+#[cfg(feature = "lapack")]
+pub trait LapackFloat: Float + sealed::Sealed {
+    /// Solve `a * x = b` for a symmetric positive-definite `n` x `n` matrix `a`
+    /// (column-major), overwriting `b` with the solution. Returns LAPACK's `info` code.
+    fn posv(uplo: u8, n: i32, a: &mut [Self], b: &mut [Self]) -> i32;
+
+    /// Estimate the reciprocal 1-norm condition number of a symmetric positive-definite
+    /// matrix from its Cholesky factor `a` as computed by `posv`, given the 1-norm
+    /// `anorm` of the original matrix. Returns the estimate and LAPACK's `info` code.
+    fn pocon(uplo: u8, n: i32, a: &[Self], anorm: Self) -> (Self, i32);
+
+    /// Solve the least squares problem `min |a * x - b|` for an `m` x `n` matrix `a`
+    /// (column-major) of full rank with `m >= n`, overwriting the first `n` values of
+    /// `b` with the solution. Returns LAPACK's `info` code.
+    fn gels(m: i32, n: i32, a: &mut [Self], b: &mut [Self]) -> i32;
+}
+
+#[cfg(feature = "lapack")]
+fn check_dimensions(rows: i32, cols: i32, len: usize) {
+    assert!(rows >= 0 && cols >= 0, "Negative matrix dimension");
+    assert!(
+        len >= rows as usize * cols as usize,
+        "Slice is shorter than the matrix dimensions"
+    );
+}
+
+#[cfg(feature = "lapack")]
+impl LapackFloat for f32 {
+    fn posv(uplo: u8, n: i32, a: &mut [f32], b: &mut [f32]) -> i32 {
+        check_dimensions(n, n, a.len());
+        check_dimensions(n, 1, b.len());
+        let mut info: i32 = 0;
+        unsafe {
+            lapack::sposv(uplo, n, 1, a, n.max(1), b, n.max(1), &mut info);
+        }
+        info
+    }
+
+    fn pocon(uplo: u8, n: i32, a: &[f32], anorm: f32) -> (f32, i32) {
+        check_dimensions(n, n, a.len());
+        let mut rcond = 0.0;
+        let mut work = vec![0.0; 3 * n as usize];
+        let mut iwork = vec![0; n as usize];
+        let mut info: i32 = 0;
+        unsafe {
+            lapack::spocon(
+                uplo,
+                n,
+                a,
+                n.max(1),
+                anorm,
+                &mut rcond,
+                &mut work,
+                &mut iwork,
+                &mut info,
+            );
+        }
+        (rcond, info)
+    }
+
+    fn gels(m: i32, n: i32, a: &mut [f32], b: &mut [f32]) -> i32 {
+        check_dimensions(m, n, a.len());
+        check_dimensions(m, 1, b.len());
+        assert!(m >= n, "Need at least as many rows as columns");
+        let mut info: i32 = 0;
+        // workspace query
+        let mut size = [0.0];
+        unsafe {
+            lapack::sgels(
+                b'N',
+                m,
+                n,
+                1,
+                a,
+                m.max(1),
+                b,
+                m.max(1),
+                &mut size,
+                -1,
+                &mut info,
+            );
+        }
+        if info != 0 {
+            return info;
+        }
+        let lwork = (size[0] as i32).max(1);
+        let mut work = vec![0.0; lwork as usize];
+        unsafe {
+            lapack::sgels(
+                b'N',
+                m,
+                n,
+                1,
+                a,
+                m.max(1),
+                b,
+                m.max(1),
+                &mut work,
+                lwork,
+                &mut info,
+            );
+        }
+        info
+    }
+}
+
+#[cfg(feature = "lapack")]
+impl LapackFloat for f64 {
+    fn posv(uplo: u8, n: i32, a: &mut [f64], b: &mut [f64]) -> i32 {
+        check_dimensions(n, n, a.len());
+        check_dimensions(n, 1, b.len());
+        let mut info: i32 = 0;
+        unsafe {
+            lapack::dposv(uplo, n, 1, a, n.max(1), b, n.max(1), &mut info);
+        }
+        info
+    }
+
+    fn pocon(uplo: u8, n: i32, a: &[f64], anorm: f64) -> (f64, i32) {
+        check_dimensions(n, n, a.len());
+        let mut rcond = 0.0;
+        let mut work = vec![0.0; 3 * n as usize];
+        let mut iwork = vec![0; n as usize];
+        let mut info: i32 = 0;
+        unsafe {
+            lapack::dpocon(
+                uplo,
+                n,
+                a,
+                n.max(1),
+                anorm,
+                &mut rcond,
+                &mut work,
+                &mut iwork,
+                &mut info,
+            );
+        }
+        (rcond, info)
+    }
+
+    fn gels(m: i32, n: i32, a: &mut [f64], b: &mut [f64]) -> i32 {
+        check_dimensions(m, n, a.len());
+        check_dimensions(m, 1, b.len());
+        assert!(m >= n, "Need at least as many rows as columns");
+        let mut info: i32 = 0;
+        // workspace query
+        let mut size = [0.0];
+        unsafe {
+            lapack::dgels(
+                b'N',
+                m,
+                n,
+                1,
+                a,
+                m.max(1),
+                b,
+                m.max(1),
+                &mut size,
+                -1,
+                &mut info,
+            );
+        }
+        if info != 0 {
+            return info;
+        }
+        let lwork = (size[0] as i32).max(1);
+        let mut work = vec![0.0; lwork as usize];
+        unsafe {
+            lapack::dgels(
+                b'N',
+                m,
+                n,
+                1,
+                a,
+                m.max(1),
+                b,
+                m.max(1),
+                &mut work,
+                lwork,
+                &mut info,
+            );
+        }
+        info
+    }
+}
+
 /// Calculate the auto-regressive coefficients of a time series of length n, given
 /// the auto-correlation coefficients rho. Uses LAPACK's DPOSV function to solve the
-/// linear system and requires BLAS (e.g. OpenBLAS). Only enabled with feature `lapack`.
+/// linear system in double precision and requires BLAS (e.g. OpenBLAS). Only enabled
+/// with feature `lapack`. Consider using `ar_coef_rho` instead, which exploits the
+/// Toeplitz structure and needs no LAPACK, or `ar_lapack_rho_checked`, which solves
+/// `f32` systems in single precision with SPOSV. Logs a warning if the system is
+/// ill-conditioned.
 ///
 /// # Arguments
 ///
@@ -109,13 +965,14 @@ pub fn ar<T: Float + From<u32> + From<f64> + Into<f64> + Copy + AddAssign>(
 ///
 /// # Returns
 ///
-/// * Output vector of length order containing the AR coefficients.
+/// * Output vector of length order containing the AR coefficients. Fails with a
+///   `ConditioningError` holding LAPACK's `info` code if the system cannot be solved.
 ///
 /// # Example
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let rho = acf::acf(&x, None, false).unwrap();
 /// let ar = acf::ar_lapack_rho(&rho, Some(2)).unwrap();
 /// assert!((ar[0] - 0.3466667).abs() < 1.0e-7);
@@ -126,6 +983,36 @@ pub fn ar_lapack_rho<T: Float + From<f64> + Into<f64> + Copy>(
     rho: &[T],
     order: Option<usize>,
 ) -> Result<Vec<T>> {
+    let rho: Vec<f64> = rho.iter().map(|v| (*v).into()).collect();
+    let solution = ar_lapack_rho_checked(&rho, order)?;
+    if let Some(warning) = solution.warning {
+        tracing::warn!("{}", warning);
+    }
+    Ok(solution.coef.into_iter().map(From::from).collect())
+}
+
+/// Same as `ar_lapack_rho`, but solves the system in the precision of the input, with
+/// SPOSV for `f32` and DPOSV for `f64`, and also returns LAPACK's estimate (SPOCON or
+/// DPOCON) of the condition number of the auto-correlation matrix and a warning if it
+/// is ill-conditioned, see `ar_coef_rho_checked`. Only enabled with feature `lapack`.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let ok = acf::ar_lapack_rho_checked(&[1.0_f64, 0.5, 0.25], Some(2)).unwrap();
+/// assert!(ok.condition < 4.0);
+/// assert!(ok.warning.is_none());
+///
+/// // single precision input is solved with SPOSV
+/// let ar32 = acf::ar_lapack_rho_checked(&[1.0_f32, 0.25, -0.3], Some(2)).unwrap();
+/// assert!((ar32.coef[1] - -0.3866667).abs() < 1.0e-5);
+/// ```
+#[cfg(feature = "lapack")]
+pub fn ar_lapack_rho_checked<T: LapackFloat + Copy>(
+    rho: &[T],
+    order: Option<usize>,
+) -> Result<ArSolution<T>> {
     // phi_0 will be calculated separately
     let n = match order {
         Some(order) => cmp::min(order, rho.len() - 1),
@@ -135,39 +1022,133 @@ pub fn ar_lapack_rho<T: Float + From<f64> + Into<f64> + Copy>(
     // we try to solve mr * x = r for x
 
     // build lower triangle matrix
-    let mut mr: Vec<f64> = vec![1.0; n * n];
+    let mut mr: Vec<T> = vec![T::one(); n * n];
 
     for i in 0..n {
         for j in i + 1..n {
-            mr[i * n + j] = std::convert::Into::into(rho[j - i]);
+            mr[i * n + j] = rho[j - i];
         }
     }
 
+    // 1-norm of the symmetric Toeplitz matrix: its largest absolute column sum
+    let anorm = (0..n)
+        .map(|j| {
+            (0..n).fold(T::zero(), |acc, i| {
+                acc + if i == j {
+                    T::one()
+                } else {
+                    rho[i.abs_diff(j)].abs()
+                }
+            })
+        })
+        .fold(T::zero(), T::max);
+
     // build right hand vector rho_1..rho_n
-    let mut b: Vec<f64> = vec![0.0; n];
-    for i in 0..n {
-        b[i] = std::convert::Into::into(rho[i + 1]);
+    let mut b: Vec<T> = rho[1..n + 1].to_vec();
+
+    // run lapack routine to solve symmetric positive-definite matrix system
+    let info = T::posv(b'L', n as i32, &mut mr, &mut b);
+
+    if info > 0 {
+        // the leading minor of order info is not positive-definite
+        let mut minor = rho[..info as usize].to_vec();
+        minor[0] = T::one();
+        anyhow::bail!(ConditioningError {
+            kind: ConditioningKind::NotPositiveDefinite,
+            info: Some(info),
+            condition: toeplitz_condition(&minor),
+            order: info as usize,
+        });
+    }
+    if info < 0 {
+        anyhow::bail!(ConditioningError {
+            kind: ConditioningKind::InvalidArgument,
+            info: Some(info),
+            condition: f64::NAN,
+            order: n,
+        });
     }
 
-    // build arguments to pass
-    let mut info: i32 = 0;
-    let ni = n as i32;
+    let condition = if n == 0 {
+        1.0
+    } else {
+        let (rcond, info) = T::pocon(b'L', n as i32, &mr, anorm);
+        if info != 0 {
+            anyhow::bail!(ConditioningError {
+                kind: ConditioningKind::InvalidArgument,
+                info: Some(info),
+                condition: f64::NAN,
+                order: n,
+            });
+        }
+        1.0 / rcond.to_f64().unwrap()
+    };
+    Ok(ArSolution::new(b, condition))
+}
 
-    // run lapack routine to solve symmetric positive-definite matrix system
-    unsafe {
-        lapack::dposv(b'L', ni, 1, &mut mr, ni, &mut b, ni, &mut info);
+/// Fit the auto-regressive coefficients of a time series by least squares, regressing
+/// each demeaned value on its `order` predecessors. Uses LAPACK's SGELS (for `f32`) or
+/// DGELS (for `f64`) function to solve the problem in the precision of the input and
+/// requires BLAS (e.g. OpenBLAS). Only enabled with feature `lapack`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `order` - Order of the AR model.
+///
+/// # Returns
+///
+/// * Output vector of length order containing the AR coefficients. Fails with a
+///   `ConditioningError` holding LAPACK's `info` code if the lagged values are
+///   collinear.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// // x_t = 0.5 x_{t-1} exactly
+/// let x = [8.0_f32, 4.0, 2.0, 1.0, 0.5, 0.25];
+/// let mean = x.iter().sum::<f32>() / 6.0;
+/// let ar = acf::ar_lapack_ls(&x, 1).unwrap();
+/// let expected = (0..5).map(|t| (x[t + 1] - mean) * (x[t] - mean)).sum::<f32>()
+///     / (0..5).map(|t| (x[t] - mean) * (x[t] - mean)).sum::<f32>();
+/// assert!((ar[0] - expected).abs() < 1.0e-5);
+/// ```
+#[cfg(feature = "lapack")]
+pub fn ar_lapack_ls<T: LapackFloat + Copy>(x: &[T], order: usize) -> Result<Vec<T>> {
+    if x.len() < 2 * order + 1 {
+        anyhow::bail!(
+            "Need at least {} values for order {}, got {}",
+            2 * order + 1,
+            order,
+            x.len()
+        );
     }
+    let mean = x.iter().fold(T::zero(), |acc, v| acc + *v) / T::from(x.len()).unwrap();
 
-    if info != 0 {
-        anyhow::bail!("Matrix is not positive-definite");
+    // column-major design matrix of lagged values
+    let m = x.len() - order;
+    let mut a: Vec<T> = Vec::with_capacity(m * order);
+    for j in 0..order {
+        a.extend(x[order - j - 1..x.len() - j - 1].iter().map(|v| *v - mean));
     }
+    let mut b: Vec<T> = x[order..].iter().map(|v| *v - mean).collect();
 
-    // convert back to T
-    let mut phi: Vec<T> = vec![From::from(0.0); n];
-    for i in 0..n {
-        phi[i] = std::convert::Into::into(b[i]);
+    let info = T::gels(m as i32, order as i32, &mut a, &mut b);
+    if info != 0 {
+        anyhow::bail!(ConditioningError {
+            kind: if info > 0 {
+                ConditioningKind::Singular
+            } else {
+                ConditioningKind::InvalidArgument
+            },
+            info: Some(info),
+            condition: if info > 0 { f64::INFINITY } else { f64::NAN },
+            order,
+        });
     }
-    Ok(phi)
+    b.truncate(order);
+    Ok(b)
 }
 
 /// Calculate the auto-regressive coefficients of a time series of length n, given
@@ -190,7 +1171,7 @@ pub fn ar_lapack_rho<T: Float + From<f64> + Into<f64> + Copy>(
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let rho = acf::acf(&x, None, false).unwrap();
 /// let cov0 = acf::acf(&x, Some(0), true).unwrap()[0];
 /// let (ar, err) = acf::ar_dl_rho_cov(&rho, cov0, Some(2)).unwrap();
@@ -202,6 +1183,43 @@ pub fn ar_dl_rho_cov<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign +
     cov0: T,
     order: Option<usize>,
 ) -> Result<(Vec<T>, T)> {
+    let mut ws = ArWorkspace::new();
+    let var = ar_dl_rho_cov_into(rho, cov0, order, &mut ws)?;
+    Ok((ws.phi, var))
+}
+
+/// Calculate the auto-regressive coefficients via Durbin-Levinson using preallocated
+/// buffers. Same as `ar_dl_rho_cov`, but the coefficients are stored in the workspace
+/// and can be read via `ws.coef()`.
+///
+/// # Arguments
+///
+/// * `&rho` - Reference to auto-correlation coefficients rho.
+/// * `cov0` - Autocovariance at lag 0.
+/// * `order` - Order of the AR model.
+/// * `&mut ws` - Workspace that holds the coefficients.
+///
+/// # Returns
+///
+/// * Estimated variance.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let rho = acf::acf(&x, None, false).unwrap();
+/// let cov0 = acf::acf(&x, Some(0), true).unwrap()[0];
+/// let mut ws = acf::ArWorkspace::new();
+/// acf::ar_dl_rho_cov_into(&rho, cov0, Some(2), &mut ws).unwrap();
+/// assert!((ws.coef()[1] - -0.3866667).abs() < 1.0e-7);
+/// ```
+pub fn ar_dl_rho_cov_into<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div>(
+    rho: &[T],
+    cov0: T,
+    order: Option<usize>,
+    ws: &mut ArWorkspace<T>,
+) -> Result<T> {
     let order = match order {
         Some(order) => cmp::min(order, rho.len() - 1),
         None => rho.len() - 1,
@@ -211,21 +1229,20 @@ pub fn ar_dl_rho_cov<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign +
     let zero = From::from(0.0);
     let one = From::from(1.0);
 
-    // these vectors will hold the parameter values
-    let mut phi: Vec<Vec<T>> = vec![Vec::new(); order + 1];
-    let mut var: Vec<T> = Vec::new();
+    // phi holds the coefficients of the current order, prev those of the previous order
+    let phi = &mut ws.phi;
+    let prev = &mut ws.prev;
+    phi.clear();
+    prev.clear();
 
     // initialize zero-order estimates
-    phi[0].push(zero);
-    var.push(cov0);
+    let mut var = cov0;
 
     for i in 1..order + 1 {
-        // first allocate values for the phi vector so we can use phi[i][i-1]
-        for _ in 0..i {
-            phi[i].push(zero);
-        }
+        prev.clone_from(phi);
+        phi.push(zero);
 
-        // estimate phi_ii, which is stored as phi[i][i-1]
+        // estimate phi_ii, which is stored as phi[i-1]
         // phi_i,i = rho(i) - sum_{k=1}^{n-1}(phi_{n-1,k} * rho(n-k) /
         //  (1 - sum_{k=1}^{n-1}(phi_{n-1,k} * rho(k))
 
@@ -233,22 +1250,241 @@ pub fn ar_dl_rho_cov<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign +
         let mut den_sum = one; // denominator sum
 
         for k in 1..i {
-            let p = phi[i - 1][k - 1];
+            let p = prev[k - 1];
             num_sum += p * rho[i - k];
             den_sum += -p * rho[k];
         }
 
         let phi_ii = (rho[i] - num_sum) / den_sum;
-        phi[i][i - 1] = phi_ii;
+        phi[i - 1] = phi_ii;
 
-        var.push(var[i - 1] * (one - phi_ii * phi_ii));
+        var = var * (one - phi_ii * phi_ii);
 
         for k in 1..i {
-            phi[i][k - 1] = phi[i - 1][k - 1] - phi[i][i - 1] * phi[i - 1][i - k - 1];
+            phi[k - 1] = prev[k - 1] - phi_ii * prev[i - k - 1];
+        }
+    }
+
+    Ok(var)
+}
+
+/// Reusable buffers for `ar_batch` and `ar_dl_rho_cov_batch`.
+#[derive(Debug, Clone, Default)]
+pub struct ArBatchWorkspace<T> {
+    order: usize,
+    // auto-correlations, lag-major: rho[lag * n_series + s]
+    rho: Vec<T>,
+    acf: Vec<T>,
+    // coefficients of the current and previous recursion step, coefficient-major
+    phi: Vec<T>,
+    prev: Vec<T>,
+    num: Vec<T>,
+    den: Vec<T>,
+    var: Vec<T>,
+    // final coefficients, series-major
+    coef: Vec<T>,
+}
+
+impl<T: Float> ArBatchWorkspace<T> {
+    /// Create an empty workspace. Buffers grow on first use.
+    pub fn new() -> Self {
+        ArBatchWorkspace {
+            order: 0,
+            rho: Vec::new(),
+            acf: Vec::new(),
+            phi: Vec::new(),
+            prev: Vec::new(),
+            num: Vec::new(),
+            den: Vec::new(),
+            var: Vec::new(),
+            coef: Vec::new(),
+        }
+    }
+
+    /// Number of series of the last batch.
+    pub fn len(&self) -> usize {
+        self.var.len()
+    }
+
+    /// True if the last batch was empty.
+    pub fn is_empty(&self) -> bool {
+        self.var.is_empty()
+    }
+
+    /// AR coefficients of series `s` of the last batch.
+    pub fn coef(&self, s: usize) -> &[T] {
+        &self.coef[s * self.order..(s + 1) * self.order]
+    }
+
+    /// Estimated variances of all series of the last batch.
+    pub fn var(&self) -> &[T] {
+        &self.var
+    }
+
+    /// Run the Durbin-Levinson recursion for all systems stored in `self.rho` at once.
+    /// The inner loops run over the series, so every series sees exactly the same
+    /// sequence of operations as in `ar_dl_rho_cov_into`.
+    fn levinson(&mut self, order: usize, n: usize) {
+        let zero = T::zero();
+        let one = T::one();
+
+        self.order = order;
+        self.phi.clear();
+        self.phi.resize(order * n, zero);
+        self.prev.clear();
+        self.prev.resize(order * n, zero);
+        self.num.clear();
+        self.num.resize(n, zero);
+        self.den.clear();
+        self.den.resize(n, zero);
+
+        let rho = &self.rho;
+        let phi = &mut self.phi;
+        let prev = &mut self.prev;
+        let num = &mut self.num;
+        let den = &mut self.den;
+        let var = &mut self.var;
+
+        for i in 1..order + 1 {
+            prev[..(i - 1) * n].copy_from_slice(&phi[..(i - 1) * n]);
+            num.fill(zero);
+            den.fill(one);
+
+            for k in 1..i {
+                for s in 0..n {
+                    let p = prev[(k - 1) * n + s];
+                    num[s] = num[s] + p * rho[(i - k) * n + s];
+                    den[s] = den[s] + -p * rho[k * n + s];
+                }
+            }
+
+            for s in 0..n {
+                let phi_ii = (rho[i * n + s] - num[s]) / den[s];
+                phi[(i - 1) * n + s] = phi_ii;
+                var[s] = var[s] * (one - phi_ii * phi_ii);
+            }
+
+            for k in 1..i {
+                for s in 0..n {
+                    let phi_ii = phi[(i - 1) * n + s];
+                    phi[(k - 1) * n + s] =
+                        prev[(k - 1) * n + s] - phi_ii * prev[(i - k - 1) * n + s];
+                }
+            }
+        }
+
+        // transpose to series-major output
+        self.coef.clear();
+        for s in 0..n {
+            for k in 0..order {
+                self.coef.push(phi[k * n + s]);
+            }
+        }
+    }
+}
+
+/// Calculate the auto-regressive coefficients of many series at once via Durbin-Levinson.
+/// The recursions of all series are interleaved, which avoids per-series overhead when
+/// fitting thousands of short windows. Results are identical to calling `ar_dl_rho_cov`
+/// for every series.
+///
+/// # Arguments
+///
+/// * `&rho` - Auto-correlations of all series, `order + 1` values per series stored one
+///   series after the other.
+/// * `&cov0` - Auto-covariance at lag 0 of every series.
+/// * `order` - Order of the AR models.
+/// * `&mut ws` - Workspace that holds the results.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let rho = [1.0_f64, 0.25, -0.3, 1.0, 0.5, 0.1];
+/// let mut ws = acf::ArBatchWorkspace::new();
+/// acf::ar_dl_rho_cov_batch(&rho, &[1.0, 2.0], 2, &mut ws).unwrap();
+/// let (phi, var) = acf::ar_dl_rho_cov(&rho[3..], 2.0, Some(2)).unwrap();
+/// assert_eq!(ws.coef(1), &phi[..]);
+/// assert_eq!(ws.var()[1], var);
+/// ```
+pub fn ar_dl_rho_cov_batch<T: Float>(
+    rho: &[T],
+    cov0: &[T],
+    order: usize,
+    ws: &mut ArBatchWorkspace<T>,
+) -> Result<()> {
+    let n = cov0.len();
+    if rho.len() != n * (order + 1) {
+        anyhow::bail!(
+            "Expected {} auto-correlations, got {}",
+            n * (order + 1),
+            rho.len()
+        );
+    }
+
+    ws.rho.clear();
+    ws.rho.resize(n * (order + 1), T::zero());
+    for (s, r) in rho.chunks(order + 1).enumerate() {
+        for (lag, &v) in r.iter().enumerate() {
+            ws.rho[lag * n + s] = v;
+        }
+    }
+    ws.var.clear();
+    ws.var.extend_from_slice(cov0);
+
+    ws.levinson(order, n);
+    Ok(())
+}
+
+/// Calculate the auto-regressive coefficients of many series at once. Same as calling
+/// `ar` for every series, but reuses one workspace and interleaves the Durbin-Levinson
+/// recursions, see `ar_dl_rho_cov_batch`.
+///
+/// # Arguments
+///
+/// * `&series` - Time series, e.g. rolling windows of a longer series. Every series must
+///   be longer than `order`.
+/// * `order` - Order of the AR models.
+/// * `&mut ws` - Workspace that holds the results.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6, 1.4, 1.3, 1.5];
+/// let windows: Vec<&[f64]> = x.windows(4).collect();
+/// let mut ws = acf::ArBatchWorkspace::new();
+/// acf::ar_batch(&windows, 2, &mut ws).unwrap();
+/// assert!((ws.coef(0)[0] - 0.3466667).abs() < 1.0e-7);
+/// ```
+pub fn ar_batch<T: Float + From<u32> + From<f64> + AddAssign, S: AsRef<[T]>>(
+    series: &[S],
+    order: usize,
+    ws: &mut ArBatchWorkspace<T>,
+) -> Result<()> {
+    let n = series.len();
+    ws.rho.clear();
+    ws.rho.resize(n * (order + 1), T::zero());
+    ws.var.clear();
+
+    let mut acf_buf = core::mem::take(&mut ws.acf);
+    for (s, x) in series.iter().enumerate() {
+        let x = x.as_ref();
+        if x.len() <= order {
+            anyhow::bail!("Series {} is too short for order {}", s, order);
+        }
+        // same as in `ar`, the ACF is calculated up to order + 1
+        acf_into(x, Some(order + 1), false, &mut acf_buf)?;
+        for (lag, &r) in acf_buf.iter().enumerate().take(order + 1) {
+            ws.rho[lag * n + s] = r;
         }
+        acf_into(x, Some(0), true, &mut acf_buf)?;
+        ws.var.push(acf_buf[0]);
     }
+    ws.acf = acf_buf;
 
-    Ok((phi[order].clone(), var[order]))
+    ws.levinson(order, n);
+    Ok(())
 }
 
 /// Estimate the variance of a time series of length n via Durbin-Levinson.
@@ -269,7 +1505,7 @@ pub fn ar_dl_rho_cov<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign +
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// acf::var(&x, Some(2));
 /// ```
 pub fn var<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign + Div>(
@@ -300,7 +1536,7 @@ pub fn var<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let rho = acf::acf(&x, Some(3), false).unwrap();
 /// let cov0 = acf::acf(&x, Some(0), true).unwrap()[0].clone();
 /// let (phi, _var) = acf::ar_dl_rho_cov(&rho, cov0, Some(2)).unwrap();
@@ -338,7 +1574,7 @@ pub fn var_phi_rho_cov<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let pr = acf::pacf(&x, Some(2)).unwrap();
 /// assert!((pr[0] - 0.25).abs() < 1.0e-7);
 /// assert!((pr[1] - -0.3866667).abs() < 1.0e-7);
@@ -369,7 +1605,7 @@ pub fn pacf<T: Float + From<u32> + From<f64> + Into<f64> + Copy + AddAssign>(
 ///
 /// ```
 /// use arima::acf;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let rho = acf::acf(&x, None, false).unwrap();
 /// let cov0 = acf::acf(&x, Some(0), true).unwrap()[0];
 /// let pr = acf::pacf_rho_cov0(&rho, cov0, Some(2)).unwrap();
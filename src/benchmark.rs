@@ -0,0 +1,439 @@
+//! Simple benchmark forecasters and a common `Forecaster` interface.
+//!
+//! The naive, seasonal naive, drift and mean methods (see Hyndman and Athanasopoulos,
+//! Forecasting: Principles and Practice, chapter 5) are the baselines a fitted model
+//! should beat before it is used. Their prediction intervals assume normal forecast
+//! errors with the variances of the methods' underlying random walk or white noise
+//! models.
+
+use anyhow::Result;
+
+use crate::ets::EtsModel;
+use crate::metrics::{self, Accuracy};
+use crate::model::FittedModel;
+use crate::smoothing::SmoothingModel;
+use crate::special;
+use crate::structural::StructuralModel;
+use crate::tbats::TbatsModel;
+use crate::theta::ThetaModel;
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+
+/// Point forecasts with forecast error variances and normal prediction intervals.
+pub trait Forecaster {
+    /// Point forecasts for the next `h` time steps.
+    fn forecast(&self, h: usize) -> Vec<f64>;
+
+    /// Variances of the forecast errors for the next `h` time steps.
+    fn forecast_variance(&self, h: usize) -> Vec<f64>;
+
+    /// Normal prediction intervals for the next `h` time steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `level` - Coverage probability of the intervals, e.g. 0.95.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of (lower, upper) bounds for each of the next `h` time steps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::benchmark::{Forecaster, Naive};
+    /// let f = Naive::fit(&[1.0, 2.0, 1.0, 2.0, 1.0]).unwrap();
+    /// let (lower, upper) = f.forecast_intervals(2, 0.95).unwrap();
+    /// // the variance of a random walk forecast grows linearly
+    /// let width = |i: usize| upper[i] - lower[i];
+    /// assert!((width(1) / width(0) - 2.0_f64.sqrt()).abs() < 1.0e-12);
+    /// ```
+    fn forecast_intervals(&self, h: usize, level: f64) -> Result<(Vec<f64>, Vec<f64>)> {
+        if !(level > 0.0 && level < 1.0) {
+            anyhow::bail!("level must be within (0, 1)");
+        }
+        let z = special::normal_quantile(0.5 + level / 2.0);
+        Ok(self
+            .forecast(h)
+            .iter()
+            .zip(self.forecast_variance(h))
+            .map(|(f, v)| (f - z * v.sqrt(), f + z * v.sqrt()))
+            .unzip())
+    }
+}
+
+impl Forecaster for FittedModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        FittedModel::forecast(self, h)
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        FittedModel::forecast_variance(self, h)
+    }
+}
+
+impl Forecaster for SmoothingModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        SmoothingModel::forecast(self, h)
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        SmoothingModel::forecast_variance(self, h)
+    }
+}
+
+impl Forecaster for EtsModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        EtsModel::forecast(self, h)
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        EtsModel::forecast_variance(self, h)
+    }
+}
+
+impl Forecaster for ThetaModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        ThetaModel::forecast(self, h)
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        ThetaModel::forecast_variance(self, h)
+    }
+}
+
+impl Forecaster for TbatsModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        TbatsModel::forecast(self, h)
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        TbatsModel::forecast_variance(self, h)
+    }
+
+    /// Intervals from the transformed scale, see `TbatsModel::forecast_intervals`.
+    fn forecast_intervals(&self, h: usize, level: f64) -> Result<(Vec<f64>, Vec<f64>)> {
+        TbatsModel::forecast_intervals(self, h, level)
+    }
+}
+
+impl Forecaster for StructuralModel {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        StructuralModel::forecast(self, h).0
+    }
+
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        StructuralModel::forecast(self, h).1
+    }
+}
+
+/// Naive method: all forecasts equal the last observation, the forecasts of a random
+/// walk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Naive {
+    /// Last observation.
+    pub last: f64,
+    /// Variance of the one-step-ahead residuals `x_t - x_{t-1}`.
+    pub sigma2: f64,
+}
+
+impl Naive {
+    /// Fit the naive method to a series with at least two values.
+    pub fn fit(x: &[f64]) -> Result<Self> {
+        check(x, 2)?;
+        let residuals: Vec<f64> = x.windows(2).map(|w| w[1] - w[0]).collect();
+        Ok(Naive {
+            last: x[x.len() - 1],
+            sigma2: mean_square(&residuals, 0),
+        })
+    }
+}
+
+impl Forecaster for Naive {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        vec![self.last; h]
+    }
+
+    /// `h sigma2`.
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        (1..h + 1).map(|i| i as f64 * self.sigma2).collect()
+    }
+}
+
+/// Seasonal naive method: each forecast equals the last observation of the same
+/// season.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeasonalNaive {
+    /// Last `period` observations.
+    pub last_season: Vec<f64>,
+    /// Variance of the one-step-ahead residuals `x_t - x_{t-period}`.
+    pub sigma2: f64,
+}
+
+impl SeasonalNaive {
+    /// Fit the seasonal naive method to a series with more than one period of values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::benchmark::{Forecaster, SeasonalNaive};
+    /// let x = [1.0, 5.0, 3.0, 1.2, 5.1, 2.9, 0.9, 5.2];
+    /// let f = SeasonalNaive::fit(&x, 3).unwrap();
+    /// assert_eq!(f.forecast(4), vec![2.9, 0.9, 5.2, 2.9]);
+    /// ```
+    pub fn fit(x: &[f64], period: usize) -> Result<Self> {
+        if period == 0 {
+            anyhow::bail!("Seasonal period must be positive");
+        }
+        check(x, period + 1)?;
+        let residuals: Vec<f64> = (period..x.len()).map(|t| x[t] - x[t - period]).collect();
+        Ok(SeasonalNaive {
+            last_season: x[x.len() - period..].to_vec(),
+            sigma2: mean_square(&residuals, 0),
+        })
+    }
+}
+
+impl Forecaster for SeasonalNaive {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        self.last_season.iter().copied().cycle().take(h).collect()
+    }
+
+    /// `k sigma2` for the `k`-th period ahead.
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let m = self.last_season.len();
+        (0..h).map(|i| (i / m + 1) as f64 * self.sigma2).collect()
+    }
+}
+
+/// Drift method: the line through the first and the last observation, the forecasts of
+/// a random walk with drift.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drift {
+    /// Last observation.
+    pub last: f64,
+    /// Average change per time step.
+    pub slope: f64,
+    /// Variance of the one-step-ahead residuals `x_t - x_{t-1} - slope`.
+    pub sigma2: f64,
+    /// Length of the series.
+    pub n: usize,
+}
+
+impl Drift {
+    /// Fit the drift method to a series with at least three values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::benchmark::{Drift, Forecaster};
+    /// let f = Drift::fit(&[1.0, 2.5, 2.0, 4.0]).unwrap();
+    /// assert_eq!(f.forecast(2), vec![5.0, 6.0]);
+    /// ```
+    pub fn fit(x: &[f64]) -> Result<Self> {
+        check(x, 3)?;
+        let n = x.len();
+        let slope = (x[n - 1] - x[0]) / (n - 1) as f64;
+        let residuals: Vec<f64> = x.windows(2).map(|w| w[1] - w[0] - slope).collect();
+        Ok(Drift {
+            last: x[n - 1],
+            slope,
+            sigma2: mean_square(&residuals, 1),
+            n,
+        })
+    }
+}
+
+impl Forecaster for Drift {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        (1..h + 1)
+            .map(|i| self.last + i as f64 * self.slope)
+            .collect()
+    }
+
+    /// `h (1 + h / n) sigma2`, including the uncertainty of the slope.
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        (1..h + 1)
+            .map(|i| {
+                let i = i as f64;
+                i * (1.0 + i / self.n as f64) * self.sigma2
+            })
+            .collect()
+    }
+}
+
+/// Mean method: all forecasts equal the mean of the series.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mean {
+    /// Mean of the series.
+    pub mean: f64,
+    /// Sample variance of the series.
+    pub sigma2: f64,
+    /// Length of the series.
+    pub n: usize,
+}
+
+impl Mean {
+    /// Fit the mean method to a series with at least two values.
+    pub fn fit(x: &[f64]) -> Result<Self> {
+        check(x, 2)?;
+        let mean = x.iter().sum::<f64>() / x.len() as f64;
+        let residuals: Vec<f64> = x.iter().map(|v| v - mean).collect();
+        Ok(Mean {
+            mean,
+            sigma2: mean_square(&residuals, 1),
+            n: x.len(),
+        })
+    }
+}
+
+impl Forecaster for Mean {
+    fn forecast(&self, h: usize) -> Vec<f64> {
+        vec![self.mean; h]
+    }
+
+    /// `(1 + 1 / n) sigma2`, including the uncertainty of the mean.
+    fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        vec![(1.0 + 1.0 / self.n as f64) * self.sigma2; h]
+    }
+}
+
+/// Benchmark method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Baseline {
+    /// See `Naive`.
+    Naive,
+    /// See `SeasonalNaive`.
+    SeasonalNaive,
+    /// See `Drift`.
+    Drift,
+    /// See `Mean`.
+    Mean,
+}
+
+impl Baseline {
+    /// Fit the benchmark method to a series.
+    ///
+    /// # Arguments
+    ///
+    /// * `&x` - Vector of the timeseries.
+    /// * `period` - Seasonal period, required for `Baseline::SeasonalNaive`.
+    pub fn fit(&self, x: &[f64], period: Option<usize>) -> Result<Box<dyn Forecaster>> {
+        Ok(match self {
+            Baseline::Naive => Box::new(Naive::fit(x)?),
+            Baseline::SeasonalNaive => match period {
+                Some(m) => Box::new(SeasonalNaive::fit(x, m)?),
+                None => anyhow::bail!("The seasonal naive method needs a seasonal period"),
+            },
+            Baseline::Drift => Box::new(Drift::fit(x)?),
+            Baseline::Mean => Box::new(Mean::fit(x)?),
+        })
+    }
+}
+
+/// Accuracy of a model and the benchmark methods on held-out values, see `benchmark`.
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    /// Accuracy of the model.
+    pub model: Accuracy,
+    /// Accuracy of each benchmark method.
+    pub baselines: Vec<(Baseline, Accuracy)>,
+}
+
+impl Benchmark {
+    /// Benchmark methods with a root mean squared error at most that of the model.
+    pub fn not_beaten(&self) -> Vec<Baseline> {
+        self.baselines
+            .iter()
+            .filter(|(_, a)| a.rmse <= self.model.rmse)
+            .map(|(b, _)| *b)
+            .collect()
+    }
+
+    /// True if the model has a lower root mean squared error than every benchmark
+    /// method.
+    pub fn beats_all(&self) -> bool {
+        self.not_beaten().is_empty()
+    }
+}
+
+/// Compare the forecasts of a model with those of the benchmark methods fitted to the
+/// same series, on held-out values that directly follow the series.
+///
+/// # Arguments
+///
+/// * `&model` - Model fitted to `x`.
+/// * `&x` - Vector of the timeseries the model was fitted to.
+/// * `&test` - Held-out values, forecast from the end of `x`.
+/// * `period` - Seasonal period; with a period, the seasonal naive method is included.
+///
+/// # Returns
+///
+/// * Accuracy of the model and of the naive, seasonal naive, drift and mean methods.
+///
+/// # Example
+///
+/// ```
+/// use arima::{benchmark, model};
+/// let x: Vec<f64> = (0..60).map(|t| 10.0 + 0.5 * t as f64 + (t as f64 * 1.7).sin()).collect();
+/// let m = model::fit(&x[..50], 1, 1, 0).unwrap();
+/// let b = benchmark::benchmark(&m, &x[..50], &x[50..], None).unwrap();
+/// assert_eq!(b.baselines.len(), 3);
+/// assert!(!b.not_beaten().contains(&benchmark::Baseline::Mean));
+/// ```
+pub fn benchmark(
+    model: &dyn Forecaster,
+    x: &[f64],
+    test: &[f64],
+    period: Option<usize>,
+) -> Result<Benchmark> {
+    let h = test.len();
+    let mut methods = vec![Baseline::Naive];
+    if period.is_some() {
+        methods.push(Baseline::SeasonalNaive);
+    }
+    methods.push(Baseline::Drift);
+    methods.push(Baseline::Mean);
+    let baselines = methods
+        .into_iter()
+        .map(|b| Ok((b, metrics::accuracy(test, &b.fit(x, period)?.forecast(h))?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Benchmark {
+        model: metrics::accuracy(test, &model.forecast(h))?,
+        baselines,
+    })
+}
+
+/// Same as `benchmark`, with the seasonal period implied by the sampling frequency of the
+/// series, see `TimeSeries::seasonal_period`. The seasonal naive method is included if
+/// the frequency has a seasonal period and the series covers more than one period.
+/// Fails if the series has gaps. Only enabled with feature `chrono`.
+#[cfg(feature = "chrono")]
+pub fn benchmark_series(
+    model: &dyn Forecaster,
+    x: &TimeSeries<f64>,
+    test: &[f64],
+) -> Result<Benchmark> {
+    let period = x.seasonal_period();
+    let period = if period >= 2 && x.len() > period {
+        Some(period)
+    } else {
+        None
+    };
+    benchmark(model, x.regular_values()?, test, period)
+}
+
+fn check(x: &[f64], min: usize) -> Result<()> {
+    if x.len() < min {
+        anyhow::bail!("Need at least {} values", min);
+    }
+    if x.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("Series must not contain missing or infinite values");
+    }
+    Ok(())
+}
+
+/// Sum of squares divided by the number of values less `df`.
+fn mean_square(residuals: &[f64], df: usize) -> f64 {
+    residuals.iter().map(|e| e * e).sum::<f64>() / (residuals.len() - df) as f64
+}
@@ -0,0 +1,73 @@
+//! Command line interface of the crate, see `arima help`.
+
+use anyhow::Result;
+use arima::serve::Server;
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "\
+Usage: arima serve [--addr ADDRESS] [NAME=]MODEL...
+
+Serve models saved with `arima::persist::save`, or parameter files in the
+statsmodels layout (extension .json), over a JSON HTTP API.
+Each model is served under NAME, or the file name without extension.
+
+Options:
+    --addr ADDRESS    Address to listen on [default: 127.0.0.1:8080]
+
+Endpoints:
+    GET  /models                              list the loaded models
+    POST /models/NAME/observations            append {\"values\": [...]}
+    GET  /models/NAME/forecast?h=12&level=0.95  forecasts with intervals";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("serve") => serve(&args[1..]),
+        Some("help") | Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => anyhow::bail!("Unknown command {:?}", command),
+        None => anyhow::bail!("Missing command"),
+    }
+}
+
+fn serve(args: &[String]) -> Result<()> {
+    let mut addr = "127.0.0.1:8080".to_string();
+    let mut server = Server::new();
+    let mut n = 0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--addr" {
+            addr = match args.next() {
+                Some(a) => a.clone(),
+                None => anyhow::bail!("Missing value of --addr"),
+            };
+            continue;
+        }
+        let (name, path) = match arg.find('=') {
+            Some(i) => (arg[..i].to_string(), &arg[i + 1..]),
+            None => match Path::new(arg).file_stem() {
+                Some(stem) => (stem.to_string_lossy().into_owned(), arg.as_str()),
+                None => anyhow::bail!("Invalid model path {:?}", arg),
+            },
+        };
+        server
+            .load(&name, path)
+            .map_err(|e| anyhow::anyhow!("Could not load {:?}: {}", path, e))?;
+        n += 1;
+    }
+    if n == 0 {
+        anyhow::bail!("No models given");
+    }
+    eprintln!("Serving {} model(s) on http://{}", n, addr);
+    server.run(addr.as_str())
+}
@@ -0,0 +1,199 @@
+//! Block bootstrap resampling of dependent data.
+//!
+//! Resamples are concatenations of blocks of consecutive observations, so that the
+//! dependence within blocks is preserved. The moving-block bootstrap (Künsch, 1989)
+//! uses blocks of fixed length; the stationary bootstrap (Politis and Romano, 1994)
+//! uses blocks of geometrically distributed length and wraps around the end of the
+//! series, which makes the resamples stationary.
+
+use anyhow::Result;
+
+use rand::Rng;
+
+use crate::acf;
+
+/// Block bootstrap scheme.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scheme {
+    /// Moving-block bootstrap with the given block length.
+    MovingBlock(usize),
+    /// Stationary bootstrap with the given mean block length.
+    Stationary(f64),
+}
+
+/// Block lengths selected by `block_length`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockLength {
+    /// Block length for the moving-block bootstrap.
+    pub moving: usize,
+    /// Mean block length for the stationary bootstrap.
+    pub stationary: f64,
+}
+
+impl BlockLength {
+    /// Moving-block bootstrap scheme with the selected block length.
+    pub fn moving_block(&self) -> Scheme {
+        Scheme::MovingBlock(self.moving)
+    }
+
+    /// Stationary bootstrap scheme with the selected mean block length.
+    pub fn stationary(&self) -> Scheme {
+        Scheme::Stationary(self.stationary)
+    }
+}
+
+/// Draw the indices of a block bootstrap resample of a series of length `n`, e.g. to
+/// resample several aligned series or pairs of regressors and responses alike.
+///
+/// # Arguments
+///
+/// * `n` - Length of the series and the resample.
+/// * `scheme` - Bootstrap scheme. Block lengths must be within 1 and `n`.
+/// * `rng` - Reference to a mutable `Rng`.
+///
+/// # Returns
+///
+/// * Indices of the resampled observations.
+///
+/// # Example
+///
+/// ```
+/// use arima::{bootstrap, sim};
+/// let mut rng = sim::stream_rng(1, 0);
+/// let idx = bootstrap::resample_indices(10, bootstrap::Scheme::MovingBlock(3), &mut rng).unwrap();
+/// assert_eq!(idx.len(), 10);
+/// // blocks of three consecutive observations
+/// assert_eq!(idx[1], idx[0] + 1);
+/// assert_eq!(idx[2], idx[0] + 2);
+/// ```
+pub fn resample_indices<R: Rng>(n: usize, scheme: Scheme, rng: &mut R) -> Result<Vec<usize>> {
+    if n == 0 {
+        anyhow::bail!("Time series must not be empty");
+    }
+    let mut idx = Vec::with_capacity(n);
+    match scheme {
+        Scheme::MovingBlock(b) => {
+            if b == 0 || b > n {
+                anyhow::bail!("Block length must be within 1 and the series length");
+            }
+            while idx.len() < n {
+                let start = rng.gen_range(0..n - b + 1);
+                idx.extend((start..start + b).take(n - idx.len()));
+            }
+        }
+        Scheme::Stationary(mean) => {
+            if !(mean >= 1.0 && mean <= n as f64) {
+                anyhow::bail!("Mean block length must be within 1 and the series length");
+            }
+            let p = 1.0 / mean;
+            let mut i = rng.gen_range(0..n);
+            idx.push(i);
+            while idx.len() < n {
+                i = if rng.gen::<f64>() < p {
+                    rng.gen_range(0..n)
+                } else {
+                    (i + 1) % n
+                };
+                idx.push(i);
+            }
+        }
+    }
+    Ok(idx)
+}
+
+/// Draw a block bootstrap resample of a series, see `resample_indices`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `scheme` - Bootstrap scheme.
+/// * `rng` - Reference to a mutable `Rng`.
+///
+/// # Returns
+///
+/// * Resample of the same length as `x`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{bootstrap, sim};
+/// let x: Vec<f64> = (0..50).map(|t| (t as f64 / 4.0).sin()).collect();
+/// let b = bootstrap::block_length(&x).unwrap();
+/// let mut rng = sim::stream_rng(1, 0);
+/// let y = bootstrap::resample(&x, b.stationary(), &mut rng).unwrap();
+/// assert_eq!(y.len(), x.len());
+/// ```
+pub fn resample<R: Rng>(x: &[f64], scheme: Scheme, rng: &mut R) -> Result<Vec<f64>> {
+    Ok(resample_indices(x.len(), scheme, rng)?
+        .into_iter()
+        .map(|i| x[i])
+        .collect())
+}
+
+/// Select block lengths automatically with the method of Politis and White (2004),
+/// including the correction of Patton, Politis and White (2009).
+///
+/// The lengths minimize the asymptotic mean squared error of the bootstrap variance of
+/// the sample mean. They are estimated from the autocovariances up to a lag chosen by
+/// the first run of insignificant autocorrelations, with a flat-top lag window, and
+/// capped at `min(3 sqrt(n), n / 3)`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least 10 values.
+///
+/// # Returns
+///
+/// * Block lengths for the moving-block and the stationary bootstrap.
+pub fn block_length(x: &[f64]) -> Result<BlockLength> {
+    let n = x.len();
+    if n < 10 {
+        anyhow::bail!("Need at least 10 values to select a block length");
+    }
+    let nf = n as f64;
+    let k_n = (nf.log10().sqrt().ceil() as usize).max(5);
+    let m_max = (nf.sqrt().ceil() as usize + k_n).min(n - 1);
+    let b_max = (3.0 * nf.sqrt()).min(nf / 3.0).ceil().max(1.0);
+    let gamma = acf::acf(x, Some(m_max), true)?;
+    if gamma[0] <= 0.0 {
+        anyhow::bail!("Time series must not be constant");
+    }
+
+    // smallest lag followed by k_n insignificant autocorrelations
+    let bound = 2.0 * (nf.log10() / nf).sqrt();
+    let insignificant = |k: usize| (gamma[k] / gamma[0]).abs() < bound;
+    let m_hat = (0..m_max + 1)
+        .find(|m| (m + 1..m + k_n + 1).all(|k| k > m_max || insignificant(k)))
+        .unwrap_or(m_max);
+    let m = (2 * m_hat).clamp(1, m_max);
+
+    let flat_top = |t: f64| {
+        let t = t.abs();
+        if t <= 0.5 {
+            1.0
+        } else if t <= 1.0 {
+            2.0 * (1.0 - t)
+        } else {
+            0.0
+        }
+    };
+    let mut g = 0.0;
+    let mut long_run = gamma[0];
+    for (k, gk) in gamma.iter().enumerate().take(m + 1).skip(1) {
+        let w = flat_top(k as f64 / m as f64);
+        g += 2.0 * w * k as f64 * gk;
+        long_run += 2.0 * w * gk;
+    }
+
+    let length = |d: f64| {
+        if d <= 0.0 || g == 0.0 {
+            1.0
+        } else {
+            ((2.0 * g * g / d).powf(1.0 / 3.0) * nf.powf(1.0 / 3.0)).clamp(1.0, b_max)
+        }
+    };
+    Ok(BlockLength {
+        moving: length(4.0 / 3.0 * long_run * long_run).ceil() as usize,
+        stationary: length(2.0 * long_run * long_run),
+    })
+}
@@ -0,0 +1,430 @@
+//! Clustering of many series by the distance of their correlation structure.
+//!
+//! Each series is summarized by a feature vector, e.g. its autocorrelations, and the
+//! series are compared by the Euclidean distance of their features (Galeano and Peña,
+//! 2000), or directly by dynamic time warping. The clustering functions work on any
+//! symmetric distance matrix.
+
+use anyhow::Result;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::acf;
+
+/// Features summarizing the correlation structure of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Features {
+    /// Autocorrelations of lags `1, ..., max_lag`.
+    Acf(usize),
+    /// Partial autocorrelations of lags `1, ..., max_lag`.
+    Pacf(usize),
+    /// Autocorrelations followed by partial autocorrelations of lags `1, ..., max_lag`.
+    AcfPacf(usize),
+}
+
+/// Linkage criterion of hierarchical clustering, i.e. the distance between clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Smallest distance between members.
+    Single,
+    /// Largest distance between members.
+    Complete,
+    /// Mean distance between members.
+    Average,
+}
+
+/// Result of k-medoids clustering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KMedoids {
+    /// Indices of the series that are the medoids of the clusters.
+    pub medoids: Vec<usize>,
+    /// Cluster of each series, an index into `medoids`.
+    pub labels: Vec<usize>,
+    /// Sum of the distances of the series to their medoids.
+    pub cost: f64,
+}
+
+/// Compute the feature vector of a series.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries, longer than the maximum lag.
+/// * `features` - Features to compute.
+///
+/// # Returns
+///
+/// * Feature vector.
+pub fn features(x: &[f64], features: Features) -> Result<Vec<f64>> {
+    let max_lag = match features {
+        Features::Acf(lag) | Features::Pacf(lag) | Features::AcfPacf(lag) => lag,
+    };
+    if max_lag == 0 || max_lag >= x.len() {
+        anyhow::bail!("max_lag must be within 1 and the series length minus one");
+    }
+    let rho = || -> Result<Vec<f64>> { Ok(acf::acf(x, Some(max_lag), false)?[1..].to_vec()) };
+    Ok(match features {
+        Features::Acf(_) => rho()?,
+        Features::Pacf(_) => acf::pacf(x, Some(max_lag))?,
+        Features::AcfPacf(_) => {
+            let mut v = rho()?;
+            v.extend(acf::pacf(x, Some(max_lag))?);
+            v
+        }
+    })
+}
+
+/// Pairwise Euclidean distances between the feature vectors of many series.
+///
+/// # Arguments
+///
+/// * `&series` - The series.
+/// * `features` - Features compared.
+///
+/// # Returns
+///
+/// * Symmetric distance matrix with zero diagonal.
+///
+/// # Example
+///
+/// ```
+/// use arima::cluster;
+/// let a: Vec<f64> = (0..50).map(|t| if t % 2 == 0 { 1.0 } else { -1.0 }).collect();
+/// let b: Vec<f64> = (0..50).map(|t| if t % 2 == 0 { 2.0 } else { -2.5 }).collect();
+/// let c: Vec<f64> = (0..50).map(|t| (t as f64 / 8.0).sin()).collect();
+/// let d = cluster::distance_matrix(&[a, b, c], cluster::Features::Acf(3)).unwrap();
+/// assert!(d[0][1] < d[0][2]);
+/// ```
+pub fn distance_matrix<S: AsRef<[f64]>>(series: &[S], features: Features) -> Result<Vec<Vec<f64>>> {
+    let f = series
+        .iter()
+        .map(|x| self::features(x.as_ref(), features))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(f.iter()
+        .map(|a| f.iter().map(|b| euclidean(a, b)).collect())
+        .collect())
+}
+
+/// Same as `distance_matrix`, but computes the features and distances in parallel.
+/// Only enabled with feature `rayon`.
+#[cfg(feature = "rayon")]
+pub fn distance_matrix_par<S: AsRef<[f64]> + Sync>(
+    series: &[S],
+    features: Features,
+) -> Result<Vec<Vec<f64>>> {
+    let f = series
+        .par_iter()
+        .map(|x| self::features(x.as_ref(), features))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(f.par_iter()
+        .map(|a| f.iter().map(|b| euclidean(a, b)).collect())
+        .collect())
+}
+
+/// Dynamic time warping distance between two series: the smallest sum of absolute
+/// differences `|a[i] - b[j]|` along a monotonic alignment of the series that matches
+/// their first and their last values.
+///
+/// # Arguments
+///
+/// * `&a` - First series.
+/// * `&b` - Second series.
+/// * `window` - Width of the Sakoe-Chiba band: only values with `|i - j| <= window` are
+///   aligned. Must be at least the difference of the lengths. `None` for no constraint.
+///
+/// # Returns
+///
+/// * DTW distance.
+///
+/// # Example
+///
+/// ```
+/// use arima::cluster;
+/// let a = [0.0, 0.0, 1.0, 2.0, 1.0, 0.0];
+/// let b = [0.0, 1.0, 2.0, 1.0, 0.0, 0.0];
+/// // the shifted peak is aligned at no cost
+/// assert_eq!(cluster::dtw(&a, &b, Some(1)).unwrap(), 0.0);
+/// // without warping the values are compared pointwise
+/// assert_eq!(cluster::dtw(&a, &b, Some(0)).unwrap(), 4.0);
+/// ```
+pub fn dtw(a: &[f64], b: &[f64], window: Option<usize>) -> Result<f64> {
+    let (n, m) = (a.len(), b.len());
+    if n == 0 || m == 0 {
+        anyhow::bail!("Series must not be empty");
+    }
+    let window = window.unwrap_or(n.max(m));
+    if window < n.abs_diff(m) {
+        anyhow::bail!("Window must be at least the difference of the series lengths");
+    }
+    // cost[j + 1] of the previous and current row, cost[0] is the border
+    let mut prev = vec![f64::INFINITY; m + 1];
+    let mut curr = vec![f64::INFINITY; m + 1];
+    prev[0] = 0.0;
+    for (i, ai) in a.iter().enumerate() {
+        curr.iter_mut().for_each(|c| *c = f64::INFINITY);
+        for j in i.saturating_sub(window)..m.min(i + window + 1) {
+            let step = prev[j].min(prev[j + 1]).min(curr[j]);
+            curr[j + 1] = (ai - b[j]).abs() + step;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    Ok(prev[m])
+}
+
+/// Pairwise DTW distances between many series, see `dtw`. The matrix can be clustered
+/// with `hierarchical` or `k_medoids`.
+///
+/// # Arguments
+///
+/// * `&series` - The series.
+/// * `window` - Width of the Sakoe-Chiba band, or `None` for no constraint.
+///
+/// # Returns
+///
+/// * Symmetric distance matrix with zero diagonal.
+pub fn dtw_matrix<S: AsRef<[f64]>>(series: &[S], window: Option<usize>) -> Result<Vec<Vec<f64>>> {
+    let n = series.len();
+    let mut d = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i + 1..n {
+            d[i][j] = dtw(series[i].as_ref(), series[j].as_ref(), window)?;
+            d[j][i] = d[i][j];
+        }
+    }
+    Ok(d)
+}
+
+/// Agglomerative hierarchical clustering: starting from single series, the two closest
+/// clusters are merged until `k` clusters are left.
+///
+/// The distances between clusters are updated after each merge with the Lance-Williams
+/// formula, which takes `O(n^3)` time and `O(n^2)` memory for `n` series. This limits
+/// it to a few thousand series; use `k_medoids` for larger collections.
+///
+/// # Arguments
+///
+/// * `&distances` - Symmetric distance matrix of the series.
+/// * `k` - Number of clusters, within 1 and the number of series.
+/// * `linkage` - Distance between clusters.
+///
+/// # Returns
+///
+/// * Cluster of each series, numbered `0, ..., k - 1` in the order of their first
+///   member.
+///
+/// # Example
+///
+/// ```
+/// use arima::cluster;
+/// let d = vec![
+///     vec![0.0, 1.0, 5.0, 6.0],
+///     vec![1.0, 0.0, 5.5, 6.5],
+///     vec![5.0, 5.5, 0.0, 0.5],
+///     vec![6.0, 6.5, 0.5, 0.0],
+/// ];
+/// let labels = cluster::hierarchical(&d, 2, cluster::Linkage::Average).unwrap();
+/// assert_eq!(labels, vec![0, 0, 1, 1]);
+/// ```
+pub fn hierarchical(distances: &[Vec<f64>], k: usize, linkage: Linkage) -> Result<Vec<usize>> {
+    let n = check_distances(distances)?;
+    if k == 0 || k > n {
+        anyhow::bail!("Number of clusters must be within 1 and the number of series");
+    }
+    // distances between clusters, indexed by the first series of each cluster
+    let mut d = distances.to_vec();
+    let mut members: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+    let mut active: Vec<usize> = (0..n).collect();
+    while active.len() > k {
+        let mut best = (0, 1, f64::INFINITY);
+        for (a, i) in active.iter().enumerate() {
+            for (b, j) in active.iter().enumerate().skip(a + 1) {
+                if d[*i][*j] < best.2 {
+                    best = (a, b, d[*i][*j]);
+                }
+            }
+        }
+        let (i, j) = (active[best.0], active[best.1]);
+        let (ni, nj) = (members[i].len() as f64, members[j].len() as f64);
+        for l in active.iter().copied().filter(|l| *l != i && *l != j) {
+            let merged = match linkage {
+                Linkage::Single => d[i][l].min(d[j][l]),
+                Linkage::Complete => d[i][l].max(d[j][l]),
+                Linkage::Average => (ni * d[i][l] + nj * d[j][l]) / (ni + nj),
+            };
+            d[i][l] = merged;
+            d[l][i] = merged;
+        }
+        let merged = std::mem::take(&mut members[j]);
+        members[i].extend(merged);
+        active.remove(best.1);
+    }
+    let mut clusters: Vec<&Vec<usize>> = active.iter().map(|i| &members[*i]).collect();
+    clusters.sort_by_key(|c| *c.iter().min().unwrap());
+    let mut labels = vec![0; n];
+    for (label, members) in clusters.iter().enumerate() {
+        for i in members.iter() {
+            labels[*i] = label;
+        }
+    }
+    Ok(labels)
+}
+
+/// k-medoids clustering with the PAM algorithm (Kaufman and Rousseeuw, 1990): a greedy
+/// initialization followed by swaps of medoids and other series as long as they
+/// reduce the total distance to the medoids.
+///
+/// The distances of each series to its nearest and second nearest medoid are cached, so
+/// that the change of the total distance by all swaps is evaluated in `O(n^2)` time per
+/// iteration for `n` series (Schubert and Rousseeuw, 2019), and the initialization in
+/// `O(k n^2)` time.
+///
+/// # Arguments
+///
+/// * `&distances` - Symmetric distance matrix of the series.
+/// * `k` - Number of clusters, within 1 and the number of series.
+///
+/// # Returns
+///
+/// * Medoids, cluster labels and total distance.
+///
+/// # Example
+///
+/// ```
+/// use arima::cluster;
+/// let d = vec![
+///     vec![0.0, 1.0, 5.0, 6.0],
+///     vec![1.0, 0.0, 5.5, 6.5],
+///     vec![5.0, 5.5, 0.0, 0.5],
+///     vec![6.0, 6.5, 0.5, 0.0],
+/// ];
+/// let km = cluster::k_medoids(&d, 2).unwrap();
+/// assert_eq!(km.labels[0], km.labels[1]);
+/// assert_ne!(km.labels[0], km.labels[2]);
+/// assert_eq!(km.cost, 1.5);
+/// ```
+pub fn k_medoids(distances: &[Vec<f64>], k: usize) -> Result<KMedoids> {
+    let n = check_distances(distances)?;
+    if k == 0 || k > n {
+        anyhow::bail!("Number of clusters must be within 1 and the number of series");
+    }
+
+    // build: add the medoid that reduces the cost the most
+    let mut medoids: Vec<usize> = Vec::with_capacity(k);
+    let mut nearest = vec![f64::INFINITY; n];
+    while medoids.len() < k {
+        let mut best = (0, f64::INFINITY);
+        for c in (0..n).filter(|c| !medoids.contains(c)) {
+            let total: f64 = (0..n).map(|i| distances[i][c].min(nearest[i])).sum();
+            if total < best.1 {
+                best = (c, total);
+            }
+        }
+        medoids.push(best.0);
+        for (i, d) in nearest.iter_mut().enumerate() {
+            *d = d.min(distances[i][best.0]);
+        }
+    }
+
+    // swap: replace medoids while the cost decreases
+    let mut cache = MedoidCache::new(distances, &medoids);
+    let mut delta = vec![0.0; k];
+    loop {
+        let mut best = (0, 0, 0.0);
+        for c in (0..n).filter(|c| !medoids.contains(c)) {
+            // change of the cost by swapping c with each medoid, split into the part
+            // shared by all medoids and the part specific to the medoid removed
+            let mut shared = 0.0;
+            delta.iter_mut().for_each(|d| *d = 0.0);
+            for i in 0..n {
+                let d = distances[i][c];
+                let gain = (d - cache.nearest[i]).min(0.0);
+                shared += gain;
+                delta[cache.medoid[i]] += d.min(cache.second[i]) - cache.nearest[i] - gain;
+            }
+            for (m, d) in delta.iter().enumerate() {
+                if shared + d < best.2 {
+                    best = (m, c, shared + d);
+                }
+            }
+        }
+        if best.2 >= 0.0 {
+            break;
+        }
+        let old = medoids[best.0];
+        medoids[best.0] = best.1;
+        let swapped = MedoidCache::new(distances, &medoids);
+        // guard against rounding errors of the cost changes
+        if swapped.cost() >= cache.cost() {
+            medoids[best.0] = old;
+            break;
+        }
+        cache = swapped;
+    }
+
+    Ok(KMedoids {
+        medoids,
+        cost: cache.cost(),
+        labels: cache.medoid,
+    })
+}
+
+/// Nearest and second nearest medoids of each series.
+struct MedoidCache {
+    /// Index into the medoids of the nearest medoid, the first one on ties.
+    medoid: Vec<usize>,
+    /// Distance to the nearest medoid.
+    nearest: Vec<f64>,
+    /// Distance to the second nearest medoid, infinite for a single medoid.
+    second: Vec<f64>,
+}
+
+impl MedoidCache {
+    fn new(distances: &[Vec<f64>], medoids: &[usize]) -> Self {
+        let n = distances.len();
+        let mut cache = MedoidCache {
+            medoid: vec![0; n],
+            nearest: vec![f64::INFINITY; n],
+            second: vec![f64::INFINITY; n],
+        };
+        for (i, row) in distances.iter().enumerate() {
+            for (m, j) in medoids.iter().enumerate() {
+                let d = row[*j];
+                if d < cache.nearest[i] || m == 0 {
+                    cache.second[i] = cache.nearest[i];
+                    cache.nearest[i] = d;
+                    cache.medoid[i] = m;
+                } else if d < cache.second[i] {
+                    cache.second[i] = d;
+                }
+            }
+        }
+        cache
+    }
+
+    fn cost(&self) -> f64 {
+        self.nearest.iter().sum()
+    }
+}
+
+fn euclidean(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(u, v)| (u - v) * (u - v))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Check that a distance matrix is square and free of NaN. Returns its size.
+fn check_distances(distances: &[Vec<f64>]) -> Result<usize> {
+    let n = distances.len();
+    if n == 0 {
+        anyhow::bail!("Need at least one series");
+    }
+    if distances.iter().any(|row| row.len() != n) {
+        anyhow::bail!("Distance matrix must be square");
+    }
+    if distances.iter().flatten().any(|d| d.is_nan()) {
+        anyhow::bail!("Distances must not be NaN");
+    }
+    Ok(n)
+}
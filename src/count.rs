@@ -0,0 +1,341 @@
+use anyhow::Result;
+
+use rand::Rng;
+use rand_distr::{Distribution, Gamma, Poisson};
+
+use crate::special::ln_gamma;
+use crate::{estimate, sim};
+
+/// Conditional distribution of the counts given the intensity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountDistribution {
+    /// Poisson distribution with mean and variance `lambda`.
+    Poisson,
+    /// Negative binomial distribution with mean `lambda` and variance
+    /// `lambda + lambda^2 / size` for overdispersed counts.
+    NegativeBinomial,
+}
+
+/// A fitted INGARCH(p, q) model (integer-valued GARCH, Poisson autoregression):
+/// `lambda_t = omega + alpha_1 y_{t-1} + ... + alpha_p y_{t-p} + beta_1 lambda_{t-1} + ...
+/// + beta_q lambda_{t-q}` where `y_t` given the past follows a Poisson or negative binomial
+/// distribution with mean `lambda_t`.
+///
+/// The parameters satisfy `omega > 0`, `alpha_i, beta_j >= 0` and
+/// `sum(alpha) + sum(beta) < 1`, so the process is stationary with mean
+/// `omega / (1 - sum(alpha) - sum(beta))`.
+#[derive(Debug, Clone)]
+pub struct IngarchModel {
+    /// Conditional distribution of the counts.
+    pub distribution: CountDistribution,
+    /// Intercept of the intensity.
+    pub omega: f64,
+    /// Coefficients of the lagged counts.
+    pub alpha: Vec<f64>,
+    /// Coefficients of the lagged intensities.
+    pub beta: Vec<f64>,
+    /// Dispersion parameter of the negative binomial distribution. `None` for Poisson.
+    pub size: Option<f64>,
+    /// One-step-ahead conditional means `lambda_t`.
+    pub intensity: Vec<f64>,
+    /// Pearson residuals `(y_t - lambda_t) / sqrt(var_t)`.
+    pub residuals: Vec<f64>,
+    /// Log-likelihood.
+    pub loglik: f64,
+    /// Number of observations the model was fitted on.
+    pub n: usize,
+    // last p counts and last q intensities, oldest first, for forecasting
+    y_tail: Vec<f64>,
+    lambda_tail: Vec<f64>,
+}
+
+impl IngarchModel {
+    /// Number of estimated parameters.
+    pub fn n_params(&self) -> usize {
+        1 + self.alpha.len() + self.beta.len() + self.size.map_or(0, |_| 1)
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik + 2.0 * self.n_params() as f64
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik + (self.n as f64).ln() * self.n_params() as f64
+    }
+
+    /// Forecasts of the conditional mean for the next `h` time steps. Future counts in
+    /// the recursion are replaced by their expectation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::count::{self, CountDistribution};
+    /// let x = [2.0, 3.0, 1.0, 4.0, 2.0, 0.0, 3.0, 5.0, 2.0, 1.0, 2.0, 3.0];
+    /// let m = count::fit(&x, 1, 0, CountDistribution::Poisson).unwrap();
+    /// let f = m.forecast(50);
+    /// // the forecasts converge to the stationary mean
+    /// let mean = m.omega / (1.0 - m.alpha[0]);
+    /// assert!((f[49] - mean).abs() < 1.0e-6);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        let mut y_hist = self.y_tail.clone();
+        let mut lambda_hist = self.lambda_tail.clone();
+        let mut out = Vec::with_capacity(h);
+        for _ in 0..h {
+            let lambda = intensity(self.omega, &self.alpha, &self.beta, &y_hist, &lambda_hist);
+            push(&mut y_hist, lambda);
+            push(&mut lambda_hist, lambda);
+            out.push(lambda);
+        }
+        out
+    }
+
+    /// Probabilities of the counts `0, ..., max` at the next time step.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::count::{self, CountDistribution};
+    /// let x = [2.0, 3.0, 1.0, 4.0, 2.0, 0.0, 3.0, 5.0, 2.0, 1.0, 2.0, 3.0];
+    /// let m = count::fit(&x, 1, 0, CountDistribution::Poisson).unwrap();
+    /// let pmf = m.forecast_pmf(100);
+    /// assert!((pmf.iter().sum::<f64>() - 1.0).abs() < 1.0e-9);
+    /// ```
+    pub fn forecast_pmf(&self, max: usize) -> Vec<f64> {
+        let lambda = intensity(
+            self.omega,
+            &self.alpha,
+            &self.beta,
+            &self.y_tail,
+            &self.lambda_tail,
+        );
+        (0..max + 1)
+            .map(|k| log_pmf(k as f64, lambda, self.size).exp())
+            .collect()
+    }
+
+    /// Simulate one future sample path of length `h`.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `rng` - Reference to a mutable `Rng`.
+    ///
+    /// # Returns
+    ///
+    /// * Simulated counts for the next `h` time steps.
+    pub fn simulate<R: Rng>(&self, h: usize, rng: &mut R) -> Vec<f64> {
+        let mut y_hist = self.y_tail.clone();
+        let mut lambda_hist = self.lambda_tail.clone();
+        (0..h)
+            .map(|_| {
+                let lambda = intensity(self.omega, &self.alpha, &self.beta, &y_hist, &lambda_hist);
+                let y = sample(lambda, self.size, rng);
+                push(&mut y_hist, y);
+                push(&mut lambda_hist, lambda);
+                y
+            })
+            .collect()
+    }
+
+    /// Prediction intervals of the counts from simulated sample paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `level` - Coverage probability of the intervals, e.g. 0.95.
+    /// * `n_paths` - Number of simulated sample paths.
+    /// * `rng` - Reference to a mutable `Rng`.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of (lower, upper) bounds for each of the next `h` time steps.
+    pub fn simulated_intervals<R: Rng>(
+        &self,
+        h: usize,
+        level: f64,
+        n_paths: usize,
+        rng: &mut R,
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        let (lower, upper) = sim::path_intervals(h, level, n_paths, || self.simulate(h, rng))?;
+        // counts are integers, so round the bounds outwards
+        Ok((
+            lower.iter().map(|v| v.floor()).collect(),
+            upper.iter().map(|v| v.ceil()).collect(),
+        ))
+    }
+}
+
+/// Append `v` to a history window of fixed length, dropping the oldest value.
+fn push(hist: &mut Vec<f64>, v: f64) {
+    if !hist.is_empty() {
+        hist.remove(0);
+        hist.push(v);
+    }
+}
+
+/// Intensity from the last `p` counts and `q` intensities (oldest first).
+fn intensity(omega: f64, alpha: &[f64], beta: &[f64], y_hist: &[f64], lambda_hist: &[f64]) -> f64 {
+    let mut lambda = omega;
+    for (i, a) in alpha.iter().enumerate() {
+        lambda += a * y_hist[y_hist.len() - 1 - i];
+    }
+    for (j, b) in beta.iter().enumerate() {
+        lambda += b * lambda_hist[lambda_hist.len() - 1 - j];
+    }
+    lambda
+}
+
+fn sample<R: Rng>(lambda: f64, size: Option<f64>, rng: &mut R) -> f64 {
+    // the negative binomial is a Poisson distribution with gamma distributed mean
+    let mean = match size {
+        Some(size) => Gamma::new(size, lambda / size).unwrap().sample(rng),
+        None => lambda,
+    };
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    Poisson::new(mean).unwrap().sample(rng)
+}
+
+fn log_pmf(y: f64, lambda: f64, size: Option<f64>) -> f64 {
+    match size {
+        None => y * lambda.ln() - lambda - ln_gamma(y + 1.0),
+        Some(size) => {
+            ln_gamma(y + size) - ln_gamma(size) - ln_gamma(y + 1.0)
+                + size * (size / (size + lambda)).ln()
+                + y * (lambda / (size + lambda)).ln()
+        }
+    }
+}
+
+/// Map the unconstrained optimization vector to `(omega, alpha, beta, size)`. The
+/// coefficients are positive with a sum below one.
+fn params(z: &[f64], p: usize, q: usize, nb: bool) -> (f64, Vec<f64>, Vec<f64>, Option<f64>) {
+    let omega = z[0].exp();
+    let u: Vec<f64> = z[1..1 + p + q].iter().map(|v| v.exp()).collect();
+    let total = 1.0 + u.iter().sum::<f64>();
+    let alpha = u[..p].iter().map(|v| v / total).collect();
+    let beta = u[p..].iter().map(|v| v / total).collect();
+    let size = nb.then(|| z[1 + p + q].exp());
+    (omega, alpha, beta, size)
+}
+
+/// Run the intensity recursion. Pre-sample counts and intensities are set to the sample
+/// mean. Returns the intensities and the log-likelihood.
+fn run(
+    x: &[f64],
+    mean: f64,
+    omega: f64,
+    alpha: &[f64],
+    beta: &[f64],
+    size: Option<f64>,
+) -> (Vec<f64>, f64) {
+    let mut y_hist = vec![mean; alpha.len()];
+    let mut lambda_hist = vec![mean; beta.len()];
+    let mut lambdas = Vec::with_capacity(x.len());
+    let mut loglik = 0.0;
+    for y in x {
+        let lambda = intensity(omega, alpha, beta, &y_hist, &lambda_hist);
+        loglik += log_pmf(*y, lambda, size);
+        push(&mut y_hist, *y);
+        push(&mut lambda_hist, lambda);
+        lambdas.push(lambda);
+    }
+    (lambdas, loglik)
+}
+
+/// Estimate an INGARCH(p, q) model for count data by maximum likelihood.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the counts, non-negative integers.
+/// * `p` - Number of lagged counts.
+/// * `q` - Number of lagged intensities.
+/// * `distribution` - Conditional distribution of the counts.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::count::{self, CountDistribution};
+/// let x = [0.0, 1.0, 0.0, 2.0, 5.0, 3.0, 1.0, 0.0, 0.0, 1.0, 4.0, 2.0, 1.0, 0.0];
+/// let m = count::fit(&x, 1, 0, CountDistribution::NegativeBinomial).unwrap();
+/// assert!(m.size.unwrap() > 0.0);
+/// assert!(m.forecast(1)[0] > 0.0);
+/// ```
+pub fn fit(x: &[f64], p: usize, q: usize, distribution: CountDistribution) -> Result<IngarchModel> {
+    if x.iter().any(|v| *v < 0.0 || v.fract() != 0.0) {
+        anyhow::bail!("Counts must be non-negative integers");
+    }
+    let nb = distribution == CountDistribution::NegativeBinomial;
+    let n_params = 1 + p + q + nb as usize;
+    if x.len() <= n_params + p.max(q) {
+        anyhow::bail!("Time series is too short for the model order");
+    }
+    let mean = x.iter().sum::<f64>() / x.len() as f64;
+    if mean <= 0.0 {
+        anyhow::bail!("Need at least one positive count");
+    }
+
+    // start with coefficients summing to 0.5, half of the mean coming from the intercept
+    let c = 0.5 / (p + q).max(1) as f64;
+    let mut z = vec![(0.5 * mean).ln()];
+    z.extend(vec![(c / 0.5).ln(); p + q]);
+    if nb {
+        let var = x.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / x.len() as f64;
+        // moment estimate of the dispersion, large if there is no overdispersion
+        let size = if var > mean {
+            mean * mean / (var - mean)
+        } else {
+            100.0
+        };
+        z.push(size.ln());
+    }
+
+    let f = |z: &[f64]| {
+        let (omega, alpha, beta, size) = params(z, p, q, nb);
+        let (_, loglik) = run(x, mean, omega, &alpha, &beta, size);
+        if loglik.is_finite() {
+            -loglik
+        } else {
+            // steer the optimizer away from invalid regions
+            1.0e10
+        }
+    };
+    estimate::minimize(f, &mut z);
+
+    let (omega, alpha, beta, size) = params(&z, p, q, nb);
+    let (intensity, loglik) = run(x, mean, omega, &alpha, &beta, size);
+    if !loglik.is_finite() {
+        anyhow::bail!("Estimation did not converge");
+    }
+    let residuals = x
+        .iter()
+        .zip(&intensity)
+        .map(|(y, l)| {
+            let var = l + size.map_or(0.0, |s| l * l / s);
+            (y - l) / var.sqrt()
+        })
+        .collect();
+
+    let n = x.len();
+    Ok(IngarchModel {
+        distribution,
+        omega,
+        alpha,
+        beta,
+        size,
+        y_tail: x[n - p..].to_vec(),
+        lambda_tail: intensity[n - q..].to_vec(),
+        intensity,
+        residuals,
+        loglik,
+        n,
+    })
+}
@@ -0,0 +1,676 @@
+use anyhow::Result;
+
+use crate::acf;
+use crate::metrics::{self, Accuracy};
+use crate::model::FittedModel;
+use crate::{estimate, special, util};
+
+/// Default number of lags of the residual diagnostics.
+pub const DIAGNOSTICS_LAGS: usize = 10;
+
+/// Result of a Ljung-Box portmanteau test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LjungBox {
+    /// Number of autocorrelations included.
+    pub lag: usize,
+    /// Test statistic `Q = n (n + 2) sum(r_k^2 / (n - k))`.
+    pub statistic: f64,
+    /// Degrees of freedom of the chi-squared reference distribution.
+    pub df: usize,
+    /// P-value of the test. `NaN` if there are no degrees of freedom left.
+    pub p_value: f64,
+}
+
+/// Ljung-Box test for autocorrelation up to lag `lag`.
+///
+/// # Arguments
+///
+/// * `&x` - Residuals of a fitted model.
+/// * `lag` - Number of autocorrelations included.
+/// * `fitdf` - Number of estimated ARMA parameters, subtracted from the degrees of freedom.
+///
+/// # Returns
+///
+/// * Test result.
+///
+/// # Example
+///
+/// ```
+/// use arima::diagnostics;
+/// let x = [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0];
+/// let lb = diagnostics::ljung_box(&x, 2, 0).unwrap();
+/// assert!(lb.p_value < 0.01);
+/// ```
+pub fn ljung_box(x: &[f64], lag: usize, fitdf: usize) -> Result<LjungBox> {
+    if lag == 0 || lag >= x.len() {
+        anyhow::bail!("lag must be within 1 and the series length minus one");
+    }
+    let rho = acf::acf(x, Some(lag), false)?;
+    Ok(ljung_box_rho(&rho, x.len(), lag, fitdf))
+}
+
+/// McLeod-Li test for remaining conditional heteroskedasticity: the Ljung-Box test
+/// applied to the squared residuals, with `lag` degrees of freedom.
+///
+/// # Arguments
+///
+/// * `&x` - Residuals of a fitted model.
+/// * `lag` - Number of autocorrelations of the squared residuals included.
+///
+/// # Returns
+///
+/// * Test result.
+///
+/// # Example
+///
+/// ```
+/// use arima::diagnostics;
+/// // volatility clusters: calm and turbulent periods alternate
+/// let x: Vec<f64> = (0..100)
+///     .map(|t| if (t / 10) % 2 == 0 { 0.1 } else { 2.0 } * if t % 2 == 0 { 1.0 } else { -1.0 })
+///     .collect();
+/// let ml = diagnostics::mcleod_li(&x, 5).unwrap();
+/// assert!(ml.p_value < 0.01);
+/// ```
+pub fn mcleod_li(x: &[f64], lag: usize) -> Result<LjungBox> {
+    let squared: Vec<f64> = x.iter().map(|v| v * v).collect();
+    ljung_box(&squared, lag, 0)
+}
+
+fn ljung_box_rho(rho: &[f64], n: usize, lag: usize, fitdf: usize) -> LjungBox {
+    let nf = n as f64;
+    let statistic = nf
+        * (nf + 2.0)
+        * (1..lag + 1)
+            .map(|k| rho[k] * rho[k] / (nf - k as f64))
+            .sum::<f64>();
+    let df = lag.saturating_sub(fitdf);
+    let p_value = if df > 0 {
+        special::chi_squared_sf(statistic, df as f64)
+    } else {
+        f64::NAN
+    };
+    LjungBox {
+        lag,
+        statistic,
+        df,
+        p_value,
+    }
+}
+
+/// Residual diagnostics of a fitted model, with the content of R's `tsdiag`.
+#[derive(Debug, Clone)]
+pub struct Diagnostics {
+    /// Standardized one-step prediction errors, see `FittedModel::standardized_residuals`.
+    pub standardized: Vec<f64>,
+    /// Autocorrelations of the residuals for lags `0, ..., lags`.
+    pub acf: Vec<f64>,
+    /// Approximate 95% bound `1.96 / sqrt(n)` of the autocorrelations of white noise.
+    pub acf_bound: f64,
+    /// Ljung-Box tests for lags `1, ..., lags`, with the degrees of freedom reduced by the
+    /// number of ARMA parameters.
+    pub ljung_box: Vec<LjungBox>,
+}
+
+impl Diagnostics {
+    /// Lags with autocorrelations outside the 95% bounds.
+    pub fn significant_lags(&self) -> Vec<usize> {
+        self.acf
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, r)| r.abs() > self.acf_bound)
+            .map(|(k, _)| k)
+            .collect()
+    }
+}
+
+/// Residual diagnostics of a fitted model for `DIAGNOSTICS_LAGS` lags. See
+/// `diagnostics_lags`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{diagnostics, model};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+/// let m = model::fit(&x, 1, 0, 0).unwrap();
+/// let d = diagnostics::diagnostics(&m).unwrap();
+/// assert_eq!(d.acf.len(), 11);
+/// assert!(d.ljung_box[0].p_value.is_nan());
+/// assert!(d.ljung_box[1].p_value > 0.0);
+/// ```
+pub fn diagnostics(fit: &FittedModel) -> Result<Diagnostics> {
+    let n = fit.n_used();
+    diagnostics_lags(fit, DIAGNOSTICS_LAGS.min(n.saturating_sub(1)))
+}
+
+/// Residual diagnostics of a fitted model: standardized residuals, their
+/// autocorrelations with 95% bounds and Ljung-Box tests up to lag `lags`.
+///
+/// # Arguments
+///
+/// * `&fit` - Fitted model.
+/// * `lags` - Maximum lag.
+///
+/// # Returns
+///
+/// * Diagnostics report.
+pub fn diagnostics_lags(fit: &FittedModel, lags: usize) -> Result<Diagnostics> {
+    let residuals = &fit.residuals[fit.ar..];
+    let n = residuals.len();
+    if lags == 0 || lags >= n {
+        anyhow::bail!("lags must be within 1 and the number of residuals minus one");
+    }
+    let standardized = fit.standardized_residuals();
+    let acf = acf::acf(residuals, Some(lags), false)?;
+    let ljung_box = (1..lags + 1)
+        .map(|lag| ljung_box_rho(&acf, n, lag, fit.ar + fit.ma))
+        .collect();
+
+    Ok(Diagnostics {
+        standardized,
+        acf,
+        acf_bound: 1.96 / (n as f64).sqrt(),
+        ljung_box,
+    })
+}
+
+/// Normality report of residuals.
+#[derive(Debug, Clone)]
+pub struct Normality {
+    /// Pairs of (theoretical, sample) quantiles for a normal Q-Q plot, with the
+    /// standardized sample sorted ascending.
+    pub qq: Vec<(f64, f64)>,
+    /// Sample skewness.
+    pub skewness: f64,
+    /// Sample excess kurtosis, zero for a normal distribution.
+    pub excess_kurtosis: f64,
+    /// Jarque-Bera statistic `n / 6 (S^2 + K^2 / 4)`.
+    pub jarque_bera: f64,
+    /// P-value of the Jarque-Bera test of normality.
+    pub p_value: f64,
+}
+
+/// Normality report of residuals: Q-Q plot data, skewness, kurtosis and the
+/// Jarque-Bera test.
+///
+/// The theoretical quantiles are evaluated at the plotting positions
+/// `(i - a) / (n + 1 - 2a)` with `a = 3/8` for `n <= 10` and `a = 1/2` otherwise, as in
+/// R's `qqnorm`.
+///
+/// # Arguments
+///
+/// * `&x` - Residuals of a fitted model.
+///
+/// # Returns
+///
+/// * Normality report.
+///
+/// # Example
+///
+/// ```
+/// use arima::diagnostics;
+/// let x = [-1.5, -0.8, -0.3, 0.0, 0.2, 0.5, 0.9, 1.4];
+/// let report = diagnostics::normality(&x).unwrap();
+/// assert_eq!(report.qq.len(), 8);
+/// assert!(report.qq[0].0 < 0.0 && report.qq[7].0 > 0.0);
+/// assert!(report.p_value > 0.05);
+/// ```
+pub fn normality(x: &[f64]) -> Result<Normality> {
+    let n = x.len();
+    if n < 3 {
+        anyhow::bail!("Need at least three values");
+    }
+    if x.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("Residuals must be finite");
+    }
+    let nf = n as f64;
+    let mean = x.iter().sum::<f64>() / nf;
+    let m2 = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / nf;
+    if m2 <= 0.0 {
+        anyhow::bail!("Residuals must not be constant");
+    }
+    let m3 = x.iter().map(|v| (v - mean).powi(3)).sum::<f64>() / nf;
+    let m4 = x.iter().map(|v| (v - mean).powi(4)).sum::<f64>() / nf;
+    if !m4.is_finite() {
+        anyhow::bail!("Residuals are too large for finite moments");
+    }
+    let skewness = m3 / m2.powf(1.5);
+    let excess_kurtosis = m4 / (m2 * m2) - 3.0;
+    let jarque_bera = nf / 6.0 * (skewness * skewness + 0.25 * excess_kurtosis * excess_kurtosis);
+
+    let sd = m2.sqrt();
+    let mut sorted: Vec<f64> = x.iter().map(|v| (v - mean) / sd).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let a = if n <= 10 { 0.375 } else { 0.5 };
+    let qq = sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let p = (i as f64 + 1.0 - a) / (nf + 1.0 - 2.0 * a);
+            (special::normal_quantile(p), v)
+        })
+        .collect();
+
+    Ok(Normality {
+        qq,
+        skewness,
+        excess_kurtosis,
+        jarque_bera,
+        p_value: special::chi_squared_sf(jarque_bera, 2.0),
+    })
+}
+
+/// Number of equally wide bins of the PIT histogram of `out_of_sample`.
+pub const PIT_BINS: usize = 10;
+
+/// Out-of-sample accuracy and interval coverage of forecasts of one horizon.
+#[derive(Debug, Clone)]
+pub struct HorizonReport {
+    /// Forecast horizon.
+    pub horizon: usize,
+    /// Forecast errors `actual - forecast`, one per forecast origin.
+    pub errors: Vec<f64>,
+    /// Point accuracy of the forecasts.
+    pub accuracy: Accuracy,
+    /// Empirical coverage of the Gaussian prediction intervals, one per nominal level.
+    pub coverage: Vec<f64>,
+}
+
+/// Out-of-sample diagnostic report of a fitted model on a held-out segment.
+#[derive(Debug, Clone)]
+pub struct OutOfSample {
+    /// Nominal levels of the prediction intervals.
+    pub levels: Vec<f64>,
+    /// Reports for the horizons `1, ..., h`.
+    pub horizons: Vec<HorizonReport>,
+    /// Probability integral transforms of the one-step-ahead actuals under the Gaussian
+    /// predictive distribution. Uniform on `[0, 1]` for a well calibrated model.
+    pub pit: Vec<f64>,
+    /// Counts of the PIT values in `PIT_BINS` equally wide bins.
+    pub pit_histogram: Vec<usize>,
+    /// P-value of the chi-squared test of uniformity of the PIT histogram.
+    pub pit_p_value: f64,
+}
+
+/// Out-of-sample diagnostic report of a fitted model on the values following the
+/// fitted series.
+///
+/// Forecasts are made from every origin within the held-out segment with the fitted
+/// coefficients, without re-estimation. The errors of horizon `k` include all origins
+/// whose `k`-step-ahead target lies within the segment.
+///
+/// # Arguments
+///
+/// * `&fit` - Fitted model.
+/// * `&test` - Held-out values directly following the fitted series.
+/// * `h` - Maximum forecast horizon.
+/// * `&levels` - Nominal levels of the prediction intervals, e.g. `[0.8, 0.95]`.
+///
+/// # Returns
+///
+/// * Out-of-sample report.
+///
+/// # Example
+///
+/// ```
+/// use arima::{diagnostics, model};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+/// let m = model::fit(&x[..10], 1, 0, 0).unwrap();
+/// let report = diagnostics::out_of_sample(&m, &x[10..], 2, &[0.8, 0.95]).unwrap();
+/// assert_eq!(report.horizons[0].errors.len(), 4);
+/// assert_eq!(report.horizons[1].errors.len(), 3);
+/// assert_eq!(report.horizons[0].coverage.len(), 2);
+/// assert_eq!(report.pit_histogram.iter().sum::<usize>(), 4);
+/// ```
+pub fn out_of_sample(
+    fit: &FittedModel,
+    test: &[f64],
+    h: usize,
+    levels: &[f64],
+) -> Result<OutOfSample> {
+    let m = test.len();
+    if h == 0 || h > m {
+        anyhow::bail!("h must be within 1 and the length of the held-out segment");
+    }
+    if test.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("Held-out values must be finite");
+    }
+    if levels.iter().any(|l| !(*l > 0.0 && *l < 1.0)) {
+        anyhow::bail!("Levels must be within 0 and 1");
+    }
+
+    let mut coef = vec![fit.intercept];
+    coef.extend_from_slice(&fit.phi);
+    coef.extend_from_slice(&fit.theta);
+    let sd: Vec<f64> = fit.forecast_variance(h).iter().map(|v| v.sqrt()).collect();
+    let z: Vec<f64> = levels
+        .iter()
+        .map(|l| special::normal_quantile(0.5 + 0.5 * l))
+        .collect();
+
+    let mut actual = vec![Vec::new(); h];
+    let mut predicted = vec![Vec::new(); h];
+    let mut x = fit.x.clone();
+    for origin in 0..m {
+        // residuals of the extended series with the fitted coefficients
+        let model = FittedModel::from_coef(&x, &coef, fit.ar, fit.d, fit.ma)?;
+        let forecast = model.forecast(h.min(m - origin));
+        for (k, f) in forecast.into_iter().enumerate() {
+            actual[k].push(test[origin + k]);
+            predicted[k].push(f);
+        }
+        x.push(test[origin]);
+    }
+
+    let mut horizons = Vec::with_capacity(h);
+    for k in 0..h {
+        let errors: Vec<f64> = actual[k]
+            .iter()
+            .zip(&predicted[k])
+            .map(|(a, p)| a - p)
+            .collect();
+        let coverage = z
+            .iter()
+            .map(|z| {
+                let inside = errors.iter().filter(|e| e.abs() <= z * sd[k]).count();
+                inside as f64 / errors.len() as f64
+            })
+            .collect();
+        horizons.push(HorizonReport {
+            horizon: k + 1,
+            accuracy: metrics::accuracy(&actual[k], &predicted[k])?,
+            errors,
+            coverage,
+        });
+    }
+
+    let pit: Vec<f64> = horizons[0]
+        .errors
+        .iter()
+        .map(|e| special::normal_cdf(e / sd[0]))
+        .collect();
+    let mut pit_histogram = vec![0; PIT_BINS];
+    for u in &pit {
+        pit_histogram[((u * PIT_BINS as f64) as usize).min(PIT_BINS - 1)] += 1;
+    }
+    let expected = m as f64 / PIT_BINS as f64;
+    let statistic: f64 = pit_histogram
+        .iter()
+        .map(|c| (*c as f64 - expected).powi(2) / expected)
+        .sum();
+
+    Ok(OutOfSample {
+        levels: levels.to_vec(),
+        horizons,
+        pit,
+        pit_histogram,
+        pit_p_value: special::chi_squared_sf(statistic, (PIT_BINS - 1) as f64),
+    })
+}
+
+/// Influence of single observations on a fitted model, see `influence`.
+#[derive(Debug, Clone)]
+pub struct Influence {
+    /// Indices of the observations in the original series.
+    pub index: Vec<usize>,
+    /// Change of the coefficients `[intercept, phi_1, ..., theta_1, ...]` when the
+    /// observation is deleted, one vector per observation.
+    pub coef_change: Vec<Vec<f64>>,
+    /// Likelihood displacement `2 (l(b) - l(b_(t)))`, where `l` is the log-likelihood of
+    /// all observations, `b` the fitted and `b_(t)` the coefficients without
+    /// observation `t`.
+    pub likelihood_displacement: Vec<f64>,
+}
+
+impl Influence {
+    /// Indices of the `k` observations with the largest likelihood displacement,
+    /// most influential first.
+    pub fn most_influential(&self, k: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.index.len()).collect();
+        order.sort_by(|i, j| {
+            self.likelihood_displacement[*j]
+                .partial_cmp(&self.likelihood_displacement[*i])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        order.into_iter().take(k).map(|i| self.index[i]).collect()
+    }
+}
+
+/// Deletion diagnostics of a fitted model: the coefficients are re-estimated with the
+/// residual of each observation removed from the conditional sum of squares, in turn.
+///
+/// The deleted residual is also set to zero in the MA recursion, so that the
+/// observation does not act as a shock on later residuals. The observation still
+/// enters later residuals as a lagged value of the AR part. Observations without a
+/// residual (the first `d + ar`) are not included. Requires one re-estimation per
+/// observation.
+///
+/// # Arguments
+///
+/// * `&fit` - Fitted model.
+///
+/// # Returns
+///
+/// * Influence measures of the observations `d + ar, ..., n - 1`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{diagnostics, model};
+/// let mut x = vec![1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+/// x[8] = 5.0;
+/// let m = model::fit(&x, 1, 0, 0).unwrap();
+/// let infl = diagnostics::influence(&m).unwrap();
+/// assert_eq!(infl.index[0], 1);
+/// assert_eq!(infl.most_influential(1), vec![8]);
+/// ```
+pub fn influence(fit: &FittedModel) -> Result<Influence> {
+    let w = if fit.d > 0 {
+        util::diff(&fit.x, fit.d)
+    } else {
+        fit.x.clone()
+    };
+    let mut coef = vec![fit.intercept];
+    coef.extend_from_slice(&fit.phi);
+    coef.extend_from_slice(&fit.theta);
+    let loglik = fit.loglik();
+
+    let mut index = Vec::with_capacity(w.len() - fit.ar);
+    let mut coef_change = Vec::with_capacity(w.len() - fit.ar);
+    let mut likelihood_displacement = Vec::with_capacity(w.len() - fit.ar);
+    for s in fit.ar..w.len() {
+        let f = |c: &[f64]| css_deleted(&w, c, fit.ar, s);
+        let mut deleted = coef.clone();
+        estimate::minimize(f, &mut deleted);
+        let refit = FittedModel::from_coef(&fit.x, &deleted, fit.ar, fit.d, fit.ma)?;
+
+        index.push(s + fit.d);
+        coef_change.push(deleted.iter().zip(&coef).map(|(a, b)| a - b).collect());
+        likelihood_displacement.push(2.0 * (loglik - refit.loglik()));
+    }
+
+    Ok(Influence {
+        index,
+        coef_change,
+        likelihood_displacement,
+    })
+}
+
+/// Conditional sum of squares of the differenced series `w` without the residual at
+/// index `deleted`, which is set to zero in the MA recursion.
+fn css_deleted(w: &[f64], coef: &[f64], ar: usize, deleted: usize) -> f64 {
+    let phi = &coef[1..ar + 1];
+    let theta = &coef[ar + 1..];
+    let mut residuals = vec![0.0; w.len()];
+    let mut css = 0.0;
+    for t in ar..w.len() {
+        let mut xt = coef[0];
+        for (j, p) in phi.iter().enumerate() {
+            xt += p * w[t - j - 1];
+        }
+        for (j, q) in theta.iter().enumerate().take(t) {
+            xt += q * residuals[t - j - 1];
+        }
+        if t != deleted {
+            residuals[t] = w[t] - xt;
+            css += residuals[t] * residuals[t];
+        }
+    }
+    css
+}
+
+/// Loss function applied to forecast errors in `diebold_mariano`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Loss {
+    /// Squared error `e^2`.
+    Squared,
+    /// Absolute error `|e|`.
+    Absolute,
+}
+
+impl Loss {
+    fn apply(&self, e: f64) -> f64 {
+        match self {
+            Loss::Squared => e * e,
+            Loss::Absolute => e.abs(),
+        }
+    }
+}
+
+/// Result of a Diebold-Mariano test of equal forecast accuracy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DieboldMariano {
+    /// Forecast horizon of the errors.
+    pub horizon: usize,
+    /// Mean loss differential `mean(L(e1) - L(e2))`. Negative if the first forecasts
+    /// are more accurate.
+    pub mean_differential: f64,
+    /// Test statistic with the Harvey, Leybourne and Newbold small-sample correction.
+    pub statistic: f64,
+    /// Degrees of freedom of the Student t reference distribution, `n - 1`.
+    pub df: usize,
+    /// Two-sided p-value of the hypothesis of equal accuracy.
+    pub p_value: f64,
+    /// One-sided p-value against the alternative that the first forecasts are more
+    /// accurate.
+    pub p_value_less: f64,
+}
+
+/// Diebold-Mariano test comparing the accuracy of two sets of forecast errors of the
+/// same targets.
+///
+/// The variance of the mean loss differential is estimated from its autocovariances
+/// up to lag `h - 1`, as `h`-step errors are autocorrelated up to that lag. If this
+/// estimate is not positive, Bartlett (Newey-West) weights are used instead. The
+/// statistic is scaled by `sqrt((n + 1 - 2h + h (h - 1) / n) / n)` and compared with
+/// Student's t distribution with `n - 1` degrees of freedom (Harvey et al., 1997).
+///
+/// # Arguments
+///
+/// * `&errors1` - Forecast errors of the first model.
+/// * `&errors2` - Forecast errors of the second model, same targets and length.
+/// * `h` - Forecast horizon of the errors.
+/// * `loss` - Loss function applied to the errors.
+///
+/// # Returns
+///
+/// * Test result.
+///
+/// # Example
+///
+/// ```
+/// use arima::diagnostics::{self, Loss};
+/// let e1 = [0.1, -0.2, 0.15, -0.1, 0.05, 0.2, -0.15, 0.1, -0.05, 0.1];
+/// let e2 = [0.9, -1.1, 0.8, -1.2, 1.0, 0.7, -0.9, 1.1, -0.8, 1.0];
+/// let dm = diagnostics::diebold_mariano(&e1, &e2, 1, Loss::Squared).unwrap();
+/// assert!(dm.mean_differential < 0.0);
+/// assert!(dm.p_value_less < 0.01);
+/// ```
+pub fn diebold_mariano(
+    errors1: &[f64],
+    errors2: &[f64],
+    h: usize,
+    loss: Loss,
+) -> Result<DieboldMariano> {
+    let n = errors1.len();
+    if errors2.len() != n {
+        anyhow::bail!("Both sets of errors must have the same length");
+    }
+    if h == 0 || h >= n {
+        anyhow::bail!("h must be within 1 and the number of errors minus one");
+    }
+    if errors1.iter().chain(errors2).any(|e| !e.is_finite()) {
+        anyhow::bail!("Errors must be finite");
+    }
+
+    let d: Vec<f64> = errors1
+        .iter()
+        .zip(errors2)
+        .map(|(a, b)| loss.apply(*a) - loss.apply(*b))
+        .collect();
+    let nf = n as f64;
+    let mean = d.iter().sum::<f64>() / nf;
+    let gamma: Vec<f64> = (0..h)
+        .map(|k| {
+            (k..n)
+                .map(|t| (d[t] - mean) * (d[t - k] - mean))
+                .sum::<f64>()
+                / nf
+        })
+        .collect();
+    let mut long_run = gamma[0] + 2.0 * gamma[1..].iter().sum::<f64>();
+    if long_run <= 0.0 {
+        long_run = gamma[0]
+            + 2.0
+                * gamma[1..]
+                    .iter()
+                    .enumerate()
+                    .map(|(k, g)| (1.0 - (k + 1) as f64 / h as f64) * g)
+                    .sum::<f64>();
+    }
+    if long_run <= 0.0 || long_run.is_nan() {
+        anyhow::bail!("Loss differential has zero variance");
+    }
+
+    let hf = h as f64;
+    let correction = ((nf + 1.0 - 2.0 * hf + hf * (hf - 1.0) / nf) / nf).sqrt();
+    let statistic = correction * mean / (long_run / nf).sqrt();
+    let df = n - 1;
+    let p_value_less = special::student_t_cdf(statistic, df as f64);
+    Ok(DieboldMariano {
+        horizon: h,
+        mean_differential: mean,
+        statistic,
+        df,
+        p_value: 2.0 * special::student_t_cdf(-statistic.abs(), df as f64),
+        p_value_less,
+    })
+}
+
+/// Diebold-Mariano test comparing the `h`-step-ahead forecasts of two fitted models on
+/// the values following the fitted series, see `out_of_sample` and `diebold_mariano`.
+///
+/// # Arguments
+///
+/// * `&fit1` - First fitted model.
+/// * `&fit2` - Second fitted model, fitted to the same series.
+/// * `&test` - Held-out values directly following the fitted series.
+/// * `h` - Forecast horizon.
+/// * `loss` - Loss function applied to the errors.
+///
+/// # Returns
+///
+/// * Test result. A small `p_value_less` supports that the first model forecasts
+///   better.
+pub fn compare_forecasts(
+    fit1: &FittedModel,
+    fit2: &FittedModel,
+    test: &[f64],
+    h: usize,
+    loss: Loss,
+) -> Result<DieboldMariano> {
+    let errors1 = &out_of_sample(fit1, test, h, &[])?.horizons[h - 1].errors;
+    let errors2 = &out_of_sample(fit2, test, h, &[])?.horizons[h - 1].errors;
+    diebold_mariano(errors1, errors2, h, loss)
+}
@@ -0,0 +1,81 @@
+//! Error types with details beyond an error message.
+
+use core::fmt;
+
+use num::Float;
+
+/// A linear system could not be solved because its matrix is singular, not
+/// positive-definite or too ill-conditioned.
+///
+/// Returned inside an `anyhow::Error`; use `downcast_ref::<ConditioningError>()` to
+/// access the details. Systems that can be solved but have a condition number above
+/// `condition_threshold` are reported as a warning of kind `IllConditioned` instead,
+/// e.g. by `acf::ar_coef_rho_checked`. No regularized fallback is attempted; shrinking
+/// the auto-correlations towards zero (`rho_k (1 - lambda)`) is a common remedy.
+///
+/// # Example
+///
+/// ```
+/// use arima::acf;
+/// use arima::error::ConditioningError;
+/// // a lag 1 auto-correlation of 1 makes the Toeplitz matrix singular at order 2
+/// let err = acf::ar_coef_rho(&[1.0, 1.0, 0.5], Some(2)).unwrap_err();
+/// let cond = err.downcast_ref::<ConditioningError>().unwrap();
+/// assert_eq!(cond.order, 2);
+/// assert!(cond.info.is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConditioningError {
+    /// Description of the failure.
+    pub kind: ConditioningKind,
+    /// Raw `info` code returned by LAPACK, if the system was solved with LAPACK.
+    pub info: Option<i32>,
+    /// Estimated 1-norm condition number of the matrix, or of the leading minor at
+    /// `order` if the factorization failed there. Infinite if that matrix is exactly
+    /// singular, NaN for `InvalidArgument`.
+    pub condition: f64,
+    /// Order (AR lag or matrix dimension, starting at 1) at which the failure was
+    /// detected.
+    pub order: usize,
+}
+
+/// Reason for a `ConditioningError`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditioningKind {
+    /// The matrix is singular to working precision.
+    Singular,
+    /// The matrix should be positive-definite, e.g. an auto-correlation matrix, but a
+    /// leading minor is not.
+    NotPositiveDefinite,
+    /// The system was solved, but the condition number exceeds `condition_threshold`,
+    /// so the solution may be inaccurate. Only used for warnings.
+    IllConditioned,
+    /// LAPACK rejected an argument, see the `info` code.
+    InvalidArgument,
+}
+
+impl fmt::Display for ConditioningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ConditioningKind::Singular => write!(f, "Matrix is singular")?,
+            ConditioningKind::NotPositiveDefinite => write!(f, "Matrix is not positive-definite")?,
+            ConditioningKind::IllConditioned => write!(f, "Matrix is ill-conditioned")?,
+            ConditioningKind::InvalidArgument => write!(f, "Invalid argument to LAPACK")?,
+        }
+        write!(f, " at order {}", self.order)?;
+        if let Some(info) = self.info {
+            write!(f, " (LAPACK info {})", info)?;
+        }
+        write!(f, ", estimated condition number {:e}", self.condition)
+    }
+}
+
+/// Condition number above which solutions computed in precision `T` are flagged as
+/// ill-conditioned: `1 / sqrt(epsilon)`, where about half of the significant digits
+/// may be lost. About `6.7e7` for `f64` and `2.9e3` for `f32`.
+pub fn condition_threshold<T: Float>() -> f64 {
+    1.0 / T::epsilon().sqrt().to_f64().unwrap()
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConditioningError {}
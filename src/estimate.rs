@@ -2,16 +2,26 @@ use anyhow::Result;
 
 use num::Float;
 
-use std::cmp::min;
-use std::convert::From;
-use std::fmt::Debug;
-use std::ops::{Add, AddAssign, Div};
-
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::min;
+use core::convert::From;
+use core::fmt::Debug;
+use core::ops::{Add, AddAssign, Div};
+
+#[cfg(feature = "std")]
 use finitediff::FiniteDiff;
+#[cfg(feature = "std")]
 use liblbfgs::lbfgs;
 
 use crate::{acf, util};
 
+/// Maximum number of optimizer iterations used by `fit`.
+pub const FIT_MAX_ITERATIONS: usize = 200;
+
+/// Convergence tolerance on the scaled gradient norm used by `fit`.
+pub const FIT_EPSILON: f64 = 1.0e-5;
+
 /// Calculate residuals given a time series, an intercept, and ARMA parameters
 /// phi and theta. Any differencing and centering should be done before.
 /// Squaring and summing the residuals yields the conditional sum of squares (CSS),
@@ -33,7 +43,7 @@ use crate::{acf, util};
 ///
 /// ```
 /// use arima::estimate;
-/// let x = [1.0, 1.2, 1.4, 1.6];
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
 /// let res = estimate::residuals(&x, 0.0, Some(&[0.6, 0.4]), Some(&[0.3])).unwrap();
 /// assert!((res[0] - 0.00).abs() < 1.0e-7);
 /// assert!((res[1] - 0.00).abs() < 1.0e-7);
@@ -46,6 +56,38 @@ pub fn residuals<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div
     phi: Option<&[T]>,
     theta: Option<&[T]>,
 ) -> Result<Vec<T>> {
+    let mut residuals: Vec<T> = Vec::new();
+    residuals_into(x, intercept, phi, theta, &mut residuals)?;
+    Ok(residuals)
+}
+
+/// Calculate residuals into an existing buffer. Same as `residuals`, but reuses the
+/// allocation of `residuals`, which is useful when evaluating the CSS many times.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `intercept` - Intercept parameter.
+/// * `&phi` - AR parameter vector.
+/// * `&theta` - MA parameter vector.
+/// * `&mut residuals` - Output buffer. Will be resized to the length of `x`.
+///
+/// # Example
+///
+/// ```
+/// use arima::estimate;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let mut res = Vec::new();
+/// estimate::residuals_into(&x, 0.0, Some(&[0.6, 0.4]), Some(&[0.3]), &mut res).unwrap();
+/// assert!((res[3] - 0.196).abs() < 1.0e-7);
+/// ```
+pub fn residuals_into<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div + Debug>(
+    x: &[T],
+    intercept: T,
+    phi: Option<&[T]>,
+    theta: Option<&[T]>,
+    residuals: &mut Vec<T>,
+) -> Result<()> {
     let phi = phi.unwrap_or(&[]);
     let theta = theta.unwrap_or(&[]);
 
@@ -55,7 +97,7 @@ pub fn residuals<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div
 
     let zero: T = From::from(0.0);
 
-    let mut residuals: Vec<T> = Vec::new();
+    residuals.clear();
     for _ in 0..phi.len() {
         residuals.push(zero);
     }
@@ -70,7 +112,67 @@ pub fn residuals<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div
         residuals.push(x[t] - xt);
     }
 
-    Ok(residuals)
+    Ok(())
+}
+
+/// Calculate the conditional sum of squares (CSS) of the residuals without storing
+/// them. Gives the same result as summing the squares of `residuals`, but only keeps
+/// the last `theta.len()` residuals in memory, so it can stream over very long series.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `intercept` - Intercept parameter.
+/// * `&phi` - AR parameter vector.
+/// * `&theta` - MA parameter vector.
+///
+/// # Returns
+///
+/// * Conditional sum of squares.
+///
+/// # Example
+///
+/// ```
+/// use arima::estimate;
+/// let x = [1.0_f64, 1.2, 1.4, 1.6];
+/// let css = estimate::css(&x, 0.0, Some(&[0.6, 0.4]), Some(&[0.3])).unwrap();
+/// assert!((css - (0.28 * 0.28 + 0.196 * 0.196)).abs() < 1.0e-7);
+/// ```
+pub fn css<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div + Debug>(
+    x: &[T],
+    intercept: T,
+    phi: Option<&[T]>,
+    theta: Option<&[T]>,
+) -> Result<T> {
+    let phi = phi.unwrap_or(&[]);
+    let theta = theta.unwrap_or(&[]);
+
+    if x.len() < phi.len() || x.len() < theta.len() {
+        anyhow::bail!("Too many items in phi or theta");
+    }
+
+    let zero: T = From::from(0.0);
+
+    // most recent residual first. residuals before the start of the series are zero.
+    let mut recent: Vec<T> = vec![zero; theta.len()];
+    let mut css = zero;
+    for t in phi.len()..x.len() {
+        let mut xt: T = intercept;
+        for j in 0..phi.len() {
+            xt += phi[j] * x[t - j - 1];
+        }
+        for j in 0..theta.len() {
+            xt += theta[j] * recent[j];
+        }
+        let residual = x[t] - xt;
+        css += residual * residual;
+        if !recent.is_empty() {
+            recent.rotate_right(1);
+            recent[0] = residual;
+        }
+    }
+
+    Ok(css)
 }
 
 /// Fit an ARIMA model. Returns the fitted coefficients.
@@ -88,6 +190,17 @@ pub fn residuals<T: Float + From<u32> + From<f64> + Copy + Add + AddAssign + Div
 ///
 /// * ARIMA coefficients minimizing the conditional sum of squares (CSS).
 ///
+/// # Reproducibility
+///
+/// The optimization is single-threaded and uses forward-difference gradients, so
+/// identical inputs yield bit-identical coefficients. The optimizer stops after
+/// `FIT_MAX_ITERATIONS` iterations or once `|g| / max(1, |coef|) < FIT_EPSILON`;
+/// coefficients are only guaranteed to be optimal up to this tolerance.
+///
+/// Without the `std` feature, L-BFGS is replaced by a pure Rust BFGS with a
+/// backtracking line search and the same stopping rule. Its coefficients agree with
+/// the `std` build up to the tolerance, but not bit for bit.
+///
 /// # Example
 ///
 /// ```
@@ -119,7 +232,7 @@ pub fn fit<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign
 
     // The objective is to minimize the conditional sum of squares (CSS),
     // i.e. the sum of the squared residuals
-    let f = |coef: &Vec<f64>| {
+    let f = |coef: &[f64]| {
         assert_eq!(coef.len(), total_size);
 
         let intercept = coef[0];
@@ -134,8 +247,6 @@ pub fn fit<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign
         }
         css
     };
-    let g = |coef: &Vec<f64>| coef.forward_diff(&f);
-
     // Initial coefficients
     // Todo: These initial guesses are rather arbitrary.
     let mut coef: Vec<f64> = Vec::new();
@@ -156,6 +267,18 @@ pub fn fit<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign
         coef.resize(coef.len() + ma, 1.0);
     }
 
+    minimize(f, &mut coef);
+
+    Ok(coef)
+}
+
+/// Minimize `f` with L-BFGS using forward-difference gradients, starting at `coef`,
+/// which is updated in place. Uses the same settings as `fit`.
+#[cfg(feature = "std")]
+pub(crate) fn minimize<F: Fn(&[f64]) -> f64>(f: F, coef: &mut [f64]) {
+    let fv = |coef: &Vec<f64>| f(coef);
+    let g = |coef: &Vec<f64>| coef.forward_diff(&fv);
+
     let evaluate = |x: &[f64], gx: &mut [f64]| {
         let x_vec = x.to_vec();
         let fx = f(&x_vec);
@@ -165,18 +288,104 @@ pub fn fit<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign
         Ok(fx)
     };
 
-    let fmin = lbfgs().with_max_iterations(200);
+    let fmin = lbfgs()
+        .with_max_iterations(FIT_MAX_ITERATIONS)
+        .with_epsilon(FIT_EPSILON);
     if let Err(e) = fmin.minimize(
-        &mut coef, // input variables
-        evaluate,  // define how to evaluate function
+        coef,     // input variables
+        evaluate, // define how to evaluate function
         |_prgr| {
             false // returning true will cancel optimization
         },
     ) {
         tracing::warn!("Got error during fit: {}", e);
     }
+}
 
-    Ok(coef)
+/// Minimize `f` with BFGS using forward-difference gradients, starting at `coef`,
+/// which is updated in place. Replaces L-BFGS without `std` and stops under the same
+/// conditions.
+#[cfg(not(feature = "std"))]
+pub(crate) fn minimize<F: Fn(&[f64]) -> f64>(f: F, coef: &mut [f64]) {
+    let n = coef.len();
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(u, v)| u * v).sum::<f64>();
+    let identity = || {
+        let mut h = vec![0.0; n * n];
+        for i in 0..n {
+            h[i * n + i] = 1.0;
+        }
+        h
+    };
+
+    let mut fx = f(coef);
+    let mut g = forward_diff(&f, coef, fx);
+    // inverse Hessian approximation, row-major
+    let mut h = identity();
+    for _ in 0..FIT_MAX_ITERATIONS {
+        if Float::sqrt(dot(&g, &g)) / Float::sqrt(dot(coef, coef)).max(1.0) < FIT_EPSILON {
+            return;
+        }
+        let mut p: Vec<f64> = h.chunks(n).map(|row| -dot(row, &g)).collect();
+        let mut slope = dot(&p, &g);
+        if slope.is_nan() || slope >= 0.0 {
+            // not a descent direction: restart from steepest descent
+            h = identity();
+            p = g.iter().map(|v| -v).collect();
+            slope = -dot(&g, &g);
+        }
+
+        // backtracking line search with the Armijo condition
+        let mut step = 1.0;
+        let mut next = vec![0.0; n];
+        let f_next = loop {
+            for i in 0..n {
+                next[i] = coef[i] + step * p[i];
+            }
+            let f_next = f(&next);
+            if f_next <= fx + 1.0e-4 * step * slope {
+                break f_next;
+            }
+            step *= 0.5;
+            if step < 1.0e-12 {
+                tracing::warn!("Line search failed during fit");
+                return;
+            }
+        };
+        let g_next = forward_diff(&f, &next, f_next);
+
+        let s: Vec<f64> = next.iter().zip(coef.iter()).map(|(a, b)| a - b).collect();
+        let y: Vec<f64> = g_next.iter().zip(&g).map(|(a, b)| a - b).collect();
+        let sy = dot(&s, &y);
+        if sy > 1.0e-12 {
+            let rho = 1.0 / sy;
+            let hy: Vec<f64> = h.chunks(n).map(|row| dot(row, &y)).collect();
+            let yhy = dot(&y, &hy);
+            for i in 0..n {
+                for j in 0..n {
+                    h[i * n + j] += rho
+                        * ((1.0 + rho * yhy) * s[i] * s[j] - hy[i] * s[j] - s[i] * hy[j]);
+                }
+            }
+        }
+        coef.copy_from_slice(&next);
+        fx = f_next;
+        g = g_next;
+    }
+}
+
+/// Forward-difference gradient of `f` at `x`, given `fx = f(x)`.
+#[cfg(not(feature = "std"))]
+fn forward_diff<F: Fn(&[f64]) -> f64>(f: &F, x: &[f64], fx: f64) -> Vec<f64> {
+    let h = Float::sqrt(f64::EPSILON);
+    let mut shifted = x.to_vec();
+    (0..x.len())
+        .map(|i| {
+            shifted[i] = x[i] + h;
+            let d = (f(&shifted) - fx) / h;
+            shifted[i] = x[i];
+            d
+        })
+        .collect()
 }
 
 /// TODO clean up
@@ -0,0 +1,691 @@
+use anyhow::Result;
+
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::fmt;
+
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+use crate::{estimate, sim, util};
+
+/// Error component of an ETS model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorType {
+    Additive,
+    Multiplicative,
+}
+
+/// Trend component of an ETS model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendType {
+    None,
+    Additive,
+    /// Additive trend damped by a factor `phi` in (0.8, 0.98).
+    Damped,
+}
+
+/// Seasonal component of an ETS model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeasonType {
+    None,
+    Additive,
+    Multiplicative,
+}
+
+/// Specification of an ETS(error, trend, season) model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EtsSpec {
+    pub error: ErrorType,
+    pub trend: TrendType,
+    pub season: SeasonType,
+}
+
+impl fmt::Display for EtsSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let e = match self.error {
+            ErrorType::Additive => "A",
+            ErrorType::Multiplicative => "M",
+        };
+        let t = match self.trend {
+            TrendType::None => "N",
+            TrendType::Additive => "A",
+            TrendType::Damped => "Ad",
+        };
+        let s = match self.season {
+            SeasonType::None => "N",
+            SeasonType::Additive => "A",
+            SeasonType::Multiplicative => "M",
+        };
+        write!(f, "ETS({},{},{})", e, t, s)
+    }
+}
+
+/// Smoothing parameters of an ETS model. Parameters of absent components are ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Params {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    phi: f64,
+}
+
+/// State of the recursions: level, trend and the last `period` seasonal states, where
+/// `season[t % period]` belongs to observation `t`.
+#[derive(Debug, Clone)]
+struct State {
+    level: f64,
+    trend: f64,
+    season: Vec<f64>,
+}
+
+/// A fitted ETS model.
+///
+/// The one-step-ahead forecast is `mu_t = (l + phi b) + s` or `(l + phi b) s` depending on
+/// the seasonal type, and the innovation is `e_t = y_t - mu_t` (additive errors) or
+/// `e_t = (y_t - mu_t) / mu_t` (multiplicative errors). The error type does not change
+/// the point forecasts, but it changes the likelihood and the forecast distribution.
+#[derive(Debug, Clone)]
+pub struct EtsModel {
+    /// Model specification.
+    pub spec: EtsSpec,
+    /// Seasonal period. 1 for non-seasonal models.
+    pub period: usize,
+    /// Smoothing parameter of the level.
+    pub alpha: f64,
+    /// Smoothing parameter of the trend. `None` if the model has no trend.
+    pub beta: Option<f64>,
+    /// Smoothing parameter of the seasonal component. `None` if the model is not seasonal.
+    pub gamma: Option<f64>,
+    /// Damping parameter. `None` if the trend is not damped.
+    pub phi: Option<f64>,
+    /// Level at the end of the series.
+    pub level: f64,
+    /// Trend at the end of the series. Zero if the model has no trend.
+    pub trend: f64,
+    /// Seasonal states, where `season[t % period]` belongs to time `t`.
+    pub season: Vec<f64>,
+    /// Innovations `e_t`, see the model definition.
+    pub residuals: Vec<f64>,
+    /// One-step-ahead predictions.
+    pub fitted: Vec<f64>,
+    /// Variance of the innovations.
+    pub sigma2: f64,
+    /// Gaussian log-likelihood.
+    pub loglik: f64,
+    /// Number of observations the model was fitted on.
+    pub n: usize,
+}
+
+impl EtsModel {
+    /// Number of parameters: smoothing parameters, initial states and the variance.
+    pub fn n_params(&self) -> usize {
+        let mut k = 3;
+        if self.spec.trend != TrendType::None {
+            k += 2;
+        }
+        if self.spec.trend == TrendType::Damped {
+            k += 1;
+        }
+        if self.spec.season != SeasonType::None {
+            k += self.period;
+        }
+        k
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik + 2.0 * self.n_params() as f64
+    }
+
+    /// Akaike information criterion with small sample correction. Infinite if there
+    /// are not enough observations for the correction.
+    pub fn aicc(&self) -> f64 {
+        let n = self.n as f64;
+        let k = self.n_params() as f64;
+        if n - k - 1.0 <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.aic() + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik + (self.n as f64).ln() * self.n_params() as f64
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            alpha: self.alpha,
+            beta: self.beta.unwrap_or(0.0),
+            gamma: self.gamma.unwrap_or(0.0),
+            phi: self.phi.unwrap_or(1.0),
+        }
+    }
+
+    fn state(&self) -> State {
+        State {
+            level: self.level,
+            trend: self.trend,
+            season: self.season.clone(),
+        }
+    }
+
+    /// Point forecasts for the next `h` time steps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::ets::{self, EtsSpec, ErrorType, SeasonType, TrendType};
+    /// let x: Vec<f64> = (0..20).map(|i| 3.0 + 2.0 * i as f64).collect();
+    /// let spec = EtsSpec {
+    ///     error: ErrorType::Additive,
+    ///     trend: TrendType::Additive,
+    ///     season: SeasonType::None,
+    /// };
+    /// let m = ets::fit(&x, spec, 1).unwrap();
+    /// assert!((m.forecast(1)[0] - 43.0).abs() < 1.0e-3);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        let p = self.params();
+        let mut damp = 0.0;
+        let mut phi_h = 1.0;
+        (1..h + 1)
+            .map(|i| {
+                phi_h *= p.phi;
+                damp += phi_h;
+                let q = self.level + damp * self.trend;
+                let s = self.season[(self.n + i - 1) % self.period];
+                match self.spec.season {
+                    SeasonType::None => q,
+                    SeasonType::Additive => q + s,
+                    SeasonType::Multiplicative => q * s,
+                }
+            })
+            .collect()
+    }
+
+    /// Simulate one future sample path of length `h` with Gaussian innovations.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `rng` - Reference to a mutable `Rng`.
+    ///
+    /// # Returns
+    ///
+    /// * Simulated values for the next `h` time steps.
+    pub fn simulate<R: Rng>(&self, h: usize, rng: &mut R) -> Vec<f64> {
+        let p = self.params();
+        let mut state = self.state();
+        let normal = Normal::new(0.0, self.sigma2.sqrt()).unwrap();
+        (0..h)
+            .map(|i| {
+                let t = self.n + i;
+                let mu = predict(&self.spec, &p, &state, self.period, t);
+                let e = normal.sample(rng);
+                let y = match self.spec.error {
+                    ErrorType::Additive => mu + e,
+                    ErrorType::Multiplicative => mu * (1.0 + e),
+                };
+                update(&self.spec, &p, &mut state, self.period, t, y);
+                y
+            })
+            .collect()
+    }
+
+    /// Variances of the forecast errors for the next `h` time steps.
+    ///
+    /// The variances are exact for models without multiplicative seasonality
+    /// (classes 1 and 2 of Hyndman et al., 2008, chapter 6). With multiplicative
+    /// seasonality they are first-order approximations that hold the seasonal indices
+    /// and levels the errors are scaled by at their forecasts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::ets::{self, EtsSpec, ErrorType, SeasonType, TrendType};
+    /// let x = [3.0, 5.0, 4.0, 6.0, 5.0, 5.5, 4.5, 5.0, 6.0, 4.0, 5.0, 5.5];
+    /// let spec = EtsSpec {
+    ///     error: ErrorType::Additive,
+    ///     trend: TrendType::None,
+    ///     season: SeasonType::None,
+    /// };
+    /// let m = ets::fit(&x, spec, 1).unwrap();
+    /// let v = m.forecast_variance(2);
+    /// assert_eq!(v[0], m.sigma2);
+    /// assert!((v[1] - m.sigma2 * (1.0 + m.alpha * m.alpha)).abs() < 1.0e-12);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let p = self.params();
+        let mu = self.forecast(h);
+        // damped trend multipliers phi + ... + phi^j
+        let mut damp = vec![0.0; h + 1];
+        for j in 1..h + 1 {
+            damp[j] = damp[j - 1] + p.phi.powi(j as i32);
+        }
+        // level without seasonality and seasonal index at forecast step i (from 1)
+        let base = |i: usize| self.level + damp[i] * self.trend;
+        let s = |i: usize| self.season[(self.n + i - 1) % self.period];
+        // effect of the error at step i on the observation at step k > i
+        let c = |i: usize, k: usize| {
+            let j = k - i;
+            let state = p.alpha * (1.0 + p.beta * damp[j]);
+            let seasonal = j % self.period == 0;
+            match self.spec.season {
+                SeasonType::None => state,
+                SeasonType::Additive if seasonal => state + p.gamma,
+                SeasonType::Additive => state,
+                SeasonType::Multiplicative if seasonal => state + p.gamma * base(k) / base(i),
+                SeasonType::Multiplicative => state * s(k) / s(i),
+            }
+        };
+        match (self.spec.error, self.spec.season) {
+            (ErrorType::Multiplicative, SeasonType::None)
+            | (ErrorType::Multiplicative, SeasonType::Additive) => {
+                // theta_k = mu_k^2 + sigma2 sum_j c_j^2 theta_{k-j}
+                let mut theta: Vec<f64> = Vec::with_capacity(h);
+                for k in 1..h + 1 {
+                    let sum: f64 = (1..k).map(|i| c(i, k).powi(2) * theta[i - 1]).sum();
+                    theta.push(mu[k - 1].powi(2) + self.sigma2 * sum);
+                }
+                theta
+                    .iter()
+                    .zip(&mu)
+                    .map(|(t, m)| (1.0 + self.sigma2) * t - m * m)
+                    .collect()
+            }
+            _ => {
+                // variance of the absolute error at step i
+                let error_var = |i: usize| match self.spec.error {
+                    ErrorType::Additive => self.sigma2,
+                    ErrorType::Multiplicative => self.sigma2 * mu[i - 1].powi(2),
+                };
+                (1..h + 1)
+                    .map(|k| {
+                        error_var(k) + (1..k).map(|i| c(i, k).powi(2) * error_var(i)).sum::<f64>()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Prediction intervals from simulated sample paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `level` - Coverage probability of the intervals, e.g. 0.95.
+    /// * `n_paths` - Number of simulated sample paths.
+    /// * `rng` - Reference to a mutable `Rng`.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of (lower, upper) bounds for each of the next `h` time steps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::{ets, sim};
+    /// let x = [3.0, 5.0, 4.0, 6.0, 5.0, 5.5, 4.5, 5.0, 6.0, 4.0, 5.0, 5.5];
+    /// let m = ets::auto_ets(&x, 1).unwrap();
+    /// let mut rng = sim::stream_rng(1, 0);
+    /// let (lower, upper) = m.simulated_intervals(3, 0.9, 500, &mut rng).unwrap();
+    /// let f = m.forecast(3);
+    /// assert!(lower[0] < f[0] && f[0] < upper[0]);
+    /// ```
+    pub fn simulated_intervals<R: Rng>(
+        &self,
+        h: usize,
+        level: f64,
+        n_paths: usize,
+        rng: &mut R,
+    ) -> Result<(Vec<f64>, Vec<f64>)> {
+        sim::path_intervals(h, level, n_paths, || self.simulate(h, rng))
+    }
+}
+
+/// One-step-ahead forecast for time `t` from the current state.
+fn predict(spec: &EtsSpec, p: &Params, state: &State, period: usize, t: usize) -> f64 {
+    let q = state.level + p.phi * state.trend;
+    let s = state.season[t % period];
+    match spec.season {
+        SeasonType::None => q,
+        SeasonType::Additive => q + s,
+        SeasonType::Multiplicative => q * s,
+    }
+}
+
+/// Update the state with observation `y` at time `t`.
+fn update(spec: &EtsSpec, p: &Params, state: &mut State, period: usize, t: usize, y: f64) {
+    let q = state.level + p.phi * state.trend;
+    let j = t % period;
+    let s = state.season[j];
+    let adjusted = match spec.season {
+        SeasonType::None => y,
+        SeasonType::Additive => y - s,
+        SeasonType::Multiplicative => y / s,
+    };
+    let level = q + p.alpha * (adjusted - q);
+    if spec.trend != TrendType::None {
+        let damped = p.phi * state.trend;
+        state.trend = damped + p.beta * (level - state.level - damped);
+    }
+    match spec.season {
+        SeasonType::None => {}
+        SeasonType::Additive => state.season[j] = s + p.gamma * (y - q - s),
+        SeasonType::Multiplicative => state.season[j] = s + p.gamma * (y / q - s),
+    }
+    state.level = level;
+}
+
+/// Heuristic initial states: seasonal indices from the first two periods, level and
+/// trend from a linear regression on the first seasonally adjusted values.
+fn initial_state(x: &[f64], spec: &EtsSpec, period: usize) -> State {
+    let mut season = match spec.season {
+        SeasonType::Multiplicative => vec![1.0; period],
+        _ => vec![0.0; period],
+    };
+    if spec.season != SeasonType::None {
+        let m = period as f64;
+        let means = [
+            x[..period].iter().sum::<f64>() / m,
+            x[period..2 * period].iter().sum::<f64>() / m,
+        ];
+        for (j, s) in season.iter_mut().enumerate() {
+            *s = match spec.season {
+                SeasonType::Multiplicative => (x[j] / means[0] + x[period + j] / means[1]) / 2.0,
+                _ => (x[j] - means[0] + x[period + j] - means[1]) / 2.0,
+            };
+        }
+        // normalize the seasonal indices
+        let total: f64 = season.iter().sum();
+        for s in season.iter_mut() {
+            match spec.season {
+                SeasonType::Multiplicative => *s *= m / total,
+                _ => *s -= total / m,
+            }
+        }
+    }
+
+    let k = x.len().min(10.max(2 * period));
+    let adjusted: Vec<f64> = x[..k]
+        .iter()
+        .enumerate()
+        .map(|(t, v)| match spec.season {
+            SeasonType::None => *v,
+            SeasonType::Additive => v - season[t % period],
+            SeasonType::Multiplicative => v / season[t % period],
+        })
+        .collect();
+
+    let (level, trend) = if spec.trend == TrendType::None {
+        (adjusted[0], 0.0)
+    } else {
+        // regress on t = 1..k, so the intercept is the state before the first observation
+        util::trend_line(&adjusted)
+    };
+
+    State {
+        level,
+        trend,
+        season,
+    }
+}
+
+/// Run the recursions over `x`. Returns fitted values, innovations and the objective
+/// `n ln(sum e^2) + 2 sum ln|mu|` (the last term only for multiplicative errors), which
+/// is minus twice the concentrated log-likelihood up to a constant. Returns `None` if the
+/// model produces non-positive predictions where they are not allowed.
+fn run(
+    x: &[f64],
+    spec: &EtsSpec,
+    p: &Params,
+    state: &mut State,
+    period: usize,
+) -> Option<(Vec<f64>, Vec<f64>, f64)> {
+    let multiplicative =
+        spec.error == ErrorType::Multiplicative || spec.season == SeasonType::Multiplicative;
+    let mut fitted = Vec::with_capacity(x.len());
+    let mut residuals = Vec::with_capacity(x.len());
+    let mut sse = 0.0;
+    let mut log_mu = 0.0;
+    for (t, &y) in x.iter().enumerate() {
+        let mu = predict(spec, p, state, period, t);
+        if multiplicative && (mu <= 0.0 || mu.is_nan()) {
+            return None;
+        }
+        let e = match spec.error {
+            ErrorType::Additive => y - mu,
+            ErrorType::Multiplicative => {
+                log_mu += mu.ln();
+                (y - mu) / mu
+            }
+        };
+        fitted.push(mu);
+        residuals.push(e);
+        sse += e * e;
+        update(spec, p, state, period, t, y);
+    }
+    // guard against perfect fits
+    let objective = x.len() as f64 * sse.max(f64::MIN_POSITIVE).ln() + 2.0 * log_mu;
+    if objective.is_finite() {
+        Some((fitted, residuals, objective))
+    } else {
+        None
+    }
+}
+
+/// Map the unconstrained optimization vector to smoothing parameters. `alpha`, `beta`
+/// and `gamma` are within (0, 1), `phi` is within (0.8, 0.98).
+fn params(z: &[f64], spec: &EtsSpec) -> Params {
+    let mut z = z.iter();
+    let alpha = util::logistic(*z.next().unwrap());
+    let beta = if spec.trend != TrendType::None {
+        util::logistic(*z.next().unwrap())
+    } else {
+        0.0
+    };
+    let gamma = if spec.season != SeasonType::None {
+        util::logistic(*z.next().unwrap())
+    } else {
+        0.0
+    };
+    let phi = if spec.trend == TrendType::Damped {
+        0.8 + 0.18 * util::logistic(*z.next().unwrap())
+    } else {
+        1.0
+    };
+    Params {
+        alpha,
+        beta,
+        gamma,
+        phi,
+    }
+}
+
+/// Fit an ETS model by maximum likelihood. The smoothing parameters are estimated, the
+/// initial states are set heuristically from the first observations.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Must be positive for multiplicative components.
+/// * `spec` - Model specification.
+/// * `period` - Seasonal period. Ignored for non-seasonal models.
+///
+/// # Returns
+///
+/// * Fitted model.
+pub fn fit(x: &[f64], spec: EtsSpec, period: usize) -> Result<EtsModel> {
+    let period = if spec.season == SeasonType::None {
+        1
+    } else {
+        period
+    };
+    if spec.season != SeasonType::None {
+        if period < 2 {
+            anyhow::bail!("Seasonal period must be at least 2");
+        }
+        if x.len() < 2 * period {
+            anyhow::bail!("Need at least two full seasonal periods");
+        }
+    }
+    if x.len() < 4 {
+        anyhow::bail!("Need at least four values");
+    }
+    if (spec.error == ErrorType::Multiplicative || spec.season == SeasonType::Multiplicative)
+        && x.iter().any(|v| *v <= 0.0)
+    {
+        anyhow::bail!("Multiplicative components require positive data");
+    }
+
+    let init = initial_state(x, &spec, period);
+
+    let mut z = vec![util::logit(0.3)];
+    if spec.trend != TrendType::None {
+        z.push(util::logit(0.1));
+    }
+    if spec.season != SeasonType::None {
+        z.push(util::logit(0.1));
+    }
+    if spec.trend == TrendType::Damped {
+        z.push(util::logit((0.95 - 0.8) / 0.18));
+    }
+
+    let f = |z: &[f64]| {
+        let p = params(z, &spec);
+        let mut state = init.clone();
+        match run(x, &spec, &p, &mut state, period) {
+            Some((_, _, objective)) => objective,
+            // steer the optimizer away from invalid regions
+            None => 1.0e10,
+        }
+    };
+    estimate::minimize(f, &mut z);
+
+    let p = params(&z, &spec);
+    let mut state = init;
+    let (fitted, residuals, objective) = match run(x, &spec, &p, &mut state, period) {
+        Some(r) => r,
+        None => anyhow::bail!("Model {} produces invalid forecasts", spec),
+    };
+
+    let n = x.len() as f64;
+    let sigma2 = residuals.iter().map(|e| e * e).sum::<f64>() / n;
+    let loglik = -0.5 * (objective + n * ((2.0 * PI / n).ln() + 1.0));
+
+    Ok(EtsModel {
+        spec,
+        period,
+        alpha: p.alpha,
+        beta: (spec.trend != TrendType::None).then_some(p.beta),
+        gamma: (spec.season != SeasonType::None).then_some(p.gamma),
+        phi: (spec.trend == TrendType::Damped).then_some(p.phi),
+        level: state.level,
+        trend: state.trend,
+        season: state.season,
+        residuals,
+        fitted,
+        sigma2,
+        loglik,
+        n: x.len(),
+    })
+}
+
+/// Fit all admissible ETS models and return the one with the smallest AICc.
+///
+/// Multiplicative components are only considered for positive data, seasonal models
+/// only if `period` is at least 2 and the series covers two full periods, and additive
+/// errors are not combined with multiplicative seasonality as those models are
+/// numerically unstable.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `period` - Seasonal period. Use 1 for non-seasonal data.
+///
+/// # Returns
+///
+/// * Best fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::ets::{self, SeasonType};
+/// let x: Vec<f64> = (0..36).map(|i| 10.0 + [2.0, -1.0, 0.5, -1.5][i % 4]).collect();
+/// let m = ets::auto_ets(&x, 4).unwrap();
+/// assert_ne!(m.spec.season, SeasonType::None);
+/// ```
+pub fn auto_ets(x: &[f64], period: usize) -> Result<EtsModel> {
+    let positive = x.iter().all(|v| *v > 0.0);
+    let seasonal = period >= 2 && x.len() >= 2 * period;
+
+    let mut errors = vec![ErrorType::Additive];
+    if positive {
+        errors.push(ErrorType::Multiplicative);
+    }
+    let trends = [TrendType::None, TrendType::Additive, TrendType::Damped];
+    let mut seasons = vec![SeasonType::None];
+    if seasonal {
+        seasons.push(SeasonType::Additive);
+        if positive {
+            seasons.push(SeasonType::Multiplicative);
+        }
+    }
+
+    let mut best: Option<EtsModel> = None;
+    for &error in errors.iter() {
+        for &trend in trends.iter() {
+            for &season in seasons.iter() {
+                if error == ErrorType::Additive && season == SeasonType::Multiplicative {
+                    continue;
+                }
+                let spec = EtsSpec {
+                    error,
+                    trend,
+                    season,
+                };
+                let model = match fit(x, spec, period) {
+                    Ok(m) => m,
+                    Err(e) => {
+                        tracing::debug!("Skipping {}: {}", spec, e);
+                        continue;
+                    }
+                };
+                let better = match &best {
+                    None => true,
+                    Some(b) => model.aicc().partial_cmp(&b.aicc()) == Some(Ordering::Less),
+                };
+                if better {
+                    best = Some(model);
+                }
+            }
+        }
+    }
+    match best {
+        Some(m) => Ok(m),
+        None => anyhow::bail!("No ETS model could be fitted"),
+    }
+}
+
+/// Same as `auto_ets`, with the seasonal period implied by the sampling frequency of
+/// the series, see `TimeSeries::seasonal_period`. Fails if the series has gaps. Only
+/// enabled with feature `chrono`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{ets, timeseries::{Frequency, TimeSeries}};
+/// use chrono::NaiveDate;
+/// let start = NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+/// let values: Vec<f64> = (0..36).map(|i| 10.0 + [2.0, -1.0, 0.5, -1.5][i % 4]).collect();
+/// let x = TimeSeries::new(values, start, Frequency::quarterly()).unwrap();
+/// let m = ets::auto_ets_series(&x).unwrap();
+/// assert_eq!(m.period, 4);
+/// ```
+#[cfg(feature = "chrono")]
+pub fn auto_ets_series(x: &TimeSeries<f64>) -> Result<EtsModel> {
+    auto_ets(x.regular_values()?, x.seasonal_period())
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use num::Float;
+
+use alloc::vec::Vec;
+
+/// Exponentially weighted moving mean and variance, updated one value at a time.
+///
+/// After the first value, which initializes the mean, each value `x` updates the
+/// statistics as `d = x - mean`, `mean += alpha d` and
+/// `variance = (1 - alpha) (variance + alpha d^2)` (West, 1979). Memory use is
+/// constant.
+///
+/// # Example
+///
+/// ```
+/// use arima::ewma::Ewma;
+/// let mut e = Ewma::from_halflife(10.0_f64).unwrap();
+/// e.extend([1.0, 1.2, 0.9, 1.1, 1.0]);
+/// assert!((e.mean().unwrap() - 1.0).abs() < 0.1);
+/// // a value far outside the recent range
+/// assert!(e.score(3.0).unwrap() > 5.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ewma<T> {
+    alpha: T,
+    n: usize,
+    mean: T,
+    variance: T,
+}
+
+impl<T: Float> Ewma<T> {
+    /// Create an accumulator with smoothing parameter `alpha`, the weight of the newest
+    /// value, within 0 (exclusive) and 1.
+    pub fn new(alpha: T) -> Result<Self> {
+        if !(alpha > T::zero() && alpha <= T::one()) {
+            anyhow::bail!("alpha must be within 0 (exclusive) and 1");
+        }
+        Ok(Ewma {
+            alpha,
+            n: 0,
+            mean: T::zero(),
+            variance: T::zero(),
+        })
+    }
+
+    /// Create an accumulator whose weights halve every `halflife` values, i.e. with
+    /// `alpha = 1 - 0.5^(1 / halflife)`.
+    pub fn from_halflife(halflife: T) -> Result<Self> {
+        if halflife.is_nan() || halflife <= T::zero() {
+            anyhow::bail!("halflife must be positive");
+        }
+        let half = T::from(0.5).unwrap();
+        Self::new(T::one() - half.powf(T::one() / halflife))
+    }
+
+    /// Smoothing parameter.
+    pub fn alpha(&self) -> T {
+        self.alpha
+    }
+
+    /// Number of values pushed so far.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// True if no values have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Add the next value of the series.
+    pub fn push(&mut self, x: T) {
+        if self.n == 0 {
+            self.mean = x;
+        } else {
+            let d = x - self.mean;
+            self.mean = self.mean + self.alpha * d;
+            self.variance = (T::one() - self.alpha) * (self.variance + self.alpha * d * d);
+        }
+        self.n += 1;
+    }
+
+    /// Exponentially weighted mean, `None` before the first value.
+    pub fn mean(&self) -> Option<T> {
+        if self.n == 0 {
+            None
+        } else {
+            Some(self.mean)
+        }
+    }
+
+    /// Exponentially weighted variance, `None` before the second value.
+    pub fn variance(&self) -> Option<T> {
+        if self.n < 2 {
+            None
+        } else {
+            Some(self.variance)
+        }
+    }
+
+    /// Exponentially weighted standard deviation, `None` before the second value.
+    pub fn std_dev(&self) -> Option<T> {
+        self.variance().map(|v| v.sqrt())
+    }
+
+    /// Control chart style anomaly score of a new value: its deviation from the mean
+    /// in standard deviations, `(x - mean) / std_dev`, computed before the value is
+    /// pushed. Absolute scores above 3 are commonly flagged. `None` before the second
+    /// value or while the variance is zero.
+    pub fn score(&self, x: T) -> Option<T> {
+        match self.std_dev() {
+            Some(sd) if sd > T::zero() => Some((x - self.mean) / sd),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Float> Extend<T> for Ewma<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+/// Calculate the exponentially weighted moving mean of a time series, see `Ewma`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `alpha` - Smoothing parameter within 0 (exclusive) and 1.
+///
+/// # Returns
+///
+/// * Output vector of the same length, the mean after each value.
+///
+/// # Example
+///
+/// ```
+/// use arima::ewma;
+/// let m = ewma::ewma(&[1.0, 2.0, 3.0], 0.5).unwrap();
+/// assert_eq!(m, vec![1.0, 1.5, 2.25]);
+/// ```
+pub fn ewma<T: Float>(x: &[T], alpha: T) -> Result<Vec<T>> {
+    let mut e = Ewma::new(alpha)?;
+    Ok(x.iter()
+        .map(|v| {
+            e.push(*v);
+            e.mean
+        })
+        .collect())
+}
+
+/// Calculate the anomaly score of each value of a time series against the
+/// exponentially weighted statistics of the values before it, see `Ewma::score`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `alpha` - Smoothing parameter within 0 (exclusive) and 1.
+///
+/// # Returns
+///
+/// * Output vector of the same length. NaN where no score is defined, e.g. for the
+///   first two values.
+///
+/// # Example
+///
+/// ```
+/// use arima::ewma;
+/// let x = [1.0_f64, 1.1, 0.9, 1.0, 1.1, 0.9, 1.0, 4.0, 1.0];
+/// let s = ewma::anomaly_scores(&x, 0.3).unwrap();
+/// assert!(s[0].is_nan() && s[1].is_nan());
+/// assert!(s[7] > 3.0);
+/// assert!(s[6].abs() < 3.0);
+/// ```
+pub fn anomaly_scores<T: Float>(x: &[T], alpha: T) -> Result<Vec<T>> {
+    let mut e = Ewma::new(alpha)?;
+    Ok(x.iter()
+        .map(|v| {
+            let s = e.score(*v).unwrap_or_else(T::nan);
+            e.push(*v);
+            s
+        })
+        .collect())
+}
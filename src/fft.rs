@@ -0,0 +1,107 @@
+//! Fast Fourier transforms of any length for the periodogram and surrogate data:
+//! radix-2 Cooley-Tukey for powers of two and Bluestein's chirp z-transform, which
+//! reduces other lengths to a convolution of power-of-two length, both in
+//! `O(n log n)` time.
+
+use num::complex::Complex64;
+
+use std::f64::consts::PI;
+
+/// Discrete Fourier transform `X_k = sum_t x_t exp(-2 pi i k t / n)`.
+pub(crate) fn fft(x: &[Complex64]) -> Vec<Complex64> {
+    let n = x.len();
+    if n.is_power_of_two() {
+        let mut a = x.to_vec();
+        radix2(&mut a, -1.0);
+        a
+    } else if n == 0 {
+        Vec::new()
+    } else {
+        bluestein(x)
+    }
+}
+
+/// Discrete Fourier transform of a real series.
+pub(crate) fn real_fft(x: &[f64]) -> Vec<Complex64> {
+    let x: Vec<Complex64> = x.iter().map(|v| Complex64::new(*v, 0.0)).collect();
+    fft(&x)
+}
+
+/// Inverse discrete Fourier transform `x_t = sum_k X_k exp(2 pi i k t / n) / n`.
+pub(crate) fn ifft(x: &[Complex64]) -> Vec<Complex64> {
+    let n = x.len() as f64;
+    let conjugate: Vec<Complex64> = x.iter().map(|v| v.conj()).collect();
+    fft(&conjugate).into_iter().map(|v| v.conj() / n).collect()
+}
+
+/// In-place unnormalized transform of a power-of-two length with the exponent sign
+/// `sign`, i.e. -1 for the forward and 1 for the inverse transform.
+fn radix2(a: &mut [Complex64], sign: f64) {
+    let n = a.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            a.swap(i, j);
+        }
+    }
+    // twiddle factors computed directly rather than by repeated multiplication, which
+    // accumulates rounding errors
+    let twiddles: Vec<Complex64> = (0..n / 2)
+        .map(|k| Complex64::from_polar(1.0, sign * 2.0 * PI * k as f64 / n as f64))
+        .collect();
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        let stride = n / len;
+        for block in a.chunks_mut(len) {
+            let (lo, hi) = block.split_at_mut(half);
+            for (k, (u, v)) in lo.iter_mut().zip(hi.iter_mut()).enumerate() {
+                let w = *v * twiddles[k * stride];
+                *v = *u - w;
+                *u += w;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Transform of any length as a circular convolution with the chirp
+/// `c_t = exp(-i pi t^2 / n)`, using `k t = (k^2 + t^2 - (k - t)^2) / 2`.
+fn bluestein(x: &[Complex64]) -> Vec<Complex64> {
+    let n = x.len();
+    let m = (2 * n - 1).next_power_of_two();
+    // t^2 modulo 2n keeps the angles small and accurate
+    let chirp: Vec<Complex64> = (0..n)
+        .map(|t| {
+            let t2 = ((t as u64 * t as u64) % (2 * n as u64)) as f64;
+            Complex64::from_polar(1.0, -PI * t2 / n as f64)
+        })
+        .collect();
+    let zero = Complex64::new(0.0, 0.0);
+    let mut a = vec![zero; m];
+    for (ai, (xt, ct)) in a.iter_mut().zip(x.iter().zip(&chirp)) {
+        *ai = xt * ct;
+    }
+    let mut b = vec![zero; m];
+    b[0] = chirp[0].conj();
+    for (t, c) in chirp.iter().enumerate().skip(1) {
+        b[t] = c.conj();
+        b[m - t] = c.conj();
+    }
+    radix2(&mut a, -1.0);
+    radix2(&mut b, -1.0);
+    for (u, v) in a.iter_mut().zip(&b) {
+        *u *= v;
+    }
+    radix2(&mut a, 1.0);
+    a.iter()
+        .zip(&chirp)
+        .map(|(v, c)| v * c / m as f64)
+        .collect()
+}
@@ -0,0 +1,470 @@
+use anyhow::Result;
+
+use std::f64::consts::PI;
+
+use crate::estimate;
+use crate::special::{self, ln_gamma};
+
+/// Variance equation of a GARCH model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GarchKind {
+    /// `s2_t = omega + sum(alpha_i e2_{t-i}) + sum(beta_j s2_{t-j})`.
+    Garch,
+    /// GJR-GARCH with an additional term `gamma_i e2_{t-i}` for negative shocks.
+    Gjr,
+    /// Exponential GARCH:
+    /// `ln s2_t = omega + sum(alpha_i (|z_{t-i}| - E|z|) + gamma_i z_{t-i}) + sum(beta_j ln s2_{t-j})`.
+    Egarch,
+}
+
+/// Distribution of the standardized innovations `z_t = e_t / s_t`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Innovations {
+    Normal,
+    /// Student's t distribution scaled to unit variance, with `nu > 2` degrees of freedom.
+    StudentT,
+}
+
+/// Specification of a GARCH(p, q) model with `p` ARCH and `q` GARCH terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GarchSpec {
+    pub kind: GarchKind,
+    pub p: usize,
+    pub q: usize,
+    pub innovations: Innovations,
+}
+
+/// A fitted GARCH model for returns `r_t = mu + e_t` with `e_t = s_t z_t`, where the
+/// conditional variance `s2_t` follows the equation given by `GarchKind`.
+#[derive(Debug, Clone)]
+pub struct GarchModel {
+    /// Model specification.
+    pub spec: GarchSpec,
+    /// Mean of the returns.
+    pub mu: f64,
+    /// Intercept of the variance equation.
+    pub omega: f64,
+    /// ARCH coefficients.
+    pub alpha: Vec<f64>,
+    /// Asymmetry coefficients. Empty for `GarchKind::Garch`.
+    pub gamma: Vec<f64>,
+    /// GARCH coefficients.
+    pub beta: Vec<f64>,
+    /// Degrees of freedom of the innovations. `None` for normal innovations.
+    pub nu: Option<f64>,
+    /// Conditional variances `s2_t`.
+    pub variance: Vec<f64>,
+    /// Standardized residuals `z_t`.
+    pub residuals: Vec<f64>,
+    /// Log-likelihood.
+    pub loglik: f64,
+    /// Number of observations the model was fitted on.
+    pub n: usize,
+    // last max(p, q) lags, oldest first, for forecasting
+    tail: Vec<Lag>,
+}
+
+/// Terms of one past time step entering the variance equation. For forecasts, the
+/// terms are replaced by their conditional expectation.
+#[derive(Debug, Clone, Copy)]
+struct Lag {
+    /// Squared shock `e2`.
+    e2: f64,
+    /// Squared shock if negative, zero otherwise.
+    e2_neg: f64,
+    /// Centered absolute standardized shock `|z| - E|z|`.
+    z_abs: f64,
+    /// Standardized shock `z`.
+    z: f64,
+    /// Conditional variance.
+    s2: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Params {
+    mu: f64,
+    omega: f64,
+    alpha: Vec<f64>,
+    gamma: Vec<f64>,
+    beta: Vec<f64>,
+    nu: Option<f64>,
+}
+
+impl GarchModel {
+    /// Number of estimated parameters.
+    pub fn n_params(&self) -> usize {
+        2 + self.alpha.len() + self.gamma.len() + self.beta.len() + self.nu.map_or(0, |_| 1)
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik + 2.0 * self.n_params() as f64
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik + (self.n as f64).ln() * self.n_params() as f64
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            mu: self.mu,
+            omega: self.omega,
+            alpha: self.alpha.clone(),
+            gamma: self.gamma.clone(),
+            beta: self.beta.clone(),
+            nu: self.nu,
+        }
+    }
+
+    /// Forecasts of the conditional variance for the next `h` time steps.
+    ///
+    /// For GARCH and GJR-GARCH models the forecasts are the conditional expectations. For
+    /// EGARCH models, future shocks are set to their expectation in the log-variance
+    /// equation, which is exact for one step ahead and an approximation beyond.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::garch::{self, GarchKind, GarchSpec, Innovations};
+    /// use arima::sim;
+    /// use rand_distr::{Distribution, Normal};
+    /// // simulate a GARCH(1, 1) process
+    /// let mut rng = sim::stream_rng(1, 0);
+    /// let normal = Normal::new(0.0, 1.0).unwrap();
+    /// let (mut e, mut s2) = (0.0_f64, 1.0);
+    /// let x: Vec<f64> = (0..1000)
+    ///     .map(|_| {
+    ///         s2 = 0.2 + 0.2 * e * e + 0.6 * s2;
+    ///         e = s2.sqrt() * normal.sample(&mut rng);
+    ///         e
+    ///     })
+    ///     .collect();
+    /// let spec = GarchSpec { kind: GarchKind::Garch, p: 1, q: 1, innovations: Innovations::Normal };
+    /// let m = garch::fit(&x, &spec).unwrap();
+    /// let f = m.forecast_variance(500);
+    /// // the forecasts converge to the unconditional variance
+    /// let unconditional = m.omega / (1.0 - m.alpha[0] - m.beta[0]);
+    /// assert!((f[499] - unconditional).abs() < 1.0e-3 * unconditional);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let p = self.params();
+        let mut hist = self.tail.clone();
+        let mut out = Vec::with_capacity(h);
+        for _ in 0..h {
+            let s2 = next_variance(&self.spec, &p, &hist);
+            if !hist.is_empty() {
+                hist.remove(0);
+                hist.push(Lag {
+                    e2: s2,
+                    // the innovations are symmetric
+                    e2_neg: 0.5 * s2,
+                    z_abs: 0.0,
+                    z: 0.0,
+                    s2,
+                });
+            }
+            out.push(s2);
+        }
+        out
+    }
+
+    /// Value at risk of the returns for each of the next `h` time steps, i.e. the loss
+    /// `-(mu + s_t q)` exceeded with probability `1 - level`, where `q` is the
+    /// `1 - level` quantile of the standardized innovations.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `level` - Confidence level, e.g. 0.99.
+    ///
+    /// # Returns
+    ///
+    /// * Value at risk for each time step, positive for losses.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::garch::{self, GarchKind, GarchSpec, Innovations};
+    /// let x: Vec<f64> = (0..200).map(|t| ((t * 37) % 17) as f64 / 17.0 - 0.5).collect();
+    /// let spec = GarchSpec { kind: GarchKind::Gjr, p: 1, q: 1, innovations: Innovations::Normal };
+    /// let m = garch::fit(&x, &spec).unwrap();
+    /// let var95 = m.value_at_risk(1, 0.95).unwrap();
+    /// let var99 = m.value_at_risk(1, 0.99).unwrap();
+    /// assert!(var99[0] > var95[0]);
+    /// ```
+    pub fn value_at_risk(&self, h: usize, level: f64) -> Result<Vec<f64>> {
+        if !(level > 0.0 && level < 1.0) {
+            anyhow::bail!("level must be within (0, 1)");
+        }
+        let q = innovation_quantile(1.0 - level, self.nu);
+        Ok(self
+            .forecast_variance(h)
+            .iter()
+            .map(|s2| -(self.mu + s2.sqrt() * q))
+            .collect())
+    }
+}
+
+/// Quantile of the standardized innovations.
+fn innovation_quantile(p: f64, nu: Option<f64>) -> f64 {
+    match nu {
+        None => special::normal_quantile(p),
+        Some(nu) => special::student_t_quantile(p, nu) * ((nu - 2.0) / nu).sqrt(),
+    }
+}
+
+/// Expected absolute value of the standardized innovations.
+fn expected_abs(nu: Option<f64>) -> f64 {
+    match nu {
+        None => (2.0 / PI).sqrt(),
+        Some(nu) => {
+            2.0 * (nu - 2.0).sqrt() * (ln_gamma(0.5 * (nu + 1.0)) - ln_gamma(0.5 * nu)).exp()
+                / ((nu - 1.0) * PI.sqrt())
+        }
+    }
+}
+
+/// Conditional variance from the last `max(p, q)` lags (oldest first).
+fn next_variance(spec: &GarchSpec, p: &Params, hist: &[Lag]) -> f64 {
+    let lag = |i: usize| &hist[hist.len() - 1 - i];
+    match spec.kind {
+        GarchKind::Garch | GarchKind::Gjr => {
+            let mut s2 = p.omega;
+            for (i, a) in p.alpha.iter().enumerate() {
+                s2 += a * lag(i).e2;
+            }
+            for (i, g) in p.gamma.iter().enumerate() {
+                s2 += g * lag(i).e2_neg;
+            }
+            for (j, b) in p.beta.iter().enumerate() {
+                s2 += b * lag(j).s2;
+            }
+            s2
+        }
+        GarchKind::Egarch => {
+            let mut ln_s2 = p.omega;
+            for (i, a) in p.alpha.iter().enumerate() {
+                ln_s2 += a * lag(i).z_abs;
+            }
+            for (i, g) in p.gamma.iter().enumerate() {
+                ln_s2 += g * lag(i).z;
+            }
+            for (j, b) in p.beta.iter().enumerate() {
+                ln_s2 += b * lag(j).s2.ln();
+            }
+            ln_s2.exp()
+        }
+    }
+}
+
+/// Log-density of a shock `e` with conditional variance `s2`.
+fn log_density(e: f64, s2: f64, nu: Option<f64>) -> f64 {
+    match nu {
+        None => -0.5 * ((2.0 * PI).ln() + s2.ln() + e * e / s2),
+        Some(nu) => {
+            ln_gamma(0.5 * (nu + 1.0))
+                - ln_gamma(0.5 * nu)
+                - 0.5 * (PI * (nu - 2.0)).ln()
+                - 0.5 * s2.ln()
+                - 0.5 * (nu + 1.0) * (1.0 + e * e / (s2 * (nu - 2.0))).ln()
+        }
+    }
+}
+
+/// Run the variance recursion. Pre-sample lags are set to the sample variance with zero
+/// standardized shocks. Returns variances, standardized residuals, the log-likelihood
+/// and the last `max(p, q)` lags.
+fn run(x: &[f64], spec: &GarchSpec, p: &Params, var: f64) -> (Vec<f64>, Vec<f64>, f64, Vec<Lag>) {
+    let e_abs = expected_abs(p.nu);
+    let m = spec.p.max(spec.q);
+    let mut hist = vec![
+        Lag {
+            e2: var,
+            e2_neg: 0.5 * var,
+            z_abs: 0.0,
+            z: 0.0,
+            s2: var,
+        };
+        m
+    ];
+    let mut variance = Vec::with_capacity(x.len());
+    let mut residuals = Vec::with_capacity(x.len());
+    let mut loglik = 0.0;
+    for v in x {
+        let s2 = next_variance(spec, p, &hist);
+        let e = v - p.mu;
+        let z = e / s2.sqrt();
+        loglik += log_density(e, s2, p.nu);
+        if m > 0 {
+            hist.remove(0);
+            hist.push(Lag {
+                e2: e * e,
+                e2_neg: if e < 0.0 { e * e } else { 0.0 },
+                z_abs: z.abs() - e_abs,
+                z,
+                s2,
+            });
+        }
+        variance.push(s2);
+        residuals.push(z);
+    }
+    (variance, residuals, loglik, hist)
+}
+
+/// Map the unconstrained optimization vector to the parameters.
+///
+/// For GARCH and GJR-GARCH the coefficients are positive with
+/// `sum(alpha) + sum(gamma) / 2 + sum(beta) < 1`. For EGARCH, `beta_j = tanh(z_j)`;
+/// the stationarity condition `sum(|beta|) < 1` is enforced by a penalty.
+fn params(z: &[f64], spec: &GarchSpec) -> Params {
+    let n_gamma = if spec.kind == GarchKind::Garch {
+        0
+    } else {
+        spec.p
+    };
+    let n = spec.p + n_gamma + spec.q;
+    let mu = z[0];
+    let (omega, alpha, gamma, beta) = match spec.kind {
+        GarchKind::Garch | GarchKind::Gjr => {
+            let u: Vec<f64> = z[2..2 + n].iter().map(|v| v.exp()).collect();
+            let total = 1.0 + u.iter().sum::<f64>();
+            let alpha = u[..spec.p].iter().map(|v| v / total).collect();
+            let gamma = u[spec.p..spec.p + n_gamma]
+                .iter()
+                .map(|v| 2.0 * v / total)
+                .collect();
+            let beta = u[spec.p + n_gamma..].iter().map(|v| v / total).collect();
+            (z[1].exp(), alpha, gamma, beta)
+        }
+        GarchKind::Egarch => {
+            let alpha = z[2..2 + spec.p].to_vec();
+            let gamma = z[2 + spec.p..2 + spec.p + n_gamma].to_vec();
+            let beta = z[2 + spec.p + n_gamma..2 + n]
+                .iter()
+                .map(|v| v.tanh())
+                .collect();
+            (z[1], alpha, gamma, beta)
+        }
+    };
+    let nu = match spec.innovations {
+        Innovations::Normal => None,
+        Innovations::StudentT => Some(2.0 + z[2 + n].exp()),
+    };
+    Params {
+        mu,
+        omega,
+        alpha,
+        gamma,
+        beta,
+        nu,
+    }
+}
+
+/// Estimate a GARCH, GJR-GARCH or EGARCH model by maximum likelihood.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the returns.
+/// * `&spec` - Model specification. Needs at least one ARCH term.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::garch::{self, GarchKind, GarchSpec, Innovations};
+/// let x: Vec<f64> = (0..200).map(|t| ((t * 37) % 17) as f64 / 17.0 - 0.5).collect();
+/// let spec = GarchSpec { kind: GarchKind::Egarch, p: 1, q: 1, innovations: Innovations::StudentT };
+/// let m = garch::fit(&x, &spec).unwrap();
+/// assert!(m.nu.unwrap() > 2.0);
+/// assert_eq!(m.variance.len(), 200);
+/// ```
+pub fn fit(x: &[f64], spec: &GarchSpec) -> Result<GarchModel> {
+    if spec.p == 0 {
+        anyhow::bail!("Need at least one ARCH term");
+    }
+    let n_gamma = if spec.kind == GarchKind::Garch {
+        0
+    } else {
+        spec.p
+    };
+    let n_params = 2 + spec.p + n_gamma + spec.q;
+    if x.len() <= n_params + spec.p.max(spec.q) + 1 {
+        anyhow::bail!("Time series is too short for the model order");
+    }
+    let n = x.len() as f64;
+    let mean = x.iter().sum::<f64>() / n;
+    let var = x.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+    if var <= 0.0 || var.is_nan() {
+        anyhow::bail!("Returns must not be constant");
+    }
+
+    // start with persistence 0.9 split across the terms
+    let (a, g, b) = if spec.q > 0 {
+        (0.05, 0.05, 0.85)
+    } else {
+        (0.5, 0.1, 0.0)
+    };
+    let a = a / spec.p as f64;
+    let g = g / spec.p as f64;
+    let b = if spec.q > 0 { b / spec.q as f64 } else { 0.0 };
+    let mut z = vec![mean];
+    match spec.kind {
+        GarchKind::Garch | GarchKind::Gjr => {
+            let g = if spec.kind == GarchKind::Gjr { g } else { 0.0 };
+            let slack = 1.0 - spec.p as f64 * (a + 0.5 * g) - spec.q as f64 * b;
+            z.push((var * slack).ln());
+            z.extend(vec![(a / slack).ln(); spec.p]);
+            z.extend(vec![(g / slack).ln(); n_gamma]);
+            z.extend(vec![(b / slack).ln(); spec.q]);
+        }
+        GarchKind::Egarch => {
+            z.push(var.ln() * (1.0 - spec.q as f64 * b));
+            z.extend(vec![0.1 / spec.p as f64; spec.p]);
+            z.extend(vec![0.0; n_gamma]);
+            z.extend(vec![b.atanh(); spec.q]);
+        }
+    }
+    if spec.innovations == Innovations::StudentT {
+        z.push(6.0_f64.ln());
+    }
+
+    let f = |z: &[f64]| {
+        let p = params(z, spec);
+        if p.beta.iter().map(|b| b.abs()).sum::<f64>() >= 1.0 {
+            return 1.0e10;
+        }
+        let (_, _, loglik, _) = run(x, spec, &p, var);
+        if loglik.is_finite() {
+            -loglik
+        } else {
+            // steer the optimizer away from invalid regions
+            1.0e10
+        }
+    };
+    estimate::minimize(f, &mut z);
+
+    let p = params(&z, spec);
+    let (variance, residuals, loglik, tail) = run(x, spec, &p, var);
+    if !loglik.is_finite() {
+        anyhow::bail!("Estimation did not converge");
+    }
+
+    Ok(GarchModel {
+        spec: *spec,
+        mu: p.mu,
+        omega: p.omega,
+        alpha: p.alpha,
+        gamma: p.gamma,
+        beta: p.beta,
+        nu: p.nu,
+        variance,
+        residuals,
+        loglik,
+        n: x.len(),
+        tail,
+    })
+}
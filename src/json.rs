@@ -0,0 +1,179 @@
+//! Import and export of fitted models in a JSON layout compatible with the results of
+//! statsmodels' `SARIMAX`. Only enabled with feature `json`.
+//!
+//! The layout holds the attributes of a `SARIMAXResults` object `res`:
+//!
+//! ```json
+//! {
+//!   "order": [1, 1, 1],
+//!   "seasonal_order": [0, 0, 0, 0],
+//!   "trend": "c",
+//!   "param_names": ["intercept", "ar.L1", "ma.L1", "sigma2"],
+//!   "params": [0.1, 0.5, -0.3, 1.2]
+//! }
+//! ```
+//!
+//! which is written in Python with
+//!
+//! ```python
+//! json.dump({"order": res.model.order, "seasonal_order": res.model.seasonal_order,
+//!            "trend": res.model.trend, "param_names": res.param_names,
+//!            "params": res.params.tolist()}, f)
+//! ```
+//!
+//! statsmodels and this crate use the same parametrization: the intercept applies to the
+//! differenced series, `ar.Lk` is `phi_k` and `ma.Lk` is `theta_k`. A `const` parameter
+//! of statsmodels' `ARIMA` class is the mean of a series without differencing and is
+//! converted into an intercept.
+
+use anyhow::Result;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::FittedModel;
+
+/// Model order, parameters and innovation variance in the layout of statsmodels'
+/// `SARIMAXResults`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarimaxParams {
+    /// Order `(p, d, q)`.
+    pub order: (usize, usize, usize),
+    /// Seasonal order `(P, D, Q, s)`. Only `(0, 0, 0, 0)` is supported.
+    #[serde(default)]
+    pub seasonal_order: (usize, usize, usize, usize),
+    /// Trend specification, `"c"` for an intercept or `null` for none.
+    #[serde(default)]
+    pub trend: Option<String>,
+    /// Parameter names, e.g. `intercept`, `ar.L1`, `ma.L2` and `sigma2`.
+    pub param_names: Vec<String>,
+    /// Parameter values in the order of `param_names`.
+    pub params: Vec<f64>,
+}
+
+impl SarimaxParams {
+    /// Parameters of a fitted model.
+    pub fn from_model(fit: &FittedModel) -> Self {
+        let mut param_names = vec!["intercept".to_string()];
+        let mut params = vec![fit.intercept];
+        for (k, phi) in fit.phi.iter().enumerate() {
+            param_names.push(format!("ar.L{}", k + 1));
+            params.push(*phi);
+        }
+        for (k, theta) in fit.theta.iter().enumerate() {
+            param_names.push(format!("ma.L{}", k + 1));
+            params.push(*theta);
+        }
+        param_names.push("sigma2".to_string());
+        params.push(fit.sigma2);
+
+        SarimaxParams {
+            order: (fit.ar, fit.d, fit.ma),
+            seasonal_order: (0, 0, 0, 0),
+            trend: Some("c".to_string()),
+            param_names,
+            params,
+        }
+    }
+
+    /// Coefficient vector `[intercept, phi_1, ..., phi_p, theta_1, ..., theta_q]` as
+    /// used by `FittedModel::from_coef`, and the innovation variance. AR and MA lags
+    /// that are not listed are zero.
+    pub fn coefficients(&self) -> Result<(Vec<f64>, f64)> {
+        let (ar, d, ma) = self.order;
+        if self.seasonal_order != (0, 0, 0, 0) {
+            anyhow::bail!("Seasonal models are not supported");
+        }
+        if self.param_names.len() != self.params.len() {
+            anyhow::bail!(
+                "Got {} parameter names but {} parameters",
+                self.param_names.len(),
+                self.params.len()
+            );
+        }
+
+        let mut intercept = 0.0;
+        let mut mean = None;
+        let mut phi = vec![0.0; ar];
+        let mut theta = vec![0.0; ma];
+        let mut sigma2 = None;
+        for (name, value) in self.param_names.iter().zip(&self.params) {
+            let lag = |prefix: &str, order: usize| -> Result<usize> {
+                match name[prefix.len()..].parse::<usize>() {
+                    Ok(k) if k >= 1 && k <= order => Ok(k - 1),
+                    _ => anyhow::bail!("Invalid lag in parameter {}", name),
+                }
+            };
+            match name.as_str() {
+                "intercept" => intercept = *value,
+                "const" => mean = Some(*value),
+                "sigma2" => sigma2 = Some(*value),
+                _ if name.starts_with("ar.L") => phi[lag("ar.L", ar)?] = *value,
+                _ if name.starts_with("ma.L") => theta[lag("ma.L", ma)?] = *value,
+                _ => anyhow::bail!("Unsupported parameter {}", name),
+            }
+        }
+        if let Some(mean) = mean {
+            if d > 0 {
+                anyhow::bail!("A constant is only supported without differencing");
+            }
+            intercept = mean * (1.0 - phi.iter().sum::<f64>());
+        }
+        let sigma2 = match sigma2 {
+            Some(sigma2) if sigma2 > 0.0 => sigma2,
+            Some(_) => anyhow::bail!("sigma2 must be positive"),
+            None => anyhow::bail!("Parameter sigma2 is missing"),
+        };
+
+        let mut coef = vec![intercept];
+        coef.extend(phi);
+        coef.extend(theta);
+        Ok((coef, sigma2))
+    }
+
+    /// Build a forecast-ready model for the series `x` from the parameters, without
+    /// re-estimation. The residuals are computed from `x` and the innovation variance
+    /// is taken from the parameters.
+    pub fn to_model(&self, x: &[f64]) -> Result<FittedModel> {
+        let (coef, sigma2) = self.coefficients()?;
+        let (ar, d, ma) = self.order;
+        let mut fit = FittedModel::from_coef(x, &coef, ar, d, ma)?;
+        fit.sigma2 = sigma2;
+        Ok(fit)
+    }
+}
+
+/// Export a fitted model to JSON in the layout of statsmodels' `SARIMAXResults`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{json, model::FittedModel};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+/// let m = FittedModel::from_coef(&x, &[0.5, 0.6], 1, 0, 0).unwrap();
+/// let s = json::to_statsmodels(&m).unwrap();
+/// assert!(s.contains("\"ar.L1\""));
+/// let imported = json::from_statsmodels(&s, &x).unwrap();
+/// assert_eq!(imported.phi, m.phi);
+/// assert_eq!(imported.sigma2, m.sigma2);
+/// ```
+pub fn to_statsmodels(fit: &FittedModel) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&SarimaxParams::from_model(
+        fit,
+    ))?)
+}
+
+/// Import a model from JSON in the layout of statsmodels' `SARIMAXResults` and attach
+/// it to the series `x`, see `SarimaxParams::to_model`.
+///
+/// # Arguments
+///
+/// * `&json` - JSON document.
+/// * `&x` - Vector of the timeseries the model is applied to.
+///
+/// # Returns
+///
+/// * Forecast-ready model with the imported coefficients and innovation variance.
+pub fn from_statsmodels(json: &str, x: &[f64]) -> Result<FittedModel> {
+    let params: SarimaxParams = serde_json::from_str(json)?;
+    params.to_model(x)
+}
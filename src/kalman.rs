@@ -0,0 +1,350 @@
+//! Kalman filter and smoother for linear Gaussian state space models with univariate
+//! observations:
+//!
+//! `y_t = Z a_t + eps_t`, `eps_t ~ N(0, H)`
+//! `a_{t+1} = T a_t + eta_t`, `eta_t ~ N(0, Q)`
+//!
+//! where `Q` is the full state disturbance covariance (i.e. `R Q R'` in the notation
+//! of Durbin and Koopman). Matrices are stored row-major. Missing observations are
+//! given as NaN. Any of `T`, `Z`, `H` and `Q` may vary over time.
+
+use std::f64::consts::PI;
+
+/// Variance of the initial state for diffuse state elements.
+pub(crate) const DIFFUSE_KAPPA: f64 = 1.0e7;
+
+/// Time-invariant system matrices.
+#[derive(Debug, Clone)]
+pub(crate) struct System {
+    /// Number of states.
+    pub m: usize,
+    /// Transition matrix, `m x m`.
+    pub t: Vec<f64>,
+    /// Observation vector, length `m`.
+    pub z: Vec<f64>,
+    /// Observation noise variance.
+    pub h: f64,
+    /// State disturbance covariance, `m x m`.
+    pub q: Vec<f64>,
+    /// Initial state mean.
+    pub a0: Vec<f64>,
+    /// Initial state covariance, `m x m`.
+    pub p0: Vec<f64>,
+    /// Number of diffuse initial states. The likelihood contributions of the first
+    /// `n_diffuse` observations are skipped.
+    pub n_diffuse: usize,
+    /// Time-varying system matrices, overriding the time-invariant ones.
+    pub varying: TimeVarying,
+}
+
+/// Time-varying system matrices. Empty vectors use the time-invariant matrices of the
+/// system; otherwise item `i` applies at time `i`, and the last item applies to all later
+/// times including forecasts.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TimeVarying {
+    pub t: Vec<Vec<f64>>,
+    pub z: Vec<Vec<f64>>,
+    pub h: Vec<f64>,
+    pub q: Vec<Vec<f64>>,
+}
+
+fn at<T>(varying: &[T], i: usize) -> Option<&T> {
+    varying.get(i).or_else(|| varying.last())
+}
+
+/// Output of the Kalman filter.
+#[derive(Debug, Clone)]
+pub struct Filtered {
+    /// Predicted states `a_{t|t-1}`; one more than observations, the last item is the
+    /// one-step-ahead prediction after the sample.
+    pub a: Vec<Vec<f64>>,
+    /// Predicted state covariances `P_{t|t-1}`, same layout as `a`.
+    pub p: Vec<Vec<f64>>,
+    /// Innovations `v_t = y_t - Z a_t`, NaN for missing observations.
+    pub v: Vec<f64>,
+    /// Innovation variances `F_t`.
+    pub f: Vec<f64>,
+    /// Kalman gains `K_t = T P_t Z' / F_t`.
+    pub k: Vec<Vec<f64>>,
+    /// Gaussian log-likelihood, excluding the diffuse observations.
+    pub loglik: f64,
+    /// Number of observations that contribute to the likelihood.
+    pub n_used: usize,
+}
+
+/// Output of the state smoother.
+#[derive(Debug, Clone)]
+pub struct Smoothed {
+    /// Smoothed states `E(a_t | y)`.
+    pub a: Vec<Vec<f64>>,
+    /// Smoothed state covariances `Var(a_t | y)`.
+    pub v: Vec<Vec<f64>>,
+}
+
+fn mat_vec(a: &[f64], x: &[f64]) -> Vec<f64> {
+    a.chunks(x.len())
+        .map(|row| row.iter().zip(x).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn mat_t_vec(a: &[f64], x: &[f64]) -> Vec<f64> {
+    let m = x.len();
+    let mut y = vec![0.0; m];
+    for (row, xi) in a.chunks(m).zip(x) {
+        for (yj, aij) in y.iter_mut().zip(row) {
+            *yj += aij * xi;
+        }
+    }
+    y
+}
+
+fn mat_mul(a: &[f64], b: &[f64], m: usize) -> Vec<f64> {
+    let mut c = vec![0.0; m * m];
+    for i in 0..m {
+        for k in 0..m {
+            let aik = a[i * m + k];
+            if aik == 0.0 {
+                continue;
+            }
+            for j in 0..m {
+                c[i * m + j] += aik * b[k * m + j];
+            }
+        }
+    }
+    c
+}
+
+fn transpose(a: &[f64], m: usize) -> Vec<f64> {
+    let mut b = vec![0.0; m * m];
+    for i in 0..m {
+        for j in 0..m {
+            b[j * m + i] = a[i * m + j];
+        }
+    }
+    b
+}
+
+/// `T P T'`.
+fn sandwich(t: &[f64], p: &[f64], m: usize) -> Vec<f64> {
+    mat_mul(&mat_mul(t, p, m), &transpose(t, m), m)
+}
+
+/// Stationary covariance `P = T P T' + Q` of the state of a stable transition matrix,
+/// computed by the doubling algorithm.
+pub(crate) fn stationary_covariance(t: &[f64], q: &[f64], m: usize) -> Vec<f64> {
+    let mut a = t.to_vec();
+    let mut p = q.to_vec();
+    for _ in 0..64 {
+        let apa = sandwich(&a, &p, m);
+        for (pij, v) in p.iter_mut().zip(&apa) {
+            *pij += v;
+        }
+        a = mat_mul(&a, &a, m);
+        if a.iter().all(|v| v.abs() < 1.0e-15) {
+            break;
+        }
+    }
+    p
+}
+
+/// Transition matrix and state disturbance covariance of an ARMA(p, q) model with
+/// innovation variance `sigma2` in Harvey's form
+/// `w_t = phi_1 w_{t-1} + ... + theta_1 e_{t-1} + ... + e_t` with state `(w_t, ...)'`
+/// of dimension `max(p, q + 1)`.
+pub(crate) fn arma_matrices(phi: &[f64], theta: &[f64], sigma2: f64) -> (Vec<f64>, Vec<f64>) {
+    let r = phi.len().max(theta.len() + 1);
+    let mut t = vec![0.0; r * r];
+    for (i, phi) in phi.iter().enumerate() {
+        t[i * r] = *phi;
+    }
+    for i in 0..r - 1 {
+        t[i * r + i + 1] = 1.0;
+    }
+    let selection: Vec<f64> = std::iter::once(1.0)
+        .chain(theta.iter().copied())
+        .chain(std::iter::repeat(0.0))
+        .take(r)
+        .collect();
+    let mut q = vec![0.0; r * r];
+    for i in 0..r {
+        for j in 0..r {
+            q[i * r + j] = sigma2 * selection[i] * selection[j];
+        }
+    }
+    (t, q)
+}
+
+impl System {
+    fn t_at(&self, i: usize) -> &[f64] {
+        at(&self.varying.t, i).unwrap_or(&self.t)
+    }
+
+    fn z_at(&self, i: usize) -> &[f64] {
+        at(&self.varying.z, i).unwrap_or(&self.z)
+    }
+
+    fn h_at(&self, i: usize) -> f64 {
+        *at(&self.varying.h, i).unwrap_or(&self.h)
+    }
+
+    fn q_at(&self, i: usize) -> &[f64] {
+        at(&self.varying.q, i).unwrap_or(&self.q)
+    }
+
+    /// Run the Kalman filter over `y`.
+    pub fn filter(&self, y: &[f64]) -> Filtered {
+        let m = self.m;
+        let mut a = self.a0.clone();
+        let mut p = self.p0.clone();
+        let mut out = Filtered {
+            a: Vec::with_capacity(y.len() + 1),
+            p: Vec::with_capacity(y.len() + 1),
+            v: Vec::with_capacity(y.len()),
+            f: Vec::with_capacity(y.len()),
+            k: Vec::with_capacity(y.len()),
+            loglik: 0.0,
+            n_used: 0,
+        };
+        let mut n_observed = 0;
+        for (step, &yt) in y.iter().enumerate() {
+            let (tt, z) = (self.t_at(step), self.z_at(step));
+            let (h, q) = (self.h_at(step), self.q_at(step));
+            out.a.push(a.clone());
+            out.p.push(p.clone());
+            let pz = mat_vec(&p, z);
+            let f = z.iter().zip(&pz).map(|(z, v)| z * v).sum::<f64>() + h;
+            if yt.is_nan() {
+                out.v.push(f64::NAN);
+                out.f.push(f);
+                out.k.push(vec![0.0; m]);
+                a = mat_vec(tt, &a);
+                p = sandwich(tt, &p, m);
+            } else {
+                let v = yt - z.iter().zip(&a).map(|(z, a)| z * a).sum::<f64>();
+                // K = T P Z' / F
+                let k: Vec<f64> = mat_vec(tt, &pz).iter().map(|x| x / f).collect();
+                n_observed += 1;
+                if n_observed > self.n_diffuse {
+                    out.loglik -= 0.5 * ((2.0 * PI).ln() + f.ln() + v * v / f);
+                    out.n_used += 1;
+                }
+                let ta = mat_vec(tt, &a);
+                a = ta.iter().zip(&k).map(|(a, k)| a + k * v).collect();
+                let mut tpt = sandwich(tt, &p, m);
+                for i in 0..m {
+                    for j in 0..m {
+                        tpt[i * m + j] -= k[i] * k[j] * f;
+                    }
+                }
+                p = tpt;
+                out.v.push(v);
+                out.f.push(f);
+                out.k.push(k);
+            }
+            for (pij, qij) in p.iter_mut().zip(q) {
+                *pij += qij;
+            }
+            // keep P symmetric against rounding
+            for i in 0..m {
+                for j in 0..i {
+                    let s = 0.5 * (p[i * m + j] + p[j * m + i]);
+                    p[i * m + j] = s;
+                    p[j * m + i] = s;
+                }
+            }
+        }
+        out.a.push(a);
+        out.p.push(p);
+        out
+    }
+
+    /// Standardized innovations `v_t / sqrt(F_t)` of the output of `filter`. Missing and
+    /// diffuse observations, whose innovation variances are dominated by the diffuse
+    /// prior, are NaN.
+    pub fn standardized(&self, filtered: &Filtered) -> Vec<f64> {
+        let mut n_observed = 0;
+        filtered
+            .v
+            .iter()
+            .zip(&filtered.f)
+            .map(|(v, f)| {
+                if v.is_nan() {
+                    return f64::NAN;
+                }
+                n_observed += 1;
+                if n_observed > self.n_diffuse {
+                    v / f.sqrt()
+                } else {
+                    f64::NAN
+                }
+            })
+            .collect()
+    }
+
+    /// Run the fixed-interval state smoother on the output of `filter`.
+    pub fn smooth(&self, filtered: &Filtered) -> Smoothed {
+        let m = self.m;
+        let n = filtered.v.len();
+        let mut r = vec![0.0; m];
+        let mut nn = vec![0.0; m * m];
+        let mut a_s = vec![Vec::new(); n];
+        let mut v_s = vec![Vec::new(); n];
+        for t in (0..n).rev() {
+            let (tt, z) = (self.t_at(t), self.z_at(t));
+            let v = filtered.v[t];
+            if v.is_nan() {
+                // r_{t-1} = T' r_t, N_{t-1} = T' N_t T
+                r = mat_t_vec(tt, &r);
+                nn = mat_mul(&mat_mul(&transpose(tt, m), &nn, m), tt, m);
+            } else {
+                let f = filtered.f[t];
+                let k = &filtered.k[t];
+                // L = T - K Z
+                let mut l = tt.to_vec();
+                for i in 0..m {
+                    for j in 0..m {
+                        l[i * m + j] -= k[i] * z[j];
+                    }
+                }
+                let lr = mat_t_vec(&l, &r);
+                r = z.iter().zip(&lr).map(|(z, lr)| z * v / f + lr).collect();
+                let mut lnl = mat_mul(&mat_mul(&transpose(&l, m), &nn, m), &l, m);
+                for i in 0..m {
+                    for j in 0..m {
+                        lnl[i * m + j] += z[i] * z[j] / f;
+                    }
+                }
+                nn = lnl;
+            }
+            let p = &filtered.p[t];
+            let pr = mat_vec(p, &r);
+            a_s[t] = filtered.a[t].iter().zip(&pr).map(|(a, b)| a + b).collect();
+            let pnp = mat_mul(&mat_mul(p, &nn, m), p, m);
+            v_s[t] = p.iter().zip(&pnp).map(|(a, b)| a - b).collect();
+        }
+        Smoothed { a: a_s, v: v_s }
+    }
+
+    /// Forecast the observations `h` steps after the end of the filtered sample.
+    /// Returns the means and variances.
+    pub fn forecast(&self, filtered: &Filtered, h: usize) -> (Vec<f64>, Vec<f64>) {
+        let m = self.m;
+        let n = filtered.v.len();
+        let mut a = filtered.a.last().unwrap().clone();
+        let mut p = filtered.p.last().unwrap().clone();
+        let mut mean = Vec::with_capacity(h);
+        let mut var = Vec::with_capacity(h);
+        for i in n..n + h {
+            let (tt, z, q) = (self.t_at(i), self.z_at(i), self.q_at(i));
+            mean.push(z.iter().zip(&a).map(|(z, a)| z * a).sum::<f64>());
+            let pz = mat_vec(&p, z);
+            var.push(z.iter().zip(&pz).map(|(z, v)| z * v).sum::<f64>() + self.h_at(i));
+            a = mat_vec(tt, &a);
+            p = sandwich(tt, &p, m);
+            for (pij, qij) in p.iter_mut().zip(q) {
+                *pij += qij;
+            }
+        }
+        (mean, var)
+    }
+}
@@ -1,8 +1,78 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod acf;
+pub mod error;
+pub mod ewma;
 pub mod sim;
 pub mod util;
 
 pub mod estimate;
+#[cfg(feature = "std")]
+pub mod benchmark;
+#[cfg(feature = "std")]
+pub mod bootstrap;
+#[cfg(feature = "std")]
+pub mod cluster;
+#[cfg(feature = "std")]
+pub mod count;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
+pub mod ets;
+#[cfg(feature = "std")]
+mod fft;
+#[cfg(feature = "std")]
+pub mod garch;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "std")]
+mod kalman;
+#[cfg(feature = "std")]
+mod linalg;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "std")]
+pub mod model;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "std")]
+pub mod persist;
+#[cfg(feature = "plot")]
+pub mod plot;
+#[cfg(feature = "std")]
+pub mod regression;
+#[cfg(feature = "serve")]
+pub mod serve;
+#[cfg(feature = "std")]
+pub mod signal;
+#[cfg(feature = "std")]
+pub mod smoothing;
+#[cfg(feature = "std")]
+mod special;
+#[cfg(feature = "std")]
+pub mod spectrum;
+#[cfg(feature = "std")]
+pub mod statespace;
+#[cfg(feature = "std")]
+pub mod structural;
+#[cfg(feature = "std")]
+pub mod surrogate;
+#[cfg(feature = "std")]
+pub mod tbats;
+#[cfg(feature = "chrono")]
+pub mod timeseries;
+#[cfg(feature = "std")]
+pub mod theta;
+#[cfg(feature = "std")]
+pub mod varma;
+#[cfg(feature = "std")]
+pub mod vecm;
+#[cfg(feature = "std")]
+pub mod x11;
 
 #[cfg(feature = "accelerate")]
 extern crate accelerate_src as raw;
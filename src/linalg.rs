@@ -0,0 +1,304 @@
+//! Small dense linear algebra helpers for the model modules. Matrices are stored
+//! row-major in flat slices.
+
+use anyhow::Result;
+
+use crate::error::{self, ConditioningError, ConditioningKind};
+use crate::util;
+
+/// Condition number above which `solve` treats a matrix as singular.
+const SINGULAR_CONDITION: f64 = 1.0e12;
+
+/// Solve `a x = b` for a square `n x n` matrix `a` by Gaussian elimination with partial
+/// pivoting. Fails if the estimated condition number exceeds `SINGULAR_CONDITION` and
+/// logs a warning if it exceeds `error::condition_threshold`.
+pub(crate) fn solve(a: &[f64], b: &[f64]) -> Result<Vec<f64>> {
+    let n = b.len();
+    if a.len() != n * n {
+        anyhow::bail!("Matrix dimensions do not match");
+    }
+    let lu = Lu::new(a, n)?;
+    let condition = lu.condition(a);
+    if condition > SINGULAR_CONDITION {
+        anyhow::bail!(ConditioningError {
+            kind: ConditioningKind::Singular,
+            info: None,
+            condition,
+            order: n,
+        });
+    }
+    if condition > error::condition_threshold::<f64>() {
+        tracing::warn!(
+            "{}",
+            ConditioningError {
+                kind: ConditioningKind::IllConditioned,
+                info: None,
+                condition,
+                order: n,
+            }
+        );
+    }
+    Ok(lu.solve(b))
+}
+
+/// Estimated 1-norm condition number of a square `n x n` matrix. Infinite if it is
+/// singular.
+pub(crate) fn condition(a: &[f64], n: usize) -> f64 {
+    match Lu::new(a, n) {
+        Ok(lu) => lu.condition(a),
+        Err(_) => f64::INFINITY,
+    }
+}
+
+/// LU factorization `P a = L U` with partial pivoting. `L` (unit diagonal, below) and
+/// `U` share one row-major matrix; row `i` of `P a` is row `perm[i]` of `a`.
+struct Lu {
+    n: usize,
+    lu: Vec<f64>,
+    perm: Vec<usize>,
+}
+
+impl Lu {
+    /// Factorize `a`. Fails with a `Singular` error at the first zero pivot.
+    fn new(a: &[f64], n: usize) -> Result<Self> {
+        let mut lu = a.to_vec();
+        let mut perm: Vec<usize> = (0..n).collect();
+        for col in 0..n {
+            let pivot = (col..n)
+                .max_by(|i, j| {
+                    lu[i * n + col]
+                        .abs()
+                        .partial_cmp(&lu[j * n + col].abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap();
+            let p = lu[pivot * n + col];
+            if p == 0.0 || p.is_nan() {
+                anyhow::bail!(ConditioningError {
+                    kind: ConditioningKind::Singular,
+                    info: None,
+                    condition: f64::INFINITY,
+                    order: col + 1,
+                });
+            }
+            if pivot != col {
+                for k in 0..n {
+                    lu.swap(pivot * n + k, col * n + k);
+                }
+                perm.swap(pivot, col);
+            }
+            for row in col + 1..n {
+                let factor = lu[row * n + col] / p;
+                lu[row * n + col] = factor;
+                if factor == 0.0 {
+                    continue;
+                }
+                for k in col + 1..n {
+                    lu[row * n + k] -= factor * lu[col * n + k];
+                }
+            }
+        }
+        Ok(Lu { n, lu, perm })
+    }
+
+    /// Solve `a x = b`.
+    fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let mut x: Vec<f64> = self.perm.iter().map(|i| b[*i]).collect();
+        for row in 0..n {
+            for k in 0..row {
+                x[row] -= self.lu[row * n + k] * x[k];
+            }
+        }
+        for row in (0..n).rev() {
+            for k in row + 1..n {
+                x[row] -= self.lu[row * n + k] * x[k];
+            }
+            x[row] /= self.lu[row * n + row];
+        }
+        x
+    }
+
+    /// Solve `a' x = b`.
+    fn solve_transposed(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let mut z = b.to_vec();
+        for row in 0..n {
+            for k in 0..row {
+                z[row] -= self.lu[k * n + row] * z[k];
+            }
+            z[row] /= self.lu[row * n + row];
+        }
+        for row in (0..n).rev() {
+            for k in row + 1..n {
+                z[row] -= self.lu[k * n + row] * z[k];
+            }
+        }
+        let mut x = vec![0.0; n];
+        for (i, v) in self.perm.iter().zip(z) {
+            x[*i] = v;
+        }
+        x
+    }
+
+    /// Estimated 1-norm condition number of the factorized matrix `a`.
+    fn condition(&self, a: &[f64]) -> f64 {
+        let n = self.n;
+        if n == 0 {
+            return 1.0;
+        }
+        let norm = (0..n)
+            .map(|j| (0..n).map(|i| a[i * n + j].abs()).sum::<f64>())
+            .fold(0.0, f64::max);
+        let inverse = util::inverse_norm1(n, |b| self.solve(b), |b| self.solve_transposed(b));
+        let condition = norm * inverse;
+        if condition.is_finite() {
+            condition
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Least squares solution of `x beta = y` for an `n x k` design matrix `x`, computed from
+/// the normal equations.
+pub(crate) fn lstsq(x: &[f64], k: usize, y: &[f64]) -> Result<Vec<f64>> {
+    let n = y.len();
+    if x.len() != n * k {
+        anyhow::bail!("Matrix dimensions do not match");
+    }
+    let mut xtx = vec![0.0; k * k];
+    let mut xty = vec![0.0; k];
+    for (row, yi) in x.chunks(k).zip(y) {
+        for i in 0..k {
+            xty[i] += row[i] * yi;
+            for j in i..k {
+                xtx[i * k + j] += row[i] * row[j];
+            }
+        }
+    }
+    for i in 0..k {
+        for j in 0..i {
+            xtx[i * k + j] = xtx[j * k + i];
+        }
+    }
+    solve(&xtx, &xty)
+}
+
+/// Cholesky factor `L` (lower triangular, row-major) of a symmetric positive definite
+/// `n x n` matrix, so that `a = L L'`.
+pub(crate) fn cholesky(a: &[f64], n: usize) -> Result<Vec<f64>> {
+    let mut l = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..i + 1 {
+            let s: f64 = (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum();
+            if i == j {
+                let d = a[i * n + i] - s;
+                if d <= 0.0 || d.is_nan() {
+                    // condition of the leading minor that is not positive-definite
+                    let minor: Vec<f64> = (0..=i)
+                        .flat_map(|r| a[r * n..r * n + i + 1].iter().copied())
+                        .collect();
+                    anyhow::bail!(ConditioningError {
+                        kind: ConditioningKind::NotPositiveDefinite,
+                        info: None,
+                        condition: condition(&minor, i + 1),
+                        order: i + 1,
+                    });
+                }
+                l[i * n + i] = d.sqrt();
+            } else {
+                l[i * n + j] = (a[i * n + j] - s) / l[j * n + j];
+            }
+        }
+    }
+    Ok(l)
+}
+
+/// Eigen decomposition of a symmetric `n x n` matrix with the cyclic Jacobi method.
+/// Returns the eigenvalues in descending order and the matching eigenvectors as the
+/// columns of a row-major `n x n` matrix.
+pub(crate) fn symmetric_eigen(a: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = a.to_vec();
+    let mut v = vec![0.0; n * n];
+    for i in 0..n {
+        v[i * n + i] = 1.0;
+    }
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i * n + j] * a[i * n + j])
+            .sum();
+        if off < 1.0e-22 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                let apq = a[p * n + q];
+                if apq == 0.0 {
+                    continue;
+                }
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                // rotate rows and columns p and q
+                for k in 0..n {
+                    let akp = a[k * n + p];
+                    let akq = a[k * n + q];
+                    a[k * n + p] = c * akp - s * akq;
+                    a[k * n + q] = s * akp + c * akq;
+                }
+                for k in 0..n {
+                    let apk = a[p * n + k];
+                    let aqk = a[q * n + k];
+                    a[p * n + k] = c * apk - s * aqk;
+                    a[q * n + k] = s * apk + c * aqk;
+                }
+                for k in 0..n {
+                    let vkp = v[k * n + p];
+                    let vkq = v[k * n + q];
+                    v[k * n + p] = c * vkp - s * vkq;
+                    v[k * n + q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|i, j| {
+        a[j * n + j]
+            .partial_cmp(&a[i * n + i])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let values = order.iter().map(|&i| a[i * n + i]).collect();
+    let mut vectors = vec![0.0; n * n];
+    for (col, &i) in order.iter().enumerate() {
+        for k in 0..n {
+            vectors[k * n + col] = v[k * n + i];
+        }
+    }
+    (values, vectors)
+}
+
+/// Solve `l x = b` for a lower triangular `n x n` matrix `l`.
+pub(crate) fn solve_lower(l: &[f64], b: &[f64]) -> Vec<f64> {
+    let n = b.len();
+    let mut x = vec![0.0; n];
+    for i in 0..n {
+        let s: f64 = (0..i).map(|k| l[i * n + k] * x[k]).sum();
+        x[i] = (b[i] - s) / l[i * n + i];
+    }
+    x
+}
+
+/// Solve `l' x = b` for a lower triangular `n x n` matrix `l`.
+pub(crate) fn solve_lower_t(l: &[f64], b: &[f64]) -> Vec<f64> {
+    let n = b.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let s: f64 = (i + 1..n).map(|k| l[k * n + i] * x[k]).sum();
+        x[i] = (b[i] - s) / l[i * n + i];
+    }
+    x
+}
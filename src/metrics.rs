@@ -0,0 +1,331 @@
+use anyhow::Result;
+
+use crate::special;
+
+/// Point forecast accuracy measures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accuracy {
+    /// Mean error.
+    pub me: f64,
+    /// Root mean squared error.
+    pub rmse: f64,
+    /// Mean absolute error.
+    pub mae: f64,
+    /// Mean percentage error. `NaN` if any actual value is zero.
+    pub mpe: f64,
+    /// Mean absolute percentage error. `NaN` if any actual value is zero.
+    pub mape: f64,
+}
+
+/// Calculate point accuracy measures of predictions against actual values.
+///
+/// # Arguments
+///
+/// * `&actual` - Observed values.
+/// * `&predicted` - Predicted values, same length as `actual`.
+///
+/// # Returns
+///
+/// * Accuracy measures computed from the errors `actual - predicted`.
+///
+/// # Example
+///
+/// ```
+/// use arima::metrics;
+/// let acc = metrics::accuracy(&[1.0, 2.0, 4.0], &[1.5, 2.0, 3.0]).unwrap();
+/// assert!((acc.me - 1.0 / 6.0).abs() < 1.0e-12);
+/// assert!((acc.mae - 0.5).abs() < 1.0e-12);
+/// ```
+pub fn accuracy(actual: &[f64], predicted: &[f64]) -> Result<Accuracy> {
+    if actual.len() != predicted.len() {
+        anyhow::bail!("actual and predicted must have the same length");
+    }
+    if actual.is_empty() {
+        anyhow::bail!("Need at least one value");
+    }
+    let n = actual.len() as f64;
+
+    let mut me = 0.0;
+    let mut mse = 0.0;
+    let mut mae = 0.0;
+    let mut mpe = 0.0;
+    let mut mape = 0.0;
+    for (a, p) in actual.iter().zip(predicted) {
+        let e = a - p;
+        me += e;
+        mse += e * e;
+        mae += e.abs();
+        if *a == 0.0 {
+            mpe = f64::NAN;
+            mape = f64::NAN;
+        } else {
+            mpe += 100.0 * e / a;
+            mape += 100.0 * (e / a).abs();
+        }
+    }
+
+    Ok(Accuracy {
+        me: me / n,
+        rmse: (mse / n).sqrt(),
+        mae: mae / n,
+        mpe: mpe / n,
+        mape: mape / n,
+    })
+}
+
+/// Mean continuous ranked probability score of predictive distributions given by
+/// samples, e.g. simulated future paths. Lower is better.
+///
+/// For each observation, `CRPS = E|X - y| - E|X - X'| / 2`, where `X` and `X'` are
+/// independent draws from the samples.
+///
+/// # Arguments
+///
+/// * `&actual` - Observed values.
+/// * `&samples` - Samples of the predictive distribution of each observed value.
+///
+/// # Returns
+///
+/// * Mean CRPS over the observations.
+///
+/// # Example
+///
+/// ```
+/// use arima::metrics;
+/// let crps = metrics::crps_sample(&[1.0], &[vec![0.0, 2.0]]).unwrap();
+/// assert!((crps - 0.5).abs() < 1.0e-12);
+/// ```
+pub fn crps_sample(actual: &[f64], samples: &[Vec<f64>]) -> Result<f64> {
+    if actual.len() != samples.len() {
+        anyhow::bail!("actual and samples must have the same length");
+    }
+    if actual.is_empty() {
+        anyhow::bail!("Need at least one value");
+    }
+    let mut total = 0.0;
+    for (y, draws) in actual.iter().zip(samples) {
+        if draws.is_empty() {
+            anyhow::bail!("Need at least one sample per observation");
+        }
+        let m = draws.len() as f64;
+        let mut sorted = draws.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let abs_error = sorted.iter().map(|x| (x - y).abs()).sum::<f64>() / m;
+        // E|X - X'| from the order statistics
+        let spread = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (2.0 * (i + 1) as f64 - m - 1.0) * x)
+            .sum::<f64>()
+            * 2.0
+            / (m * m);
+        total += abs_error - 0.5 * spread;
+    }
+    Ok(total / actual.len() as f64)
+}
+
+/// Mean continuous ranked probability score of Gaussian predictive distributions, in
+/// closed form. Lower is better.
+///
+/// # Arguments
+///
+/// * `&actual` - Observed values.
+/// * `&mean` - Predictive means.
+/// * `&sd` - Predictive standard deviations, positive.
+///
+/// # Returns
+///
+/// * Mean CRPS over the observations.
+///
+/// # Example
+///
+/// ```
+/// use arima::metrics;
+/// // a point forecast is scored by its absolute error
+/// let crps = metrics::crps_gaussian(&[1.0, 3.0], &[0.0, 0.0], &[1.0e-9, 1.0e-9]).unwrap();
+/// assert!((crps - 2.0).abs() < 1.0e-6);
+/// ```
+pub fn crps_gaussian(actual: &[f64], mean: &[f64], sd: &[f64]) -> Result<f64> {
+    if actual.len() != mean.len() || actual.len() != sd.len() {
+        anyhow::bail!("actual, mean and sd must have the same length");
+    }
+    if actual.is_empty() {
+        anyhow::bail!("Need at least one value");
+    }
+    if sd.iter().any(|s| *s <= 0.0 || s.is_nan()) {
+        anyhow::bail!("Standard deviations must be positive");
+    }
+    let total: f64 = actual
+        .iter()
+        .zip(mean)
+        .zip(sd)
+        .map(|((y, mu), s)| {
+            let z = (y - mu) / s;
+            let pdf = (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt();
+            s * (z * (2.0 * special::normal_cdf(z) - 1.0) + 2.0 * pdf
+                - 1.0 / std::f64::consts::PI.sqrt())
+        })
+        .sum();
+    Ok(total / actual.len() as f64)
+}
+
+/// Mean quantile (pinball) loss of predicted `tau`-quantiles. Lower is better.
+///
+/// The loss of an observation is `tau (y - q)` if `y >= q` and `(1 - tau) (q - y)`
+/// otherwise. With `tau = 0.5` it is half the absolute error.
+///
+/// # Arguments
+///
+/// * `&actual` - Observed values.
+/// * `&predicted` - Predicted `tau`-quantiles, same length as `actual`.
+/// * `tau` - Quantile level within 0 and 1.
+///
+/// # Returns
+///
+/// * Mean quantile loss.
+///
+/// # Example
+///
+/// ```
+/// use arima::metrics;
+/// let loss = metrics::quantile_loss(&[1.0, 2.0], &[2.0, 1.0], 0.9).unwrap();
+/// assert!((loss - 0.5).abs() < 1.0e-12);
+/// ```
+pub fn quantile_loss(actual: &[f64], predicted: &[f64], tau: f64) -> Result<f64> {
+    if actual.len() != predicted.len() {
+        anyhow::bail!("actual and predicted must have the same length");
+    }
+    if actual.is_empty() {
+        anyhow::bail!("Need at least one value");
+    }
+    if !(tau > 0.0 && tau < 1.0) {
+        anyhow::bail!("tau must be within 0 and 1");
+    }
+    let total: f64 = actual
+        .iter()
+        .zip(predicted)
+        .map(|(y, q)| {
+            let e = y - q;
+            if e >= 0.0 {
+                tau * e
+            } else {
+                (tau - 1.0) * e
+            }
+        })
+        .sum();
+    Ok(total / actual.len() as f64)
+}
+
+/// Empirical coverage and width of prediction intervals of one nominal level and
+/// horizon, see `interval_coverage`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coverage {
+    /// Nominal level of the intervals.
+    pub level: f64,
+    /// Forecast horizon.
+    pub horizon: usize,
+    /// Number of forecasts evaluated.
+    pub count: usize,
+    /// Share of actual values within the bounds. Clearly below `level` for an
+    /// overconfident model.
+    pub coverage: f64,
+    /// Share of actual values below the lower bound.
+    pub below: f64,
+    /// Share of actual values above the upper bound.
+    pub above: f64,
+    /// Mean width `upper - lower` of the intervals.
+    pub mean_width: f64,
+}
+
+/// Empirical coverage per nominal level and horizon of prediction intervals from a
+/// backtest.
+///
+/// # Arguments
+///
+/// * `&levels` - Nominal levels of the intervals, e.g. `[0.8, 0.95]`.
+/// * `&actual` - Observed values per forecast origin, `actual[o][k]` for horizon
+///   `k + 1`. Origins near the end of the data may have fewer values.
+/// * `&bounds` - Lower and upper bounds per forecast origin and level, `bounds[o][l]`
+///   for `levels[l]`, as returned by `Forecaster::forecast_intervals` or the
+///   `simulated_intervals` methods. The bounds must cover at least as many horizons as
+///   the observed values of the origin.
+///
+/// # Returns
+///
+/// * Coverage for each level and horizon, ordered by level, then horizon.
+///
+/// # Example
+///
+/// ```
+/// use arima::metrics;
+/// let actual = vec![vec![1.0, 2.0], vec![3.0, 9.0]];
+/// let bounds = vec![
+///     vec![(vec![0.0, 0.0], vec![2.0, 3.0])],
+///     vec![(vec![2.0, 1.0], vec![4.0, 5.0])],
+/// ];
+/// let cov = metrics::interval_coverage(&[0.9], &actual, &bounds).unwrap();
+/// assert_eq!(cov[0].coverage, 1.0);
+/// assert_eq!(cov[1].coverage, 0.5);
+/// assert_eq!(cov[1].above, 0.5);
+/// assert_eq!(cov[1].mean_width, 3.5);
+/// ```
+pub fn interval_coverage(
+    levels: &[f64],
+    actual: &[Vec<f64>],
+    bounds: &[Vec<(Vec<f64>, Vec<f64>)>],
+) -> Result<Vec<Coverage>> {
+    if actual.len() != bounds.len() {
+        anyhow::bail!("actual and bounds must have the same number of forecast origins");
+    }
+    if levels.iter().any(|l| !(*l > 0.0 && *l < 1.0)) {
+        anyhow::bail!("Levels must be within 0 and 1");
+    }
+    for (values, intervals) in actual.iter().zip(bounds) {
+        if intervals.len() != levels.len() {
+            anyhow::bail!("Need bounds for each level at every forecast origin");
+        }
+        if intervals
+            .iter()
+            .any(|(lower, upper)| lower.len() < values.len() || upper.len() < values.len())
+        {
+            anyhow::bail!("Bounds must cover all horizons with observed values");
+        }
+    }
+    let h = actual.iter().map(|v| v.len()).max().unwrap_or(0);
+    if h == 0 {
+        anyhow::bail!("Need at least one value");
+    }
+
+    let mut out = Vec::with_capacity(levels.len() * h);
+    for (l, level) in levels.iter().enumerate() {
+        for k in 0..h {
+            let mut count = 0;
+            let mut below = 0;
+            let mut above = 0;
+            let mut width = 0.0;
+            for (values, intervals) in actual.iter().zip(bounds) {
+                if let Some(y) = values.get(k) {
+                    let (lower, upper) = &intervals[l];
+                    count += 1;
+                    width += upper[k] - lower[k];
+                    if *y < lower[k] {
+                        below += 1;
+                    } else if *y > upper[k] {
+                        above += 1;
+                    }
+                }
+            }
+            let n = count as f64;
+            out.push(Coverage {
+                level: *level,
+                horizon: k + 1,
+                count,
+                coverage: (count - below - above) as f64 / n,
+                below: below as f64 / n,
+                above: above as f64 / n,
+                mean_width: width / n,
+            });
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+
+use std::fs::File;
+use std::mem;
+use std::ops::Deref;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+/// A time series of `f64` values backed by a read-only memory-mapped file.
+///
+/// The file must contain the raw values in native byte order without any header.
+/// `MmapSeries` dereferences to `&[f64]`, so it can be passed to every function of this
+/// crate that takes a slice; pages are only loaded when they are accessed.
+///
+/// # Example
+///
+/// ```
+/// use arima::{acf, mmap::MmapSeries};
+/// # let path = std::env::temp_dir().join("arima_mmap_doc.bin");
+/// # let values = [1.0_f64, 1.2, 1.4, 1.6];
+/// # let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+/// # std::fs::write(&path, bytes).unwrap();
+/// let x = MmapSeries::open(&path).unwrap();
+/// let ac = acf::acf(&x, Some(2), false).unwrap();
+/// assert!((ac[1] - 0.25).abs() < 1.0e-7);
+/// ```
+pub struct MmapSeries {
+    mmap: Option<Mmap>,
+}
+
+impl MmapSeries {
+    /// Memory-map the file at `path`. Fails if the file size is not a multiple of the
+    /// size of `f64`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len % mem::size_of::<f64>() != 0 {
+            anyhow::bail!(
+                "File size {} is not a multiple of {}",
+                len,
+                mem::size_of::<f64>()
+            );
+        }
+        // mapping an empty file fails on some platforms
+        if len == 0 {
+            return Ok(MmapSeries { mmap: None });
+        }
+
+        // safety: the mapping is read-only. As with any memory map, the file must not be
+        // modified by other processes while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        if mmap.as_ptr().align_offset(mem::align_of::<f64>()) != 0 {
+            anyhow::bail!("Memory map is not aligned for f64");
+        }
+        Ok(MmapSeries { mmap: Some(mmap) })
+    }
+
+    /// The mapped values.
+    pub fn as_slice(&self) -> &[f64] {
+        match &self.mmap {
+            // safety: alignment and length were checked in `open`, and every bit pattern
+            // is a valid f64
+            Some(mmap) => unsafe {
+                std::slice::from_raw_parts(
+                    mmap.as_ptr() as *const f64,
+                    mmap.len() / mem::size_of::<f64>(),
+                )
+            },
+            None => &[],
+        }
+    }
+
+    /// Iterate over the series in chunks of `chunk_len` values, where consecutive chunks
+    /// overlap by `overlap` values. See `chunks` for details.
+    pub fn chunks(&self, chunk_len: usize, overlap: usize) -> Result<Chunks<'_>> {
+        chunks(self.as_slice(), chunk_len, overlap)
+    }
+}
+
+impl Deref for MmapSeries {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        self.as_slice()
+    }
+}
+
+/// Iterator over overlapping chunks of a series, created by `chunks`.
+pub struct Chunks<'a> {
+    x: &'a [f64],
+    chunk_len: usize,
+    overlap: usize,
+    start: usize,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a [f64];
+
+    fn next(&mut self) -> Option<&'a [f64]> {
+        if self.start + self.overlap >= self.x.len() {
+            return None;
+        }
+        let end = (self.start + self.chunk_len).min(self.x.len());
+        let chunk = &self.x[self.start..end];
+        self.start = end - self.overlap;
+        Some(chunk)
+    }
+}
+
+/// Split a series into chunks of `chunk_len` values, where each chunk repeats the last
+/// `overlap` values of the previous one. With `overlap = d`, the differences
+/// `util::diff(chunk, d)` of all chunks concatenate to `util::diff(x, d)`; with
+/// `overlap = p`, the CSS of an AR(p) model is the sum of the CSS over all chunks.
+/// This allows processing series that are too large to transform in one piece.
+///
+/// # Arguments
+///
+/// * `&x` - Time series, e.g. an `MmapSeries`.
+/// * `chunk_len` - Number of values per chunk. Must be larger than `overlap`.
+/// * `overlap` - Number of values shared by consecutive chunks.
+///
+/// # Returns
+///
+/// * Iterator over the chunks.
+///
+/// # Example
+///
+/// ```
+/// use arima::{mmap, util};
+/// let x = [1.0, 3.0, 6.0, 10.0, 15.0, 21.0, 28.0];
+/// let mut y = Vec::new();
+/// for chunk in mmap::chunks(&x, 3, 1).unwrap() {
+///     y.extend(util::diff(chunk, 1));
+/// }
+/// assert_eq!(y, util::diff(&x, 1));
+/// ```
+pub fn chunks(x: &[f64], chunk_len: usize, overlap: usize) -> Result<Chunks<'_>> {
+    if chunk_len <= overlap {
+        anyhow::bail!("chunk_len must be larger than overlap");
+    }
+    Ok(Chunks {
+        x,
+        chunk_len,
+        overlap,
+        start: 0,
+    })
+}
@@ -0,0 +1,966 @@
+use anyhow::Result;
+
+use num::complex::Complex64;
+use num::Float;
+
+use std::convert::From;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::kalman::{self, System, TimeVarying};
+use crate::metrics::{self, Accuracy};
+use crate::{estimate, spectrum, util};
+
+/// Roots with a modulus within this distance of one are reported as near unit roots.
+pub const UNIT_ROOT_TOLERANCE: f64 = 0.05;
+
+/// AR and MA roots within this distance of each other are reported as nearly
+/// cancelling.
+pub const CANCELLATION_TOLERANCE: f64 = 0.1;
+
+/// A fitted ARIMA(p, d, q) model together with the data it was fitted on.
+///
+/// The model is parametrized as
+/// `w_t = intercept + phi_1 w_{t-1} + ... + phi_p w_{t-p} + e_t + theta_1 e_{t-1} + ...`
+/// where `w` is the series `x` differenced `d` times.
+#[derive(Debug, Clone)]
+pub struct FittedModel {
+    /// Order of the AR part.
+    pub ar: usize,
+    /// Order of differencing.
+    pub d: usize,
+    /// Order of the MA part.
+    pub ma: usize,
+    /// Intercept of the differenced series.
+    pub intercept: f64,
+    /// AR coefficients.
+    pub phi: Vec<f64>,
+    /// MA coefficients.
+    pub theta: Vec<f64>,
+    /// Innovation variance, estimated as CSS/n where n is the number of used residuals.
+    pub sigma2: f64,
+    /// Residuals of the differenced series. The first `ar` items are zeros.
+    pub residuals: Vec<f64>,
+    /// The original (undifferenced) time series.
+    pub x: Vec<f64>,
+}
+
+impl FittedModel {
+    /// Build a model from a coefficient vector as returned by `estimate::fit`, i.e.
+    /// `[intercept, phi_1, ..., phi_p, theta_1, ..., theta_q]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `&x` - Vector of the timeseries.
+    /// * `&coef` - Coefficient vector of length `1 + ar + ma`.
+    /// * `ar` - Order of the AR coefficients.
+    /// * `d` - Order of differencing.
+    /// * `ma` - Order of the MA coefficients.
+    ///
+    /// # Returns
+    ///
+    /// * Fitted model including residuals and variance estimate.
+    pub fn from_coef(x: &[f64], coef: &[f64], ar: usize, d: usize, ma: usize) -> Result<Self> {
+        if coef.len() != 1 + ar + ma {
+            anyhow::bail!("Expected {} coefficients, got {}", 1 + ar + ma, coef.len());
+        }
+        if x.len() <= d + ar {
+            anyhow::bail!("Time series is too short for the model order");
+        }
+
+        let w = if d > 0 { util::diff(x, d) } else { x.to_vec() };
+        let intercept = coef[0];
+        let phi = coef[1..ar + 1].to_vec();
+        let theta = coef[ar + 1..].to_vec();
+
+        let residuals = estimate::residuals(&w, intercept, Some(&phi), Some(&theta))?;
+        let n_used = (w.len() - ar) as f64;
+        let css: f64 = residuals.iter().map(|r| r * r).sum();
+
+        Ok(FittedModel {
+            ar,
+            d,
+            ma,
+            intercept,
+            phi,
+            theta,
+            sigma2: css / n_used,
+            residuals,
+            x: x.to_vec(),
+        })
+    }
+
+    /// Build a forecast-ready model from coefficients estimated elsewhere, as printed by
+    /// R's `arima()`. R parametrizes the mean instead of the intercept,
+    /// `phi(B) (x_t - mean) = theta(B) e_t`, and fits no mean for differenced series.
+    /// The model must be stationary and invertible.
+    ///
+    /// # Arguments
+    ///
+    /// * `&x` - Vector of the timeseries the model is applied to.
+    /// * `&phi` - AR coefficients (`ar1, ar2, ...` in R).
+    /// * `d` - Order of differencing.
+    /// * `&theta` - MA coefficients (`ma1, ma2, ...` in R, same sign convention).
+    /// * `mean` - Mean of the series (`intercept` in R). Must be zero if `d > 0`.
+    /// * `sigma2` - Innovation variance (`sigma^2` in R).
+    ///
+    /// # Returns
+    ///
+    /// * Model with residuals computed from `x` and the given innovation variance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+    /// let m = FittedModel::from_r(&x, &[0.5], 0, &[0.2], 1.25, 0.03).unwrap();
+    /// assert!((m.intercept - 0.625).abs() < 1.0e-12);
+    /// assert_eq!(m.sigma2, 0.03);
+    /// // an explosive AR part is rejected
+    /// assert!(FittedModel::from_r(&x, &[1.2], 0, &[], 1.25, 0.03).is_err());
+    /// ```
+    pub fn from_r(
+        x: &[f64],
+        phi: &[f64],
+        d: usize,
+        theta: &[f64],
+        mean: f64,
+        sigma2: f64,
+    ) -> Result<Self> {
+        if sigma2 <= 0.0 || !sigma2.is_finite() {
+            anyhow::bail!("sigma2 must be positive");
+        }
+        if d > 0 && mean != 0.0 {
+            anyhow::bail!("R fits no mean for differenced series");
+        }
+        if phi
+            .iter()
+            .chain(theta)
+            .chain(&[mean])
+            .any(|v| !v.is_finite())
+        {
+            anyhow::bail!("Coefficients must be finite");
+        }
+        let roots = Roots::new(phi, theta);
+        if !roots.stationary {
+            anyhow::bail!("AR part is not stationary");
+        }
+        if !roots.invertible {
+            anyhow::bail!("MA part is not invertible");
+        }
+
+        let mut coef = vec![mean * (1.0 - phi.iter().sum::<f64>())];
+        coef.extend_from_slice(phi);
+        coef.extend_from_slice(theta);
+        let mut fit = FittedModel::from_coef(x, &coef, phi.len(), d, theta.len())?;
+        fit.sigma2 = sigma2;
+        Ok(fit)
+    }
+
+    /// Standardized one-step prediction errors of the differenced series, without the
+    /// first `ar` values. For a stationary AR part these are the exact innovations
+    /// `v_t / sqrt(F_t)` of the Kalman filter of the ARMA model started from its
+    /// stationary distribution, which account for the larger uncertainty of the first
+    /// predictions, as R's `tsdiag`. Otherwise the residuals are divided by the constant
+    /// innovation standard deviation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model;
+    /// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+    /// let m = model::fit(&x, 1, 0, 0).unwrap();
+    /// let z = m.standardized_residuals();
+    /// assert_eq!(z.len(), 9);
+    /// // an AR(1) model has the prediction error variance sigma2 from the second value on
+    /// let sd = m.sigma2.sqrt();
+    /// for (z, r) in z.iter().zip(&m.residuals[1..]) {
+    ///     assert!((z - r / sd).abs() < 1.0e-9);
+    /// }
+    /// ```
+    pub fn standardized_residuals(&self) -> Vec<f64> {
+        let sd = self.sigma2.sqrt();
+        if !self.roots().stationary {
+            return self.residuals[self.ar..].iter().map(|r| r / sd).collect();
+        }
+        let (t, q) = kalman::arma_matrices(&self.phi, &self.theta, self.sigma2);
+        let m = self.ar.max(self.ma + 1);
+        let mut z = vec![0.0; m];
+        z[0] = 1.0;
+        let system = System {
+            m,
+            p0: kalman::stationary_covariance(&t, &q, m),
+            t,
+            z,
+            h: 0.0,
+            q,
+            a0: vec![0.0; m],
+            n_diffuse: 0,
+            varying: TimeVarying::default(),
+        };
+        let mean = self.intercept / (1.0 - self.phi.iter().sum::<f64>());
+        let w = util::diff(&self.x, self.d);
+        let y: Vec<f64> = w.iter().map(|v| v - mean).collect();
+        let standardized = system.standardized(&system.filter(&y));
+        standardized[self.ar..].to_vec()
+    }
+
+    /// Number of residuals used in the conditional sum of squares.
+    pub fn n_used(&self) -> usize {
+        self.residuals.len() - self.ar
+    }
+
+    /// Number of estimated parameters, including the intercept and the variance.
+    pub fn n_params(&self) -> usize {
+        self.ar + self.ma + 2
+    }
+
+    /// Conditional sum of squares of the residuals.
+    pub fn css(&self) -> f64 {
+        self.residuals.iter().map(|r| r * r).sum()
+    }
+
+    /// Gaussian log-likelihood conditional on the first `ar` values of the
+    /// differenced series.
+    pub fn loglik(&self) -> f64 {
+        let n = self.n_used() as f64;
+        -0.5 * n * ((2.0 * PI).ln() + 1.0 + self.sigma2.ln())
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik() + 2.0 * self.n_params() as f64
+    }
+
+    /// Akaike information criterion with small sample correction. Infinite if there
+    /// are not enough observations for the correction.
+    pub fn aicc(&self) -> f64 {
+        let n = self.n_used() as f64;
+        let k = self.n_params() as f64;
+        if n - k - 1.0 <= 0.0 {
+            return f64::INFINITY;
+        }
+        self.aic() + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik() + (self.n_used() as f64).ln() * self.n_params() as f64
+    }
+
+    /// In-sample one-step-ahead fitted values of the original series. The first
+    /// `d + ar` values cannot be predicted and are omitted, so the result has length
+    /// `x.len() - d - ar`.
+    pub fn fitted(&self) -> Vec<f64> {
+        let offset = self.d + self.ar;
+        // the one-step error of the integrated series equals the error of the
+        // differenced series
+        self.x[offset..]
+            .iter()
+            .zip(&self.residuals[self.ar..])
+            .map(|(x, r)| x - r)
+            .collect()
+    }
+
+    /// In-sample accuracy of the one-step-ahead fitted values.
+    pub fn accuracy(&self) -> Result<Accuracy> {
+        metrics::accuracy(&self.x[self.d + self.ar..], &self.fitted())
+    }
+
+    /// Roots of the AR and MA polynomials with flags for near unit roots and nearly
+    /// cancelling roots.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+    /// let m = FittedModel::from_coef(&x, &[0.0, 0.9, 0.1], 1, 0, 1).unwrap();
+    /// let roots = m.roots();
+    /// assert!(roots.stationary);
+    /// assert!(!roots.near_unit_ar);
+    /// ```
+    pub fn roots(&self) -> Roots {
+        Roots::new(&self.phi, &self.theta)
+    }
+
+    /// Point forecasts of the original series for the next `h` time steps. Future
+    /// innovations are set to their expectation zero and the forecasts of the
+    /// differenced series are integrated `d` times.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+    /// // random walk with drift 1
+    /// let m = FittedModel::from_coef(&x, &[1.0], 0, 1, 0).unwrap();
+    /// assert_eq!(m.forecast(2), vec![6.0, 7.0]);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        self.forecast_from(h, self.intercept, true, true)
+    }
+
+    /// Decompose the point forecasts of the next `h` time steps into the contributions
+    /// of the intercept, of the observations through the AR terms and the
+    /// integration, and of the recent shocks (residuals) through the MA terms. The
+    /// forecasts are linear in these inputs, so the contributions add up to `forecast`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+    /// let m = FittedModel::from_coef(&x, &[0.5, 0.6, 0.2], 1, 0, 1).unwrap();
+    /// let c = m.forecast_contributions(3);
+    /// // AR(1) with intercept 0.5: 0.5 from the intercept, 0.6 * 1.2 from the last value
+    /// assert!((c.intercept[0] - 0.5).abs() < 1.0e-12);
+    /// assert!((c.ar[0] - 0.72).abs() < 1.0e-12);
+    /// assert!((c.ma[0] - 0.2 * m.residuals[9]).abs() < 1.0e-12);
+    /// let f = m.forecast(3);
+    /// assert!((c.total()[2] - f[2]).abs() < 1.0e-12);
+    /// ```
+    pub fn forecast_contributions(&self, h: usize) -> Contributions {
+        Contributions {
+            intercept: self.forecast_from(h, self.intercept, false, false),
+            ar: self.forecast_from(h, 0.0, true, false),
+            ma: self.forecast_from(h, 0.0, false, true),
+            regressors: Vec::new(),
+        }
+    }
+
+    /// Forecast recursion with the given intercept, and with the observations and the
+    /// residuals or zeros in their place.
+    fn forecast_from(
+        &self,
+        h: usize,
+        intercept: f64,
+        observations: bool,
+        shocks: bool,
+    ) -> Vec<f64> {
+        // last value of the series differenced k times, for k in 0..d
+        let mut last: Vec<f64> = (0..self.d)
+            .map(|k| {
+                let w = if k > 0 {
+                    util::diff(&self.x, k)
+                } else {
+                    self.x.clone()
+                };
+                w[w.len() - 1]
+            })
+            .collect();
+        let mut w = if self.d > 0 {
+            util::diff(&self.x, self.d)
+        } else {
+            self.x.clone()
+        };
+        let mut e = self.residuals.clone();
+        if !observations {
+            last.iter_mut().for_each(|v| *v = 0.0);
+            w.iter_mut().for_each(|v| *v = 0.0);
+        }
+        if !shocks {
+            e.iter_mut().for_each(|v| *v = 0.0);
+        }
+
+        let mut out = Vec::with_capacity(h);
+        for _ in 0..h {
+            let t = w.len();
+            let mut next = intercept;
+            for (i, phi) in self.phi.iter().enumerate() {
+                next += phi * w[t - i - 1];
+            }
+            for (j, theta) in self.theta.iter().enumerate() {
+                if t > j {
+                    next += theta * e[t - j - 1];
+                }
+            }
+            w.push(next);
+            e.push(0.0);
+
+            let mut value = next;
+            for l in last.iter_mut().rev() {
+                *l += value;
+                value = *l;
+            }
+            out.push(value);
+        }
+        out
+    }
+
+    /// Variances of the point forecast errors for the next `h` time steps,
+    /// `sigma2 * (psi_0^2 + ... + psi_{k-1}^2)` with the psi-weights of the integrated
+    /// model. Parameter uncertainty is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 2.0, 4.0, 3.0, 5.0];
+    /// // the forecast error variance of a random walk grows linearly
+    /// let m = FittedModel::from_coef(&x, &[0.0], 0, 1, 0).unwrap();
+    /// let var = m.forecast_variance(3);
+    /// assert!((var[2] - 3.0 * m.sigma2).abs() < 1.0e-12);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let mut sum = 0.0;
+        self.psi_weights(h)
+            .iter()
+            .take(h)
+            .map(|p| {
+                sum += p * p;
+                self.sigma2 * sum
+            })
+            .collect()
+    }
+
+    /// Psi-weights `psi_0, ..., psi_h` of the integrated model, i.e. the impulse
+    /// responses of the original series to a unit innovation. See `psi_weights`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::FittedModel;
+    /// let x = [1.0, 2.0, 4.0, 3.0, 5.0];
+    /// // an innovation of a random walk persists forever
+    /// let m = FittedModel::from_coef(&x, &[0.0], 0, 1, 0).unwrap();
+    /// assert_eq!(m.psi_weights(3), vec![1.0, 1.0, 1.0, 1.0]);
+    /// ```
+    pub fn psi_weights(&self, h: usize) -> Vec<f64> {
+        psi_weights(&integrated_phi(&self.phi, self.d), &self.theta, h)
+    }
+
+    /// Pi-weights `pi_1, ..., pi_h` of the integrated model, the coefficients of its
+    /// AR(infinity) representation in terms of the original series. See `pi_weights`.
+    pub fn pi_weights(&self, h: usize) -> Vec<f64> {
+        pi_weights(&integrated_phi(&self.phi, self.d), &self.theta, h)
+    }
+
+    /// Spectral density of the differenced series implied by the fitted ARMA part, at
+    /// the frequencies `freqs` in cycles per time step. Compare with the periodogram of
+    /// the differenced series, see `spectrum::periodogram`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::{model, spectrum, util};
+    /// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+    /// let m = model::fit(&x, 1, 1, 0).unwrap();
+    /// let (freqs, pgram) = spectrum::periodogram(&util::diff(&x, 1)).unwrap();
+    /// let f = m.spectrum(&freqs);
+    /// assert_eq!(f.len(), pgram.len());
+    /// ```
+    pub fn spectrum(&self, freqs: &[f64]) -> Vec<f64> {
+        spectrum::arma_spectrum(&self.phi, &self.theta, self.sigma2, freqs)
+    }
+}
+
+/// A root of a characteristic polynomial.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Root {
+    /// Real part.
+    pub re: f64,
+    /// Imaginary part.
+    pub im: f64,
+    /// Modulus. Stationarity and invertibility require all moduli to exceed one.
+    pub modulus: f64,
+    /// Frequency in cycles per time step within [0, 0.5]. Zero for real positive roots.
+    pub frequency: f64,
+}
+
+/// Roots of the AR polynomial `1 - phi_1 z - ... - phi_p z^p` and the MA polynomial
+/// `1 + theta_1 z + ... + theta_q z^q` of a model.
+#[derive(Debug, Clone)]
+pub struct Roots {
+    /// Roots of the AR polynomial.
+    pub ar: Vec<Root>,
+    /// Roots of the MA polynomial.
+    pub ma: Vec<Root>,
+    /// True if all AR roots lie outside the unit circle.
+    pub stationary: bool,
+    /// True if all MA roots lie outside the unit circle.
+    pub invertible: bool,
+    /// True if an AR root has a modulus within `UNIT_ROOT_TOLERANCE` of one, which
+    /// suggests that the series should be differenced.
+    pub near_unit_ar: bool,
+    /// True if an MA root has a modulus within `UNIT_ROOT_TOLERANCE` of one, which
+    /// suggests that the series is over-differenced.
+    pub near_unit_ma: bool,
+    /// Pairs of indices into `ar` and `ma` of roots within `CANCELLATION_TOLERANCE` of
+    /// each other, which suggests a redundant model with too high orders.
+    pub near_cancellation: Vec<(usize, usize)>,
+}
+
+impl Roots {
+    /// Roots of the polynomials of the given AR and MA coefficients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::model::Roots;
+    /// // 1 - 0.5 z has the root 2, 1 + 0.5 z the root -2
+    /// let roots = Roots::new(&[0.5], &[0.5]);
+    /// assert!((roots.ar[0].re - 2.0).abs() < 1.0e-9);
+    /// assert!((roots.ma[0].frequency - 0.5).abs() < 1.0e-9);
+    /// assert!(roots.stationary && roots.invertible);
+    /// ```
+    pub fn new(phi: &[f64], theta: &[f64]) -> Self {
+        let ar_poly: Vec<f64> = std::iter::once(1.0).chain(phi.iter().map(|v| -v)).collect();
+        let ma_poly: Vec<f64> = std::iter::once(1.0).chain(theta.iter().copied()).collect();
+        let ar_roots = polyroots(&ar_poly);
+        let ma_roots = polyroots(&ma_poly);
+
+        let mut near_cancellation = Vec::new();
+        for (i, a) in ar_roots.iter().enumerate() {
+            for (j, m) in ma_roots.iter().enumerate() {
+                if (a - m).norm() < CANCELLATION_TOLERANCE {
+                    near_cancellation.push((i, j));
+                }
+            }
+        }
+        let ar: Vec<Root> = ar_roots.iter().map(root).collect();
+        let ma: Vec<Root> = ma_roots.iter().map(root).collect();
+        let near_unit = |r: &Root| (r.modulus - 1.0).abs() < UNIT_ROOT_TOLERANCE;
+        Roots {
+            stationary: ar.iter().all(|r| r.modulus > 1.0),
+            invertible: ma.iter().all(|r| r.modulus > 1.0),
+            near_unit_ar: ar.iter().any(near_unit),
+            near_unit_ma: ma.iter().any(near_unit),
+            near_cancellation,
+            ar,
+            ma,
+        }
+    }
+}
+
+fn root(z: &Complex64) -> Root {
+    Root {
+        re: z.re,
+        im: z.im,
+        modulus: z.norm(),
+        frequency: z.arg().abs() / (2.0 * PI),
+    }
+}
+
+/// Roots of the polynomial `c_0 + c_1 z + ... + c_n z^n` by the Durand-Kerner method.
+pub(crate) fn polyroots(coef: &[f64]) -> Vec<Complex64> {
+    let degree = match coef.iter().rposition(|c| *c != 0.0) {
+        Some(d) => d,
+        None => return Vec::new(),
+    };
+    let lead = coef[degree];
+    let monic: Vec<f64> = coef[..degree + 1].iter().map(|c| c / lead).collect();
+    let eval = |z: Complex64| {
+        monic
+            .iter()
+            .rev()
+            .fold(Complex64::new(0.0, 0.0), |acc, c| acc * z + c)
+    };
+
+    let seed = Complex64::new(0.4, 0.9);
+    let mut roots: Vec<Complex64> = (0..degree).map(|k| seed.powu(k as u32)).collect();
+    for _ in 0..1000 {
+        let mut change: f64 = 0.0;
+        for i in 0..degree {
+            let mut denom = Complex64::new(1.0, 0.0);
+            for j in 0..degree {
+                if i != j {
+                    denom *= roots[i] - roots[j];
+                }
+            }
+            let delta = eval(roots[i]) / denom;
+            roots[i] -= delta;
+            change = change.max(delta.norm());
+        }
+        if change < 1.0e-14 {
+            break;
+        }
+    }
+    // clean up imaginary parts of real roots
+    for r in roots.iter_mut() {
+        if r.im.abs() < 1.0e-10 * r.norm().max(1.0) {
+            r.im = 0.0;
+        }
+    }
+    roots
+}
+
+/// AR coefficients of `phi(B) (1 - B)^d`, the AR polynomial of the integrated series.
+fn integrated_phi(phi: &[f64], d: usize) -> Vec<f64> {
+    let mut poly: Vec<f64> = std::iter::once(1.0).chain(phi.iter().map(|v| -v)).collect();
+    for _ in 0..d {
+        let mut next = vec![0.0; poly.len() + 1];
+        for (i, c) in poly.iter().enumerate() {
+            next[i] += c;
+            next[i + 1] -= c;
+        }
+        poly = next;
+    }
+    poly[1..].iter().map(|c| -c).collect()
+}
+
+/// Psi-weights of an ARMA model, the coefficients of its MA(infinity) representation
+/// `x_t = mu + psi_0 e_t + psi_1 e_{t-1} + ...` with `psi_0 = 1`. They are the impulse
+/// responses to a unit innovation and determine the forecast error variances.
+///
+/// # Arguments
+///
+/// * `&phi` - AR coefficients.
+/// * `&theta` - MA coefficients.
+/// * `h` - Largest lag.
+///
+/// # Returns
+///
+/// * Vector of the `h + 1` weights `psi_0, ..., psi_h`.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// // ARMA(1, 1): psi_j = (phi + theta) phi^(j - 1)
+/// let psi = model::psi_weights(&[0.5], &[0.3], 3);
+/// assert!((psi[1] - 0.8).abs() < 1.0e-12);
+/// assert!((psi[3] - 0.2).abs() < 1.0e-12);
+/// ```
+pub fn psi_weights(phi: &[f64], theta: &[f64], h: usize) -> Vec<f64> {
+    let mut psi: Vec<f64> = Vec::with_capacity(h + 1);
+    for j in 0..h + 1 {
+        let mut p = if j == 0 {
+            1.0
+        } else {
+            theta.get(j - 1).copied().unwrap_or(0.0)
+        };
+        for (i, a) in phi.iter().enumerate().take(j) {
+            p += a * psi[j - i - 1];
+        }
+        psi.push(p);
+    }
+    psi
+}
+
+/// Pi-weights of an ARMA model, the coefficients of its AR(infinity) representation
+/// `x_t = c + pi_1 x_{t-1} + pi_2 x_{t-2} + ... + e_t`. Truncating them gives a
+/// one-sided filter that recovers the innovations from past values. The weights decay
+/// geometrically only for an invertible model; weights that stay large or grow
+/// indicate an MA root on or inside the unit circle.
+///
+/// # Arguments
+///
+/// * `&phi` - AR coefficients.
+/// * `&theta` - MA coefficients.
+/// * `h` - Largest lag.
+///
+/// # Returns
+///
+/// * Vector of the `h` weights `pi_1, ..., pi_h`.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// // MA(1): pi_j = -(-theta)^j
+/// let pi = model::pi_weights(&[], &[0.5], 3);
+/// assert_eq!(pi, vec![0.5, -0.25, 0.125]);
+/// // an AR model is its own AR(infinity) representation
+/// assert_eq!(model::pi_weights(&[0.6, 0.2], &[], 3), vec![0.6, 0.2, 0.0]);
+/// ```
+pub fn pi_weights(phi: &[f64], theta: &[f64], h: usize) -> Vec<f64> {
+    // coefficients of phi(B) / theta(B) = 1 - pi_1 B - pi_2 B^2 - ...
+    let mut c: Vec<f64> = Vec::with_capacity(h + 1);
+    c.push(1.0);
+    for j in 1..h + 1 {
+        let mut v = -phi.get(j - 1).copied().unwrap_or(0.0);
+        for (i, t) in theta.iter().enumerate().take(j) {
+            v -= t * c[j - i - 1];
+        }
+        c.push(v);
+    }
+    c[1..].iter().map(|v| -v).collect()
+}
+
+/// Fit an ARIMA model and return it as a `FittedModel`.
+/// See `estimate::fit` for details on the estimation.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `ar` - Order of the AR coefficients.
+/// * `d` - Order of differencing.
+/// * `ma` - Order of the MA coefficients.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+/// let m = model::fit(&x, 1, 0, 0).unwrap();
+/// assert_eq!(m.phi.len(), 1);
+/// assert!(m.aicc() > m.aic());
+/// ```
+pub fn fit<T: Float + From<u32> + From<f64> + Into<f64> + Copy + Add + AddAssign + Div + Debug>(
+    x: &[T],
+    ar: usize,
+    d: usize,
+    ma: usize,
+) -> Result<FittedModel> {
+    let coef = estimate::fit(x, ar, d, ma)?;
+    let x: Vec<f64> = x.iter().map(|v| (*v).into()).collect();
+    FittedModel::from_coef(&x, &coef, ar, d, ma)
+}
+
+/// Additive contributions to point forecasts, see
+/// `FittedModel::forecast_contributions`. All vectors have one item per forecast step.
+#[derive(Debug, Clone)]
+pub struct Contributions {
+    /// Contribution of the intercept, i.e. of the mean or, for differenced models, of
+    /// the drift.
+    pub intercept: Vec<f64>,
+    /// Contribution of the observations through the AR terms and, for differenced
+    /// models, the last levels of the series.
+    pub ar: Vec<f64>,
+    /// Contribution of the recent shocks (residuals) through the MA terms.
+    pub ma: Vec<f64>,
+    /// Contribution of each exogenous regressor, empty for models without regressors.
+    pub regressors: Vec<Vec<f64>>,
+}
+
+impl Contributions {
+    /// Sum of the contributions, i.e. the point forecasts.
+    pub fn total(&self) -> Vec<f64> {
+        (0..self.intercept.len())
+            .map(|i| {
+                self.intercept[i]
+                    + self.ar[i]
+                    + self.ma[i]
+                    + self.regressors.iter().map(|r| r[i]).sum::<f64>()
+            })
+            .collect()
+    }
+}
+
+/// One row of the table returned by `compare`.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// Index of the model in the slice passed to `compare`.
+    pub index: usize,
+    /// Model order (p, d, q).
+    pub order: (usize, usize, usize),
+    /// Log-likelihood.
+    pub loglik: f64,
+    /// Akaike information criterion.
+    pub aic: f64,
+    /// Corrected Akaike information criterion.
+    pub aicc: f64,
+    /// Bayesian information criterion.
+    pub bic: f64,
+    /// In-sample accuracy.
+    pub accuracy: Accuracy,
+}
+
+/// Compare fitted models by their information criteria and in-sample accuracy.
+///
+/// All models must be fitted to the same series with the same order of differencing.
+/// Like `ic_table`, every model is evaluated conditional on the first `max(ar)` values
+/// of the differenced series, so the criteria are computed on the same observations:
+/// the innovation variance is re-estimated from the residuals of that common sample,
+/// and models of lower AR order skip a few leading residuals. The rows therefore
+/// differ from `FittedModel::aicc` and friends if the AR orders differ.
+///
+/// # Arguments
+///
+/// * `&models` - Fitted candidate models.
+///
+/// # Returns
+///
+/// * One row per model, ranked by ascending AICc, with NaN values last.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+/// let m1 = model::fit(&x, 1, 0, 0).unwrap();
+/// let m2 = model::fit(&x, 2, 0, 0).unwrap();
+/// let table = model::compare(&[m1, m2]).unwrap();
+/// assert_eq!(table.len(), 2);
+/// assert!(table[0].aicc <= table[1].aicc);
+/// ```
+pub fn compare(models: &[FittedModel]) -> Result<Vec<Comparison>> {
+    let first = match models.first() {
+        Some(m) => m,
+        None => return Ok(Vec::new()),
+    };
+    if models.iter().any(|m| m.d != first.d || m.x != first.x) {
+        anyhow::bail!(
+            "Models must be fitted to the same series with the same order of differencing"
+        );
+    }
+    let max_ar = models.iter().map(|m| m.ar).max().unwrap_or(0);
+    if first.residuals.len() <= max_ar {
+        anyhow::bail!(
+            "Not enough observations to compare AR orders up to {}",
+            max_ar
+        );
+    }
+
+    let mut table = Vec::with_capacity(models.len());
+    for (index, m) in models.iter().enumerate() {
+        let residuals = &m.residuals[max_ar..];
+        let n = residuals.len() as f64;
+        let k = m.n_params() as f64;
+        let sigma2 = residuals.iter().map(|r| r * r).sum::<f64>() / n;
+        let loglik = -0.5 * n * ((2.0 * PI).ln() + 1.0 + sigma2.ln());
+        let aic = -2.0 * loglik + 2.0 * k;
+        let aicc = if n - k - 1.0 <= 0.0 {
+            f64::INFINITY
+        } else {
+            aic + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+        };
+        let fitted = m.fitted();
+        table.push(Comparison {
+            index,
+            order: (m.ar, m.d, m.ma),
+            loglik,
+            aic,
+            aicc,
+            bic: -2.0 * loglik + n.ln() * k,
+            accuracy: metrics::accuracy(&m.x[m.d + max_ar..], &fitted[max_ar - m.ar..])?,
+        });
+    }
+    table.sort_by(|a, b| {
+        a.aicc
+            .is_nan()
+            .cmp(&b.aicc.is_nan())
+            .then(a.aicc.total_cmp(&b.aicc))
+    });
+    Ok(table)
+}
+
+/// AICc values of ARIMA(p, d, q) models over a grid of AR and MA orders.
+#[derive(Debug, Clone)]
+pub struct IcTable {
+    /// AR orders, one per row.
+    pub ar: Vec<usize>,
+    /// Order of differencing.
+    pub d: usize,
+    /// MA orders, one per column.
+    pub ma: Vec<usize>,
+    /// AICc values, `aicc[i][j]` for AR order `ar[i]` and MA order `ma[j]`. NaN if the
+    /// model could not be fitted.
+    pub aicc: Vec<Vec<f64>>,
+}
+
+impl IcTable {
+    /// Orders (p, q) and AICc of the model with the smallest AICc, or `None` if no model
+    /// could be fitted.
+    pub fn best(&self) -> Option<(usize, usize, f64)> {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (i, row) in self.aicc.iter().enumerate() {
+            for (j, v) in row.iter().enumerate() {
+                let better = match best {
+                    Some(b) => *v < b.2,
+                    None => true,
+                };
+                if better && !v.is_nan() {
+                    best = Some((self.ar[i], self.ma[j], *v));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// AICc of ARIMA(ar, d, ma) conditioned on the first `max_ar` (differenced) values, so
+/// that all models of a table are evaluated on the same observations.
+fn ic_cell(x: &[f64], max_ar: usize, ar: usize, d: usize, ma: usize) -> f64 {
+    match fit(x.get(max_ar - ar..).unwrap_or(&[]), ar, d, ma) {
+        Ok(m) => m.aicc(),
+        Err(e) => {
+            tracing::warn!("Could not fit ARIMA({}, {}, {}): {}", ar, d, ma, e);
+            f64::NAN
+        }
+    }
+}
+
+/// Fit ARIMA(p, d, q) models for all combinations of the given AR and MA orders and
+/// tabulate their AICc values for model identification.
+///
+/// All models condition on the first `max(ar)` values of the differenced series, so
+/// their likelihoods are computed on the same observations and the AICc values are
+/// comparable. Models of lower AR order therefore skip a few leading values.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `&ar` - AR orders to try.
+/// * `d` - Order of differencing.
+/// * `&ma` - MA orders to try.
+///
+/// # Returns
+///
+/// * Table of AICc values.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+/// let table = model::ic_table(&x, &[0, 1, 2], 0, &[0, 1]);
+/// assert_eq!(table.aicc.len(), 3);
+/// assert_eq!(table.aicc[0].len(), 2);
+/// let (p, q, aicc) = table.best().unwrap();
+/// let i = table.ar.iter().position(|v| *v == p).unwrap();
+/// let j = table.ma.iter().position(|v| *v == q).unwrap();
+/// assert_eq!(table.aicc[i][j], aicc);
+/// ```
+pub fn ic_table(x: &[f64], ar: &[usize], d: usize, ma: &[usize]) -> IcTable {
+    let max_ar = ar.iter().copied().max().unwrap_or(0);
+    let aicc = ar
+        .iter()
+        .map(|p| ma.iter().map(|q| ic_cell(x, max_ar, *p, d, *q)).collect())
+        .collect();
+    IcTable {
+        ar: ar.to_vec(),
+        d,
+        ma: ma.to_vec(),
+        aicc,
+    }
+}
+
+/// Same as `ic_table`, but fits the models in parallel. Only enabled with feature
+/// `rayon`.
+///
+/// # Example
+///
+/// ```
+/// use arima::model;
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9, 1.1, 1.3];
+/// let table = model::ic_table_par(&x, &[0, 1], 0, &[0, 1]);
+/// assert_eq!(table.aicc, model::ic_table(&x, &[0, 1], 0, &[0, 1]).aicc);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn ic_table_par(x: &[f64], ar: &[usize], d: usize, ma: &[usize]) -> IcTable {
+    let max_ar = ar.iter().copied().max().unwrap_or(0);
+    let aicc = ar
+        .par_iter()
+        .map(|p| {
+            ma.par_iter()
+                .map(|q| ic_cell(x, max_ar, *p, d, *q))
+                .collect()
+        })
+        .collect();
+    IcTable {
+        ar: ar.to_vec(),
+        d,
+        ma: ma.to_vec(),
+        aicc,
+    }
+}
@@ -0,0 +1,138 @@
+//! Loading time series from Parquet files. Only enabled with feature `parquet`.
+
+use anyhow::Result;
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use ::parquet::basic::{LogicalType, TimeUnit};
+use ::parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use ::parquet::record::Field;
+use ::parquet::schema::types::Type;
+use bytes::Bytes;
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Values of a time series read from a Parquet file, with optional timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetSeries {
+    /// Values of the series. Null values are NaN.
+    pub values: Vec<f64>,
+    /// Timestamps in microseconds since the Unix epoch, if a timestamp column was read.
+    pub timestamps: Option<Vec<i64>>,
+}
+
+/// Read a value column and an optional timestamp column from a Parquet file.
+///
+/// The value column may have any integer or floating point type; nulls become NaN. The
+/// timestamp column must be a timestamp (milli-, micro- or nanosecond) or date column.
+/// Snappy, gzip, LZ4 and zstd compressed files are supported.
+///
+/// # Arguments
+///
+/// * `path` - Path of the Parquet file.
+/// * `value_column` - Name of the value column.
+/// * `timestamp_column` - Name of the timestamp column, if any.
+///
+/// # Returns
+///
+/// * Values and timestamps in the order of the rows of the file.
+pub fn read_parquet<P: AsRef<Path>>(
+    path: P,
+    value_column: &str,
+    timestamp_column: Option<&str>,
+) -> Result<ParquetSeries> {
+    read(File::open(path)?, value_column, timestamp_column)
+}
+
+/// Read a value column and an optional timestamp column from the contents of a Parquet
+/// file, e.g. an object downloaded from object storage. See `read_parquet`.
+pub fn read_parquet_bytes(
+    data: Vec<u8>,
+    value_column: &str,
+    timestamp_column: Option<&str>,
+) -> Result<ParquetSeries> {
+    read(Bytes::from(data), value_column, timestamp_column)
+}
+
+fn read<R: ChunkReader + 'static>(
+    chunk_reader: R,
+    value_column: &str,
+    timestamp_column: Option<&str>,
+) -> Result<ParquetSeries> {
+    let reader = SerializedFileReader::new(chunk_reader)?;
+    let schema = reader.metadata().file_metadata().schema();
+    let find = |name: &str| -> Result<Arc<Type>> {
+        match schema.get_fields().iter().find(|f| f.name() == name) {
+            Some(f) if f.is_primitive() => Ok(f.clone()),
+            Some(_) => anyhow::bail!("Column {} is not a primitive column", name),
+            None => anyhow::bail!("Column {} not found", name),
+        }
+    };
+
+    let mut fields = vec![find(value_column)?];
+    let nanos = match timestamp_column {
+        Some(name) => {
+            let field = find(name)?;
+            let nanos = matches!(
+                field.get_basic_info().logical_type_ref(),
+                Some(LogicalType::Timestamp(t)) if matches!(t.unit, TimeUnit::NANOS)
+            );
+            fields.push(field);
+            nanos
+        }
+        None => false,
+    };
+    let projection = Type::group_type_builder(schema.name())
+        .with_fields(fields)
+        .build()?;
+
+    let mut values = Vec::new();
+    let mut timestamps = timestamp_column.map(|_| Vec::new());
+    for row in reader.get_row_iter(Some(projection))? {
+        let row = row?;
+        let mut columns = row.get_column_iter();
+        if let Some((name, field)) = columns.next() {
+            values.push(value(name, field)?);
+        }
+        if let (Some(timestamps), Some((name, field))) = (timestamps.as_mut(), columns.next()) {
+            timestamps.push(timestamp(name, field, nanos)?);
+        }
+    }
+    if let Some(timestamps) = &timestamps {
+        if timestamps.len() != values.len() {
+            anyhow::bail!("Timestamp column has a different number of rows");
+        }
+    }
+
+    Ok(ParquetSeries { values, timestamps })
+}
+
+fn value(name: &str, field: &Field) -> Result<f64> {
+    Ok(match field {
+        Field::Null => f64::NAN,
+        Field::Double(v) => *v,
+        Field::Float(v) => *v as f64,
+        Field::Byte(v) => *v as f64,
+        Field::Short(v) => *v as f64,
+        Field::Int(v) => *v as f64,
+        Field::Long(v) => *v as f64,
+        Field::UByte(v) => *v as f64,
+        Field::UShort(v) => *v as f64,
+        Field::UInt(v) => *v as f64,
+        Field::ULong(v) => *v as f64,
+        _ => anyhow::bail!("Column {} is not numeric", name),
+    })
+}
+
+fn timestamp(name: &str, field: &Field, nanos: bool) -> Result<i64> {
+    Ok(match field {
+        Field::TimestampMillis(v) => v * 1000,
+        Field::TimestampMicros(v) => *v,
+        Field::Long(v) if nanos => v.div_euclid(1000),
+        Field::Date(v) => *v as i64 * MICROS_PER_DAY,
+        Field::Null => anyhow::bail!("Column {} contains nulls", name),
+        _ => anyhow::bail!("Column {} is not a timestamp or date column", name),
+    })
+}
@@ -0,0 +1,189 @@
+//! Versioned binary format for fitted models.
+//!
+//! A file starts with the 8 magic bytes `MAGIC` and the format version as a
+//! little-endian `u16`, followed by a sequence of fields. Each field is a `u16` tag, a
+//! `u32` payload length in bytes and the payload. Integers are little-endian `u32`,
+//! floats little-endian `f64` and vectors a sequence of floats.
+//!
+//! Compatibility rules:
+//!
+//! * New fields get new tags and must be optional. Readers skip fields with unknown
+//!   tags, so files written by newer versions of this crate load in older ones as long
+//!   as the format version is unchanged.
+//! * Tags of removed fields are never reused.
+//! * The format version is only increased for changes that older readers cannot
+//!   handle, such as a changed payload of an existing tag. Readers reject files with a
+//!   newer format version and keep loading all older versions.
+
+use anyhow::Result;
+
+use std::fs;
+use std::path::Path;
+
+use crate::model::FittedModel;
+
+/// Magic bytes at the start of a model file.
+pub const MAGIC: [u8; 8] = *b"ARIMAFIT";
+
+/// Current format version.
+pub const FORMAT_VERSION: u16 = 1;
+
+// field tags of format version 1
+const TAG_ORDER: u16 = 1;
+const TAG_INTERCEPT: u16 = 2;
+const TAG_PHI: u16 = 3;
+const TAG_THETA: u16 = 4;
+const TAG_SIGMA2: u16 = 5;
+const TAG_RESIDUALS: u16 = 6;
+const TAG_X: u16 = 7;
+
+/// Serialize a fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::{model, persist};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+/// let m = model::fit(&x, 1, 0, 0).unwrap();
+/// let bytes = persist::to_bytes(&m);
+/// assert_eq!(&bytes[..8], b"ARIMAFIT");
+/// let loaded = persist::from_bytes(&bytes).unwrap();
+/// assert_eq!(loaded.forecast(3), m.forecast(3));
+/// ```
+pub fn to_bytes(fit: &FittedModel) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+
+    let order: Vec<u8> = [fit.ar, fit.d, fit.ma]
+        .iter()
+        .flat_map(|v| (*v as u32).to_le_bytes())
+        .collect();
+    write_field(&mut out, TAG_ORDER, &order);
+    write_floats(&mut out, TAG_INTERCEPT, &[fit.intercept]);
+    write_floats(&mut out, TAG_PHI, &fit.phi);
+    write_floats(&mut out, TAG_THETA, &fit.theta);
+    write_floats(&mut out, TAG_SIGMA2, &[fit.sigma2]);
+    write_floats(&mut out, TAG_RESIDUALS, &fit.residuals);
+    write_floats(&mut out, TAG_X, &fit.x);
+    out
+}
+
+/// Deserialize a fitted model written by `to_bytes` with this or an older format
+/// version.
+pub fn from_bytes(bytes: &[u8]) -> Result<FittedModel> {
+    if bytes.len() < MAGIC.len() + 2 || bytes[..MAGIC.len()] != MAGIC {
+        anyhow::bail!("Not a model file");
+    }
+    let version = u16::from_le_bytes([bytes[8], bytes[9]]);
+    if version == 0 || version > FORMAT_VERSION {
+        anyhow::bail!(
+            "Unsupported format version {}, expected at most {}",
+            version,
+            FORMAT_VERSION
+        );
+    }
+
+    let mut order = None;
+    let mut intercept = None;
+    let mut phi = None;
+    let mut theta = None;
+    let mut sigma2 = None;
+    let mut residuals = None;
+    let mut x = None;
+    let mut rest = &bytes[10..];
+    while !rest.is_empty() {
+        if rest.len() < 6 {
+            anyhow::bail!("Truncated field header");
+        }
+        let tag = u16::from_le_bytes([rest[0], rest[1]]);
+        let len = u32::from_le_bytes([rest[2], rest[3], rest[4], rest[5]]) as usize;
+        if rest.len() - 6 < len {
+            anyhow::bail!("Truncated field {}", tag);
+        }
+        let payload = &rest[6..6 + len];
+        rest = &rest[6 + len..];
+        match tag {
+            TAG_ORDER => {
+                if len != 12 {
+                    anyhow::bail!("Invalid model order");
+                }
+                let v: Vec<usize> = payload
+                    .chunks(4)
+                    .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as usize)
+                    .collect();
+                order = Some((v[0], v[1], v[2]));
+            }
+            TAG_INTERCEPT => intercept = Some(read_scalar(payload, tag)?),
+            TAG_PHI => phi = Some(read_floats(payload, tag)?),
+            TAG_THETA => theta = Some(read_floats(payload, tag)?),
+            TAG_SIGMA2 => sigma2 = Some(read_scalar(payload, tag)?),
+            TAG_RESIDUALS => residuals = Some(read_floats(payload, tag)?),
+            TAG_X => x = Some(read_floats(payload, tag)?),
+            // fields added after this version of the crate
+            _ => {}
+        }
+    }
+
+    let missing = |name: &str| anyhow::anyhow!("Missing field {}", name);
+    let (ar, d, ma) = order.ok_or_else(|| missing("order"))?;
+    let fit = FittedModel {
+        ar,
+        d,
+        ma,
+        intercept: intercept.ok_or_else(|| missing("intercept"))?,
+        phi: phi.ok_or_else(|| missing("phi"))?,
+        theta: theta.ok_or_else(|| missing("theta"))?,
+        sigma2: sigma2.ok_or_else(|| missing("sigma2"))?,
+        residuals: residuals.ok_or_else(|| missing("residuals"))?,
+        x: x.ok_or_else(|| missing("x"))?,
+    };
+    if fit.phi.len() != ar || fit.theta.len() != ma {
+        anyhow::bail!("Coefficients do not match the model order");
+    }
+    if fit.x.len() <= d + ar {
+        anyhow::bail!("Time series is too short for the model order");
+    }
+    if fit.residuals.len() != fit.x.len() - d {
+        anyhow::bail!("Residuals do not match the series");
+    }
+    Ok(fit)
+}
+
+/// Write a fitted model to the file at `path`, see `to_bytes`.
+pub fn save<P: AsRef<Path>>(path: P, fit: &FittedModel) -> Result<()> {
+    fs::write(path, to_bytes(fit))?;
+    Ok(())
+}
+
+/// Read a fitted model from the file at `path`, see `from_bytes`.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<FittedModel> {
+    from_bytes(&fs::read(path)?)
+}
+
+fn write_field(out: &mut Vec<u8>, tag: u16, payload: &[u8]) {
+    out.extend_from_slice(&tag.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+}
+
+fn write_floats(out: &mut Vec<u8>, tag: u16, values: &[f64]) {
+    let payload: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    write_field(out, tag, &payload);
+}
+
+fn read_floats(payload: &[u8], tag: u16) -> Result<Vec<f64>> {
+    if payload.len() % 8 != 0 {
+        anyhow::bail!("Invalid length of field {}", tag);
+    }
+    Ok(payload
+        .chunks(8)
+        .map(|c| f64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]]))
+        .collect())
+}
+
+fn read_scalar(payload: &[u8], tag: u16) -> Result<f64> {
+    match read_floats(payload, tag)?.as_slice() {
+        [v] => Ok(*v),
+        _ => anyhow::bail!("Invalid length of field {}", tag),
+    }
+}
@@ -0,0 +1,331 @@
+//! Plots of auto-correlations and forecasts with the plotters crate. Only enabled with
+//! feature `plot`.
+//!
+//! Files with the extension `.svg` are written as SVG, all others as PNG bitmaps.
+
+use anyhow::Result;
+
+use std::ops::Range;
+use std::path::Path;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::acf;
+use crate::model::FittedModel;
+use crate::special;
+
+/// Width and height of the plots in pixels.
+pub const PLOT_SIZE: (u32, u32) = (800, 480);
+
+/// Prediction interval of a fan chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    /// Nominal level, e.g. 0.95.
+    pub level: f64,
+    /// Lower bounds, one per forecast step.
+    pub lower: Vec<f64>,
+    /// Upper bounds, one per forecast step.
+    pub upper: Vec<f64>,
+}
+
+/// Plot the auto-correlations of `x` for lags `0, ..., max_lag` as stems with 95%
+/// bounds `+-1.96 / sqrt(n)` of white noise.
+///
+/// # Arguments
+///
+/// * `path` - Output file, SVG if the extension is `.svg` and PNG otherwise.
+/// * `&x` - Vector of the timeseries.
+/// * `max_lag` - Maximum lag.
+///
+/// # Example
+///
+/// ```
+/// use arima::plot;
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+/// let path = std::env::temp_dir().join("arima_acf_doc.svg");
+/// plot::acf_plot(&path, &x, 5).unwrap();
+/// assert!(std::fs::read_to_string(&path).unwrap().starts_with("<svg"));
+/// ```
+pub fn acf_plot<P: AsRef<Path>>(path: P, x: &[f64], max_lag: usize) -> Result<()> {
+    let rho = acf::acf(x, Some(max_lag), false)?;
+    let bound = 1.96 / (x.len() as f64).sqrt();
+    render(
+        path.as_ref(),
+        &Figure::Stems {
+            title: "ACF",
+            first_lag: 0,
+            values: &rho,
+            bound,
+        },
+    )
+}
+
+/// Plot the partial auto-correlations of `x` for lags `1, ..., max_lag` as stems with
+/// 95% bounds `+-1.96 / sqrt(n)` of white noise.
+///
+/// # Arguments
+///
+/// * `path` - Output file, SVG if the extension is `.svg` and PNG otherwise.
+/// * `&x` - Vector of the timeseries.
+/// * `max_lag` - Maximum lag.
+pub fn pacf_plot<P: AsRef<Path>>(path: P, x: &[f64], max_lag: usize) -> Result<()> {
+    let pacf = acf::pacf(x, Some(max_lag))?;
+    let bound = 1.96 / (x.len() as f64).sqrt();
+    render(
+        path.as_ref(),
+        &Figure::Stems {
+            title: "PACF",
+            first_lag: 1,
+            values: &pacf,
+            bound,
+        },
+    )
+}
+
+/// Plot a fan chart: the observed series followed by the point forecasts, shaded by
+/// the prediction intervals in `bands` (wider intervals lighter).
+///
+/// # Arguments
+///
+/// * `path` - Output file, SVG if the extension is `.svg` and PNG otherwise.
+/// * `&history` - Observed values.
+/// * `&forecast` - Point forecasts of the following time steps.
+/// * `&bands` - Prediction intervals with levels within 0 and 1 (exclusive) and bounds
+///   of the same length as `forecast`.
+pub fn fan_chart<P: AsRef<Path>>(
+    path: P,
+    history: &[f64],
+    forecast: &[f64],
+    bands: &[Band],
+) -> Result<()> {
+    if bands
+        .iter()
+        .any(|b| b.lower.len() != forecast.len() || b.upper.len() != forecast.len())
+    {
+        anyhow::bail!("Interval bounds must have the same length as the forecast");
+    }
+    if bands.iter().any(|b| !(b.level > 0.0 && b.level < 1.0)) {
+        anyhow::bail!("Interval levels must be within 0 and 1 (exclusive)");
+    }
+    render(
+        path.as_ref(),
+        &Figure::Fan {
+            history,
+            forecast,
+            bands,
+        },
+    )
+}
+
+/// Plot a fan chart of the Gaussian `h`-step forecasts of a fitted model, see
+/// `fan_chart` and `FittedModel::forecast_variance`.
+///
+/// # Arguments
+///
+/// * `path` - Output file, SVG if the extension is `.svg` and PNG otherwise.
+/// * `&fit` - Fitted model.
+/// * `h` - Forecast horizon.
+/// * `&levels` - Nominal levels of the prediction intervals, e.g. `[0.8, 0.95]`.
+///
+/// # Example
+///
+/// ```
+/// use arima::{model, plot};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+/// let m = model::fit(&x, 1, 0, 0).unwrap();
+/// let path = std::env::temp_dir().join("arima_fan_doc.png");
+/// plot::forecast_fan_chart(&path, &m, 6, &[0.8, 0.95]).unwrap();
+/// ```
+pub fn forecast_fan_chart<P: AsRef<Path>>(
+    path: P,
+    fit: &FittedModel,
+    h: usize,
+    levels: &[f64],
+) -> Result<()> {
+    if levels.iter().any(|l| !(*l > 0.0 && *l < 1.0)) {
+        anyhow::bail!("Levels must be within 0 and 1");
+    }
+    let forecast = fit.forecast(h);
+    let sd: Vec<f64> = fit.forecast_variance(h).iter().map(|v| v.sqrt()).collect();
+    let bands: Vec<Band> = levels
+        .iter()
+        .map(|level| {
+            let z = special::normal_quantile(0.5 + 0.5 * level);
+            Band {
+                level: *level,
+                lower: forecast.iter().zip(&sd).map(|(f, s)| f - z * s).collect(),
+                upper: forecast.iter().zip(&sd).map(|(f, s)| f + z * s).collect(),
+            }
+        })
+        .collect();
+    fan_chart(path, &fit.x, &forecast, &bands)
+}
+
+/// A figure that can be drawn on any backend.
+enum Figure<'a> {
+    Stems {
+        title: &'a str,
+        first_lag: usize,
+        values: &'a [f64],
+        bound: f64,
+    },
+    Fan {
+        history: &'a [f64],
+        forecast: &'a [f64],
+        bands: &'a [Band],
+    },
+}
+
+impl Figure<'_> {
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> Result<()>
+    where
+        DB::ErrorType: 'static,
+    {
+        match self {
+            Figure::Stems {
+                title,
+                first_lag,
+                values,
+                bound,
+            } => stems(root, title, *first_lag, values, *bound),
+            Figure::Fan {
+                history,
+                forecast,
+                bands,
+            } => fan(root, history, forecast, bands),
+        }
+    }
+}
+
+/// Draw on an SVG or bitmap backend, depending on the extension of `path`.
+fn render(path: &Path, figure: &Figure) -> Result<()> {
+    let svg = match path.extension() {
+        Some(ext) => ext.eq_ignore_ascii_case("svg"),
+        None => false,
+    };
+    if svg {
+        let root = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+        figure.draw(&root)?;
+        root.present()?;
+    } else {
+        let root = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+        figure.draw(&root)?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+fn stems<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    first_lag: usize,
+    values: &[f64],
+    bound: f64,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let last_lag = first_lag + values.len();
+    let y_max = values.iter().fold(bound, |m, v| m.max(v.abs())) * 1.1;
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(40)
+        .build_cartesian_2d(first_lag as f64 - 0.5..last_lag as f64 - 0.5, -y_max..y_max)?;
+    chart.configure_mesh().x_desc("Lag").draw()?;
+
+    let band = BLUE.mix(0.15);
+    chart.draw_series(std::iter::once(Rectangle::new(
+        [
+            (first_lag as f64 - 0.5, -bound),
+            (last_lag as f64 - 0.5, bound),
+        ],
+        band.filled(),
+    )))?;
+    chart.draw_series(values.iter().enumerate().map(|(i, v)| {
+        let lag = (first_lag + i) as f64;
+        PathElement::new(vec![(lag, 0.0), (lag, *v)], BLACK.stroke_width(2))
+    }))?;
+    chart.draw_series(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| Circle::new(((first_lag + i) as f64, *v), 3, BLACK.filled())),
+    )?;
+    Ok(())
+}
+
+fn fan<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    history: &[f64],
+    forecast: &[f64],
+    bands: &[Band],
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let n = history.len();
+    let end = n + forecast.len();
+    let y = history
+        .iter()
+        .chain(forecast)
+        .chain(bands.iter().flat_map(|b| b.lower.iter().chain(&b.upper)))
+        .filter(|v| v.is_finite());
+    let (lo, hi) = y.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(*v), hi.max(*v))
+    });
+    if !lo.is_finite() {
+        anyhow::bail!("Nothing to plot");
+    }
+    let pad = 0.05 * (hi - lo).max(1.0e-12);
+    let x_range: Range<f64> = 0.0..(end.max(2) - 1) as f64;
+    let mut chart = ChartBuilder::on(root)
+        .caption("Forecast", ("sans-serif", 20))
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_range, lo - pad..hi + pad)?;
+    chart.configure_mesh().x_desc("Time").draw()?;
+
+    // widest interval first, so narrower ones are drawn on top
+    let mut order: Vec<&Band> = bands.iter().collect();
+    order.sort_by(|a, b| b.level.partial_cmp(&a.level).unwrap());
+    for (i, band) in order.iter().enumerate() {
+        let alpha = 0.15 + 0.25 * (i + 1) as f64 / order.len() as f64;
+        let mut polygon: Vec<(f64, f64)> = band
+            .upper
+            .iter()
+            .enumerate()
+            .map(|(k, v)| ((n + k) as f64, *v))
+            .collect();
+        polygon.extend(
+            band.lower
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(k, v)| ((n + k) as f64, *v)),
+        );
+        chart.draw_series(std::iter::once(Polygon::new(polygon, BLUE.mix(alpha))))?;
+    }
+
+    chart.draw_series(LineSeries::new(
+        history.iter().enumerate().map(|(t, v)| (t as f64, *v)),
+        &BLACK,
+    ))?;
+    // connect the forecasts to the last observation
+    let start = history.last().map(|v| ((n - 1) as f64, *v));
+    chart.draw_series(LineSeries::new(
+        start.into_iter().chain(
+            forecast
+                .iter()
+                .enumerate()
+                .map(|(k, v)| ((n + k) as f64, *v)),
+        ),
+        BLUE.stroke_width(2),
+    ))?;
+    Ok(())
+}
@@ -0,0 +1,523 @@
+use anyhow::Result;
+
+use std::f64::consts::PI;
+
+use crate::acf;
+use crate::linalg;
+use crate::model::{self, Contributions, FittedModel};
+#[cfg(feature = "chrono")]
+use crate::tbats;
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+
+/// Fourier terms `sin(2 pi j t / period)` and `cos(2 pi j t / period)` for
+/// `j = 1, ..., k`.
+///
+/// # Arguments
+///
+/// * `n` - Number of time steps.
+/// * `period` - Seasonal period, may be non-integer.
+/// * `k` - Number of harmonics.
+/// * `start` - Time index of the first row, e.g. the length of the sample to get
+///   terms for forecasting.
+///
+/// # Returns
+///
+/// * `2 k` columns of length `n`, ordered `sin_1, cos_1, sin_2, cos_2, ...`.
+///
+/// # Example
+///
+/// ```
+/// use arima::regression;
+/// let f = regression::fourier(4, 4.0, 1, 0);
+/// assert!((f[0][1] - 1.0).abs() < 1.0e-12);
+/// assert!((f[1][2] + 1.0).abs() < 1.0e-12);
+/// ```
+pub fn fourier(n: usize, period: f64, k: usize, start: usize) -> Vec<Vec<f64>> {
+    let mut columns = Vec::with_capacity(2 * k);
+    for j in 1..k + 1 {
+        let freq = 2.0 * PI * j as f64 / period;
+        let (sin, cos): (Vec<f64>, Vec<f64>) = (start..start + n)
+            .map(|t| (freq * t as f64).sin_cos())
+            .unzip();
+        columns.push(sin);
+        columns.push(cos);
+    }
+    columns
+}
+
+/// A dynamic harmonic regression: a linear regression on an intercept and Fourier terms
+/// of one or more seasonal periods, with ARMA errors.
+#[derive(Debug, Clone)]
+pub struct HarmonicModel {
+    /// Seasonal periods together with their number of harmonics.
+    pub seasons: Vec<(f64, usize)>,
+    /// Regression coefficients: the intercept followed by the Fourier coefficients of
+    /// each season in the order of `fourier`.
+    pub coef: Vec<f64>,
+    /// ARMA model of the regression errors.
+    pub errors: FittedModel,
+}
+
+impl HarmonicModel {
+    /// Number of parameters: regression coefficients plus the ARMA parameters,
+    /// intercept and variance of the errors.
+    pub fn n_params(&self) -> usize {
+        self.coef.len() + self.errors.n_params()
+    }
+
+    /// Akaike information criterion with small sample correction, based on the
+    /// conditional likelihood of the ARMA errors.
+    pub fn aicc(&self) -> f64 {
+        aicc(&self.errors, self.coef.len())
+    }
+
+    /// Point forecasts for the next `h` time steps: the regression on future Fourier
+    /// terms plus the ARMA forecast of the errors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::regression;
+    /// let x: Vec<f64> = (0..48)
+    ///     .map(|t| 3.0 + (2.0 * std::f64::consts::PI * t as f64 / 12.0).cos())
+    ///     .collect();
+    /// let m = regression::harmonic_regression(&x, &[(12.0, 1)], 0, 0).unwrap();
+    /// let f = m.forecast(12);
+    /// assert!((f[0] - 4.0).abs() < 1.0e-6);
+    /// assert!((f[6] - 2.0).abs() < 1.0e-6);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        let n = self.errors.x.len();
+        let design = design(&self.seasons, h, n);
+        let errors = self.errors.forecast(h);
+        design
+            .chunks(self.coef.len())
+            .zip(errors)
+            .map(|(row, e)| row.iter().zip(&self.coef).map(|(x, b)| x * b).sum::<f64>() + e)
+            .collect()
+    }
+
+    /// Decompose the point forecasts of the next `h` time steps into the contributions
+    /// of the regression intercept together with the intercept of the errors, of the
+    /// Fourier terms of each seasonal period as regressors, and of the AR and MA terms
+    /// of the errors. See `FittedModel::forecast_contributions`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::regression;
+    /// let x: Vec<f64> = (0..48)
+    ///     .map(|t| 3.0 + (2.0 * std::f64::consts::PI * t as f64 / 12.0).cos())
+    ///     .collect();
+    /// let m = regression::harmonic_regression(&x, &[(12.0, 1)], 0, 0).unwrap();
+    /// let c = m.forecast_contributions(12);
+    /// assert_eq!(c.regressors.len(), 1);
+    /// assert!((c.regressors[0][0] - 1.0).abs() < 1.0e-6);
+    /// assert!((c.intercept[0] - 3.0).abs() < 1.0e-6);
+    /// ```
+    pub fn forecast_contributions(&self, h: usize) -> Contributions {
+        let n = self.errors.x.len();
+        let mut c = self.errors.forecast_contributions(h);
+        for v in c.intercept.iter_mut() {
+            *v += self.coef[0];
+        }
+        let mut k = 1;
+        for (m, harmonics) in &self.seasons {
+            let columns = fourier(h, *m, *harmonics, n);
+            let coef = &self.coef[k..k + columns.len()];
+            c.regressors.push(
+                (0..h)
+                    .map(|t| columns.iter().zip(coef).map(|(x, b)| x[t] * b).sum())
+                    .collect(),
+            );
+            k += columns.len();
+        }
+        c
+    }
+}
+
+/// AICc of a regression with `n_coef` coefficients and ARMA errors, based on the
+/// conditional likelihood of the errors.
+fn aicc(errors: &FittedModel, n_coef: usize) -> f64 {
+    let n = errors.n_used() as f64;
+    let k = (n_coef + errors.n_params()) as f64;
+    if n - k - 1.0 <= 0.0 {
+        return f64::INFINITY;
+    }
+    -2.0 * errors.loglik() + 2.0 * k + 2.0 * k * (k + 1.0) / (n - k - 1.0)
+}
+
+/// Row-major design matrix with an intercept and the Fourier terms of all seasons.
+fn design(seasons: &[(f64, usize)], n: usize, start: usize) -> Vec<f64> {
+    let columns: Vec<Vec<f64>> = seasons
+        .iter()
+        .flat_map(|(m, k)| fourier(n, *m, *k, start))
+        .collect();
+    let mut design = Vec::with_capacity(n * (1 + columns.len()));
+    for t in 0..n {
+        design.push(1.0);
+        design.extend(columns.iter().map(|c| c[t]));
+    }
+    design
+}
+
+/// Fit a regression on Fourier terms with ARMA(p, q) errors. The regression is estimated
+/// by least squares, the ARMA model on its residuals with `model::fit`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `&seasons` - Seasonal periods together with their number of harmonics `k`, where
+///   `2 k < period`.
+/// * `ar` - Order of the AR part of the errors.
+/// * `ma` - Order of the MA part of the errors.
+///
+/// # Returns
+///
+/// * Fitted model.
+pub fn harmonic_regression(
+    x: &[f64],
+    seasons: &[(f64, usize)],
+    ar: usize,
+    ma: usize,
+) -> Result<HarmonicModel> {
+    for (m, k) in seasons {
+        if *k == 0 || 2.0 * *k as f64 >= *m {
+            anyhow::bail!("Number of harmonics must be positive and below half the period");
+        }
+    }
+    let n_coef = 1 + 2 * seasons.iter().map(|(_, k)| k).sum::<usize>();
+    if x.len() <= n_coef + ar + ma + 2 {
+        anyhow::bail!("Time series is too short for the model");
+    }
+
+    let design = design(seasons, x.len(), 0);
+    let coef = linalg::lstsq(&design, n_coef, x)?;
+    let residuals: Vec<f64> = design
+        .chunks(n_coef)
+        .zip(x)
+        .map(|(row, v)| v - row.iter().zip(&coef).map(|(a, b)| a * b).sum::<f64>())
+        .collect();
+    let errors = model::fit(&residuals, ar, 0, ma)?;
+
+    Ok(HarmonicModel {
+        seasons: seasons.to_vec(),
+        coef,
+        errors,
+    })
+}
+
+/// Fit a dynamic harmonic regression and select the number of harmonics of each
+/// seasonal period by AICc. Starting from one harmonic per period, all admissible
+/// numbers of harmonics are tried for each period in turn, keeping the best model.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `&periods` - Seasonal periods, each larger than 2.
+/// * `ar` - Order of the AR part of the errors.
+/// * `ma` - Order of the MA part of the errors.
+///
+/// # Returns
+///
+/// * Fitted model with the selected harmonics.
+///
+/// # Example
+///
+/// ```
+/// use arima::regression;
+/// use std::f64::consts::PI;
+/// let x: Vec<f64> = (0..200)
+///     .map(|t| {
+///         let t = t as f64;
+///         (2.0 * PI * t / 52.18).sin() + 0.5 * (6.0 * PI * t / 52.18).cos()
+///             + 0.1 * ((t * 7.3).sin())
+///     })
+///     .collect();
+/// let m = regression::auto_harmonic(&x, &[52.18], 1, 0).unwrap();
+/// assert!(m.seasons[0].1 >= 3);
+/// ```
+pub fn auto_harmonic(x: &[f64], periods: &[f64], ar: usize, ma: usize) -> Result<HarmonicModel> {
+    for m in periods {
+        if *m <= 2.0 || m.is_nan() {
+            anyhow::bail!("Seasonal periods must be larger than 2");
+        }
+    }
+    let seasons: Vec<(f64, usize)> = periods.iter().map(|m| (*m, 1)).collect();
+    let mut best = harmonic_regression(x, &seasons, ar, ma)?;
+    for i in 0..seasons.len() {
+        let mut k = 2;
+        while 2.0 * (k as f64) < seasons[i].0 {
+            let mut candidate_seasons = best.seasons.clone();
+            candidate_seasons[i].1 = k;
+            if let Ok(candidate) = harmonic_regression(x, &candidate_seasons, ar, ma) {
+                if candidate.aicc() < best.aicc() {
+                    best = candidate;
+                }
+            }
+            k += 1;
+        }
+    }
+    Ok(best)
+}
+
+/// Same as `auto_harmonic`, with the seasonal periods implied by the sampling frequency
+/// of the series that it covers at least twice, see `TimeSeries::seasonal_periods`.
+/// Fails if the series has gaps. Only enabled with feature `chrono`.
+#[cfg(feature = "chrono")]
+pub fn auto_harmonic_series(x: &TimeSeries<f64>, ar: usize, ma: usize) -> Result<HarmonicModel> {
+    let values = x.regular_values()?;
+    auto_harmonic(values, &tbats::covered_periods(x), ar, ma)
+}
+
+/// Method of `select_lags`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LagMethod {
+    /// Cross-correlations of the series and the regressor after filtering both with an
+    /// AR model of the regressor, whose order is selected by AIC (prewhitening, Box and
+    /// Jenkins, 1976). Without the filter, autocorrelation of the regressor spreads a
+    /// dependence at one shift over the neighbouring shifts.
+    Prewhitened,
+    /// AICc of regressions of the series on an intercept and the shifted regressor with
+    /// ARMA(p, q) errors, all fitted on the observations available at every shift.
+    Aicc(usize, usize),
+}
+
+/// Lag structure of one regressor, see `select_lags`. A shift `k` relates the series at
+/// time `t` to the regressor at time `t - k`, so positive shifts are lags and negative
+/// shifts are leads of the regressor.
+#[derive(Debug, Clone)]
+pub struct LagSelection {
+    /// Best shift, or `None` if no shift is significant.
+    pub shift: Option<isize>,
+    /// Shifts from `-max_lead` to `max_lag` with their prewhitened cross-correlation or
+    /// AICc.
+    pub scores: Vec<(isize, f64)>,
+    /// Shifts to include in a regression, strongest first: those with a prewhitened
+    /// cross-correlation beyond `1.96 / sqrt(n)`, or with an AICc more than 2 below that
+    /// of the regression without the regressor.
+    pub significant: Vec<isize>,
+}
+
+/// Select the lead and lag shifts of exogenous regressors for a regression with ARMA
+/// errors. Nonstationary series and regressors should be differenced first.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `&regressors` - Candidate regressors, each of the length of `x`.
+/// * `max_lead` - Largest lead of the regressors to consider.
+/// * `max_lag` - Largest lag of the regressors to consider.
+/// * `method` - Selection by prewhitened cross-correlation or by AICc.
+///
+/// # Returns
+///
+/// * Lag structure of each regressor.
+///
+/// # Example
+///
+/// ```
+/// use arima::{regression, sim};
+/// use rand_distr::{Distribution, Normal};
+/// let mut rng = sim::stream_rng(1, 0);
+/// let normal = Normal::new(0.0, 1.0).unwrap();
+/// let mut z = vec![0.0];
+/// for t in 1..200 {
+///     z.push(0.7 * z[t - 1] + normal.sample(&mut rng));
+/// }
+/// // the series responds to the regressor three steps later
+/// let x: Vec<f64> = (0..200)
+///     .map(|t| if t >= 3 { 2.0 * z[t - 3] } else { 0.0 } + 0.5 * normal.sample(&mut rng))
+///     .collect();
+/// let s = regression::select_lags(&x, &[z], 2, 6, regression::LagMethod::Prewhitened).unwrap();
+/// assert_eq!(s[0].shift, Some(3));
+/// ```
+pub fn select_lags(
+    x: &[f64],
+    regressors: &[Vec<f64>],
+    max_lead: usize,
+    max_lag: usize,
+    method: LagMethod,
+) -> Result<Vec<LagSelection>> {
+    let n = x.len();
+    if n < max_lead + max_lag + 10 {
+        anyhow::bail!("Time series is too short for the shifts");
+    }
+    if x.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("Series must not contain missing or infinite values");
+    }
+    for z in regressors {
+        if z.len() != n {
+            anyhow::bail!("Regressors must have the length of the series");
+        }
+        if z.iter().any(|v| !v.is_finite()) {
+            anyhow::bail!("Regressors must not contain missing or infinite values");
+        }
+        if z.iter().all(|v| *v == z[0]) {
+            anyhow::bail!("Regressors must not be constant");
+        }
+    }
+    regressors
+        .iter()
+        .map(|z| match method {
+            LagMethod::Prewhitened => select_prewhitened(x, z, max_lead, max_lag),
+            LagMethod::Aicc(ar, ma) => select_aicc(x, z, max_lead, max_lag, ar, ma),
+        })
+        .collect()
+}
+
+/// Shift a regressor by `shift` steps, i.e. lag it for positive and lead it for negative
+/// shifts, e.g. to build the regressors selected by `select_lags`. Values before the
+/// start or after the end of the regressor are NaN.
+///
+/// # Example
+///
+/// ```
+/// use arima::regression;
+/// let z = regression::shifted(&[1.0, 2.0, 3.0], 1);
+/// assert!(z[0].is_nan());
+/// assert_eq!(&z[1..], &[1.0, 2.0]);
+/// ```
+pub fn shifted(z: &[f64], shift: isize) -> Vec<f64> {
+    (0..z.len() as isize)
+        .map(|t| {
+            let s = t - shift;
+            if s >= 0 && s < z.len() as isize {
+                z[s as usize]
+            } else {
+                f64::NAN
+            }
+        })
+        .collect()
+}
+
+fn select_prewhitened(
+    x: &[f64],
+    z: &[f64],
+    max_lead: usize,
+    max_lag: usize,
+) -> Result<LagSelection> {
+    let phi = prewhitening_filter(z)?;
+    let a = ar_filter(x, &phi);
+    let b = ar_filter(z, &phi);
+    let m = a.len();
+    if m <= max_lead.max(max_lag) + 2 {
+        anyhow::bail!("Time series is too short for the shifts");
+    }
+    let mean_a = a.iter().sum::<f64>() / m as f64;
+    let mean_b = b.iter().sum::<f64>() / m as f64;
+    let sd_a = (a.iter().map(|v| (v - mean_a).powi(2)).sum::<f64>() / m as f64).sqrt();
+    let sd_b = (b.iter().map(|v| (v - mean_b).powi(2)).sum::<f64>() / m as f64).sqrt();
+    if sd_a <= 0.0 {
+        anyhow::bail!("Time series must not be constant");
+    }
+
+    let scores: Vec<(isize, f64)> = (-(max_lead as isize)..max_lag as isize + 1)
+        .map(|k| {
+            let lo = k.max(0) as usize;
+            let hi = (m as isize + k.min(0)) as usize;
+            let c: f64 = (lo..hi)
+                .map(|t| (a[t] - mean_a) * (b[(t as isize - k) as usize] - mean_b))
+                .sum();
+            (k, c / (m as f64 * sd_a * sd_b))
+        })
+        .collect();
+    let bound = 1.96 / (m as f64).sqrt();
+    let mut significant: Vec<(isize, f64)> = scores
+        .iter()
+        .filter(|(_, r)| r.abs() > bound)
+        .copied()
+        .collect();
+    significant.sort_by(|u, v| v.1.abs().partial_cmp(&u.1.abs()).unwrap());
+    Ok(LagSelection {
+        shift: significant.first().map(|(k, _)| *k),
+        scores,
+        significant: significant.into_iter().map(|(k, _)| k).collect(),
+    })
+}
+
+/// AR coefficients of the regressor with the order selected by AIC, up to
+/// `min(10 log10(n), n / 4)`.
+fn prewhitening_filter(z: &[f64]) -> Result<Vec<f64>> {
+    let n = z.len();
+    let max_order = ((10.0 * (n as f64).log10()) as usize).min(n / 4);
+    let cov0 = acf::acf(z, Some(0), true)?[0];
+    if cov0 <= 0.0 || cov0.is_nan() {
+        anyhow::bail!("Regressors must not be constant");
+    }
+    let rho = acf::acf(z, Some(max_order + 1), false)?;
+    let mut best = (n as f64 * cov0.ln(), Vec::new());
+    for p in 1..max_order + 1 {
+        let (phi, var) = acf::ar_dl_rho_cov(&rho, cov0, Some(p))?;
+        let aic = n as f64 * var.ln() + 2.0 * p as f64;
+        if aic < best.0 {
+            best = (aic, phi);
+        }
+    }
+    Ok(best.1)
+}
+
+/// Apply the filter `x[t] - phi_1 x[t-1] - ... - phi_p x[t-p]` for `t >= p`.
+fn ar_filter(x: &[f64], phi: &[f64]) -> Vec<f64> {
+    (phi.len()..x.len())
+        .map(|t| {
+            x[t] - phi
+                .iter()
+                .enumerate()
+                .map(|(i, p)| p * x[t - i - 1])
+                .sum::<f64>()
+        })
+        .collect()
+}
+
+fn select_aicc(
+    x: &[f64],
+    z: &[f64],
+    max_lead: usize,
+    max_lag: usize,
+    ar: usize,
+    ma: usize,
+) -> Result<LagSelection> {
+    let n = x.len();
+    let sample = max_lag..n - max_lead;
+    let y = &x[sample.clone()];
+    let baseline = regression_aicc(y, &[], ar, ma)?;
+    let scores = (-(max_lead as isize)..max_lag as isize + 1)
+        .map(|k| {
+            let column: Vec<f64> = sample
+                .clone()
+                .map(|t| z[(t as isize - k) as usize])
+                .collect();
+            Ok((k, regression_aicc(y, &[column], ar, ma)?))
+        })
+        .collect::<Result<Vec<(isize, f64)>>>()?;
+    let mut significant: Vec<(isize, f64)> = scores
+        .iter()
+        .filter(|(_, a)| *a < baseline - 2.0)
+        .copied()
+        .collect();
+    significant.sort_by(|u, v| u.1.partial_cmp(&v.1).unwrap());
+    Ok(LagSelection {
+        shift: significant.first().map(|(k, _)| *k),
+        scores,
+        significant: significant.into_iter().map(|(k, _)| k).collect(),
+    })
+}
+
+/// AICc of a least squares regression on an intercept and `columns` with ARMA errors.
+fn regression_aicc(y: &[f64], columns: &[Vec<f64>], ar: usize, ma: usize) -> Result<f64> {
+    let k = 1 + columns.len();
+    let mut design = Vec::with_capacity(y.len() * k);
+    for t in 0..y.len() {
+        design.push(1.0);
+        design.extend(columns.iter().map(|c| c[t]));
+    }
+    let coef = linalg::lstsq(&design, k, y)?;
+    let residuals: Vec<f64> = design
+        .chunks(k)
+        .zip(y)
+        .map(|(row, v)| v - row.iter().zip(&coef).map(|(a, b)| a * b).sum::<f64>())
+        .collect();
+    Ok(aicc(&model::fit(&residuals, ar, 0, ma)?, k))
+}
@@ -0,0 +1,490 @@
+//! JSON HTTP API serving forecasts of saved models, so that a fitted model can be
+//! deployed without a bespoke service. Only enabled with feature `serve`, which also
+//! builds the `arima` binary with its `serve` subcommand:
+//!
+//! ```text
+//! arima serve --addr 127.0.0.1:8080 sales=sales.bin returns.json
+//! ```
+//!
+//! Models are loaded from files written by `persist::save`, which include the series the
+//! model was fitted to, or from parameter files in the statsmodels layout of
+//! `json::to_statsmodels` (extension `.json`), which start without observations.
+//!
+//! Endpoints:
+//!
+//! * `GET /models` - names, orders and number of observations of the loaded models.
+//! * `POST /models/{name}/observations` with body `{"values": [...]}` - append new
+//!   observations to the series of a model. Coefficients and innovation variance stay
+//!   fixed and the residuals are extended, so the next forecasts start from the latest
+//!   observations. Only the last observations and residuals needed for forecasting are
+//!   kept, so the memory and time per observation do not grow with the series.
+//! * `GET /models/{name}/forecast?h=12&level=0.95` - point forecasts, forecast error
+//!   variances and normal prediction intervals. `h` defaults to 1 and `level` to 0.95.
+//!   Needs more than `d + p` observations.
+//!
+//! Errors are returned as `{"error": "..."}` with status 400 (invalid request), 404
+//! (unknown model or endpoint), 405 (wrong method), 413 (body larger than `MAX_BODY`),
+//! 414 (request line longer than `MAX_LINE`) or 431 (header line longer than `MAX_LINE`
+//! or more than `MAX_HEADERS` headers). Requests are handled one at a time and
+//! connections are closed after each response; put a reverse proxy in front for TLS or
+//! concurrent clients.
+
+use anyhow::Result;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::json::SarimaxParams;
+use crate::model::FittedModel;
+use crate::{persist, special, util};
+
+/// Largest accepted request body in bytes.
+pub const MAX_BODY: usize = 1 << 20;
+
+/// Largest accepted request line or header line in bytes.
+pub const MAX_LINE: usize = 8 * 1024;
+
+/// Largest accepted number of headers.
+pub const MAX_HEADERS: usize = 64;
+
+/// Largest forecast horizon.
+pub const MAX_HORIZON: usize = 10_000;
+
+/// Response to a request, see `Server::handle`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// HTTP status code.
+    pub status: u16,
+    /// JSON body.
+    pub body: String,
+}
+
+/// Entry of the `GET /models` response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Name the model is served under.
+    pub name: String,
+    /// Order `(p, d, q)`.
+    pub order: (usize, usize, usize),
+    /// Number of observations of the series, including appended ones.
+    pub n: usize,
+}
+
+/// Body of the `GET /models/{name}/forecast` response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForecastResponse {
+    /// Name of the model.
+    pub model: String,
+    /// Coverage probability of the intervals.
+    pub level: f64,
+    /// Point forecasts.
+    pub forecast: Vec<f64>,
+    /// Variances of the forecast errors.
+    pub variance: Vec<f64>,
+    /// Lower bounds of the prediction intervals.
+    pub lower: Vec<f64>,
+    /// Upper bounds of the prediction intervals.
+    pub upper: Vec<f64>,
+}
+
+/// Body of the `POST /models/{name}/observations` request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observations {
+    /// New observations, oldest first, directly following the series of the model.
+    pub values: Vec<f64>,
+}
+
+/// Fitted models served by name.
+///
+/// # Example
+///
+/// ```
+/// use arima::{model, serve};
+/// let x = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2];
+/// let mut server = serve::Server::new();
+/// server.insert("demo", model::fit(&x, 1, 0, 0).unwrap());
+///
+/// let r = server.handle("POST", "/models/demo/observations", br#"{"values": [1.1]}"#);
+/// assert_eq!(r.status, 200);
+/// let r = server.handle("GET", "/models/demo/forecast?h=3&level=0.8", b"");
+/// let f: serve::ForecastResponse = serde_json::from_str(&r.body).unwrap();
+/// assert_eq!(f.forecast.len(), 3);
+/// assert!(f.lower[0] < f.forecast[0] && f.forecast[0] < f.upper[0]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Server {
+    models: BTreeMap<String, Served>,
+}
+
+/// Forecasting state of a served model.
+#[derive(Debug, Clone)]
+struct Served {
+    /// Model with the last observations and residuals only, see `Served::trim`.
+    model: FittedModel,
+    /// Number of observations of the series.
+    n: usize,
+}
+
+impl Served {
+    fn new(model: FittedModel) -> Self {
+        let mut served = Served {
+            n: model.x.len(),
+            model,
+        };
+        served.trim();
+        served
+    }
+
+    /// Append an observation and its residual, computed with the same recursion as
+    /// `estimate::residuals`.
+    fn push(&mut self, value: f64) {
+        let m = &mut self.model;
+        m.x.push(value);
+        self.n += 1;
+        if m.x.len() > m.d {
+            let w = util::diff(&m.x, m.d);
+            let t = w.len() - 1;
+            let residual = if t < m.ar {
+                0.0
+            } else {
+                let mut prediction = m.intercept;
+                for (i, phi) in m.phi.iter().enumerate() {
+                    prediction += phi * w[t - i - 1];
+                }
+                for (j, theta) in m.theta.iter().enumerate().take(t) {
+                    prediction += theta * m.residuals[t - j - 1];
+                }
+                w[t] - prediction
+            };
+            m.residuals.push(residual);
+        }
+        self.trim();
+    }
+
+    /// Keep the last `d + max(p, q) + 1` observations and the residuals of their
+    /// differences, which determine the forecasts.
+    fn trim(&mut self) {
+        let m = &mut self.model;
+        let keep = m.d + m.ar.max(m.ma) + 1;
+        if m.x.len() > keep {
+            m.x.drain(..m.x.len() - keep);
+            m.residuals.drain(..m.residuals.len() - (keep - m.d));
+        }
+    }
+
+    fn info(&self, name: &str) -> ModelInfo {
+        ModelInfo {
+            name: name.to_string(),
+            order: (self.model.ar, self.model.d, self.model.ma),
+            n: self.n,
+        }
+    }
+}
+
+impl Server {
+    /// Create a server without models.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `model` under `name`, replacing any model of the same name.
+    pub fn insert(&mut self, name: &str, model: FittedModel) {
+        self.models.insert(name.to_string(), Served::new(model));
+    }
+
+    /// Load a model and serve it under `name`: parameters in the statsmodels layout if
+    /// the file name ends with `.json`, see `json::SarimaxParams`, and a model saved with
+    /// `persist::save` otherwise.
+    pub fn load<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
+        if name.is_empty() || name.contains('/') {
+            anyhow::bail!("Invalid model name {:?}", name);
+        }
+        let path = path.as_ref();
+        let model = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            let params: SarimaxParams = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            without_observations(&params)?
+        } else {
+            persist::load(path)?
+        };
+        self.insert(name, model);
+        Ok(())
+    }
+
+    /// The forecasting state of the model served under `name`: its coefficients with the
+    /// last observations and residuals, including appended ones. Older observations are
+    /// dropped, see the module documentation.
+    pub fn model(&self, name: &str) -> Option<&FittedModel> {
+        self.models.get(name).map(|s| &s.model)
+    }
+
+    /// Append observations to the series of the model served under `name`, keeping its
+    /// coefficients and innovation variance.
+    pub fn observe(&mut self, name: &str, values: &[f64]) -> Result<()> {
+        if values.iter().any(|v| !v.is_finite()) {
+            anyhow::bail!("Observations must be finite");
+        }
+        let served = match self.models.get_mut(name) {
+            Some(served) => served,
+            None => anyhow::bail!("Unknown model {:?}", name),
+        };
+        for v in values {
+            served.push(*v);
+        }
+        Ok(())
+    }
+
+    /// Handle a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - HTTP method, e.g. `GET`.
+    /// * `target` - Request target: the path with an optional query string.
+    /// * `&body` - Request body.
+    ///
+    /// # Returns
+    ///
+    /// * Response with a JSON body.
+    pub fn handle(&mut self, method: &str, target: &str, body: &[u8]) -> Response {
+        let (path, query) = match target.find('?') {
+            Some(i) => (&target[..i], &target[i + 1..]),
+            None => (target, ""),
+        };
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        match segments.as_slice() {
+            ["models"] => match method {
+                "GET" => self.list(),
+                _ => error(405, "Use GET"),
+            },
+            ["models", name, "forecast"] => match method {
+                "GET" => self.forecast(name, query),
+                _ => error(405, "Use GET"),
+            },
+            ["models", name, "observations"] => match method {
+                "POST" => self.append(name, body),
+                _ => error(405, "Use POST"),
+            },
+            _ => error(404, "Unknown endpoint"),
+        }
+    }
+
+    /// Accept connections on `addr` and handle their requests, see the module
+    /// documentation. Only returns if the address cannot be bound.
+    pub fn run<A: ToSocketAddrs>(self, addr: A) -> Result<()> {
+        self.run_listener(TcpListener::bind(addr)?)
+    }
+
+    /// Same as `run`, but with a bound listener, e.g. on port 0.
+    pub fn run_listener(mut self, listener: TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            let result = stream
+                .map_err(anyhow::Error::from)
+                .and_then(|s| self.connection(s));
+            if let Err(e) = result {
+                tracing::warn!("Could not handle request: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn connection(&mut self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let response = self.respond(&mut reader)?;
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            response.status,
+            reason(response.status),
+            response.body.len(),
+            response.body
+        )?;
+        stream.flush()?;
+        // read what is left of a rejected request, so that closing the connection does
+        // not reset it before the client has read the response
+        stream.shutdown(Shutdown::Write)?;
+        io::copy(&mut reader.take(MAX_BODY as u64), &mut io::sink()).ok();
+        Ok(())
+    }
+
+    /// Read a request and handle it, or reject it if it exceeds the limits.
+    fn respond<R: BufRead>(&mut self, reader: &mut R) -> Result<Response> {
+        let line = match read_line(reader)? {
+            Some(line) => line,
+            None => return Ok(error(414, "Request line too long")),
+        };
+        let mut parts = line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+            _ => return Ok(error(400, "Invalid request line")),
+        };
+
+        let mut length = 0;
+        let mut headers = 0;
+        loop {
+            let header = match read_line(reader)? {
+                Some(header) => header,
+                None => return Ok(error(431, "Header line too long")),
+            };
+            if header.trim().is_empty() {
+                break;
+            }
+            headers += 1;
+            if headers > MAX_HEADERS {
+                return Ok(error(431, "Too many headers"));
+            }
+            if let Some(i) = header.find(':') {
+                if header[..i].trim().eq_ignore_ascii_case("content-length") {
+                    length = match header[i + 1..].trim().parse() {
+                        Ok(length) => length,
+                        Err(_) => return Ok(error(400, "Invalid Content-Length")),
+                    };
+                }
+            }
+        }
+
+        if length > MAX_BODY {
+            return Ok(error(413, "Request body too large"));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        Ok(self.handle(&method, &target, &body))
+    }
+
+    fn list(&self) -> Response {
+        let models: Vec<ModelInfo> = self
+            .models
+            .iter()
+            .map(|(name, served)| served.info(name))
+            .collect();
+        ok(&serde_json::json!({ "models": models }))
+    }
+
+    fn forecast(&self, name: &str, query: &str) -> Response {
+        let m = match self.models.get(name) {
+            Some(served) => &served.model,
+            None => return error(404, &format!("Unknown model {:?}", name)),
+        };
+        let mut h = 1;
+        let mut level = 0.95;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = match pair.find('=') {
+                Some(i) => (&pair[..i], &pair[i + 1..]),
+                None => (pair, ""),
+            };
+            match key {
+                "h" => match value.parse() {
+                    Ok(v) if (1..=MAX_HORIZON).contains(&v) => h = v,
+                    _ => {
+                        let msg = format!("h must be an integer within 1 and {}", MAX_HORIZON);
+                        return error(400, &msg);
+                    }
+                },
+                "level" => match value.parse() {
+                    Ok(v) if v > 0.0 && v < 1.0 => level = v,
+                    _ => return error(400, "level must be within (0, 1)"),
+                },
+                _ => return error(400, &format!("Unknown parameter {:?}", key)),
+            }
+        }
+        if m.x.len() <= m.d + m.ar {
+            let msg = format!(
+                "Model {:?} needs at least {} observations",
+                name,
+                m.d + m.ar + 1
+            );
+            return error(400, &msg);
+        }
+        let forecast = m.forecast(h);
+        let variance = m.forecast_variance(h);
+        let z = special::normal_quantile(0.5 + level / 2.0);
+        let (lower, upper) = forecast
+            .iter()
+            .zip(&variance)
+            .map(|(f, v)| (f - z * v.sqrt(), f + z * v.sqrt()))
+            .unzip();
+        ok(&ForecastResponse {
+            model: name.to_string(),
+            level,
+            forecast,
+            variance,
+            lower,
+            upper,
+        })
+    }
+
+    fn append(&mut self, name: &str, body: &[u8]) -> Response {
+        if !self.models.contains_key(name) {
+            return error(404, &format!("Unknown model {:?}", name));
+        }
+        let observations: Observations = match serde_json::from_slice(body) {
+            Ok(o) => o,
+            Err(e) => return error(400, &format!("Invalid body: {}", e)),
+        };
+        if let Err(e) = self.observe(name, &observations.values) {
+            return error(400, &e.to_string());
+        }
+        ok(&self.models[name].info(name))
+    }
+}
+
+/// A model with the given parameters and no observations yet.
+fn without_observations(params: &SarimaxParams) -> Result<FittedModel> {
+    let (coef, sigma2) = params.coefficients()?;
+    let (ar, d, ma) = params.order;
+    Ok(FittedModel {
+        ar,
+        d,
+        ma,
+        intercept: coef[0],
+        phi: coef[1..ar + 1].to_vec(),
+        theta: coef[ar + 1..].to_vec(),
+        sigma2,
+        residuals: Vec::new(),
+        x: Vec::new(),
+    })
+}
+
+/// Read a line of at most `MAX_LINE` bytes, or `None` if it is longer.
+fn read_line<R: BufRead>(reader: &mut R) -> Result<Option<String>> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE as u64 + 1)
+        .read_until(b'\n', &mut line)?;
+    if line.len() > MAX_LINE {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+fn ok<T: Serialize>(value: &T) -> Response {
+    match serde_json::to_string(value) {
+        Ok(body) => Response { status: 200, body },
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> Response {
+    Response {
+        status,
+        body: serde_json::json!({ "error": message }).to_string(),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
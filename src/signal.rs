@@ -0,0 +1,569 @@
+//! Model-based signal extraction from a fitted ARIMA model, in the manner of SEATS
+//! (Burman, 1980; Gómez and Maravall, 1996).
+//!
+//! The roots of the AR polynomial `phi(B) (1 - B)^d` are allocated to a trend (unit and
+//! positive real roots), a seasonal (roots at the seasonal frequencies) and a transitory
+//! component (all other roots). The pseudo-spectrum of the model is split into the
+//! spectra of the components by partial fractions, and each component spectrum is made
+//! canonical by moving its minimum into a white noise irregular, which maximizes the
+//! variance of the irregular and makes the other components as smooth as possible. The
+//! component ARIMA models are obtained by spectral factorization and the components are
+//! estimated with the Kalman smoother, which gives the finite-sample Wiener-Kolmogorov
+//! estimates together with their standard errors.
+
+use anyhow::Result;
+
+use num::complex::Complex64;
+
+use std::f64::consts::PI;
+
+use crate::kalman::{self, System, TimeVarying, DIFFUSE_KAPPA};
+use crate::linalg;
+use crate::model::{self, FittedModel};
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+
+/// Stationary AR roots with a modulus of at most `MAX_ROOT_MODULUS` are allocated to the
+/// trend or the seasonal component, roots with a larger modulus (i.e. faster decaying
+/// dynamics) to the transitory component.
+pub const MAX_ROOT_MODULUS: f64 = 2.0;
+
+/// Number of frequencies between 0 and pi at which component spectra are evaluated to
+/// find their minimum.
+const GRID: usize = 2000;
+
+/// ARIMA model of a component,
+/// `w_t = phi_1 w_{t-1} + ... + phi_p w_{t-p} + e_t + theta_1 e_{t-1} + ...`, where the
+/// AR coefficients include the unit roots of the component.
+#[derive(Debug, Clone)]
+pub struct ComponentModel {
+    /// AR coefficients.
+    pub phi: Vec<f64>,
+    /// MA coefficients.
+    pub theta: Vec<f64>,
+    /// Innovation variance.
+    pub sigma2: f64,
+}
+
+/// Estimate of one component.
+#[derive(Debug, Clone)]
+pub struct Component {
+    /// Smoothed estimate, of the length of the series.
+    pub estimate: Vec<f64>,
+    /// Standard errors of the estimate.
+    pub se: Vec<f64>,
+    /// Model of the component, `None` if the model has no such component, in which case
+    /// the estimate is zero.
+    pub model: Option<ComponentModel>,
+}
+
+/// Components of a series extracted by `extract`. The four components add up to the
+/// series.
+#[derive(Debug, Clone)]
+pub struct SignalExtraction {
+    /// Trend-cycle, including the mean or deterministic drift of the model.
+    pub trend: Component,
+    /// Seasonal component.
+    pub seasonal: Component,
+    /// Transitory component: stationary short-term dynamics that are neither trend nor
+    /// seasonal.
+    pub transitory: Component,
+    /// White noise irregular.
+    pub irregular: Component,
+    /// Seasonally adjusted series, the series minus the seasonal component.
+    pub adjusted: Vec<f64>,
+}
+
+/// Decompose the series of a fitted ARIMA model into trend, seasonal, transitory and
+/// irregular components consistent with the model.
+///
+/// # Arguments
+///
+/// * `&model` - Fitted model; the series is `model.x`.
+/// * `period` - Seasonal period, at least 2. AR roots at the frequencies `j / period`
+///   form the seasonal component; without a period, there is none.
+///
+/// # Returns
+///
+/// * Estimated components with standard errors and models. Fails if the model has no
+///   admissible decomposition, i.e. if the irregular would get a negative variance.
+///
+/// # Example
+///
+/// ```
+/// use arima::{model, signal, sim};
+/// use rand_distr::{Distribution, Normal};
+/// // random walk plus noise
+/// let mut rng = sim::stream_rng(1, 0);
+/// let normal = Normal::new(0.0, 1.0).unwrap();
+/// let mut level = 0.0;
+/// let x: Vec<f64> = (0..200)
+///     .map(|_| {
+///         level += 0.5 * normal.sample(&mut rng);
+///         level + normal.sample(&mut rng)
+///     })
+///     .collect();
+/// let m = model::fit(&x, 0, 1, 1).unwrap();
+/// let s = signal::extract(&m, None).unwrap();
+/// // ARIMA(0, 1, 1) decomposes into a smooth trend and white noise
+/// assert_eq!(s.trend.model.as_ref().unwrap().theta.len(), 1);
+/// assert!(s.seasonal.model.is_none());
+/// let sum = s.trend.estimate[100] + s.irregular.estimate[100];
+/// assert!((sum - x[100]).abs() < 1.0e-6);
+/// assert!(s.trend.se[100] > 0.0);
+/// ```
+pub fn extract(model: &FittedModel, period: Option<usize>) -> Result<SignalExtraction> {
+    if let Some(s) = period {
+        if s < 2 {
+            anyhow::bail!("Seasonal period must be at least 2");
+        }
+    }
+    let n = model.x.len();
+    let (groups, unit_roots) = allocate(model, period);
+    let theta: Vec<f64> = std::iter::once(1.0)
+        .chain(model.theta.iter().copied())
+        .collect();
+    let (numerators, remainder) = partial_fractions(&acgf(&theta), &groups)?;
+
+    // canonical decomposition: the minima of the trend and seasonal spectra, and of the
+    // transitory spectrum including the remainder, go to the irregular
+    let mut irregular = 0.0;
+    let mut spectra = Vec::with_capacity(3);
+    for (i, (ar, numerator)) in groups.iter().zip(numerators).enumerate() {
+        let denominator = acgf(ar);
+        let mut numerator = numerator;
+        if i == 2 {
+            numerator = sym_add(&numerator, &sym_mul(&remainder, &denominator));
+        }
+        if ar.len() == 1 && numerator.iter().all(|c| *c == 0.0) {
+            spectra.push(None);
+            continue;
+        }
+        let minimum = spectrum_min(&numerator, &denominator);
+        irregular += minimum;
+        spectra.push(Some(sym_add(
+            &numerator,
+            &sym_scale(&denominator, -minimum),
+        )));
+    }
+    let scale = acgf(&theta)[0];
+    if irregular < -1.0e-9 * scale {
+        anyhow::bail!("Model has no admissible decomposition");
+    }
+    let irregular = irregular.max(0.0);
+
+    // component models by spectral factorization
+    let mut models = Vec::with_capacity(3);
+    for ((ar, numerator), unit) in groups.iter().zip(spectra).zip(&unit_roots) {
+        let numerator = match numerator {
+            Some(numerator) => numerator,
+            None => {
+                models.push(None);
+                continue;
+            }
+        };
+        let (ma, variance) = factorize(&numerator)?;
+        if variance <= 1.0e-12 * scale && !unit {
+            models.push(None);
+            continue;
+        }
+        models.push(Some(ComponentModel {
+            phi: ar[1..].iter().map(|c| -c).collect(),
+            theta: ma[1..].to_vec(),
+            sigma2: variance.max(0.0) * model.sigma2,
+        }));
+    }
+
+    // remove the mean or deterministic drift, which is added to the trend
+    let mut deterministic = vec![0.0; n];
+    let phi_sum: f64 = model.phi.iter().sum();
+    if model.intercept != 0.0 && phi_sum != 1.0 {
+        deterministic = vec![model.intercept / (1.0 - phi_sum); n];
+        for _ in 0..model.d {
+            for t in 1..n {
+                deterministic[t] += deterministic[t - 1];
+            }
+        }
+    }
+    let y: Vec<f64> = model
+        .x
+        .iter()
+        .zip(&deterministic)
+        .map(|(x, m)| x - m)
+        .collect();
+
+    let (system, offsets) = system(&models, &unit_roots, irregular * model.sigma2);
+    let smoothed = system.smooth(&system.filter(&y));
+    let mut components: Vec<Component> = models
+        .into_iter()
+        .zip(offsets)
+        .map(|(model, offset)| match offset {
+            Some(o) => Component {
+                estimate: smoothed.a.iter().map(|a| a[o]).collect(),
+                se: smoothed
+                    .v
+                    .iter()
+                    .map(|v| v[o * system.m + o].max(0.0).sqrt())
+                    .collect(),
+                model,
+            },
+            None => Component {
+                estimate: vec![0.0; n],
+                se: vec![0.0; n],
+                model: None,
+            },
+        })
+        .collect();
+    for (t, m) in deterministic.iter().enumerate() {
+        components[0].estimate[t] += m;
+    }
+
+    // the irregular is the observation noise
+    let z = &system.z;
+    let irregular = Component {
+        estimate: (0..n)
+            .map(|t| {
+                y[t] - z
+                    .iter()
+                    .zip(&smoothed.a[t])
+                    .map(|(z, a)| z * a)
+                    .sum::<f64>()
+            })
+            .collect(),
+        se: smoothed
+            .v
+            .iter()
+            .map(|v| {
+                let vz: Vec<f64> = v
+                    .chunks(system.m)
+                    .map(|row| row.iter().zip(z).map(|(a, b)| a * b).sum())
+                    .collect();
+                z.iter()
+                    .zip(&vz)
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    .max(0.0)
+                    .sqrt()
+            })
+            .collect(),
+        model: Some(ComponentModel {
+            phi: Vec::new(),
+            theta: Vec::new(),
+            sigma2: irregular * model.sigma2,
+        }),
+    };
+
+    let transitory = components.pop().unwrap();
+    let seasonal = components.pop().unwrap();
+    let trend = components.pop().unwrap();
+    let adjusted = model
+        .x
+        .iter()
+        .zip(&seasonal.estimate)
+        .map(|(x, s)| x - s)
+        .collect();
+    Ok(SignalExtraction {
+        trend,
+        seasonal,
+        transitory,
+        irregular,
+        adjusted,
+    })
+}
+
+/// Same as `extract`, with the seasonal period implied by the sampling frequency of the
+/// series `x` that `model` was fitted to, see `TimeSeries::seasonal_period`; without a
+/// seasonal component if the frequency has no seasonal period. Fails if the series has
+/// gaps or differs from the series of the model. Only enabled with feature `chrono`.
+#[cfg(feature = "chrono")]
+pub fn extract_series(model: &FittedModel, x: &TimeSeries<f64>) -> Result<SignalExtraction> {
+    if model.x.as_slice() != x.regular_values()? {
+        anyhow::bail!("The model was not fitted to the series");
+    }
+    let period = x.seasonal_period();
+    extract(model, if period >= 2 { Some(period) } else { None })
+}
+
+/// AR polynomials `1 - c_1 z - ...` of the trend, seasonal and transitory components,
+/// and whether each has unit roots.
+fn allocate(model: &FittedModel, period: Option<usize>) -> ([Vec<f64>; 3], [bool; 3]) {
+    let ar: Vec<f64> = std::iter::once(1.0)
+        .chain(model.phi.iter().map(|v| -v))
+        .collect();
+    let mut roots: [Vec<Complex64>; 3] = [
+        vec![Complex64::new(1.0, 0.0); model.d],
+        Vec::new(),
+        Vec::new(),
+    ];
+    for r in model::polyroots(&ar) {
+        let frequency = r.arg().abs() / (2.0 * PI);
+        let group = if r.norm() > MAX_ROOT_MODULUS {
+            2
+        } else if r.im == 0.0 && r.re > 0.0 {
+            0
+        } else {
+            match period {
+                Some(s) => {
+                    let j = (frequency * s as f64).round();
+                    if j >= 1.0 && (frequency * s as f64 - j).abs() < 0.25 {
+                        1
+                    } else {
+                        2
+                    }
+                }
+                None => 2,
+            }
+        };
+        roots[group].push(r);
+    }
+    let unit = [
+        roots[0].iter().any(|r| r.norm() <= 1.0 + 1.0e-8),
+        roots[1].iter().any(|r| r.norm() <= 1.0 + 1.0e-8),
+        roots[2].iter().any(|r| r.norm() <= 1.0 + 1.0e-8),
+    ];
+    let polys = [
+        from_roots(&roots[0]),
+        from_roots(&roots[1]),
+        from_roots(&roots[2]),
+    ];
+    (polys, unit)
+}
+
+/// Polynomial `prod (1 - z / r)` of the given roots, which are closed under conjugation.
+fn from_roots(roots: &[Complex64]) -> Vec<f64> {
+    let mut poly = vec![Complex64::new(1.0, 0.0)];
+    for r in roots {
+        let mut next = vec![Complex64::new(0.0, 0.0); poly.len() + 1];
+        for (i, c) in poly.iter().enumerate() {
+            next[i] += c;
+            next[i + 1] -= c / r;
+        }
+        poly = next;
+    }
+    poly.iter().map(|c| c.re).collect()
+}
+
+/// Coefficients `c_0, ..., c_k` of the symmetric Laurent polynomial
+/// `a(z) a(1/z) = c_0 + sum_j c_j (z^j + z^-j)`.
+fn acgf(a: &[f64]) -> Vec<f64> {
+    (0..a.len())
+        .map(|k| a.iter().zip(&a[k..]).map(|(u, v)| u * v).sum())
+        .collect()
+}
+
+/// Product of two symmetric Laurent polynomials.
+fn sym_mul(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let (ka, kb) = (a.len() as isize - 1, b.len() as isize - 1);
+    (0..ka + kb + 1)
+        .map(|k| {
+            (-ka..ka + 1)
+                .filter(|i| (k - i).abs() <= kb)
+                .map(|i| a[i.unsigned_abs()] * b[(k - i).unsigned_abs()])
+                .sum()
+        })
+        .collect()
+}
+
+fn sym_add(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut c = vec![0.0; a.len().max(b.len())];
+    for (i, v) in a.iter().enumerate() {
+        c[i] += v;
+    }
+    for (i, v) in b.iter().enumerate() {
+        c[i] += v;
+    }
+    c
+}
+
+fn sym_scale(a: &[f64], s: f64) -> Vec<f64> {
+    a.iter().map(|v| v * s).collect()
+}
+
+/// Value `c_0 + 2 sum_j c_j cos(j w)` on the unit circle.
+fn sym_eval(c: &[f64], w: f64) -> f64 {
+    c[0] + 2.0
+        * c.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(j, v)| v * (j as f64 * w).cos())
+            .sum::<f64>()
+}
+
+/// Minimum of the spectrum `numerator / denominator` between 0 and pi, excluding the
+/// poles of the denominator.
+fn spectrum_min(numerator: &[f64], denominator: &[f64]) -> f64 {
+    let scale = denominator.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+    (0..GRID + 1)
+        .map(|i| PI * i as f64 / GRID as f64)
+        .filter_map(|w| {
+            let d = sym_eval(denominator, w);
+            if d.abs() <= 1.0e-6 * scale {
+                None
+            } else {
+                Some(sym_eval(numerator, w) / d)
+            }
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Split `numerator / prod_c acgf(ar_c)` into `sum_c n_c / acgf(ar_c) + remainder` with
+/// symmetric Laurent polynomials `n_c` of lower degree than `acgf(ar_c)`.
+fn partial_fractions(
+    numerator: &[f64],
+    groups: &[Vec<f64>; 3],
+) -> Result<(Vec<Vec<f64>>, Vec<f64>)> {
+    let denominators: Vec<Vec<f64>> = groups.iter().map(|ar| acgf(ar)).collect();
+    let degrees: Vec<usize> = groups.iter().map(|ar| ar.len() - 1).collect();
+    let p: usize = degrees.iter().sum();
+    let q = numerator.len() - 1;
+    let n_remainder = if q >= p { q - p + 1 } else { 0 };
+    let size = p + n_remainder;
+
+    let unit = |j: usize| {
+        let mut e = vec![0.0; j + 1];
+        e[j] = 1.0;
+        e
+    };
+    let mut columns: Vec<Vec<f64>> = Vec::with_capacity(size);
+    for (c, degree) in degrees.iter().enumerate() {
+        let others = denominators
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != c)
+            .fold(vec![1.0], |acc, (_, d)| sym_mul(&acc, d));
+        for j in 0..*degree {
+            columns.push(sym_mul(&unit(j), &others));
+        }
+    }
+    let all = denominators
+        .iter()
+        .fold(vec![1.0], |acc, d| sym_mul(&acc, d));
+    for j in 0..n_remainder {
+        columns.push(sym_mul(&unit(j), &all));
+    }
+
+    let mut a = vec![0.0; size * size];
+    for (col, c) in columns.iter().enumerate() {
+        for (row, v) in c.iter().enumerate().take(size) {
+            a[row * size + col] = *v;
+        }
+    }
+    let mut b = vec![0.0; size];
+    for (row, v) in numerator.iter().enumerate() {
+        b[row] = *v;
+    }
+    let solution = linalg::solve(&a, &b)?;
+
+    let mut numerators = Vec::with_capacity(3);
+    let mut k = 0;
+    for degree in degrees {
+        let mut n_c = solution[k..k + degree].to_vec();
+        if n_c.is_empty() {
+            n_c.push(0.0);
+        }
+        numerators.push(n_c);
+        k += degree;
+    }
+    let mut remainder = solution[k..].to_vec();
+    if remainder.is_empty() {
+        remainder.push(0.0);
+    }
+    Ok((numerators, remainder))
+}
+
+/// Spectral factorization of a non-negative symmetric Laurent polynomial into
+/// `v theta(z) theta(1/z)` with `theta(0) = 1` and the roots of `theta` on or outside
+/// the unit circle. Returns the coefficients of `theta` and `v`.
+fn factorize(c: &[f64]) -> Result<(Vec<f64>, f64)> {
+    let scale = c.iter().fold(0.0_f64, |m, v| m.max(v.abs()));
+    let k = match c.iter().rposition(|v| v.abs() > 1.0e-12 * scale) {
+        Some(k) => k,
+        None => return Ok((vec![1.0], 0.0)),
+    };
+    if k == 0 {
+        return Ok((vec![1.0], c[0]));
+    }
+    let poly: Vec<f64> = (0..2 * k + 1)
+        .map(|i| c[(i as isize - k as isize).unsigned_abs()])
+        .collect();
+    let roots = model::polyroots(&poly);
+    let mut outside: Vec<Complex64> = roots
+        .iter()
+        .filter(|r| r.norm() > 1.0 + 1.0e-5)
+        .copied()
+        .collect();
+    let mut circle: Vec<Complex64> = roots
+        .iter()
+        .filter(|r| (r.norm() - 1.0).abs() <= 1.0e-5)
+        .copied()
+        .collect();
+    // roots on the unit circle are double roots, of which one is kept
+    circle.sort_by(|a, b| a.arg().partial_cmp(&b.arg()).unwrap());
+    outside.extend(circle.iter().step_by(2).map(|r| r / r.norm()));
+    if circle.len() % 2 != 0 || outside.len() != k {
+        anyhow::bail!("Spectral factorization of a component failed");
+    }
+    let theta = from_roots(&outside);
+    let variance = c[0] / theta.iter().map(|v| v * v).sum::<f64>();
+    Ok((theta, variance))
+}
+
+/// State space form with one block per component, in the form
+/// `w_t = phi_1 w_{t-1} + ... + eta_t` with state `(w_t, ...)'` of dimension
+/// `max(p, q + 1)`, and the irregular as observation noise. Returns the system and the
+/// offsets of the blocks.
+fn system(
+    models: &[Option<ComponentModel>],
+    unit_roots: &[bool; 3],
+    irregular: f64,
+) -> (System, Vec<Option<usize>>) {
+    let dims: Vec<usize> = models
+        .iter()
+        .map(|m| m.as_ref().map_or(0, |m| m.phi.len().max(m.theta.len() + 1)))
+        .collect();
+    let m: usize = dims.iter().sum::<usize>().max(1);
+    let mut sys = System {
+        m,
+        t: vec![0.0; m * m],
+        z: vec![0.0; m],
+        h: irregular,
+        q: vec![0.0; m * m],
+        a0: vec![0.0; m],
+        p0: vec![0.0; m * m],
+        n_diffuse: 0,
+        varying: TimeVarying::default(),
+    };
+    let mut offsets = Vec::with_capacity(models.len());
+    let mut o = 0;
+    for ((model, r), unit) in models.iter().zip(dims).zip(unit_roots) {
+        let model = match model {
+            Some(model) => model,
+            None => {
+                offsets.push(None);
+                continue;
+            }
+        };
+        let (t, q) = kalman::arma_matrices(&model.phi, &model.theta, model.sigma2);
+        let p0 = if *unit {
+            sys.n_diffuse += r;
+            let mut p0 = vec![0.0; r * r];
+            for i in 0..r {
+                p0[i * r + i] = DIFFUSE_KAPPA;
+            }
+            p0
+        } else {
+            kalman::stationary_covariance(&t, &q, r)
+        };
+        for i in 0..r {
+            for j in 0..r {
+                sys.t[(o + i) * m + o + j] = t[i * r + j];
+                sys.q[(o + i) * m + o + j] = q[i * r + j];
+                sys.p0[(o + i) * m + o + j] = p0[i * r + j];
+            }
+        }
+        sys.z[o] = 1.0;
+        offsets.push(Some(o));
+        o += r;
+    }
+    (sys, offsets)
+}
@@ -1,7 +1,44 @@
 use anyhow::Result;
 
+use alloc::vec;
+use alloc::vec::Vec;
+
 use crate::util;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Create a reproducible random number generator for one of many independent streams.
+/// The generator is ChaCha20, whose output is fixed for a given seed and stream across
+/// platforms and crate versions. Assigning each series (or each task of a parallel job)
+/// its own stream makes results independent of how the work is scheduled across threads.
+///
+/// # Arguments
+///
+/// * `seed` - Seed shared by all streams of a run.
+/// * `stream` - Stream index, e.g. the index of the simulated series.
+///
+/// # Returns
+///
+/// * Seeded random number generator.
+///
+/// # Example
+///
+/// ```
+/// use rand::Rng;
+/// use arima::sim;
+///
+/// let mut a = sim::stream_rng(42, 0);
+/// let mut b = sim::stream_rng(42, 0);
+/// let mut c = sim::stream_rng(42, 1);
+/// let (xa, xb, xc): (u64, u64, u64) = (a.gen(), b.gen(), c.gen());
+/// assert_eq!(xa, xb);
+/// assert_ne!(xa, xc);
+/// ```
+pub fn stream_rng(seed: u64, stream: u64) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
 
 /// Simulate an ARIMA model time series
 ///
@@ -200,3 +237,57 @@ pub fn arima_forecast<F: Fn(usize, &mut T) -> f64, T: Rng>(
 
     Ok(x)
 }
+
+/// Pointwise prediction intervals from simulated future sample paths, taking the
+/// empirical `(1 - level) / 2` and `(1 + level) / 2` quantiles at every time step.
+///
+/// # Arguments
+///
+/// * `h` - Forecast horizon.
+/// * `level` - Coverage probability of the intervals, e.g. 0.95.
+/// * `n_paths` - Number of simulated sample paths.
+/// * `simulate` - Closure returning one simulated path of length `h` per call.
+///
+/// # Returns
+///
+/// * Tuple of (lower, upper) bounds for each of the next `h` time steps.
+///
+/// # Example
+///
+/// ```
+/// use arima::sim;
+/// let mut k = 0.0;
+/// let (lower, upper) = sim::path_intervals(2, 0.5, 5, || {
+///     k += 1.0;
+///     vec![k, 10.0 * k]
+/// })
+/// .unwrap();
+/// assert_eq!(lower, vec![2.0, 20.0]);
+/// assert_eq!(upper, vec![4.0, 40.0]);
+/// ```
+pub fn path_intervals<F: FnMut() -> Vec<f64>>(
+    h: usize,
+    level: f64,
+    n_paths: usize,
+    mut simulate: F,
+) -> Result<(Vec<f64>, Vec<f64>)> {
+    if !(level > 0.0 && level < 1.0) {
+        anyhow::bail!("level must be within (0, 1)");
+    }
+    if n_paths == 0 {
+        anyhow::bail!("Need at least one sample path");
+    }
+    let paths: Vec<Vec<f64>> = (0..n_paths).map(|_| simulate()).collect();
+    let tail = (1.0 - level) / 2.0;
+    let mut lower = Vec::with_capacity(h);
+    let mut upper = Vec::with_capacity(h);
+    let mut values = vec![0.0; n_paths];
+    for i in 0..h {
+        for (v, path) in values.iter_mut().zip(&paths) {
+            *v = path[i];
+        }
+        lower.push(util::quantile(&values, tail));
+        upper.push(util::quantile(&values, 1.0 - tail));
+    }
+    Ok((lower, upper))
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+
+use crate::{estimate, util};
+
+/// Type of the seasonal component of a Holt-Winters model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seasonal {
+    /// Seasonal effects are added to the level.
+    Additive,
+    /// Seasonal effects scale the level. Requires positive data.
+    Multiplicative,
+}
+
+/// A fitted exponential smoothing model: simple exponential smoothing (SES), Holt's linear
+/// trend method, or Holt-Winters seasonal smoothing.
+#[derive(Debug, Clone)]
+pub struct SmoothingModel {
+    /// Smoothing parameter of the level.
+    pub alpha: f64,
+    /// Smoothing parameter of the trend. `None` if the model has no trend.
+    pub beta: Option<f64>,
+    /// Smoothing parameter of the seasonal component. `None` if the model is not seasonal.
+    pub gamma: Option<f64>,
+    /// Seasonal period and type. `None` if the model is not seasonal.
+    pub seasonal: Option<(usize, Seasonal)>,
+    /// Level at the end of the series.
+    pub level: f64,
+    /// Trend at the end of the series. Zero if the model has no trend.
+    pub trend: f64,
+    /// Seasonal states of the last period, oldest first. Empty if the model is not seasonal.
+    pub season: Vec<f64>,
+    /// One-step-ahead predictions for `x[start..]`.
+    pub fitted: Vec<f64>,
+    /// Index of the first observation with a one-step-ahead prediction.
+    pub start: usize,
+    /// Sum of squared one-step-ahead errors.
+    pub sse: f64,
+}
+
+impl SmoothingModel {
+    /// Forecast the next `h` values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::smoothing;
+    /// let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    /// let m = smoothing::holt(&x, Some(0.5), Some(0.5)).unwrap();
+    /// let f = m.forecast(2);
+    /// assert!((f[0] - 7.0).abs() < 1.0e-9);
+    /// assert!((f[1] - 8.0).abs() < 1.0e-9);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        (1..h + 1)
+            .map(|i| {
+                let base = self.level + i as f64 * self.trend;
+                match self.seasonal {
+                    None => base,
+                    Some((period, kind)) => {
+                        let s = self.season[(i - 1) % period];
+                        match kind {
+                            Seasonal::Additive => base + s,
+                            Seasonal::Multiplicative => base * s,
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Variances of the forecast errors for the next `h` time steps, from the mean
+    /// squared one-step-ahead error and the equivalent innovations state space model.
+    /// With multiplicative seasonality they are first-order approximations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::smoothing;
+    /// let x = [1.0, 2.0, 1.5, 2.5, 2.0, 3.0];
+    /// let m = smoothing::ses(&x, Some(0.5)).unwrap();
+    /// let v = m.forecast_variance(3);
+    /// assert!((v[2] / v[0] - 1.5).abs() < 1.0e-12);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let sigma2 = self.sse / self.fitted.len() as f64;
+        let beta = self.beta.unwrap_or(0.0);
+        let gamma = self.gamma.unwrap_or(0.0);
+        let base = |i: usize| self.level + i as f64 * self.trend;
+        // effect of the error at step i on the observation at step k > i
+        let c = |i: usize, k: usize| {
+            let j = k - i;
+            let state = self.alpha * (1.0 + j as f64 * beta);
+            match self.seasonal {
+                None => state,
+                Some((period, Seasonal::Additive)) if j % period == 0 => state + gamma,
+                Some((_, Seasonal::Additive)) => state,
+                Some((period, Seasonal::Multiplicative)) if j % period == 0 => {
+                    state + gamma * base(k) / base(i)
+                }
+                Some((period, Seasonal::Multiplicative)) => {
+                    state * self.season[(k - 1) % period] / self.season[(i - 1) % period]
+                }
+            }
+        };
+        (1..h + 1)
+            .map(|k| sigma2 * (1.0 + (1..k).map(|i| c(i, k).powi(2)).sum::<f64>()))
+            .collect()
+    }
+
+    /// One-step-ahead errors `x[t] - fitted[t - start]`.
+    pub fn residuals(&self, x: &[f64]) -> Vec<f64> {
+        x[self.start..]
+            .iter()
+            .zip(&self.fitted)
+            .map(|(x, f)| x - f)
+            .collect()
+    }
+}
+
+/// Run the smoothing recursions for fixed parameters. `beta` and `gamma` are ignored if
+/// the model has no trend or no seasonal component.
+fn smooth(
+    x: &[f64],
+    alpha: f64,
+    beta: Option<f64>,
+    gamma: Option<f64>,
+    seasonal: Option<(usize, Seasonal)>,
+) -> SmoothingModel {
+    // initial states
+    let (mut level, mut trend, mut season, start) = match seasonal {
+        None => match beta {
+            None => (x[0], 0.0, Vec::new(), 1),
+            Some(_) => (x[1], x[1] - x[0], Vec::new(), 2),
+        },
+        Some((period, kind)) => {
+            // level from the first period, trend from the change to the second period
+            let m = period as f64;
+            let first: f64 = x[..period].iter().sum::<f64>() / m;
+            let second: f64 = x[period..2 * period].iter().sum::<f64>() / m;
+            let trend = if beta.is_some() {
+                (second - first) / m
+            } else {
+                0.0
+            };
+            let season: Vec<f64> = x[..period]
+                .iter()
+                .map(|v| match kind {
+                    Seasonal::Additive => v - first,
+                    Seasonal::Multiplicative => v / first,
+                })
+                .collect();
+            // the level refers to the last observation of the first period
+            let level = first + (m - 1.0) / 2.0 * trend;
+            (level, trend, season, period)
+        }
+    };
+
+    let beta_v = beta.unwrap_or(0.0);
+    let gamma_v = gamma.unwrap_or(0.0);
+
+    let mut fitted = Vec::with_capacity(x.len() - start);
+    let mut sse = 0.0;
+    for (i, &y) in x.iter().enumerate().skip(start) {
+        let base = level + trend;
+        let (pred, s_old) = match seasonal {
+            None => (base, 0.0),
+            Some((period, kind)) => {
+                let s = season[(i - start) % period];
+                match kind {
+                    Seasonal::Additive => (base + s, s),
+                    Seasonal::Multiplicative => (base * s, s),
+                }
+            }
+        };
+        fitted.push(pred);
+        sse += (y - pred) * (y - pred);
+
+        let prev_level = level;
+        level = match seasonal {
+            None => alpha * y + (1.0 - alpha) * base,
+            Some((_, Seasonal::Additive)) => alpha * (y - s_old) + (1.0 - alpha) * base,
+            Some((_, Seasonal::Multiplicative)) => alpha * (y / s_old) + (1.0 - alpha) * base,
+        };
+        if beta.is_some() {
+            trend = beta_v * (level - prev_level) + (1.0 - beta_v) * trend;
+        }
+        if let Some((period, kind)) = seasonal {
+            let j = (i - start) % period;
+            season[j] = match kind {
+                Seasonal::Additive => gamma_v * (y - base) + (1.0 - gamma_v) * s_old,
+                Seasonal::Multiplicative => gamma_v * (y / base) + (1.0 - gamma_v) * s_old,
+            };
+        }
+    }
+
+    // rotate the seasonal states so that season[0] belongs to the next observation
+    if let Some((period, _)) = seasonal {
+        season.rotate_left((x.len() - start) % period);
+    }
+
+    SmoothingModel {
+        alpha,
+        beta,
+        gamma,
+        seasonal,
+        level,
+        trend,
+        season,
+        fitted,
+        start,
+        sse,
+    }
+}
+
+/// Fit the model, estimating all smoothing parameters that are `None` by minimizing the
+/// sum of squared one-step-ahead errors. Parameters are optimized on the logit scale so
+/// they stay within (0, 1).
+fn fit(
+    x: &[f64],
+    alpha: Option<f64>,
+    beta: Option<Option<f64>>,
+    gamma: Option<Option<f64>>,
+    seasonal: Option<(usize, Seasonal)>,
+) -> Result<SmoothingModel> {
+    for p in [alpha, beta.flatten(), gamma.flatten()].iter().flatten() {
+        if !(0.0..=1.0).contains(p) {
+            anyhow::bail!("Smoothing parameters must be within [0, 1]");
+        }
+    }
+
+    // collect the free parameters with their initial values
+    let mut z: Vec<f64> = Vec::new();
+    if alpha.is_none() {
+        z.push(util::logit(0.3));
+    }
+    if let Some(None) = beta {
+        z.push(util::logit(0.1));
+    }
+    if let Some(None) = gamma {
+        z.push(util::logit(0.1));
+    }
+
+    let params = |z: &[f64]| {
+        let mut free = z.iter().map(|v| util::logistic(*v));
+        let a = alpha.unwrap_or_else(|| free.next().unwrap());
+        let b = beta.map(|b| b.unwrap_or_else(|| free.next().unwrap()));
+        let g = gamma.map(|g| g.unwrap_or_else(|| free.next().unwrap()));
+        (a, b, g)
+    };
+
+    if !z.is_empty() {
+        let f = |z: &[f64]| {
+            let (a, b, g) = params(z);
+            smooth(x, a, b, g, seasonal).sse
+        };
+        estimate::minimize(f, &mut z);
+    }
+
+    let (a, b, g) = params(&z);
+    Ok(smooth(x, a, b, g, seasonal))
+}
+
+/// Fit a simple exponential smoothing model.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least two values.
+/// * `alpha` - Smoothing parameter of the level. Estimated if `None`.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::smoothing;
+/// let x = [3.0, 5.0, 4.0, 6.0, 5.0, 5.5, 4.5, 5.0];
+/// let m = smoothing::ses(&x, None).unwrap();
+/// assert!(m.alpha > 0.0 && m.alpha < 1.0);
+/// let f = m.forecast(3);
+/// assert_eq!(f[0], f[2]);
+/// ```
+pub fn ses(x: &[f64], alpha: Option<f64>) -> Result<SmoothingModel> {
+    if x.len() < 2 {
+        anyhow::bail!("Need at least two values");
+    }
+    fit(x, alpha, None, None, None)
+}
+
+/// Fit Holt's linear trend model.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least three values.
+/// * `alpha` - Smoothing parameter of the level. Estimated if `None`.
+/// * `beta` - Smoothing parameter of the trend. Estimated if `None`.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::smoothing;
+/// let x = [1.0, 2.1, 2.9, 4.2, 5.0, 5.9, 7.1, 8.0];
+/// let m = smoothing::holt(&x, None, None).unwrap();
+/// assert!(m.trend > 0.5);
+/// ```
+pub fn holt(x: &[f64], alpha: Option<f64>, beta: Option<f64>) -> Result<SmoothingModel> {
+    if x.len() < 3 {
+        anyhow::bail!("Need at least three values");
+    }
+    fit(x, alpha, Some(beta), None, None)
+}
+
+/// Fit a Holt-Winters seasonal model with trend.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least two full periods.
+/// * `period` - Seasonal period, e.g. 12 for monthly data.
+/// * `seasonal` - Additive or multiplicative seasonality.
+/// * `alpha` - Smoothing parameter of the level. Estimated if `None`.
+/// * `beta` - Smoothing parameter of the trend. Estimated if `None`.
+/// * `gamma` - Smoothing parameter of the seasonal component. Estimated if `None`.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::smoothing::{self, Seasonal};
+/// let x: Vec<f64> = (0..24).map(|i| 10.0 + [1.0, -1.0, 2.0, -2.0][i % 4]).collect();
+/// let m = smoothing::holt_winters(&x, 4, Seasonal::Additive, None, None, None).unwrap();
+/// let f = m.forecast(4);
+/// assert!((f[0] - 11.0).abs() < 1.0e-3);
+/// assert!((f[3] - 8.0).abs() < 1.0e-3);
+/// ```
+pub fn holt_winters(
+    x: &[f64],
+    period: usize,
+    seasonal: Seasonal,
+    alpha: Option<f64>,
+    beta: Option<f64>,
+    gamma: Option<f64>,
+) -> Result<SmoothingModel> {
+    if period < 2 {
+        anyhow::bail!("Seasonal period must be at least 2");
+    }
+    if x.len() < 2 * period {
+        anyhow::bail!("Need at least two full seasonal periods");
+    }
+    if seasonal == Seasonal::Multiplicative && x.iter().any(|v| *v <= 0.0) {
+        anyhow::bail!("Multiplicative seasonality requires positive data");
+    }
+    fit(x, alpha, Some(beta), Some(gamma), Some((period, seasonal)))
+}
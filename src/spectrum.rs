@@ -0,0 +1,127 @@
+//! Spectral densities of ARMA models and sample periodograms. Frequencies are given in
+//! cycles per time step within `[0, 0.5]`, and densities are normalized so that they
+//! integrate to the variance over `[-0.5, 0.5]`, as in R's `spectrum`.
+
+use anyhow::Result;
+
+use num::complex::Complex64;
+
+use std::f64::consts::PI;
+
+use crate::fft;
+
+/// Fourier frequencies `k / n` for `k = 1, ..., n / 2` of a series of length `n`.
+pub fn fourier_frequencies(n: usize) -> Vec<f64> {
+    (1..n / 2 + 1).map(|k| k as f64 / n as f64).collect()
+}
+
+/// Autocovariance generating function
+/// `g(z) = sigma2 theta(z) theta(1/z) / (phi(z) phi(1/z))` of an ARMA model with
+/// `phi(z) = 1 - phi_1 z - ...` and `theta(z) = 1 + theta_1 z + ...`. The coefficient of
+/// `z^k` in its Laurent expansion is the autocovariance at lag `k`.
+///
+/// # Arguments
+///
+/// * `&phi` - AR coefficients.
+/// * `&theta` - MA coefficients.
+/// * `sigma2` - Innovation variance.
+/// * `z` - Complex argument.
+///
+/// # Returns
+///
+/// * Value of the generating function at `z`.
+pub fn acgf(phi: &[f64], theta: &[f64], sigma2: f64, z: Complex64) -> Complex64 {
+    let poly = |c: &[f64], sign: f64, z: Complex64| {
+        c.iter()
+            .rev()
+            .fold(Complex64::new(0.0, 0.0), |acc, v| (acc + sign * v) * z)
+            + 1.0
+    };
+    let num = poly(theta, 1.0, z) * poly(theta, 1.0, z.inv());
+    let den = poly(phi, -1.0, z) * poly(phi, -1.0, z.inv());
+    sigma2 * num / den
+}
+
+/// Spectral density `f(v) = sigma2 |theta(e^(-2 pi i v))|^2 / |phi(e^(-2 pi i v))|^2` of
+/// an ARMA model.
+///
+/// # Arguments
+///
+/// * `&phi` - AR coefficients.
+/// * `&theta` - MA coefficients.
+/// * `sigma2` - Innovation variance.
+/// * `&freqs` - Frequencies in cycles per time step.
+///
+/// # Returns
+///
+/// * Spectral density at each frequency. Infinite at the frequency of an AR unit root.
+///
+/// # Example
+///
+/// ```
+/// use arima::spectrum;
+/// // AR(1): f(0) = sigma2 / (1 - phi)^2
+/// let f = spectrum::arma_spectrum(&[0.5], &[], 2.0, &[0.0, 0.5]);
+/// assert!((f[0] - 8.0).abs() < 1.0e-12);
+/// assert!((f[1] - 2.0 / 2.25).abs() < 1.0e-12);
+/// ```
+pub fn arma_spectrum(phi: &[f64], theta: &[f64], sigma2: f64, freqs: &[f64]) -> Vec<f64> {
+    freqs
+        .iter()
+        .map(|v| {
+            let z = Complex64::from_polar(1.0, -2.0 * PI * v);
+            let num = theta
+                .iter()
+                .enumerate()
+                .fold(Complex64::new(1.0, 0.0), |acc, (j, t)| {
+                    acc + t * z.powu(j as u32 + 1)
+                });
+            let den = phi
+                .iter()
+                .enumerate()
+                .fold(Complex64::new(1.0, 0.0), |acc, (j, p)| {
+                    acc - p * z.powu(j as u32 + 1)
+                });
+            sigma2 * num.norm_sqr() / den.norm_sqr()
+        })
+        .collect()
+}
+
+/// Raw periodogram `I(v) = |sum_t (x_t - mean) e^(-2 pi i v t)|^2 / n` of a series at
+/// the Fourier frequencies `fourier_frequencies(n)`. Its expectation approximates the
+/// spectral density, so the spectrum of a fitted model can be compared with it.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+///
+/// # Returns
+///
+/// * Tuple of frequencies and periodogram ordinates.
+///
+/// # Example
+///
+/// ```
+/// use arima::spectrum;
+/// // a cosine with period 4 puts all its power at frequency 0.25
+/// let x: Vec<f64> = (0..16).map(|t| (0.5 * std::f64::consts::PI * t as f64).cos()).collect();
+/// let (freqs, pgram) = spectrum::periodogram(&x).unwrap();
+/// assert_eq!(freqs.len(), 8);
+/// assert!((pgram[3] - 4.0).abs() < 1.0e-9);
+/// assert!(pgram[2].abs() < 1.0e-9);
+/// ```
+pub fn periodogram(x: &[f64]) -> Result<(Vec<f64>, Vec<f64>)> {
+    let n = x.len();
+    if n < 2 {
+        anyhow::bail!("Need at least two values");
+    }
+    let mean = x.iter().sum::<f64>() / n as f64;
+    let centered: Vec<f64> = x.iter().map(|v| v - mean).collect();
+    let transform = fft::real_fft(&centered);
+    let freqs = fourier_frequencies(n);
+    let pgram = transform[1..freqs.len() + 1]
+        .iter()
+        .map(|v| v.norm_sqr() / n as f64)
+        .collect();
+    Ok((freqs, pgram))
+}
@@ -0,0 +1,309 @@
+//! User-defined linear Gaussian state space models with univariate observations:
+//!
+//! `y_t = Z_t a_t + eps_t`, `eps_t ~ N(0, H_t)`
+//! `a_{t+1} = T_t a_t + R eta_t`, `eta_t ~ N(0, Q_t)`
+//!
+//! Models are assembled with `StateSpaceBuilder`. Matrices are stored row-major and
+//! missing observations are given as NaN.
+
+use anyhow::Result;
+
+use crate::kalman::{System, TimeVarying, DIFFUSE_KAPPA};
+
+pub use crate::kalman::{Filtered, Smoothed};
+
+/// Builder of a `StateSpaceModel` with `m` states.
+///
+/// The transition matrix defaults to the identity, the selection matrix `R` to the
+/// identity and the initial state to a diffuse prior. The observation vector and the
+/// variances must be set. Time-varying matrices apply at the time index of their
+/// position; the last one applies to all later times, including forecasts.
+#[derive(Debug, Clone)]
+pub struct StateSpaceBuilder {
+    m: usize,
+    t: Vec<f64>,
+    z: Option<Vec<f64>>,
+    h: Option<f64>,
+    r: Option<(Vec<f64>, usize)>,
+    q: Option<Vec<f64>>,
+    init: Option<(Vec<f64>, Vec<f64>)>,
+    varying: TimeVarying,
+}
+
+impl StateSpaceBuilder {
+    /// Start building a model with `m` states.
+    pub fn new(m: usize) -> Self {
+        let mut t = vec![0.0; m * m];
+        for i in 0..m {
+            t[i * m + i] = 1.0;
+        }
+        StateSpaceBuilder {
+            m,
+            t,
+            z: None,
+            h: None,
+            r: None,
+            q: None,
+            init: None,
+            varying: TimeVarying::default(),
+        }
+    }
+
+    /// Set the transition matrix `T`, `m x m`.
+    pub fn transition(mut self, t: &[f64]) -> Self {
+        self.t = t.to_vec();
+        self
+    }
+
+    /// Set the observation vector `Z`, length `m`.
+    pub fn observation(mut self, z: &[f64]) -> Self {
+        self.z = Some(z.to_vec());
+        self
+    }
+
+    /// Set the observation noise variance `H`.
+    pub fn observation_variance(mut self, h: f64) -> Self {
+        self.h = Some(h);
+        self
+    }
+
+    /// Set the selection matrix `R`, `m x r`.
+    pub fn selection(mut self, r: &[f64], r_cols: usize) -> Self {
+        self.r = Some((r.to_vec(), r_cols));
+        self
+    }
+
+    /// Set the state disturbance covariance `Q`, `r x r` where `r` is the number of
+    /// columns of the selection matrix (`m` without selection matrix).
+    pub fn state_covariance(mut self, q: &[f64]) -> Self {
+        self.q = Some(q.to_vec());
+        self
+    }
+
+    /// Set a known initial state distribution with mean `a0` and covariance `p0`
+    /// instead of the diffuse prior.
+    pub fn initial_state(mut self, a0: &[f64], p0: &[f64]) -> Self {
+        self.init = Some((a0.to_vec(), p0.to_vec()));
+        self
+    }
+
+    /// Set time-varying transition matrices `T_t`.
+    pub fn time_varying_transition(mut self, t: Vec<Vec<f64>>) -> Self {
+        self.varying.t = t;
+        self
+    }
+
+    /// Set time-varying observation vectors `Z_t`.
+    pub fn time_varying_observation(mut self, z: Vec<Vec<f64>>) -> Self {
+        self.varying.z = z;
+        self
+    }
+
+    /// Set time-varying observation noise variances `H_t`.
+    pub fn time_varying_observation_variance(mut self, h: Vec<f64>) -> Self {
+        self.varying.h = h;
+        self
+    }
+
+    /// Set time-varying state disturbance covariances `Q_t`, each `r x r`.
+    pub fn time_varying_state_covariance(mut self, q: Vec<Vec<f64>>) -> Self {
+        self.varying.q = q;
+        self
+    }
+
+    /// Validate the dimensions and build the model.
+    pub fn build(self) -> Result<StateSpaceModel> {
+        let m = self.m;
+        if m == 0 {
+            anyhow::bail!("Need at least one state");
+        }
+        if self.t.len() != m * m || self.varying.t.iter().any(|t| t.len() != m * m) {
+            anyhow::bail!("Transition matrix must be {} x {}", m, m);
+        }
+        let z = match (self.z, self.varying.z.first()) {
+            (Some(z), _) => z,
+            (None, Some(z)) => z.clone(),
+            (None, None) => anyhow::bail!("Observation vector is not set"),
+        };
+        if z.len() != m || self.varying.z.iter().any(|z| z.len() != m) {
+            anyhow::bail!("Observation vector must have length {}", m);
+        }
+        let h = match (self.h, self.varying.h.first()) {
+            (Some(h), _) => h,
+            (None, Some(h)) => *h,
+            (None, None) => anyhow::bail!("Observation variance is not set"),
+        };
+        if h < 0.0 || h.is_nan() || self.varying.h.iter().any(|h| *h < 0.0 || h.is_nan()) {
+            anyhow::bail!("Observation variance must be non-negative");
+        }
+
+        let (r, r_cols) = match self.r {
+            Some((r, r_cols)) => {
+                if r.len() != m * r_cols {
+                    anyhow::bail!("Selection matrix must be {} x {}", m, r_cols);
+                }
+                (r, r_cols)
+            }
+            None => {
+                let mut r = vec![0.0; m * m];
+                for i in 0..m {
+                    r[i * m + i] = 1.0;
+                }
+                (r, m)
+            }
+        };
+        let q = match (self.q, self.varying.q.first()) {
+            (Some(q), _) => q,
+            (None, Some(q)) => q.clone(),
+            (None, None) => anyhow::bail!("State covariance is not set"),
+        };
+        if q.len() != r_cols * r_cols || self.varying.q.iter().any(|q| q.len() != r_cols * r_cols) {
+            anyhow::bail!("State covariance must be {} x {}", r_cols, r_cols);
+        }
+
+        let (a0, p0, n_diffuse) = match self.init {
+            Some((a0, p0)) => {
+                if a0.len() != m || p0.len() != m * m {
+                    anyhow::bail!(
+                        "Initial state must have length {} and covariance {} x {}",
+                        m,
+                        m,
+                        m
+                    );
+                }
+                (a0, p0, 0)
+            }
+            None => {
+                let mut p0 = vec![0.0; m * m];
+                for i in 0..m {
+                    p0[i * m + i] = DIFFUSE_KAPPA;
+                }
+                (vec![0.0; m], p0, m)
+            }
+        };
+
+        let varying = TimeVarying {
+            q: self
+                .varying
+                .q
+                .iter()
+                .map(|q| select(&r, q, m, r_cols))
+                .collect(),
+            ..self.varying
+        };
+        Ok(StateSpaceModel {
+            system: System {
+                m,
+                t: self.t,
+                z,
+                h,
+                q: select(&r, &q, m, r_cols),
+                a0,
+                p0,
+                n_diffuse,
+                varying,
+            },
+        })
+    }
+}
+
+/// `R Q R'` for an `m x r` matrix `R` and an `r x r` matrix `Q`.
+fn select(r: &[f64], q: &[f64], m: usize, r_cols: usize) -> Vec<f64> {
+    let mut rq = vec![0.0; m * r_cols];
+    for i in 0..m {
+        for j in 0..r_cols {
+            rq[i * r_cols + j] = (0..r_cols)
+                .map(|k| r[i * r_cols + k] * q[k * r_cols + j])
+                .sum();
+        }
+    }
+    let mut out = vec![0.0; m * m];
+    for i in 0..m {
+        for j in 0..m {
+            out[i * m + j] = (0..r_cols)
+                .map(|k| rq[i * r_cols + k] * r[j * r_cols + k])
+                .sum();
+        }
+    }
+    out
+}
+
+/// A linear Gaussian state space model, see the module documentation. With the default
+/// diffuse initialization, the likelihood contributions of the first `m` observations
+/// are skipped.
+#[derive(Debug, Clone)]
+pub struct StateSpaceModel {
+    system: System,
+}
+
+impl StateSpaceModel {
+    /// Number of states.
+    pub fn n_states(&self) -> usize {
+        self.system.m
+    }
+
+    /// Run the Kalman filter over the observations `y`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::statespace::StateSpaceBuilder;
+    /// // AR(1) with coefficient 0.5 and unit innovation variance
+    /// let model = StateSpaceBuilder::new(1)
+    ///     .transition(&[0.5])
+    ///     .observation(&[1.0])
+    ///     .observation_variance(0.0)
+    ///     .state_covariance(&[1.0])
+    ///     .initial_state(&[0.0], &[1.0 / 0.75])
+    ///     .build()
+    ///     .unwrap();
+    /// let filtered = model.filter(&[1.0, 2.0, 0.5]);
+    /// // innovations are the AR(1) residuals
+    /// assert!((filtered.v[1] - 1.5).abs() < 1.0e-12);
+    /// assert!((filtered.v[2] + 0.5).abs() < 1.0e-12);
+    /// ```
+    pub fn filter(&self, y: &[f64]) -> Filtered {
+        self.system.filter(y)
+    }
+
+    /// Run the Kalman filter and the fixed-interval state smoother over `y`.
+    pub fn smooth(&self, y: &[f64]) -> Smoothed {
+        self.system.smooth(&self.system.filter(y))
+    }
+
+    /// Standardized innovations `v_t / sqrt(F_t)` of `y`. Missing observations and the
+    /// observations initializing a diffuse state are NaN.
+    pub fn standardized_residuals(&self, y: &[f64]) -> Vec<f64> {
+        self.system.standardized(&self.system.filter(y))
+    }
+
+    /// Gaussian log-likelihood of `y`.
+    pub fn loglik(&self, y: &[f64]) -> f64 {
+        self.system.filter(y).loglik
+    }
+
+    /// Forecast the observations for the next `h` time steps after `y`.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of forecast means and variances.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::statespace::StateSpaceBuilder;
+    /// let model = StateSpaceBuilder::new(1)
+    ///     .transition(&[0.5])
+    ///     .observation(&[1.0])
+    ///     .observation_variance(0.0)
+    ///     .state_covariance(&[1.0])
+    ///     .build()
+    ///     .unwrap();
+    /// let (mean, var) = model.forecast(&[1.0, 2.0], 2);
+    /// assert!((mean[1] - 0.5).abs() < 1.0e-9);
+    /// assert!((var[1] - 1.25).abs() < 1.0e-9);
+    /// ```
+    pub fn forecast(&self, y: &[f64], h: usize) -> (Vec<f64>, Vec<f64>) {
+        self.system.forecast(&self.system.filter(y), h)
+    }
+}
@@ -0,0 +1,468 @@
+use anyhow::Result;
+
+use crate::kalman::{Filtered, System, TimeVarying, DIFFUSE_KAPPA};
+use crate::{estimate, util};
+
+/// Trend component of a structural time series model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    /// Random walk level: `mu_{t+1} = mu_t + xi_t`.
+    LocalLevel,
+    /// Random walk level with random walk slope: `mu_{t+1} = mu_t + nu_t + xi_t`,
+    /// `nu_{t+1} = nu_t + zeta_t`.
+    LocalLinearTrend,
+}
+
+/// Stochastic seasonal component of an unobserved components model. All seasonal
+/// disturbances share one variance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seasonal {
+    /// Dummy seasonal with the given period: `gamma_{t+1} = -sum_{j=0}^{s-2} gamma_{t-j}
+    /// + omega_t`, i.e. the seasonal effects sum to zero up to noise.
+    Dummy(usize),
+    /// Trigonometric seasonal with the given period: one pair of rotating states per
+    /// harmonic frequency `2 pi j / s`, `j = 1, ..., s/2`.
+    Trigonometric(usize),
+}
+
+impl Seasonal {
+    fn period(&self) -> usize {
+        match self {
+            Seasonal::Dummy(s) | Seasonal::Trigonometric(s) => *s,
+        }
+    }
+
+    /// Number of seasonal states.
+    fn n_states(&self) -> usize {
+        self.period() - 1
+    }
+}
+
+/// Specification of an unobserved components model
+/// `y_t = mu_t + gamma_t + psi_t + eps_t` with trend `mu_t`, optional seasonal
+/// `gamma_t` and optional cycle `psi_t`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UcSpec {
+    /// Trend component.
+    pub trend: Trend,
+    /// Seasonal component, if any.
+    pub seasonal: Option<Seasonal>,
+    /// Stochastic cycle `psi_{t+1} = rho (cos(l) psi_t + sin(l) psi*_t) + kappa_t`,
+    /// `psi*_{t+1} = rho (-sin(l) psi_t + cos(l) psi*_t) + kappa*_t`. The value is the
+    /// starting guess of the cycle period `2 pi / l`; damping `rho` and period are
+    /// estimated together with the variances.
+    pub cycle: Option<f64>,
+}
+
+/// A structural time series model fitted by maximum likelihood with the Kalman filter.
+///
+/// The observations are `y_t = mu_t + gamma_t + psi_t + eps_t` with the trend `mu_t`
+/// given by `Trend` and the seasonal `gamma_t` and cycle `psi_t` given by `UcSpec`.
+/// Missing observations (NaN) are skipped by the filter and interpolated by the
+/// smoother.
+#[derive(Debug, Clone)]
+pub struct StructuralModel {
+    /// Model specification.
+    pub spec: UcSpec,
+    /// Variance of the irregular component `eps_t`.
+    pub irregular_var: f64,
+    /// Variance of the level disturbance `xi_t`.
+    pub level_var: f64,
+    /// Variance of the slope disturbance `zeta_t`. `None` for the local level model.
+    pub slope_var: Option<f64>,
+    /// Smoothed level `E(mu_t | y)`.
+    pub level: Vec<f64>,
+    /// Variance of the smoothed level.
+    pub level_se2: Vec<f64>,
+    /// Smoothed slope `E(nu_t | y)`. `None` for the local level model.
+    pub slope: Option<Vec<f64>>,
+    /// Variance of the seasonal disturbances. `None` without seasonal component.
+    pub seasonal_var: Option<f64>,
+    /// Smoothed seasonal component `E(gamma_t | y)`.
+    pub seasonal: Option<Vec<f64>>,
+    /// Variance of the cycle disturbances. `None` without cycle.
+    pub cycle_var: Option<f64>,
+    /// Damping factor `rho` of the cycle.
+    pub cycle_rho: Option<f64>,
+    /// Period `2 pi / l` of the cycle.
+    pub cycle_period: Option<f64>,
+    /// Smoothed cycle component `E(psi_t | y)`.
+    pub cycle: Option<Vec<f64>>,
+    /// One-step-ahead prediction errors of the Kalman filter. NaN for missing values.
+    pub innovations: Vec<f64>,
+    /// Variances of the one-step-ahead prediction errors.
+    pub innovation_var: Vec<f64>,
+    /// Diffuse log-likelihood.
+    pub loglik: f64,
+    /// Number of observations that contribute to the likelihood.
+    pub n_used: usize,
+    system: System,
+    filtered: Filtered,
+}
+
+impl StructuralModel {
+    /// Number of estimated parameters: variances and the damping and period of the
+    /// cycle.
+    pub fn n_params(&self) -> usize {
+        n_params(&self.spec)
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik + 2.0 * self.n_params() as f64
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik + (self.n_used as f64).ln() * self.n_params() as f64
+    }
+
+    /// Innovations scaled by their time-varying standard deviations `sqrt(F_t)`. Missing
+    /// observations and the first observations, which initialize the diffuse states,
+    /// are NaN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::structural::{self, Trend};
+    /// let x = [1.0, 1.1, 0.9, 1.0, 1.2, 1.1, 1.0, 0.9, 1.1, 1.0];
+    /// let m = structural::fit(&x, Trend::LocalLevel).unwrap();
+    /// let z = m.standardized_residuals();
+    /// assert!(z[0].is_nan());
+    /// assert!((z[5] - m.innovations[5] / m.innovation_var[5].sqrt()).abs() < 1.0e-12);
+    /// ```
+    pub fn standardized_residuals(&self) -> Vec<f64> {
+        self.system.standardized(&self.filtered)
+    }
+
+    /// Forecast the next `h` observations.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of (means, variances) of the forecasts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::structural::{self, Trend};
+    /// let x = [1.0, 1.1, 0.9, 1.0, 1.2, 1.1, 1.0, 0.9, 1.1, 1.0];
+    /// let m = structural::fit(&x, Trend::LocalLevel).unwrap();
+    /// let (mean, var) = m.forecast(3);
+    /// assert_eq!(mean[0], mean[2]);
+    /// assert!(var[2] >= var[0]);
+    /// ```
+    pub fn forecast(&self, h: usize) -> (Vec<f64>, Vec<f64>) {
+        self.system.forecast(&self.filtered, h)
+    }
+}
+
+fn n_params(spec: &UcSpec) -> usize {
+    let mut k = match spec.trend {
+        Trend::LocalLevel => 2,
+        Trend::LocalLinearTrend => 3,
+    };
+    if spec.seasonal.is_some() {
+        k += 1;
+    }
+    if spec.cycle.is_some() {
+        k += 3;
+    }
+    k
+}
+
+/// Model parameters on their natural scale.
+#[derive(Debug, Clone, Copy)]
+struct Params {
+    irregular: f64,
+    level: f64,
+    slope: f64,
+    seasonal: f64,
+    cycle: f64,
+    rho: f64,
+    lambda: f64,
+}
+
+/// Map the unconstrained optimization vector to the parameters: log variances, the
+/// damping factor on the logit scale and the cycle frequency in (0, pi) on the logit
+/// scale.
+fn params(z: &[f64], spec: &UcSpec) -> Params {
+    let mut z = z.iter().copied();
+    let irregular = z.next().unwrap().exp();
+    let level = z.next().unwrap().exp();
+    let slope = match spec.trend {
+        Trend::LocalLevel => 0.0,
+        Trend::LocalLinearTrend => z.next().unwrap().exp(),
+    };
+    let seasonal = match spec.seasonal {
+        Some(_) => z.next().unwrap().exp(),
+        None => 0.0,
+    };
+    let (cycle, rho, lambda) = match spec.cycle {
+        Some(_) => (
+            z.next().unwrap().exp(),
+            util::logistic(z.next().unwrap()),
+            std::f64::consts::PI * util::logistic(z.next().unwrap()),
+        ),
+        None => (0.0, 0.0, 0.0),
+    };
+    Params {
+        irregular,
+        level,
+        slope,
+        seasonal,
+        cycle,
+        rho,
+        lambda,
+    }
+}
+
+/// Indices of the first trend, seasonal and cycle state.
+fn offsets(spec: &UcSpec) -> (usize, usize, usize) {
+    let n_trend = match spec.trend {
+        Trend::LocalLevel => 1,
+        Trend::LocalLinearTrend => 2,
+    };
+    let n_seasonal = spec.seasonal.map_or(0, |s| s.n_states());
+    (0, n_trend, n_trend + n_seasonal)
+}
+
+/// Build the block diagonal state space form of the model.
+fn system(spec: &UcSpec, p: &Params) -> System {
+    let (_, seasonal_start, cycle_start) = offsets(spec);
+    let m = cycle_start + if spec.cycle.is_some() { 2 } else { 0 };
+    let mut sys = System {
+        m,
+        t: vec![0.0; m * m],
+        z: vec![0.0; m],
+        h: p.irregular,
+        q: vec![0.0; m * m],
+        a0: vec![0.0; m],
+        p0: vec![0.0; m * m],
+        n_diffuse: cycle_start,
+        varying: TimeVarying::default(),
+    };
+
+    // trend
+    sys.t[0] = 1.0;
+    sys.z[0] = 1.0;
+    sys.q[0] = p.level;
+    if spec.trend == Trend::LocalLinearTrend {
+        sys.t[1] = 1.0;
+        sys.t[m + 1] = 1.0;
+        sys.q[m + 1] = p.slope;
+    }
+
+    // seasonal
+    if let Some(seasonal) = spec.seasonal {
+        let o = seasonal_start;
+        let n = seasonal.n_states();
+        match seasonal {
+            Seasonal::Dummy(_) => {
+                for j in 0..n {
+                    sys.t[o * m + o + j] = -1.0;
+                }
+                for j in 1..n {
+                    sys.t[(o + j) * m + o + j - 1] = 1.0;
+                }
+                sys.z[o] = 1.0;
+                sys.q[o * m + o] = p.seasonal;
+            }
+            Seasonal::Trigonometric(period) => {
+                let mut i = o;
+                for j in 1..period / 2 + 1 {
+                    let freq = 2.0 * std::f64::consts::PI * j as f64 / period as f64;
+                    sys.z[i] = 1.0;
+                    if 2 * j == period {
+                        // the Nyquist frequency has a single state
+                        sys.t[i * m + i] = -1.0;
+                        sys.q[i * m + i] = p.seasonal;
+                        i += 1;
+                    } else {
+                        let (sin, cos) = freq.sin_cos();
+                        sys.t[i * m + i] = cos;
+                        sys.t[i * m + i + 1] = sin;
+                        sys.t[(i + 1) * m + i] = -sin;
+                        sys.t[(i + 1) * m + i + 1] = cos;
+                        sys.q[i * m + i] = p.seasonal;
+                        sys.q[(i + 1) * m + i + 1] = p.seasonal;
+                        i += 2;
+                    }
+                }
+            }
+        }
+    }
+
+    for i in 0..cycle_start {
+        sys.p0[i * m + i] = DIFFUSE_KAPPA;
+    }
+
+    // cycle, initialized with its stationary distribution
+    if spec.cycle.is_some() {
+        let c = cycle_start;
+        let (sin, cos) = p.lambda.sin_cos();
+        sys.t[c * m + c] = p.rho * cos;
+        sys.t[c * m + c + 1] = p.rho * sin;
+        sys.t[(c + 1) * m + c] = -p.rho * sin;
+        sys.t[(c + 1) * m + c + 1] = p.rho * cos;
+        sys.z[c] = 1.0;
+        sys.q[c * m + c] = p.cycle;
+        sys.q[(c + 1) * m + c + 1] = p.cycle;
+        let stationary = p.cycle / (1.0 - p.rho * p.rho);
+        sys.p0[c * m + c] = stationary;
+        sys.p0[(c + 1) * m + c + 1] = stationary;
+    }
+    sys
+}
+
+/// Fit a structural time series model by maximizing the diffuse Gaussian likelihood over
+/// the log variances, and extract the trend components with the state smoother.
+/// See `fit_uc` for models with seasonal and cycle components.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Missing values can be given as NaN.
+/// * `trend` - Trend component.
+///
+/// # Returns
+///
+/// * Fitted model with smoothed components.
+///
+/// # Example
+///
+/// ```
+/// use arima::structural::{self, Trend};
+/// let x = [1.0, 2.1, 2.9, 4.2, 5.0, 5.9, 7.1, 8.0, 8.8, 10.1, 11.0, 11.9];
+/// let m = structural::fit(&x, Trend::LocalLinearTrend).unwrap();
+/// let slope = m.slope.unwrap();
+/// assert!((slope[11] - 1.0).abs() < 0.2);
+/// ```
+pub fn fit(x: &[f64], trend: Trend) -> Result<StructuralModel> {
+    fit_uc(
+        x,
+        &UcSpec {
+            trend,
+            seasonal: None,
+            cycle: None,
+        },
+    )
+}
+
+/// Fit an unobserved components model with trend, stochastic seasonal and stochastic
+/// cycle by maximum likelihood, and extract all components with the state smoother.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Missing values can be given as NaN.
+/// * `&spec` - Model specification.
+///
+/// # Returns
+///
+/// * Fitted model with smoothed components.
+///
+/// # Example
+///
+/// ```
+/// use arima::structural::{self, Seasonal, Trend, UcSpec};
+/// let x: Vec<f64> = (0..48).map(|t| 5.0 + [1.0, -0.5, 0.5, -1.0][t % 4]).collect();
+/// let spec = UcSpec {
+///     trend: Trend::LocalLevel,
+///     seasonal: Some(Seasonal::Dummy(4)),
+///     cycle: None,
+/// };
+/// let m = structural::fit_uc(&x, &spec).unwrap();
+/// let seasonal = m.seasonal.unwrap();
+/// assert!((seasonal[40] - 1.0).abs() < 1.0e-2);
+/// ```
+pub fn fit_uc(x: &[f64], spec: &UcSpec) -> Result<StructuralModel> {
+    if let Some(seasonal) = spec.seasonal {
+        if seasonal.period() < 2 {
+            anyhow::bail!("Seasonal period must be at least 2");
+        }
+    }
+    if let Some(period) = spec.cycle {
+        if period <= 2.0 || period.is_nan() {
+            anyhow::bail!("Cycle period must be larger than 2");
+        }
+    }
+    let observed: Vec<f64> = x.iter().copied().filter(|v| !v.is_nan()).collect();
+    let n_diffuse = offsets(spec).2;
+    if observed.len() < n_diffuse + n_params(spec) {
+        anyhow::bail!(
+            "Need at least {} observed values",
+            n_diffuse + n_params(spec)
+        );
+    }
+
+    // scale the starting values with the variance of the differenced observations
+    let diffs: Vec<f64> = observed.windows(2).map(|w| w[1] - w[0]).collect();
+    let mean = diffs.iter().sum::<f64>() / diffs.len() as f64;
+    let scale = diffs.iter().map(|d| (d - mean) * (d - mean)).sum::<f64>() / diffs.len() as f64;
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+
+    let mut z = vec![(scale / 2.0).ln(), (scale / 2.0).ln()];
+    if spec.trend == Trend::LocalLinearTrend {
+        z.push((scale / 100.0).ln());
+    }
+    if spec.seasonal.is_some() {
+        z.push((scale / 100.0).ln());
+    }
+    if let Some(period) = spec.cycle {
+        z.push((scale / 10.0).ln());
+        z.push(util::logit(0.9));
+        z.push(util::logit(2.0 / period));
+    }
+
+    let f = |z: &[f64]| {
+        let loglik = system(spec, &params(z, spec)).filter(x).loglik;
+        if loglik.is_finite() {
+            -loglik
+        } else {
+            1.0e10
+        }
+    };
+    estimate::minimize(f, &mut z);
+
+    let p = params(&z, spec);
+    let system = system(spec, &p);
+    let filtered = system.filter(x);
+    let smoothed = system.smooth(&filtered);
+    let (_, seasonal_start, cycle_start) = offsets(spec);
+
+    // the seasonal effect is the sum of the states that enter the observation
+    let seasonal = spec.seasonal.map(|_| {
+        smoothed
+            .a
+            .iter()
+            .map(|a| {
+                (seasonal_start..cycle_start)
+                    .map(|i| system.z[i] * a[i])
+                    .sum()
+            })
+            .collect()
+    });
+
+    Ok(StructuralModel {
+        spec: *spec,
+        irregular_var: p.irregular,
+        level_var: p.level,
+        slope_var: (spec.trend == Trend::LocalLinearTrend).then_some(p.slope),
+        level: smoothed.a.iter().map(|a| a[0]).collect(),
+        level_se2: smoothed.v.iter().map(|v| v[0]).collect(),
+        slope: (spec.trend == Trend::LocalLinearTrend)
+            .then(|| smoothed.a.iter().map(|a| a[1]).collect()),
+        seasonal_var: spec.seasonal.map(|_| p.seasonal),
+        seasonal,
+        cycle_var: spec.cycle.map(|_| p.cycle),
+        cycle_rho: spec.cycle.map(|_| p.rho),
+        cycle_period: spec.cycle.map(|_| 2.0 * std::f64::consts::PI / p.lambda),
+        cycle: spec
+            .cycle
+            .map(|_| smoothed.a.iter().map(|a| a[cycle_start]).collect()),
+        innovations: filtered.v.clone(),
+        innovation_var: filtered.f.clone(),
+        loglik: filtered.loglik,
+        n_used: filtered.n_used,
+        system,
+        filtered,
+    })
+}
@@ -0,0 +1,268 @@
+//! Surrogate data for tests of nonlinearity (Theiler et al., 1992).
+//!
+//! Surrogates share the linear properties of a series, its periodogram and, for AAFT
+//! surrogates, its marginal distribution, but are otherwise random. A statistic that
+//! measures nonlinear structure is compared between the series and its surrogates: if
+//! the series stands out, a linear Gaussian process (observed through a monotonic
+//! transformation, for AAFT) is rejected in favour of nonlinear models.
+
+use anyhow::Result;
+
+use num::complex::Complex64;
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::fft;
+
+/// Surrogate generation method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Phase randomization: the Fourier amplitudes of the series with random phases.
+    /// Null hypothesis: a linear Gaussian process.
+    PhaseRandomization,
+    /// Amplitude adjusted Fourier transform: phase randomization of a Gaussianized copy
+    /// of the series, rescaled to the values of the series. Null hypothesis: a monotonic
+    /// transformation of a linear Gaussian process.
+    Aaft,
+}
+
+/// Result of a surrogate data test, see `surrogate_test`.
+#[derive(Debug, Clone)]
+pub struct SurrogateTest {
+    /// Statistic of the series.
+    pub statistic: f64,
+    /// Statistics of the surrogates.
+    pub surrogates: Vec<f64>,
+    /// Two-sided rank-based p-value.
+    pub p_value: f64,
+}
+
+/// Generator of surrogates of one series. The Fourier transform of the series is
+/// computed once and reused for every phase-randomized surrogate; AAFT surrogates need
+/// the transform of a new Gaussian sample for every draw.
+#[derive(Debug, Clone)]
+pub struct Surrogates {
+    method: Method,
+    sorted: Vec<f64>,
+    ranks: Vec<usize>,
+    spectrum: Spectrum,
+}
+
+/// Mean, amplitudes `2 |X_k| / n` of the Fourier frequencies `k = 1, ..., (n - 1) / 2`,
+/// and the coefficient `X_{n/2} / n` of the Nyquist frequency for even `n` (else zero).
+#[derive(Debug, Clone)]
+struct Spectrum {
+    n: usize,
+    mean: f64,
+    amplitudes: Vec<f64>,
+    nyquist: f64,
+}
+
+impl Surrogates {
+    /// Prepare surrogates of a series.
+    ///
+    /// # Arguments
+    ///
+    /// * `&x` - Vector of the timeseries. Needs at least four values.
+    /// * `method` - Surrogate generation method.
+    pub fn new(x: &[f64], method: Method) -> Result<Self> {
+        if x.len() < 4 {
+            anyhow::bail!("Need at least four values");
+        }
+        if x.iter().any(|v| !v.is_finite()) {
+            anyhow::bail!("Series must not contain missing or infinite values");
+        }
+        let mut sorted = x.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Ok(Surrogates {
+            method,
+            sorted,
+            ranks: ranks(x),
+            spectrum: Spectrum::new(x),
+        })
+    }
+
+    /// Draw a surrogate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::{sim, surrogate};
+    /// let x: Vec<f64> = (0..64).map(|t| (t as f64 / 3.0).sin() + 0.1 * t as f64).collect();
+    /// let s = surrogate::Surrogates::new(&x, surrogate::Method::Aaft).unwrap();
+    /// let mut rng = sim::stream_rng(1, 0);
+    /// let y = s.draw(&mut rng);
+    /// // same values in a different order
+    /// let mut a = x.clone();
+    /// let mut b = y.clone();
+    /// a.sort_by(|u, v| u.partial_cmp(v).unwrap());
+    /// b.sort_by(|u, v| u.partial_cmp(v).unwrap());
+    /// assert_eq!(a, b);
+    /// assert_ne!(x, y);
+    /// ```
+    pub fn draw<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        match self.method {
+            Method::PhaseRandomization => self.spectrum.randomize(rng),
+            Method::Aaft => {
+                // Gaussian sample in the rank order of the series
+                let mut gaussian: Vec<f64> = (0..self.ranks.len())
+                    .map(|_| StandardNormal.sample(rng))
+                    .collect();
+                gaussian.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                let scores: Vec<f64> = self.ranks.iter().map(|r| gaussian[*r]).collect();
+                let y = Spectrum::new(&scores).randomize(rng);
+                // values of the series in the rank order of the surrogate
+                ranks(&y).into_iter().map(|r| self.sorted[r]).collect()
+            }
+        }
+    }
+}
+
+impl Spectrum {
+    fn new(x: &[f64]) -> Self {
+        let n = x.len();
+        let nf = n as f64;
+        let mean = x.iter().sum::<f64>() / nf;
+        let centered: Vec<f64> = x.iter().map(|v| v - mean).collect();
+        let transform = fft::real_fft(&centered);
+        let amplitudes = transform[1..(n - 1) / 2 + 1]
+            .iter()
+            .map(|v| 2.0 * v.norm() / nf)
+            .collect();
+        let nyquist = if n % 2 == 0 {
+            transform[n / 2].re / nf
+        } else {
+            0.0
+        };
+        Spectrum {
+            n,
+            mean,
+            amplitudes,
+            nyquist,
+        }
+    }
+
+    /// Inverse Fourier transform with random phases.
+    fn randomize<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let nf = self.n as f64;
+        let mut transform = vec![Complex64::new(0.0, 0.0); self.n];
+        transform[0] = Complex64::new(nf * self.mean, 0.0);
+        for (k, a) in self.amplitudes.iter().enumerate() {
+            let phase = rng.gen_range(0.0..2.0 * PI);
+            transform[k + 1] = Complex64::from_polar(nf * a / 2.0, phase);
+            transform[self.n - k - 1] = transform[k + 1].conj();
+        }
+        if self.n % 2 == 0 {
+            transform[self.n / 2] = Complex64::new(nf * self.nyquist, 0.0);
+        }
+        fft::ifft(&transform).into_iter().map(|v| v.re).collect()
+    }
+}
+
+/// Draw a surrogate of a series, see `Surrogates`. Use `Surrogates` to draw many
+/// surrogates of the same series.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least four values.
+/// * `method` - Surrogate generation method.
+/// * `rng` - Reference to a mutable `Rng`.
+///
+/// # Returns
+///
+/// * Surrogate of the same length as `x`.
+pub fn surrogate<R: Rng>(x: &[f64], method: Method, rng: &mut R) -> Result<Vec<f64>> {
+    Ok(Surrogates::new(x, method)?.draw(rng))
+}
+
+/// Test a series for nonlinearity by comparing a statistic with its values on
+/// surrogates.
+///
+/// The p-value is `2 min(P_low, P_high)`, capped at one, where `P_low` and `P_high`
+/// are the shares of the series and its surrogates with a statistic at most and at
+/// least that of the series.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `method` - Surrogate generation method.
+/// * `n_surrogates` - Number of surrogates, e.g. 99 or 199.
+/// * `statistic` - Statistic measuring nonlinear structure, e.g.
+///   `time_reversal_asymmetry`.
+/// * `rng` - Reference to a mutable `Rng`.
+///
+/// # Returns
+///
+/// * Test result.
+///
+/// # Example
+///
+/// ```
+/// use arima::{sim, surrogate};
+/// // slow rise and sudden fall: not time reversible
+/// let x: Vec<f64> = (0..200).map(|t| (t % 10) as f64 + 0.01 * ((t * 7) % 3) as f64).collect();
+/// let mut rng = sim::stream_rng(1, 0);
+/// let test = surrogate::surrogate_test(&x, surrogate::Method::Aaft, 99, |y| {
+///     surrogate::time_reversal_asymmetry(y, 1)
+/// }, &mut rng).unwrap();
+/// assert!(test.p_value < 0.05);
+/// ```
+pub fn surrogate_test<F: Fn(&[f64]) -> f64, R: Rng>(
+    x: &[f64],
+    method: Method,
+    n_surrogates: usize,
+    statistic: F,
+    rng: &mut R,
+) -> Result<SurrogateTest> {
+    if n_surrogates == 0 {
+        anyhow::bail!("Need at least one surrogate");
+    }
+    let generator = Surrogates::new(x, method)?;
+    let value = statistic(x);
+    let surrogates: Vec<f64> = (0..n_surrogates)
+        .map(|_| statistic(&generator.draw(rng)))
+        .collect();
+    let total = (n_surrogates + 1) as f64;
+    let low = (1 + surrogates.iter().filter(|s| **s <= value).count()) as f64 / total;
+    let high = (1 + surrogates.iter().filter(|s| **s >= value).count()) as f64 / total;
+    Ok(SurrogateTest {
+        statistic: value,
+        surrogates,
+        p_value: (2.0 * low.min(high)).min(1.0),
+    })
+}
+
+/// Time reversal asymmetry `mean((x[t] - x[t-lag])^3) / mean((x[t] - x[t-lag])^2)^(3/2)`,
+/// zero in expectation for linear Gaussian processes.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `lag` - Lag of the differences, less than the length of `x`.
+pub fn time_reversal_asymmetry(x: &[f64], lag: usize) -> f64 {
+    if lag == 0 || lag >= x.len() {
+        return f64::NAN;
+    }
+    let (mut m2, mut m3) = (0.0, 0.0);
+    for t in lag..x.len() {
+        let d = x[t] - x[t - lag];
+        m2 += d * d;
+        m3 += d * d * d;
+    }
+    let n = (x.len() - lag) as f64;
+    (m3 / n) / (m2 / n).powf(1.5)
+}
+
+/// Ranks `0, ..., n - 1` of the values, ties broken by position.
+fn ranks(x: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..x.len()).collect();
+    order.sort_by(|a, b| x[*a].partial_cmp(&x[*b]).unwrap());
+    let mut ranks = vec![0; x.len()];
+    for (r, i) in order.into_iter().enumerate() {
+        ranks[i] = r;
+    }
+    ranks
+}
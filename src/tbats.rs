@@ -0,0 +1,602 @@
+use anyhow::Result;
+
+use std::f64::consts::PI;
+
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+use crate::{estimate, linalg, special, util};
+
+/// Specification of a TBATS model: Box-Cox transform, level with optional (damped)
+/// trend, trigonometric seasonal terms and ARMA errors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TbatsSpec {
+    /// Box-Cox parameter. `None` to model the series untransformed.
+    pub lambda: Option<f64>,
+    /// Whether the model has a trend.
+    pub trend: bool,
+    /// Whether the trend is damped. Ignored without trend.
+    pub damped: bool,
+    /// Seasonal periods together with their number of harmonics `k`. Periods may be
+    /// non-integer (e.g. 365.25) and must satisfy `2 k < period`.
+    pub seasons: Vec<(f64, usize)>,
+    /// Order of the AR part of the error process.
+    pub ar: usize,
+    /// Order of the MA part of the error process.
+    pub ma: usize,
+}
+
+/// Estimated parameters.
+#[derive(Debug, Clone)]
+struct Params {
+    alpha: f64,
+    beta: f64,
+    phi: f64,
+    gamma: Vec<(f64, f64)>,
+    ar: Vec<f64>,
+    ma: Vec<f64>,
+}
+
+/// State of the recursions.
+#[derive(Debug, Clone)]
+struct State {
+    level: f64,
+    trend: f64,
+    // (s, s*) for every harmonic of every season, in order
+    season: Vec<(f64, f64)>,
+    // recent values of the error process d and the innovations e, newest first
+    d: Vec<f64>,
+    e: Vec<f64>,
+}
+
+/// A fitted TBATS model.
+///
+/// On the Box-Cox transformed scale the model is
+/// `y_t = l_{t-1} + phi b_{t-1} + sum_i s^(i)_{t-1} + d_t`, where each seasonal component
+/// `s^(i) = sum_j s_j` is a sum of `k_i` harmonics that rotate by `2 pi j / m_i` per
+/// time step, and `d_t` is an ARMA(p, q) process with innovations `e_t`. The states are
+/// updated with `d_t`: `l_t = l_{t-1} + phi b_{t-1} + alpha d_t`, `b_t = phi b_{t-1} +
+/// beta d_t` and `s_j += gamma1 d_t`, `s*_j += gamma2 d_t` after the rotation.
+#[derive(Debug, Clone)]
+pub struct TbatsModel {
+    /// Model specification.
+    pub spec: TbatsSpec,
+    /// Smoothing parameter of the level.
+    pub alpha: f64,
+    /// Smoothing parameter of the trend. `None` if the model has no trend.
+    pub beta: Option<f64>,
+    /// Damping parameter. `None` if the trend is not damped.
+    pub phi: Option<f64>,
+    /// Smoothing parameters `(gamma1, gamma2)` of each seasonal component.
+    pub gamma: Vec<(f64, f64)>,
+    /// AR coefficients of the error process.
+    pub ar: Vec<f64>,
+    /// MA coefficients of the error process.
+    pub ma: Vec<f64>,
+    /// Innovations on the transformed scale.
+    pub residuals: Vec<f64>,
+    /// One-step-ahead predictions on the original scale.
+    pub fitted: Vec<f64>,
+    /// Variance of the innovations.
+    pub sigma2: f64,
+    /// Gaussian log-likelihood of the original series, including the Jacobian of the
+    /// Box-Cox transform.
+    pub loglik: f64,
+    state: State,
+}
+
+impl TbatsModel {
+    /// Number of parameters: smoothing and ARMA parameters, initial states, the
+    /// variance and the Box-Cox parameter if used.
+    pub fn n_params(&self) -> usize {
+        let spec = &self.spec;
+        let mut k = 1 + 1 + spec.ar + spec.ma + 2 * spec.seasons.len();
+        if spec.trend {
+            k += 2;
+            if spec.damped {
+                k += 1;
+            }
+        }
+        k += spec.seasons.iter().map(|(_, h)| 2 * h).sum::<usize>();
+        if spec.lambda.is_some() {
+            k += 1;
+        }
+        k + 1
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        -2.0 * self.loglik + 2.0 * self.n_params() as f64
+    }
+
+    /// Bayesian information criterion.
+    pub fn bic(&self) -> f64 {
+        -2.0 * self.loglik + (self.residuals.len() as f64).ln() * self.n_params() as f64
+    }
+
+    /// Point forecasts for the next `h` time steps on the original scale. With a Box-Cox
+    /// transform these are forecast medians.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::tbats::{self, TbatsSpec};
+    /// let x: Vec<f64> = (0..60)
+    ///     .map(|t| 10.0 + (2.0 * std::f64::consts::PI * t as f64 / 12.0).sin())
+    ///     .collect();
+    /// let spec = TbatsSpec {
+    ///     lambda: None,
+    ///     trend: false,
+    ///     damped: false,
+    ///     seasons: vec![(12.0, 1)],
+    ///     ar: 0,
+    ///     ma: 0,
+    /// };
+    /// let m = tbats::fit(&x, &spec).unwrap();
+    /// let f = m.forecast(4);
+    /// assert!((f[0] - 10.0).abs() < 1.0e-3);
+    /// assert!((f[3] - 11.0).abs() < 1.0e-3);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        self.original_scale(&self.transformed_forecast(h))
+    }
+
+    /// Variances of the forecast errors for the next `h` time steps on the original
+    /// scale. With a Box-Cox transform these are delta method approximations around the
+    /// forecast medians; use `forecast_intervals` for exact intervals.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::tbats::{self, TbatsSpec};
+    /// let x: Vec<f64> = (0..60)
+    ///     .map(|t| (2.0 * std::f64::consts::PI * t as f64 / 12.0).sin() + (t % 3) as f64)
+    ///     .collect();
+    /// let spec = TbatsSpec {
+    ///     lambda: None,
+    ///     trend: false,
+    ///     damped: false,
+    ///     seasons: vec![(12.0, 1)],
+    ///     ar: 0,
+    ///     ma: 0,
+    /// };
+    /// let m = tbats::fit(&x, &spec).unwrap();
+    /// let v = m.forecast_variance(2);
+    /// assert!((v[0] - m.sigma2).abs() < 1.0e-12);
+    /// assert!(v[1] >= v[0]);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        let var = self.transformed_variance(h);
+        match self.spec.lambda {
+            // derivative of the inverse transform, y^(1 - lambda)
+            Some(lambda) => self
+                .forecast(h)
+                .iter()
+                .zip(var)
+                .map(|(f, v)| v * f.powf(2.0 * (1.0 - lambda)))
+                .collect(),
+            None => var,
+        }
+    }
+
+    /// Normal prediction intervals for the next `h` time steps, computed on the
+    /// transformed scale and transformed back.
+    ///
+    /// # Arguments
+    ///
+    /// * `h` - Forecast horizon.
+    /// * `level` - Coverage probability of the intervals, e.g. 0.95.
+    ///
+    /// # Returns
+    ///
+    /// * Tuple of (lower, upper) bounds for each of the next `h` time steps.
+    pub fn forecast_intervals(&self, h: usize, level: f64) -> Result<(Vec<f64>, Vec<f64>)> {
+        if !(level > 0.0 && level < 1.0) {
+            anyhow::bail!("level must be within (0, 1)");
+        }
+        let z = special::normal_quantile(0.5 + level / 2.0);
+        let (lower, upper): (Vec<f64>, Vec<f64>) = self
+            .transformed_forecast(h)
+            .iter()
+            .zip(self.transformed_variance(h))
+            .map(|(f, v)| (f - z * v.sqrt(), f + z * v.sqrt()))
+            .unzip();
+        Ok((self.original_scale(&lower), self.original_scale(&upper)))
+    }
+
+    /// Point forecasts on the transformed scale.
+    fn transformed_forecast(&self, h: usize) -> Vec<f64> {
+        let p = self.params();
+        let mut state = self.state.clone();
+        let freqs = frequencies(&self.spec);
+        (0..h)
+            .map(|_| {
+                let (mu, arma) = predict(&p, &state);
+                step(&p, &freqs, &mut state, arma, 0.0);
+                mu + arma
+            })
+            .collect()
+    }
+
+    /// Forecast error variances on the transformed scale, `sigma2 sum_j psi_j^2` with
+    /// the responses `psi_j` of the model to a unit innovation.
+    fn transformed_variance(&self, h: usize) -> Vec<f64> {
+        let p = self.params();
+        let freqs = frequencies(&self.spec);
+        let mut state = State {
+            level: 0.0,
+            trend: 0.0,
+            season: vec![(0.0, 0.0); self.state.season.len()],
+            d: vec![0.0; self.state.d.len()],
+            e: vec![0.0; self.state.e.len()],
+        };
+        step(&p, &freqs, &mut state, 1.0, 1.0);
+        let mut var = self.sigma2;
+        let mut result = Vec::with_capacity(h);
+        for _ in 0..h {
+            result.push(var);
+            let (mu, arma) = predict(&p, &state);
+            step(&p, &freqs, &mut state, arma, 0.0);
+            var += self.sigma2 * (mu + arma).powi(2);
+        }
+        result
+    }
+
+    /// Map values from the transformed to the original scale.
+    fn original_scale(&self, y: &[f64]) -> Vec<f64> {
+        match self.spec.lambda {
+            Some(lambda) => util::inv_box_cox(y, lambda),
+            None => y.to_vec(),
+        }
+    }
+
+    fn params(&self) -> Params {
+        Params {
+            alpha: self.alpha,
+            beta: self.beta.unwrap_or(0.0),
+            phi: self.phi.unwrap_or(1.0),
+            gamma: self.gamma.clone(),
+            ar: self.ar.clone(),
+            ma: self.ma.clone(),
+        }
+    }
+}
+
+/// Frequencies `2 pi j / m` of all harmonics, together with the index of their season.
+fn frequencies(spec: &TbatsSpec) -> Vec<(usize, f64)> {
+    let mut freqs = Vec::new();
+    for (i, (m, k)) in spec.seasons.iter().enumerate() {
+        for j in 1..k + 1 {
+            freqs.push((i, 2.0 * PI * j as f64 / m));
+        }
+    }
+    freqs
+}
+
+/// Prediction of the structural part and of the ARMA error process.
+fn predict(p: &Params, state: &State) -> (f64, f64) {
+    let mu = state.level + p.phi * state.trend + state.season.iter().map(|(s, _)| s).sum::<f64>();
+    let arma = p.ar.iter().zip(&state.d).map(|(a, d)| a * d).sum::<f64>()
+        + p.ma.iter().zip(&state.e).map(|(b, e)| b * e).sum::<f64>();
+    (mu, arma)
+}
+
+/// Update the state with the error `d` and innovation `e`.
+fn step(p: &Params, freqs: &[(usize, f64)], state: &mut State, d: f64, e: f64) {
+    state.level += p.phi * state.trend + p.alpha * d;
+    state.trend = p.phi * state.trend + p.beta * d;
+    for ((i, lambda), (s, s_star)) in freqs.iter().zip(state.season.iter_mut()) {
+        let (sin, cos) = lambda.sin_cos();
+        let (g1, g2) = p.gamma[*i];
+        let rotated = *s * cos + *s_star * sin + g1 * d;
+        *s_star = -*s * sin + *s_star * cos + g2 * d;
+        *s = rotated;
+    }
+    if !state.d.is_empty() {
+        state.d.rotate_right(1);
+        state.d[0] = d;
+    }
+    if !state.e.is_empty() {
+        state.e.rotate_right(1);
+        state.e[0] = e;
+    }
+}
+
+/// Initial level, trend and seasonal states from a least squares fit of a linear trend
+/// and the harmonics to the series.
+fn initial_state(y: &[f64], spec: &TbatsSpec, freqs: &[(usize, f64)]) -> Result<State> {
+    let k = 1 + spec.trend as usize + 2 * freqs.len();
+    let mut design = Vec::with_capacity(y.len() * k);
+    for t in 0..y.len() {
+        design.push(1.0);
+        if spec.trend {
+            design.push((t + 1) as f64);
+        }
+        for (_, lambda) in freqs {
+            let (sin, cos) = (lambda * t as f64).sin_cos();
+            design.push(cos);
+            design.push(sin);
+        }
+    }
+    let beta = linalg::lstsq(&design, k, y)?;
+    let offset = 1 + spec.trend as usize;
+    Ok(State {
+        level: beta[0],
+        trend: if spec.trend { beta[1] } else { 0.0 },
+        season: beta[offset..].chunks(2).map(|c| (c[0], c[1])).collect(),
+        d: vec![0.0; spec.ar],
+        e: vec![0.0; spec.ma],
+    })
+}
+
+/// Run the model over the transformed series. Returns predictions, innovations and the
+/// final state, or `None` if the recursions diverge.
+fn run(
+    y: &[f64],
+    p: &Params,
+    freqs: &[(usize, f64)],
+    init: &State,
+) -> Option<(Vec<f64>, Vec<f64>, State)> {
+    let mut state = init.clone();
+    let mut fitted = Vec::with_capacity(y.len());
+    let mut residuals = Vec::with_capacity(y.len());
+    for v in y {
+        let (mu, arma) = predict(p, &state);
+        let d = v - mu;
+        let e = d - arma;
+        if !e.is_finite() {
+            return None;
+        }
+        fitted.push(mu + arma);
+        residuals.push(e);
+        step(p, freqs, &mut state, d, e);
+    }
+    Some((fitted, residuals, state))
+}
+
+/// Map the unconstrained optimization vector to the model parameters. `alpha` and
+/// `beta` are within (0, 1) and `phi` within (0.8, 0.98).
+fn params(z: &[f64], spec: &TbatsSpec) -> Params {
+    let mut z = z.iter().copied();
+    let alpha = util::logistic(z.next().unwrap());
+    let (beta, phi) = if spec.trend {
+        let beta = util::logistic(z.next().unwrap());
+        let phi = if spec.damped {
+            0.8 + 0.18 * util::logistic(z.next().unwrap())
+        } else {
+            1.0
+        };
+        (beta, phi)
+    } else {
+        (0.0, 1.0)
+    };
+    let gamma = spec
+        .seasons
+        .iter()
+        .map(|_| (z.next().unwrap(), z.next().unwrap()))
+        .collect();
+    let ar = (0..spec.ar).map(|_| z.next().unwrap()).collect();
+    let ma = (0..spec.ma).map(|_| z.next().unwrap()).collect();
+    Params {
+        alpha,
+        beta,
+        phi,
+        gamma,
+        ar,
+        ma,
+    }
+}
+
+/// Fit a TBATS model by maximum likelihood. The smoothing and ARMA parameters are
+/// estimated; the initial states are set from a least squares fit of trend and
+/// harmonics, and the Box-Cox parameter is taken from the specification.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Must be positive if a Box-Cox transform is used.
+/// * `&spec` - Model specification.
+///
+/// # Returns
+///
+/// * Fitted model.
+pub fn fit(x: &[f64], spec: &TbatsSpec) -> Result<TbatsModel> {
+    for (m, k) in spec.seasons.iter() {
+        if *k == 0 || 2.0 * *k as f64 >= *m {
+            anyhow::bail!("Number of harmonics must be positive and below half the period");
+        }
+    }
+    let y = match spec.lambda {
+        Some(lambda) => {
+            if x.iter().any(|v| *v <= 0.0) {
+                anyhow::bail!("Box-Cox transform requires positive data");
+            }
+            util::box_cox(x, lambda)
+        }
+        None => x.to_vec(),
+    };
+
+    let freqs = frequencies(spec);
+    // start from small smoothing parameters, alpha = 0.09 and beta = 0.05
+    let mut z = vec![util::logit(0.09)];
+    if spec.trend {
+        z.push(util::logit(0.05));
+        if spec.damped {
+            z.push(1.0);
+        }
+    }
+    z.extend(vec![0.0; 2 * spec.seasons.len() + spec.ar + spec.ma]);
+    if y.len() <= z.len() + 1 + spec.trend as usize + 2 * freqs.len() {
+        anyhow::bail!("Time series is too short for the model");
+    }
+    let init = initial_state(&y, spec, &freqs)?;
+
+    let f = |z: &[f64]| {
+        let p = params(z, spec);
+        match run(&y, &p, &freqs, &init) {
+            Some((_, residuals, _)) => {
+                let sse = residuals.iter().map(|e| e * e).sum::<f64>();
+                if sse.is_finite() {
+                    sse
+                } else {
+                    1.0e10
+                }
+            }
+            // steer the optimizer away from diverging parameters
+            None => 1.0e10,
+        }
+    };
+    estimate::minimize(f, &mut z);
+
+    let p = params(&z, spec);
+    let (fitted, residuals, state) = match run(&y, &p, &freqs, &init) {
+        Some(r) => r,
+        None => anyhow::bail!("TBATS recursions diverged"),
+    };
+
+    let n = y.len() as f64;
+    let sigma2 = residuals.iter().map(|e| e * e).sum::<f64>() / n;
+    let mut loglik = -0.5 * n * ((2.0 * PI * sigma2).ln() + 1.0);
+    let fitted = match spec.lambda {
+        Some(lambda) => {
+            loglik += (lambda - 1.0) * x.iter().map(|v| v.ln()).sum::<f64>();
+            util::inv_box_cox(&fitted, lambda)
+        }
+        None => fitted,
+    };
+
+    Ok(TbatsModel {
+        spec: spec.clone(),
+        alpha: p.alpha,
+        beta: spec.trend.then_some(p.beta),
+        phi: (spec.trend && spec.damped).then_some(p.phi),
+        gamma: p.gamma,
+        ar: p.ar,
+        ma: p.ma,
+        residuals,
+        fitted,
+        sigma2,
+        loglik,
+        state,
+    })
+}
+
+/// Select a TBATS model by AIC.
+///
+/// The number of harmonics of each season is increased one at a time as long as the
+/// AIC improves. The Box-Cox transform (none, log, square root; only for positive data)
+/// and the trend (none, additive, damped) are chosen together with the harmonics, and
+/// ARMA(1, 0), ARMA(0, 1) and ARMA(1, 1) errors are tried on the best model.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries.
+/// * `&periods` - Seasonal periods, each larger than 2.
+///
+/// # Returns
+///
+/// * Best fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::tbats;
+/// let x: Vec<f64> = (0..96)
+///     .map(|t| {
+///         let t = t as f64;
+///         20.0 + 3.0 * (2.0 * std::f64::consts::PI * t / 24.0).sin()
+///             + (2.0 * std::f64::consts::PI * t / 7.5).cos()
+///     })
+///     .collect();
+/// let m = tbats::auto_tbats(&x, &[7.5, 24.0]).unwrap();
+/// assert_eq!(m.spec.seasons.len(), 2);
+/// ```
+pub fn auto_tbats(x: &[f64], periods: &[f64]) -> Result<TbatsModel> {
+    for m in periods {
+        if *m <= 2.0 || m.is_nan() {
+            anyhow::bail!("Seasonal periods must be larger than 2");
+        }
+    }
+    let mut lambdas = vec![None];
+    if x.iter().all(|v| *v > 0.0) {
+        lambdas.push(Some(0.0));
+        lambdas.push(Some(0.5));
+    }
+
+    // AIC of a fit, infinite if it failed
+    let aic = |model: &Result<TbatsModel>| match model {
+        Ok(m) => m.aic(),
+        Err(_) => f64::INFINITY,
+    };
+
+    let mut best: Result<TbatsModel> = Err(anyhow::anyhow!("No TBATS model could be fitted"));
+    for lambda in lambdas {
+        for &(trend, damped) in [(false, false), (true, false), (true, true)].iter() {
+            let mut spec = TbatsSpec {
+                lambda,
+                trend,
+                damped,
+                seasons: periods.iter().map(|m| (*m, 1)).collect(),
+                ar: 0,
+                ma: 0,
+            };
+            let mut current = fit(x, &spec);
+            // greedily add harmonics while the AIC improves
+            for i in 0..periods.len() {
+                loop {
+                    let (m, k) = spec.seasons[i];
+                    if 2.0 * (k + 1) as f64 >= m {
+                        break;
+                    }
+                    let mut candidate_spec = spec.clone();
+                    candidate_spec.seasons[i].1 = k + 1;
+                    let candidate = fit(x, &candidate_spec);
+                    if aic(&candidate) < aic(&current) {
+                        spec = candidate_spec;
+                        current = candidate;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            if aic(&current) < aic(&best) {
+                best = current;
+            }
+        }
+    }
+
+    let base = best
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("{}", e))?
+        .spec
+        .clone();
+    for &(ar, ma) in [(1, 0), (0, 1), (1, 1)].iter() {
+        let spec = TbatsSpec {
+            ar,
+            ma,
+            ..base.clone()
+        };
+        let candidate = fit(x, &spec);
+        if aic(&candidate) < aic(&best) {
+            best = candidate;
+        }
+    }
+    best
+}
+
+/// Same as `auto_tbats`, with the seasonal periods implied by the sampling frequency of
+/// the series that it covers at least twice, see `TimeSeries::seasonal_periods`, e.g. 24
+/// and 168 for two months of hourly data. Fails if the series has gaps. Only enabled
+/// with feature `chrono`.
+#[cfg(feature = "chrono")]
+pub fn auto_tbats_series(x: &TimeSeries<f64>) -> Result<TbatsModel> {
+    let values = x.regular_values()?;
+    auto_tbats(values, &covered_periods(x))
+}
+
+/// Seasonal periods of `x` larger than 2 that the series covers at least twice.
+#[cfg(feature = "chrono")]
+pub(crate) fn covered_periods(x: &TimeSeries<f64>) -> Vec<f64> {
+    x.seasonal_periods()
+        .into_iter()
+        .filter(|m| *m > 2.0 && 2.0 * m <= x.len() as f64)
+        .collect()
+}
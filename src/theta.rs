@@ -0,0 +1,171 @@
+use anyhow::Result;
+
+use crate::{estimate, util};
+
+/// A fitted Theta model.
+///
+/// The model follows the state space formulation of the optimised Theta method
+/// (Fiorucci et al., 2016):
+/// `mu_t = l_{t-1} + (1 - 1/theta) ((1 - alpha)^(t-1) a + (1 - (1 - alpha)^t) / alpha b)`,
+/// `l_t = alpha y_t + (1 - alpha) l_{t-1}`, where `a` and `b` are intercept and slope
+/// of a linear regression of the series on time. With `theta = 2` this is the classic
+/// Theta method of Assimakopoulos and Nikolopoulos, i.e. simple exponential smoothing
+/// with a drift of half the regression slope.
+#[derive(Debug, Clone)]
+pub struct ThetaModel {
+    /// Smoothing parameter of the level.
+    pub alpha: f64,
+    /// Theta coefficient, at least 1. With `theta = 1` the model reduces to SES.
+    pub theta: f64,
+    /// Initial level.
+    pub l0: f64,
+    /// Level at the end of the series.
+    pub level: f64,
+    /// Intercept of the linear trend line (at `t = 0`).
+    pub a: f64,
+    /// Slope of the linear trend line, i.e. the drift.
+    pub b: f64,
+    /// One-step-ahead predictions.
+    pub fitted: Vec<f64>,
+    /// One-step-ahead errors.
+    pub residuals: Vec<f64>,
+    /// Mean squared one-step-ahead error.
+    pub sigma2: f64,
+}
+
+impl ThetaModel {
+    /// Forecast the next `h` values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::theta;
+    /// let x: Vec<f64> = (0..30).map(|i| 5.0 + 0.5 * i as f64).collect();
+    /// let m = theta::fit(&x, None).unwrap();
+    /// let f = m.forecast(2);
+    /// assert!((f[0] - 20.0).abs() < 0.1);
+    /// assert!((f[1] - 20.5).abs() < 0.1);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<f64> {
+        let n = self.fitted.len() as f64;
+        let decay = 1.0 - self.alpha;
+        let weight = 1.0 - 1.0 / self.theta;
+        (1..h + 1)
+            .map(|i| {
+                let drift = (i - 1) as f64 + (1.0 - decay.powf(n + 1.0)) / self.alpha;
+                self.level + weight * (decay.powf(n) * self.a + drift * self.b)
+            })
+            .collect()
+    }
+
+    /// Variances of the forecast errors for the next `h` time steps. As in simple
+    /// exponential smoothing, every error moves all later forecasts by `alpha` times
+    /// its size, so the variance grows by `alpha^2 sigma2` per step.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::theta;
+    /// let x = [10.0, 12.0, 11.0, 13.0, 14.0, 13.5, 15.0, 16.0, 15.5, 17.0];
+    /// let m = theta::fit(&x, Some(2.0)).unwrap();
+    /// let v = m.forecast_variance(2);
+    /// assert_eq!(v[0], m.sigma2);
+    /// assert!((v[1] - m.sigma2 * (1.0 + m.alpha * m.alpha)).abs() < 1.0e-12);
+    /// ```
+    pub fn forecast_variance(&self, h: usize) -> Vec<f64> {
+        (0..h)
+            .map(|i| self.sigma2 * (1.0 + i as f64 * self.alpha * self.alpha))
+            .collect()
+    }
+}
+
+/// Run the recursions. Returns the fitted values and the final level.
+fn run(x: &[f64], alpha: f64, theta: f64, l0: f64, a: f64, b: f64) -> (Vec<f64>, f64) {
+    let weight = 1.0 - 1.0 / theta;
+    let decay = 1.0 - alpha;
+    let mut level = l0;
+    // (1 - alpha)^(t-1)
+    let mut decay_t = 1.0;
+    let fitted = x
+        .iter()
+        .map(|y| {
+            let mu = level + weight * (decay_t * a + (1.0 - decay_t * decay) / alpha * b);
+            level = alpha * y + decay * level;
+            decay_t *= decay;
+            mu
+        })
+        .collect();
+    (fitted, level)
+}
+
+/// Fit a Theta model by minimizing the mean squared one-step-ahead error over the
+/// smoothing parameter, the initial level and, if not given, theta.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least three values.
+/// * `theta` - Theta coefficient, at least 1. Estimated if `None`; use `Some(2.0)` for
+///   the classic Theta method.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::theta;
+/// let x = [10.0, 12.0, 11.0, 13.0, 14.0, 13.5, 15.0, 16.0, 15.5, 17.0];
+/// let m = theta::fit(&x, Some(2.0)).unwrap();
+/// assert_eq!(m.theta, 2.0);
+/// assert!(m.b > 0.0);
+/// ```
+pub fn fit(x: &[f64], theta: Option<f64>) -> Result<ThetaModel> {
+    if x.len() < 3 {
+        anyhow::bail!("Need at least three values");
+    }
+    if let Some(theta) = theta {
+        if theta < 1.0 || theta.is_nan() {
+            anyhow::bail!("theta must be at least 1");
+        }
+    }
+    let (a, b) = util::trend_line(x);
+
+    // alpha on the logit scale, the initial level, and theta - 1 on the log scale
+    let mut z = vec![0.0, x[0]];
+    if theta.is_none() {
+        z.push(0.0);
+    }
+    let params = |z: &[f64]| {
+        let alpha = util::logistic(z[0]).clamp(1.0e-6, 1.0 - 1.0e-6);
+        let theta = theta.unwrap_or_else(|| 1.0 + z[2].exp());
+        (alpha, theta, z[1])
+    };
+
+    let f = |z: &[f64]| {
+        let (alpha, theta, l0) = params(z);
+        let (fitted, _) = run(x, alpha, theta, l0, a, b);
+        x.iter()
+            .zip(&fitted)
+            .map(|(y, mu)| (y - mu) * (y - mu))
+            .sum::<f64>()
+    };
+    estimate::minimize(f, &mut z);
+
+    let (alpha, theta, l0) = params(&z);
+    let (fitted, level) = run(x, alpha, theta, l0, a, b);
+    let residuals: Vec<f64> = x.iter().zip(&fitted).map(|(y, mu)| y - mu).collect();
+    let sigma2 = residuals.iter().map(|e| e * e).sum::<f64>() / x.len() as f64;
+
+    Ok(ThetaModel {
+        alpha,
+        theta,
+        l0,
+        level,
+        a,
+        b,
+        fitted,
+        residuals,
+        sigma2,
+    })
+}
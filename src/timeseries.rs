@@ -0,0 +1,576 @@
+//! Time series with a timestamp index and a sampling frequency. Only enabled with
+//! feature `chrono`.
+//!
+//! `TimeSeries` dereferences to a slice of its values, so it can be passed to the
+//! functions of this crate that take a slice of values, such as `acf::acf` or
+//! `model::fit`. These see the values only: gaps are not accounted for (see `fill_gaps`)
+//! and their results are not dated, which is left to the index of the series, e.g.
+//! `forecast_index` for the dates of forecasts, and to `align` for series with
+//! different timestamps. Multivariate functions taking several series, such as
+//! `varma::fit`, need the aligned values collected first.
+//!
+//! The frequency can be inferred from the timestamps with `infer_frequency`, and implies
+//! the seasonal periods of the seasonal models, see `Frequency::seasonal_period`. Their
+//! entry points ending in `_series`, e.g. `ets::auto_ets_series` or
+//! `x11::adjust_series`, take a `TimeSeries`, check that it has no gaps and use these
+//! periods.
+
+use anyhow::Result;
+
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+use chrono::{Datelike, Months, NaiveDateTime, TimeDelta};
+
+/// Sampling frequency of a time series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    /// A fixed duration between observations, e.g. one hour or one week.
+    Fixed(TimeDelta),
+    /// A number of calendar months between observations. Days beyond the end of a
+    /// month are clamped to its last day.
+    Months(u32),
+}
+
+impl Frequency {
+    pub fn hourly() -> Self {
+        Frequency::Fixed(TimeDelta::hours(1))
+    }
+
+    pub fn daily() -> Self {
+        Frequency::Fixed(TimeDelta::days(1))
+    }
+
+    pub fn weekly() -> Self {
+        Frequency::Fixed(TimeDelta::weeks(1))
+    }
+
+    pub fn monthly() -> Self {
+        Frequency::Months(1)
+    }
+
+    pub fn quarterly() -> Self {
+        Frequency::Months(3)
+    }
+
+    pub fn yearly() -> Self {
+        Frequency::Months(12)
+    }
+
+    /// Check that the frequency moves forward in time: a positive duration that fits in
+    /// `i64` nanoseconds, or a positive number of months. Checked by the constructors of
+    /// `TimeSeries`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::timeseries::Frequency;
+    /// use chrono::TimeDelta;
+    /// assert!(Frequency::daily().validate().is_ok());
+    /// assert!(Frequency::Fixed(TimeDelta::zero()).validate().is_err());
+    /// assert!(Frequency::Months(0).validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let positive = match *self {
+            Frequency::Fixed(delta) => matches!(delta.num_nanoseconds(), Some(n) if n > 0),
+            Frequency::Months(m) => m > 0,
+        };
+        if !positive {
+            anyhow::bail!("Frequency {:?} must be a positive step", self);
+        }
+        Ok(())
+    }
+
+    /// Seasonal periods implied by the frequency, in observations and in increasing
+    /// order. For fixed frequencies, these are the cycles of an hour, a day and a week that
+    /// are whole multiples of at least two steps, and the year of 365.25 days for steps of
+    /// at most half a year (e.g. 7 and 365.25 for daily data). For calendar months, it is
+    /// the year if it is a whole multiple of at least two steps (e.g. 12 for monthly and 4
+    /// for quarterly data). For models with multiple seasonal periods such as
+    /// `tbats::auto_tbats`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::timeseries::Frequency;
+    /// assert_eq!(Frequency::hourly().seasonal_periods(), vec![24.0, 168.0, 8766.0]);
+    /// assert_eq!(Frequency::quarterly().seasonal_periods(), vec![4.0]);
+    /// assert!(Frequency::yearly().seasonal_periods().is_empty());
+    /// ```
+    pub fn seasonal_periods(&self) -> Vec<f64> {
+        match *self {
+            Frequency::Fixed(delta) => {
+                let step = match delta.num_nanoseconds() {
+                    Some(step) if step > 0 => step,
+                    _ => return Vec::new(),
+                };
+                let mut periods: Vec<f64> =
+                    [TimeDelta::hours(1), TimeDelta::days(1), TimeDelta::weeks(1)]
+                        .iter()
+                        .map(|cycle| cycle.num_nanoseconds().unwrap())
+                        .filter(|cycle| *cycle > step && cycle % step == 0)
+                        .map(|cycle| (cycle / step) as f64)
+                        .collect();
+                let year = 365.25 * TimeDelta::days(1).num_nanoseconds().unwrap() as f64;
+                if year >= 2.0 * step as f64 {
+                    periods.push(year / step as f64);
+                }
+                periods
+            }
+            Frequency::Months(m) => {
+                if m > 0 && m < 12 && 12 % m == 0 {
+                    vec![(12 / m) as f64]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    /// The shortest seasonal period of `seasonal_periods`, rounded to whole observations,
+    /// or 1 if there is none, e.g. 7 for daily and 52 for weekly data. For models with
+    /// one seasonal period such as `ets::auto_ets` and `x11::adjust`.
+    pub fn seasonal_period(&self) -> usize {
+        self.seasonal_periods()
+            .first()
+            .map_or(1, |p| p.round() as usize)
+    }
+
+    /// Timestamp `k` steps after `start`.
+    pub fn step(&self, start: NaiveDateTime, k: usize) -> Option<NaiveDateTime> {
+        match *self {
+            Frequency::Fixed(delta) => start.checked_add_signed(delta * i32::try_from(k).ok()?),
+            Frequency::Months(m) => {
+                start.checked_add_months(Months::new(m.checked_mul(u32::try_from(k).ok()?)?))
+            }
+        }
+    }
+
+    /// Number of steps from `start` to `t`, or `None` if `t` is not on the grid of
+    /// timestamps starting at `start`.
+    pub fn steps_between(&self, start: NaiveDateTime, t: NaiveDateTime) -> Option<usize> {
+        if t < start {
+            return None;
+        }
+        let k = match *self {
+            Frequency::Fixed(delta) => {
+                let span = (t - start).num_nanoseconds()?;
+                let step = delta.num_nanoseconds()?;
+                if step <= 0 || span % step != 0 {
+                    return None;
+                }
+                (span / step) as usize
+            }
+            Frequency::Months(m) => {
+                if m == 0 {
+                    return None;
+                }
+                let months =
+                    (t.year() - start.year()) * 12 + t.month() as i32 - start.month() as i32;
+                months as usize / m as usize
+            }
+        };
+        if self.step(start, k)? == t {
+            Some(k)
+        } else {
+            None
+        }
+    }
+}
+
+/// A run of missing timestamps in the index of a `TimeSeries`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// Position of the last observation before the gap.
+    pub after: usize,
+    /// Number of missing timestamps.
+    pub missing: usize,
+}
+
+/// Result of `infer_frequency`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrequencyInference {
+    /// Inferred sampling frequency, the most common step between the timestamps.
+    pub frequency: Frequency,
+    /// Runs of missing timestamps, with positions in the given index.
+    pub gaps: Vec<Gap>,
+    /// Positions of the timestamps that are not on the grid of the frequency starting
+    /// at the first timestamp.
+    pub off_grid: Vec<usize>,
+}
+
+impl FrequencyInference {
+    /// True if the timestamps have neither gaps nor off-grid timestamps.
+    pub fn is_regular(&self) -> bool {
+        self.gaps.is_empty() && self.off_grid.is_empty()
+    }
+}
+
+/// Infer the sampling frequency of strictly increasing timestamps.
+///
+/// Both the most common fixed duration between consecutive timestamps and the most
+/// common number of calendar months between them are considered; the one for which more
+/// consecutive timestamps are exactly one step apart is chosen, calendar months on ties.
+/// Timestamps more than one step apart are reported as gaps and timestamps between the
+/// steps as off-grid, with the first timestamp anchoring the grid.
+///
+/// # Arguments
+///
+/// * `&index` - Timestamps, at least two.
+///
+/// # Returns
+///
+/// * Frequency with the gaps and off-grid timestamps relative to it.
+///
+/// # Example
+///
+/// ```
+/// use arima::timeseries::{self, Frequency};
+/// use chrono::NaiveDate;
+/// let month = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+/// // October is missing
+/// let index = vec![month(2024, 1), month(2024, 4), month(2024, 7), month(2025, 1)];
+/// let f = timeseries::infer_frequency(&index).unwrap();
+/// assert_eq!(f.frequency, Frequency::quarterly());
+/// assert_eq!(f.frequency.seasonal_period(), 4);
+/// assert_eq!(f.gaps.len(), 1);
+/// assert!(f.off_grid.is_empty());
+/// ```
+pub fn infer_frequency(index: &[NaiveDateTime]) -> Result<FrequencyInference> {
+    if index.len() < 2 {
+        anyhow::bail!("Need at least two timestamps to infer a frequency");
+    }
+    if let Some(w) = index.windows(2).find(|w| w[1] <= w[0]) {
+        anyhow::bail!("Timestamps must be strictly increasing at {}", w[1]);
+    }
+    let start = index[0];
+    let fixed = Frequency::Fixed(mode(index.windows(2).map(|w| w[1] - w[0])));
+    let months = mode(
+        index
+            .windows(2)
+            .map(|w| (w[1].year() - w[0].year()) * 12 + w[1].month() as i32 - w[0].month() as i32),
+    );
+    let mut frequency = fixed;
+    if months > 0 {
+        let calendar = Frequency::Months(months as u32);
+        if unit_steps(&calendar, index) >= unit_steps(&fixed, index) {
+            frequency = calendar;
+        }
+    }
+
+    let mut gaps = Vec::new();
+    let mut off_grid = Vec::new();
+    let mut last: Option<(usize, usize)> = None;
+    for (i, t) in index.iter().enumerate() {
+        match frequency.steps_between(start, *t) {
+            Some(k) => {
+                if let Some((after, l)) = last {
+                    if k > l + 1 {
+                        gaps.push(Gap {
+                            after,
+                            missing: k - l - 1,
+                        });
+                    }
+                }
+                last = Some((i, k));
+            }
+            None => off_grid.push(i),
+        }
+    }
+    Ok(FrequencyInference {
+        frequency,
+        gaps,
+        off_grid,
+    })
+}
+
+/// Number of consecutive timestamps exactly one step apart on the grid of `frequency`.
+fn unit_steps(frequency: &Frequency, index: &[NaiveDateTime]) -> usize {
+    let steps: Vec<Option<usize>> = index
+        .iter()
+        .map(|t| frequency.steps_between(index[0], *t))
+        .collect();
+    steps
+        .windows(2)
+        .filter(|w| matches!(w, [Some(a), Some(b)] if *b == a + 1))
+        .count()
+}
+
+/// Most common value, the smallest one on ties.
+fn mode<T: Ord + Copy, I: Iterator<Item = T>>(values: I) -> T {
+    let mut values: Vec<T> = values.collect();
+    values.sort();
+    let mut best = (values[0], 0);
+    let mut run = 0;
+    for (i, v) in values.iter().enumerate() {
+        run = if i > 0 && values[i - 1] == *v {
+            run + 1
+        } else {
+            1
+        };
+        if run > best.1 {
+            best = (*v, run);
+        }
+    }
+    best.0
+}
+
+/// Values with strictly increasing timestamps on the grid of a sampling frequency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeSeries<T> {
+    values: Vec<T>,
+    index: Vec<NaiveDateTime>,
+    frequency: Frequency,
+}
+
+impl<T> TimeSeries<T> {
+    /// A regular series starting at `start`, without gaps.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::{acf, timeseries::{Frequency, TimeSeries}};
+    /// use chrono::NaiveDate;
+    /// let start = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let x = TimeSeries::new(vec![1.0, 1.2, 1.4, 1.6], start, Frequency::monthly()).unwrap();
+    /// assert_eq!(x.index()[1].date(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    /// assert_eq!(x.index()[2].date(), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+    /// // the series is accepted where a slice is expected
+    /// let ac: Vec<f64> = acf::acf(&x, Some(2), false).unwrap();
+    /// assert!((ac[1] - 0.25).abs() < 1.0e-7);
+    /// ```
+    pub fn new(values: Vec<T>, start: NaiveDateTime, frequency: Frequency) -> Result<Self> {
+        frequency.validate()?;
+        let index = (0..values.len())
+            .map(|k| frequency.step(start, k))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow::anyhow!("Timestamps out of range"))?;
+        Ok(TimeSeries {
+            values,
+            index,
+            frequency,
+        })
+    }
+
+    /// A series with the given timestamps, which must be strictly increasing and lie on
+    /// the grid of `frequency` starting at the first timestamp. Missing timestamps are
+    /// allowed and reported by `gaps`.
+    pub fn from_timestamps(
+        values: Vec<T>,
+        index: Vec<NaiveDateTime>,
+        frequency: Frequency,
+    ) -> Result<Self> {
+        frequency.validate()?;
+        if values.len() != index.len() {
+            anyhow::bail!("Got {} values but {} timestamps", values.len(), index.len());
+        }
+        let mut last = None;
+        for t in &index {
+            let k = match frequency.steps_between(index[0], *t) {
+                Some(k) => k,
+                None => anyhow::bail!("Timestamp {} is not on the grid of {:?}", t, frequency),
+            };
+            if let Some(last) = last {
+                if k <= last {
+                    anyhow::bail!("Timestamps must be strictly increasing at {}", t);
+                }
+            }
+            last = Some(k);
+        }
+        Ok(TimeSeries {
+            values,
+            index,
+            frequency,
+        })
+    }
+
+    /// A series with the given timestamps and the frequency inferred from them, see
+    /// `infer_frequency`. Fails if a timestamp is off the grid of the inferred
+    /// frequency; gaps are allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::{ets, timeseries::TimeSeries};
+    /// use chrono::{NaiveDate, TimeDelta};
+    /// let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let index: Vec<_> = (0..42).map(|d| start + TimeDelta::days(d)).collect();
+    /// let values: Vec<f64> = (0..42).map(|d| 10.0 + [3.0, 1.0, 0.0, 0.0, 1.0, -2.0, -3.0][d % 7]).collect();
+    /// let x = TimeSeries::from_timestamps_inferred(values, index).unwrap();
+    /// // weekly seasonality of daily data
+    /// let m = ets::auto_ets_series(&x).unwrap();
+    /// assert_eq!(m.period, 7);
+    /// ```
+    pub fn from_timestamps_inferred(values: Vec<T>, index: Vec<NaiveDateTime>) -> Result<Self> {
+        let inferred = infer_frequency(&index)?;
+        if let Some(i) = inferred.off_grid.first() {
+            anyhow::bail!(
+                "{} timestamps are not on the grid of the inferred frequency {:?}, the first is {}",
+                inferred.off_grid.len(),
+                inferred.frequency,
+                index[*i]
+            );
+        }
+        Self::from_timestamps(values, index, inferred.frequency)
+    }
+
+    /// Values of the series.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Timestamps of the values.
+    pub fn index(&self) -> &[NaiveDateTime] {
+        &self.index
+    }
+
+    /// Sampling frequency.
+    pub fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+
+    /// Values of a series without gaps, for models that need equally spaced observations.
+    /// Fails if timestamps are missing, see `gaps` and `fill_gaps`.
+    pub fn regular_values(&self) -> Result<&[T]> {
+        if let Some(gap) = self.gaps().first() {
+            anyhow::bail!(
+                "{} timestamps are missing after {}, fill the gaps first",
+                gap.missing,
+                self.index[gap.after]
+            );
+        }
+        Ok(&self.values)
+    }
+
+    /// Seasonal periods implied by the frequency, see `Frequency::seasonal_periods`.
+    pub fn seasonal_periods(&self) -> Vec<f64> {
+        self.frequency.seasonal_periods()
+    }
+
+    /// Seasonal period implied by the frequency, see `Frequency::seasonal_period`.
+    pub fn seasonal_period(&self) -> usize {
+        self.frequency.seasonal_period()
+    }
+
+    /// Consume the series and return its values.
+    pub fn into_values(self) -> Vec<T> {
+        self.values
+    }
+
+    /// Runs of missing timestamps.
+    pub fn gaps(&self) -> Vec<Gap> {
+        let steps: Vec<usize> = self.steps();
+        steps
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[1] - w[0] > 1)
+            .map(|(i, w)| Gap {
+                after: i,
+                missing: w[1] - w[0] - 1,
+            })
+            .collect()
+    }
+
+    /// Timestamps of the `h` steps after the end of the series, e.g. to date forecasts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::timeseries::{Frequency, TimeSeries};
+    /// use chrono::NaiveDate;
+    /// let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let x = TimeSeries::new(vec![1.0, 2.0, 3.0], start, Frequency::quarterly()).unwrap();
+    /// let dates = x.forecast_index(2);
+    /// assert_eq!(dates[0].date(), NaiveDate::from_ymd_opt(2024, 10, 1).unwrap());
+    /// assert_eq!(dates[1].date(), NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    /// ```
+    pub fn forecast_index(&self, h: usize) -> Vec<NaiveDateTime> {
+        let start = match self.index.first() {
+            Some(start) => *start,
+            None => return Vec::new(),
+        };
+        let last = *self.steps().last().unwrap();
+        (1..h + 1)
+            .filter_map(|k| self.frequency.step(start, last + k))
+            .collect()
+    }
+
+    /// Step numbers of the timestamps relative to the first one.
+    fn steps(&self) -> Vec<usize> {
+        self.index
+            .iter()
+            .map(|t| self.frequency.steps_between(self.index[0], *t).unwrap())
+            .collect()
+    }
+}
+
+impl<T: Copy> TimeSeries<T> {
+    /// The values at the timestamps present in both series, in the order of time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::timeseries::{Frequency, TimeSeries};
+    /// use chrono::NaiveDate;
+    /// let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    /// let x = TimeSeries::new(vec![1.0, 2.0, 3.0], day(1), Frequency::daily()).unwrap();
+    /// let y = TimeSeries::new(vec![10.0, 20.0, 30.0], day(2), Frequency::daily()).unwrap();
+    /// let (a, b) = x.align(&y).unwrap();
+    /// assert_eq!(a.values(), &[2.0, 3.0]);
+    /// assert_eq!(b.values(), &[10.0, 20.0]);
+    /// ```
+    pub fn align<U: Copy>(&self, other: &TimeSeries<U>) -> Result<(Self, TimeSeries<U>)> {
+        if self.frequency != other.frequency {
+            anyhow::bail!("Series have different frequencies");
+        }
+        let (mut i, mut j) = (0, 0);
+        let mut index = Vec::new();
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        while i < self.index.len() && j < other.index.len() {
+            match self.index[i].cmp(&other.index[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    index.push(self.index[i]);
+                    a.push(self.values[i]);
+                    b.push(other.values[j]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        Ok((
+            TimeSeries::from_timestamps(a, index.clone(), self.frequency)?,
+            TimeSeries::from_timestamps(b, index, self.frequency)?,
+        ))
+    }
+}
+
+impl<T: num::Float> TimeSeries<T> {
+    /// A regular series without gaps, with NaN at the missing timestamps.
+    pub fn fill_gaps(&self) -> Self {
+        let steps = self.steps();
+        let n = steps.last().map_or(0, |k| k + 1);
+        let mut values = vec![T::nan(); n];
+        for (k, v) in steps.iter().zip(&self.values) {
+            values[*k] = *v;
+        }
+        let index = (0..n)
+            .map(|k| self.frequency.step(self.index[0], k).unwrap())
+            .collect();
+        TimeSeries {
+            values,
+            index,
+            frequency: self.frequency,
+        }
+    }
+}
+
+impl<T> Deref for TimeSeries<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.values
+    }
+}
@@ -1,6 +1,7 @@
-use core::ops::{Neg, Sub};
-use num::{Float, Num};
-use std::ops::{Add, AddAssign};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Add, AddAssign, Neg, Sub};
+use num::{Float, Num, NumCast};
 
 /// Returns a n-tau vector containing the time series lagged by tau.
 ///
@@ -206,3 +207,320 @@ pub fn center<T: Num + Copy + Add + AddAssign + Copy + From<i32>>(x: &[T]) -> (V
     let m = mean(x);
     (x.iter().map(|&x| x - m).collect(), m)
 }
+
+/// Calculate the mean of the values produced by an iterator in a single pass, without
+/// collecting them first.
+///
+/// # Arguments
+///
+/// * `x` - Values to calculate the mean for.
+///
+/// # Returns
+///
+/// * The mean sum(x)/n, or `None` if the iterator is empty or the count n is not
+///   representable in `T`, e.g. more than 127 values of type `i8`. Integer sums can
+///   overflow like any integer addition.
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let y = util::mean_iter((2..7).map(|v| v as f64));
+/// assert_eq!(y, Some(4.0));
+/// assert_eq!(util::mean_iter(Vec::<i32>::new()), None);
+/// ```
+pub fn mean_iter<T: Num + NumCast + Copy, I: IntoIterator<Item = T>>(x: I) -> Option<T> {
+    let (sum, n) = x
+        .into_iter()
+        .fold((T::zero(), 0_usize), |(sum, n), item| (sum + item, n + 1));
+    if n == 0 {
+        return None;
+    }
+    T::from(n).map(|n| sum / n)
+}
+
+/// Calculate mean and variance of the values produced by an iterator in a single pass
+/// (Welford's algorithm). The variance is normalized by n, consistent with the lag zero
+/// auto-covariance returned by `acf::acf`.
+///
+/// # Arguments
+///
+/// * `x` - Values to calculate mean and variance for.
+///
+/// # Returns
+///
+/// * Tuple of (mean, variance). Both are NaN if the iterator is empty.
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let (m, v) = util::mean_var_iter([2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+/// assert_eq!(m, 5.0);
+/// assert_eq!(v, 4.0);
+/// ```
+pub fn mean_var_iter<T: Float, I: IntoIterator<Item = T>>(x: I) -> (T, T) {
+    let mut n = T::zero();
+    let mut mean = T::zero();
+    let mut m2 = T::zero();
+    for item in x {
+        n = n + T::one();
+        let delta = item - mean;
+        mean = mean + delta / n;
+        m2 = m2 + delta * (item - mean);
+    }
+    if n == T::zero() {
+        return (T::nan(), T::nan());
+    }
+    (mean, m2 / n)
+}
+
+/// Iterator adapter returned by `diff_iter`.
+#[derive(Debug, Clone)]
+pub struct DiffIter<I, T> {
+    iter: I,
+    d: usize,
+    // last value of the series differenced k times, for k in 0..d
+    last: Vec<T>,
+}
+
+impl<T: Num + Copy, I: Iterator<Item = T>> Iterator for DiffIter<I, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            let mut v = self.iter.next()?;
+            let mut complete = true;
+            for k in 0..self.d {
+                if k == self.last.len() {
+                    // not enough values yet to build the (k+1)-th difference
+                    self.last.push(v);
+                    complete = false;
+                    break;
+                }
+                let next = v - self.last[k];
+                self.last[k] = v;
+                v = next;
+            }
+            if complete {
+                return Some(v);
+            }
+        }
+    }
+}
+
+/// Lazily difference the values of an iterator. Yields the same values as `diff`,
+/// but only keeps the last `d` values in memory.
+///
+/// # Arguments
+///
+/// * `x` - Values to be differenced.
+/// * `d` - Number of differences to be taken.
+///
+/// # Returns
+///
+/// * Iterator over the differenced values.
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let x = [-4, -9, 20, 23, -18, 6];
+/// let y: Vec<i32> = util::diff_iter(x.iter().copied(), 2).collect();
+/// assert_eq!(y, util::diff(&x, 2));
+/// ```
+pub fn diff_iter<T: Num + Copy, I: IntoIterator<Item = T>>(
+    x: I,
+    d: usize,
+) -> DiffIter<I::IntoIter, T> {
+    DiffIter {
+        iter: x.into_iter(),
+        d,
+        last: Vec::with_capacity(d),
+    }
+}
+
+/// Calculate the empirical `p`-quantile of a sample, interpolating linearly between
+/// order statistics (type 7 in the classification of Hyndman and Fan).
+///
+/// # Arguments
+///
+/// * `&x` - Sample values. NaN values are sorted last.
+/// * `p` - Probability in [0, 1].
+///
+/// # Returns
+///
+/// * The quantile. NaN if `x` is empty.
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let x = [4.0, 1.0, 3.0, 2.0];
+/// assert_eq!(util::quantile(&x, 0.5), 2.5);
+/// assert_eq!(util::quantile(&x, 1.0), 4.0);
+/// ```
+pub fn quantile<T: Float>(x: &[T], p: T) -> T {
+    if x.is_empty() {
+        return T::nan();
+    }
+    let mut sorted = x.to_vec();
+    sorted.sort_by(|a, b| {
+        a.partial_cmp(b)
+            .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+    });
+    let h = p * T::from(sorted.len() - 1).unwrap();
+    let lo = h.floor().to_usize().unwrap().min(sorted.len() - 1);
+    let hi = h.ceil().to_usize().unwrap().min(sorted.len() - 1);
+    sorted[lo] + (h - T::from(lo).unwrap()) * (sorted[hi] - sorted[lo])
+}
+
+/// Box-Cox transform `(x^lambda - 1) / lambda`, or `ln(x)` for `lambda = 0`.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of positive values.
+/// * `lambda` - Transformation parameter.
+///
+/// # Returns
+///
+/// * Transformed vector. Non-positive values map to NaN unless `lambda = 1`.
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let x = [1.0_f64, 4.0, 9.0];
+/// let y = util::box_cox(&x, 0.5);
+/// assert_eq!(y, [0.0, 2.0, 4.0]);
+/// assert_eq!(util::inv_box_cox(&y, 0.5), x);
+/// ```
+pub fn box_cox<T: Float>(x: &[T], lambda: T) -> Vec<T> {
+    x.iter()
+        .map(|&v| {
+            if lambda == T::zero() {
+                v.ln()
+            } else if lambda == T::one() {
+                v - T::one()
+            } else {
+                (v.powf(lambda) - T::one()) / lambda
+            }
+        })
+        .collect()
+}
+
+/// Inverse of the Box-Cox transform, see `box_cox`.
+///
+/// # Arguments
+///
+/// * `&y` - Transformed values.
+/// * `lambda` - Transformation parameter.
+///
+/// # Returns
+///
+/// * Vector on the original scale.
+pub fn inv_box_cox<T: Float>(y: &[T], lambda: T) -> Vec<T> {
+    y.iter()
+        .map(|&v| {
+            if lambda == T::zero() {
+                v.exp()
+            } else {
+                (lambda * v + T::one()).powf(T::one() / lambda)
+            }
+        })
+        .collect()
+}
+
+/// Logistic function `1 / (1 + exp(-z))`, mapping the real line to (0, 1).
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// assert_eq!(util::logistic(0.0_f64), 0.5);
+/// assert!((util::logit(util::logistic(1.5_f64)) - 1.5).abs() < 1.0e-12);
+/// ```
+pub fn logistic<T: Float>(z: T) -> T {
+    T::one() / (T::one() + (-z).exp())
+}
+
+/// Logit function `ln(p / (1 - p))`, the inverse of `logistic`.
+pub fn logit<T: Float>(p: T) -> T {
+    (p / (T::one() - p)).ln()
+}
+
+/// Intercept and slope of the least squares line through `(t, x[t-1])`, `t = 1..n`.
+/// The intercept is the value of the line at `t = 0`, just before the first value.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least two values.
+///
+/// # Returns
+///
+/// * Tuple of (intercept, slope).
+///
+/// # Example
+///
+/// ```
+/// use arima::util;
+/// let (a, b) = util::trend_line(&[3.0, 5.0, 7.0]);
+/// assert_eq!((a, b), (1.0, 2.0));
+/// ```
+pub fn trend_line<T: Float>(x: &[T]) -> (T, T) {
+    let n = T::from(x.len()).unwrap();
+    let two = T::one() + T::one();
+    let t_mean = (n + T::one()) / two;
+    let x_mean = x.iter().fold(T::zero(), |acc, v| acc + *v) / n;
+    let mut sxy = T::zero();
+    let mut sxx = T::zero();
+    for (i, v) in x.iter().enumerate() {
+        let dt = T::from(i + 1).unwrap() - t_mean;
+        sxy = sxy + dt * (*v - x_mean);
+        sxx = sxx + dt * dt;
+    }
+    let b = sxy / sxx;
+    (x_mean - b * t_mean, b)
+}
+
+/// Hager's (1984) estimate of the 1-norm `|A^-1|_1` of the inverse of an `n x n` matrix,
+/// given solvers for `A x = b` and `A' x = b`. Usually exact, and never an overestimate.
+pub(crate) fn inverse_norm1<T: Float, F: Fn(&[T]) -> Vec<T>, G: Fn(&[T]) -> Vec<T>>(
+    n: usize,
+    solve: F,
+    solve_transposed: G,
+) -> T {
+    let norm1 = |v: &[T]| v.iter().fold(T::zero(), |acc, x| acc + x.abs());
+    let mut x = vec![T::one() / T::from(n).unwrap(); n];
+    let mut estimate = T::zero();
+    for _ in 0..5 {
+        let y = solve(&x);
+        let norm = norm1(&y);
+        if norm.is_nan() || norm <= estimate {
+            return if norm.is_nan() { norm } else { estimate };
+        }
+        estimate = norm;
+        let sign: Vec<T> = y
+            .iter()
+            .map(|v| if *v < T::zero() { -T::one() } else { T::one() })
+            .collect();
+        let z = solve_transposed(&sign);
+        let (j, z_max) = z.iter().enumerate().fold((0, T::zero()), |(j, m), (i, v)| {
+            if v.abs() > m {
+                (i, v.abs())
+            } else {
+                (j, m)
+            }
+        });
+        let zx = z
+            .iter()
+            .zip(&x)
+            .fold(T::zero(), |acc, (a, b)| acc + *a * *b);
+        if z_max <= zx {
+            break;
+        }
+        x = vec![T::zero(); n];
+        x[j] = T::one();
+    }
+    estimate
+}
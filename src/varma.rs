@@ -0,0 +1,348 @@
+use anyhow::Result;
+
+use crate::linalg;
+
+/// Maximum number of least squares iterations in `fit`.
+pub const VARMA_MAX_ITERATIONS: usize = 50;
+
+/// Convergence tolerance of the largest coefficient change in `fit`.
+pub const VARMA_TOLERANCE: f64 = 1.0e-8;
+
+/// A fitted VARMA(p, q) model for `k` series:
+/// `y_t = c + A_1 y_{t-1} + ... + A_p y_{t-p} + e_t + M_1 e_{t-1} + ... + M_q e_{t-q}`.
+///
+/// Coefficient matrices are `k x k` and stored row-major, so `ar[i][r * k + s]` is the
+/// effect of series `s` at lag `i + 1` on series `r`.
+#[derive(Debug, Clone)]
+pub struct VarmaModel {
+    /// Number of series.
+    pub k: usize,
+    /// Intercepts, one per series.
+    pub intercept: Vec<f64>,
+    /// AR coefficient matrices `A_1, ..., A_p`.
+    pub ar: Vec<Vec<f64>>,
+    /// MA coefficient matrices `M_1, ..., M_q`.
+    pub ma: Vec<Vec<f64>>,
+    /// Residual covariance matrix, `k x k` row-major.
+    pub sigma: Vec<f64>,
+    /// Residuals, one vector per series. The first `p` residuals are zeros.
+    pub residuals: Vec<Vec<f64>>,
+    /// Number of least squares iterations. Zero for a VAR model, which needs none.
+    pub iterations: usize,
+    /// False if the coefficients still changed by more than `VARMA_TOLERANCE` after
+    /// `VARMA_MAX_ITERATIONS` iterations. The estimates are then unreliable.
+    pub converged: bool,
+    // last p observations and last q residuals, oldest first, for forecasting
+    y_tail: Vec<Vec<f64>>,
+    e_tail: Vec<Vec<f64>>,
+}
+
+impl VarmaModel {
+    /// Forecast all series jointly for the next `h` time steps.
+    ///
+    /// # Returns
+    ///
+    /// * One vector of length `h` per series.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arima::varma;
+    /// let a: Vec<f64> = (0..50).map(|t| (t as f64 * 0.3).sin()).collect();
+    /// let b: Vec<f64> = (0..50).map(|t| (t as f64 * 0.3).cos()).collect();
+    /// let m = varma::fit(&[a, b], 1, 0).unwrap();
+    /// let f = m.forecast(5);
+    /// assert_eq!(f.len(), 2);
+    /// assert!((f[0][0] - (50.0_f64 * 0.3).sin()).abs() < 1.0e-6);
+    /// ```
+    pub fn forecast(&self, h: usize) -> Vec<Vec<f64>> {
+        let k = self.k;
+        let mut y_hist = self.y_tail.clone();
+        let mut e_hist = self.e_tail.clone();
+        let mut out = vec![Vec::with_capacity(h); k];
+        for _ in 0..h {
+            let pred = predict(&self.intercept, &self.ar, &self.ma, &y_hist, &e_hist, k);
+            for (o, v) in out.iter_mut().zip(&pred) {
+                o.push(*v);
+            }
+            if !y_hist.is_empty() {
+                y_hist.remove(0);
+                y_hist.push(pred);
+            }
+            if !e_hist.is_empty() {
+                // future innovations have expectation zero
+                e_hist.remove(0);
+                e_hist.push(vec![0.0; k]);
+            }
+        }
+        out
+    }
+
+    /// Gaussian log-likelihood conditional on the first `p` observations, evaluated at
+    /// the residual covariance estimate.
+    pub fn loglik(&self) -> f64 {
+        let k = self.k;
+        let n = (self.residuals[0].len() - self.ar.len()) as f64;
+        let det = determinant(&self.sigma, k);
+        -0.5 * n * (k as f64 * ((2.0 * std::f64::consts::PI).ln() + 1.0) + det.ln())
+    }
+
+    /// Akaike information criterion.
+    pub fn aic(&self) -> f64 {
+        let k = self.k;
+        let n_params = k + k * k * (self.ar.len() + self.ma.len()) + k * (k + 1) / 2;
+        -2.0 * self.loglik() + 2.0 * n_params as f64
+    }
+}
+
+/// One-step prediction from the last `p` observations and `q` residuals (oldest first).
+fn predict(
+    intercept: &[f64],
+    ar: &[Vec<f64>],
+    ma: &[Vec<f64>],
+    y_hist: &[Vec<f64>],
+    e_hist: &[Vec<f64>],
+    k: usize,
+) -> Vec<f64> {
+    let mut pred = intercept.to_vec();
+    for (i, a) in ar.iter().enumerate() {
+        let y = &y_hist[y_hist.len() - 1 - i];
+        for (r, pr) in pred.iter_mut().enumerate() {
+            *pr += (0..k).map(|s| a[r * k + s] * y[s]).sum::<f64>();
+        }
+    }
+    for (i, m) in ma.iter().enumerate() {
+        let e = &e_hist[e_hist.len() - 1 - i];
+        for (r, pr) in pred.iter_mut().enumerate() {
+            *pr += (0..k).map(|s| m[r * k + s] * e[s]).sum::<f64>();
+        }
+    }
+    pred
+}
+
+fn determinant(a: &[f64], k: usize) -> f64 {
+    // LU decomposition with partial pivoting
+    let mut a = a.to_vec();
+    let mut det = 1.0;
+    for col in 0..k {
+        let pivot = (col..k)
+            .max_by(|i, j| {
+                a[i * k + col]
+                    .abs()
+                    .partial_cmp(&a[j * k + col].abs())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        if a[pivot * k + col] == 0.0 {
+            return 0.0;
+        }
+        if pivot != col {
+            for j in 0..k {
+                a.swap(pivot * k + j, col * k + j);
+            }
+            det = -det;
+        }
+        det *= a[col * k + col];
+        for row in col + 1..k {
+            let factor = a[row * k + col] / a[col * k + col];
+            for j in col..k {
+                a[row * k + j] -= factor * a[col * k + j];
+            }
+        }
+    }
+    det
+}
+
+/// Regress every series at times `start..n` on an intercept, `p` lags of the series and
+/// `q` lags of `e`. Returns intercepts, AR and MA matrices.
+#[allow(clippy::type_complexity)]
+fn regress(
+    y: &[Vec<f64>],
+    e: &[Vec<f64>],
+    p: usize,
+    q: usize,
+    start: usize,
+) -> Result<(Vec<f64>, Vec<Vec<f64>>, Vec<Vec<f64>>)> {
+    let k = y.len();
+    let n = y[0].len();
+    let d = 1 + k * (p + q);
+    let mut design = Vec::with_capacity((n - start) * d);
+    for t in start..n {
+        design.push(1.0);
+        for i in 1..p + 1 {
+            design.extend(y.iter().map(|s| s[t - i]));
+        }
+        for i in 1..q + 1 {
+            design.extend(e.iter().map(|s| s[t - i]));
+        }
+    }
+
+    let mut intercept = vec![0.0; k];
+    let mut ar = vec![vec![0.0; k * k]; p];
+    let mut ma = vec![vec![0.0; k * k]; q];
+    for r in 0..k {
+        let beta = linalg::lstsq(&design, d, &y[r][start..])?;
+        intercept[r] = beta[0];
+        for (i, a) in ar.iter_mut().enumerate() {
+            let o = 1 + i * k;
+            a[r * k..(r + 1) * k].copy_from_slice(&beta[o..o + k]);
+        }
+        for (i, m) in ma.iter_mut().enumerate() {
+            let o = 1 + k * p + i * k;
+            m[r * k..(r + 1) * k].copy_from_slice(&beta[o..o + k]);
+        }
+    }
+    Ok((intercept, ar, ma))
+}
+
+/// Residuals of the VARMA recursion, with zero residuals for the first `p` values.
+fn residuals(y: &[Vec<f64>], intercept: &[f64], ar: &[Vec<f64>], ma: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let k = y.len();
+    let n = y[0].len();
+    let p = ar.len();
+    let q = ma.len();
+    let mut e = vec![vec![0.0; n]; k];
+    for t in p..n {
+        let y_hist: Vec<Vec<f64>> = (t - p..t)
+            .map(|j| y.iter().map(|s| s[j]).collect())
+            .collect();
+        let e_hist: Vec<Vec<f64>> = (t.saturating_sub(q)..t)
+            .map(|j| e.iter().map(|s| s[j]).collect())
+            .collect();
+        let e_hist = if e_hist.len() < q {
+            // pad missing residuals before the start with zeros
+            let mut padded = vec![vec![0.0; k]; q - e_hist.len()];
+            padded.extend(e_hist);
+            padded
+        } else {
+            e_hist
+        };
+        let pred = predict(intercept, ar, ma, &y_hist, &e_hist, k);
+        for r in 0..k {
+            e[r][t] = y[r][t] - pred[r];
+        }
+    }
+    e
+}
+
+/// Estimate a VARMA(p, q) model by iterative least squares.
+///
+/// Initial residuals are taken from a long VAR fitted by least squares
+/// (Hannan-Rissanen). The model is then regressed on lagged series and lagged residuals,
+/// and residuals and coefficients are updated alternately until the coefficients
+/// converge. For `q = 0` this is the least squares estimate of a VAR(p) model.
+/// A warning is logged and `converged` is false if the coefficients have not converged
+/// after `VARMA_MAX_ITERATIONS` iterations.
+///
+/// # Arguments
+///
+/// * `&y` - The series, all of the same length.
+/// * `p` - Order of the AR part.
+/// * `q` - Order of the MA part.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::varma;
+/// let a: Vec<f64> = (0..40).map(|t| ((t * 7) % 11) as f64).collect();
+/// let b: Vec<f64> = (0..40).map(|t| ((t * 5) % 13) as f64).collect();
+/// let m = varma::fit(&[a, b], 1, 1).unwrap();
+/// assert_eq!(m.ar.len(), 1);
+/// assert_eq!(m.ma[0].len(), 4);
+/// ```
+pub fn fit<S: AsRef<[f64]>>(y: &[S], p: usize, q: usize) -> Result<VarmaModel> {
+    let k = y.len();
+    if k == 0 {
+        anyhow::bail!("Need at least one series");
+    }
+    let y: Vec<Vec<f64>> = y.iter().map(|s| s.as_ref().to_vec()).collect();
+    let n = y[0].len();
+    if y.iter().any(|s| s.len() != n) {
+        anyhow::bail!("All series must have the same length");
+    }
+
+    // order of the long VAR for the initial residuals
+    let m = if q > 0 {
+        (p + q).max((n as f64).ln().ceil() as usize)
+    } else {
+        0
+    };
+    let start = m + p.max(q);
+    if n <= start + 1 + k * (p + q) {
+        anyhow::bail!("Time series is too short for the model order");
+    }
+
+    let mut iterations = 0;
+    let mut converged = true;
+    let (intercept, ar, ma) = if q == 0 {
+        regress(&y, &[], p, 0, p)?
+    } else {
+        let (c, a, _) = regress(&y, &[], m, 0, m)?;
+        let e = residuals(&y, &c, &a, &[]);
+        let mut est = regress(&y, &e, p, q, start)?;
+        loop {
+            iterations += 1;
+            let e = residuals(&y, &est.0, &est.1, &est.2);
+            let next = regress(&y, &e, p, q, start)?;
+            let change = next
+                .1
+                .iter()
+                .chain(&next.2)
+                .flatten()
+                .zip(est.1.iter().chain(&est.2).flatten())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            est = next;
+            if !change.is_finite() {
+                anyhow::bail!("Least squares iterations diverged");
+            }
+            if change < VARMA_TOLERANCE {
+                break;
+            }
+            if iterations >= VARMA_MAX_ITERATIONS {
+                tracing::warn!(
+                    "VARMA({}, {}) least squares did not converge after {} iterations",
+                    p,
+                    q,
+                    iterations
+                );
+                converged = false;
+                break;
+            }
+        }
+        est
+    };
+
+    let e = residuals(&y, &intercept, &ar, &ma);
+    let used = (n - p) as f64;
+    let mut sigma = vec![0.0; k * k];
+    for r in 0..k {
+        for s in 0..k {
+            sigma[r * k + s] = (p..n).map(|t| e[r][t] * e[s][t]).sum::<f64>() / used;
+        }
+    }
+
+    let y_tail = (n - p..n)
+        .map(|t| y.iter().map(|s| s[t]).collect())
+        .collect();
+    let e_tail = (n - q..n)
+        .map(|t| e.iter().map(|s| s[t]).collect())
+        .collect();
+
+    Ok(VarmaModel {
+        k,
+        intercept,
+        ar,
+        ma,
+        sigma,
+        residuals: e,
+        iterations,
+        converged,
+        y_tail,
+        e_tail,
+    })
+}
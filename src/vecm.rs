@@ -0,0 +1,420 @@
+use anyhow::Result;
+
+use std::cmp::Ordering;
+
+use crate::linalg;
+
+/// 95% critical values of the Johansen trace test with an unrestricted constant,
+/// indexed by the number of common stochastic trends `k - r` (MacKinnon, Haug and
+/// Michelis, 1999).
+const TRACE_CRIT_95: [f64; 10] = [
+    3.8415, 15.4943, 29.7961, 47.8545, 69.8189, 95.7542, 125.6185, 159.5290, 197.3772, 239.2468,
+];
+
+/// 95% critical values of the Johansen maximum eigenvalue test with an unrestricted
+/// constant, indexed like `TRACE_CRIT_95`.
+const MAX_EIG_CRIT_95: [f64; 10] = [
+    3.8415, 14.2639, 21.1314, 27.5858, 33.8777, 40.0763, 46.2299, 52.3624, 58.4332, 64.5040,
+];
+
+/// Result of the Johansen cointegration test.
+#[derive(Debug, Clone)]
+pub struct Johansen {
+    /// Eigenvalues in descending order.
+    pub eigenvalues: Vec<f64>,
+    /// Eigenvectors as the columns of a row-major `k x k` matrix, normalized so that
+    /// `v' S11 v = I`.
+    pub eigenvectors: Vec<f64>,
+    /// Trace statistics for the null hypotheses `rank <= r`, `r = 0, ..., k - 1`.
+    pub trace: Vec<f64>,
+    /// Maximum eigenvalue statistics for the null hypotheses `rank = r` against
+    /// `rank = r + 1`.
+    pub max_eig: Vec<f64>,
+    /// 95% critical values of the trace statistics. NaN for more than 10 series.
+    pub trace_crit: Vec<f64>,
+    /// 95% critical values of the maximum eigenvalue statistics.
+    pub max_eig_crit: Vec<f64>,
+    /// Cointegration rank selected by the sequential trace test at the 5% level.
+    pub rank: usize,
+    // moment matrices, kept for the VECM estimation
+    s01: Vec<f64>,
+}
+
+/// A fitted vector error correction model
+/// `dy_t = c + alpha beta' y_{t-1} + G_1 dy_{t-1} + ... + G_{p-1} dy_{t-p+1} + e_t`.
+///
+/// Matrices are stored row-major; `alpha` and `beta` are `k x rank`, the short-run
+/// matrices `gamma` are `k x k`.
+#[derive(Debug, Clone)]
+pub struct VecmModel {
+    /// Number of series.
+    pub k: usize,
+    /// Lag order of the underlying VAR in levels.
+    pub p: usize,
+    /// Cointegration rank.
+    pub rank: usize,
+    /// Loading matrix, `k x rank`.
+    pub alpha: Vec<f64>,
+    /// Cointegrating vectors, `k x rank`.
+    pub beta: Vec<f64>,
+    /// Long-run impact matrix `alpha beta'`, `k x k`.
+    pub pi: Vec<f64>,
+    /// Short-run coefficient matrices `G_1, ..., G_{p-1}`.
+    pub gamma: Vec<Vec<f64>>,
+    /// Intercepts of the differenced equations.
+    pub intercept: Vec<f64>,
+    /// Residual covariance matrix, `k x k`.
+    pub sigma: Vec<f64>,
+    /// Residuals, one vector of length `n - p` per series.
+    pub residuals: Vec<Vec<f64>>,
+    // last p observations, oldest first
+    y_tail: Vec<Vec<f64>>,
+}
+
+impl VecmModel {
+    /// Coefficient matrices `A_1, ..., A_p` of the equivalent VAR(p) model in levels.
+    pub fn var_coefficients(&self) -> Vec<Vec<f64>> {
+        let k = self.k;
+        let mut a = vec![vec![0.0; k * k]; self.p];
+        for i in 0..k {
+            a[0][i * k + i] = 1.0;
+        }
+        for (j, v) in a[0].iter_mut().enumerate() {
+            *v += self.pi[j];
+        }
+        for (i, g) in self.gamma.iter().enumerate() {
+            for j in 0..k * k {
+                a[i][j] += g[j];
+                a[i + 1][j] -= g[j];
+            }
+        }
+        a
+    }
+
+    /// Forecast the series in levels for the next `h` time steps.
+    ///
+    /// # Returns
+    ///
+    /// * One vector of length `h` per series.
+    pub fn forecast(&self, h: usize) -> Vec<Vec<f64>> {
+        let k = self.k;
+        let a = self.var_coefficients();
+        let mut hist = self.y_tail.clone();
+        let mut out = vec![Vec::with_capacity(h); k];
+        for _ in 0..h {
+            let mut pred = self.intercept.clone();
+            for (i, ai) in a.iter().enumerate() {
+                let y = &hist[hist.len() - 1 - i];
+                for (r, pr) in pred.iter_mut().enumerate() {
+                    *pr += (0..k).map(|s| ai[r * k + s] * y[s]).sum::<f64>();
+                }
+            }
+            for (o, v) in out.iter_mut().zip(&pred) {
+                o.push(*v);
+            }
+            hist.remove(0);
+            hist.push(pred);
+        }
+        out
+    }
+}
+
+/// Regressions of the Johansen procedure: `dy_t` and `y_{t-1}` on an intercept and
+/// `p - 1` lagged differences, for `t = p..n`.
+struct Regressors {
+    z0: Vec<Vec<f64>>,
+    z1: Vec<Vec<f64>>,
+    // row-major design matrix with `d` columns
+    z2: Vec<f64>,
+    d: usize,
+}
+
+fn regressors(y: &[Vec<f64>], p: usize) -> Regressors {
+    let k = y.len();
+    let n = y[0].len();
+    let d = 1 + k * (p - 1);
+    let mut z2 = Vec::with_capacity((n - p) * d);
+    for t in p..n {
+        z2.push(1.0);
+        for i in 1..p {
+            z2.extend(y.iter().map(|s| s[t - i] - s[t - i - 1]));
+        }
+    }
+    Regressors {
+        z0: y
+            .iter()
+            .map(|s| (p..n).map(|t| s[t] - s[t - 1]).collect())
+            .collect(),
+        z1: y.iter().map(|s| s[p - 1..n - 1].to_vec()).collect(),
+        z2,
+        d,
+    }
+}
+
+/// Residuals of regressing each column of `z` on the design matrix.
+fn partial_out(z: &[Vec<f64>], design: &[f64], d: usize) -> Result<Vec<Vec<f64>>> {
+    z.iter()
+        .map(|col| {
+            let beta = linalg::lstsq(design, d, col)?;
+            Ok(col
+                .iter()
+                .zip(design.chunks(d))
+                .map(|(v, row)| v - row.iter().zip(&beta).map(|(a, b)| a * b).sum::<f64>())
+                .collect())
+        })
+        .collect()
+}
+
+/// `a' b / n` for column vectors `a` and `b`.
+fn moments(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<f64> {
+    let n = a[0].len() as f64;
+    let mut s = Vec::with_capacity(a.len() * b.len());
+    for x in a {
+        for y in b {
+            s.push(x.iter().zip(y).map(|(u, v)| u * v).sum::<f64>() / n);
+        }
+    }
+    s
+}
+
+fn to_columns<S: AsRef<[f64]>>(y: &[S], p: usize) -> Result<Vec<Vec<f64>>> {
+    let k = y.len();
+    if k == 0 {
+        anyhow::bail!("Need at least one series");
+    }
+    if p == 0 {
+        anyhow::bail!("VAR order p must be at least 1");
+    }
+    let y: Vec<Vec<f64>> = y.iter().map(|s| s.as_ref().to_vec()).collect();
+    let n = y[0].len();
+    if y.iter().any(|s| s.len() != n) {
+        anyhow::bail!("All series must have the same length");
+    }
+    if n <= p + 1 + k * p {
+        anyhow::bail!("Time series is too short for the model order");
+    }
+    Ok(y)
+}
+
+/// Johansen cointegration test with an unrestricted constant.
+///
+/// # Arguments
+///
+/// * `&y` - The series in levels, all of the same length.
+/// * `p` - Lag order of the VAR in levels, at least 1.
+///
+/// # Returns
+///
+/// * Eigenvalues, test statistics, critical values and the selected rank.
+///
+/// # Example
+///
+/// ```
+/// use arima::{sim, vecm};
+/// use rand_distr::{Distribution, Normal};
+/// // two series sharing one random walk trend
+/// let mut rng = sim::stream_rng(1, 0);
+/// let normal = Normal::new(0.0, 1.0).unwrap();
+/// let mut trend = 0.0;
+/// let (mut a, mut b) = (Vec::new(), Vec::new());
+/// for _ in 0..300 {
+///     trend += normal.sample(&mut rng);
+///     a.push(trend + normal.sample(&mut rng));
+///     b.push(2.0 * trend + normal.sample(&mut rng));
+/// }
+/// let j = vecm::johansen(&[a, b], 2).unwrap();
+/// assert_eq!(j.rank, 1);
+/// ```
+pub fn johansen<S: AsRef<[f64]>>(y: &[S], p: usize) -> Result<Johansen> {
+    let y = to_columns(y, p)?;
+    let k = y.len();
+    let reg = regressors(&y, p);
+    let r0 = partial_out(&reg.z0, &reg.z2, reg.d)?;
+    let r1 = partial_out(&reg.z1, &reg.z2, reg.d)?;
+    let n = r0[0].len() as f64;
+
+    let s00 = moments(&r0, &r0);
+    let s01 = moments(&r0, &r1);
+    let s11 = moments(&r1, &r1);
+
+    // M = S10 S00^-1 S01, symmetric
+    let mut s00_inv_s01 = vec![0.0; k * k];
+    for j in 0..k {
+        let col: Vec<f64> = (0..k).map(|i| s01[i * k + j]).collect();
+        let x = linalg::solve(&s00, &col)?;
+        for i in 0..k {
+            s00_inv_s01[i * k + j] = x[i];
+        }
+    }
+    let mut m = vec![0.0; k * k];
+    for i in 0..k {
+        for j in 0..k {
+            m[i * k + j] = (0..k)
+                .map(|l| s01[l * k + i] * s00_inv_s01[l * k + j])
+                .sum();
+        }
+    }
+
+    // C = L^-1 M L^-T with S11 = L L'
+    let l = linalg::cholesky(&s11, k)?;
+    let mut w = vec![0.0; k * k];
+    for j in 0..k {
+        let col: Vec<f64> = (0..k).map(|i| m[i * k + j]).collect();
+        let x = linalg::solve_lower(&l, &col);
+        for i in 0..k {
+            w[i * k + j] = x[i];
+        }
+    }
+    let mut c = vec![0.0; k * k];
+    for j in 0..k {
+        // column j of L^-1 W'
+        let col: Vec<f64> = (0..k).map(|i| w[j * k + i]).collect();
+        let x = linalg::solve_lower(&l, &col);
+        for i in 0..k {
+            c[i * k + j] = x[i];
+        }
+    }
+    let (eigenvalues, u) = linalg::symmetric_eigen(&c, k);
+    let eigenvalues: Vec<f64> = eigenvalues.iter().map(|v| v.clamp(0.0, 1.0)).collect();
+    let mut eigenvectors = vec![0.0; k * k];
+    for j in 0..k {
+        let col: Vec<f64> = (0..k).map(|i| u[i * k + j]).collect();
+        let x = linalg::solve_lower_t(&l, &col);
+        for i in 0..k {
+            eigenvectors[i * k + j] = x[i];
+        }
+    }
+
+    let trace: Vec<f64> = (0..k)
+        .map(|r| -n * eigenvalues[r..].iter().map(|l| (1.0 - l).ln()).sum::<f64>())
+        .collect();
+    let max_eig: Vec<f64> = (0..k).map(|r| -n * (1.0 - eigenvalues[r]).ln()).collect();
+    let crit = |table: &[f64], r: usize| *table.get(k - r - 1).unwrap_or(&f64::NAN);
+    let trace_crit: Vec<f64> = (0..k).map(|r| crit(&TRACE_CRIT_95, r)).collect();
+    let max_eig_crit: Vec<f64> = (0..k).map(|r| crit(&MAX_EIG_CRIT_95, r)).collect();
+    if k > TRACE_CRIT_95.len() {
+        tracing::warn!("No critical values for more than 10 series; rank set to 0");
+    }
+    // first r not rejected; a missing critical value stops the sequence
+    let rank = (0..k)
+        .find(|&r| trace[r].partial_cmp(&trace_crit[r]) != Some(Ordering::Greater))
+        .unwrap_or(k);
+
+    Ok(Johansen {
+        eigenvalues,
+        eigenvectors,
+        trace,
+        max_eig,
+        trace_crit,
+        max_eig_crit,
+        rank,
+        s01,
+    })
+}
+
+/// Estimate a vector error correction model by reduced rank regression.
+///
+/// # Arguments
+///
+/// * `&y` - The series in levels, all of the same length.
+/// * `p` - Lag order of the VAR in levels, at least 1. The VECM has `p - 1` lagged
+///   differences.
+/// * `rank` - Cointegration rank. Selected with the Johansen trace test if `None`.
+///
+/// # Returns
+///
+/// * Fitted model.
+///
+/// # Example
+///
+/// ```
+/// use arima::{sim, vecm};
+/// use rand_distr::{Distribution, Normal};
+/// // two series sharing one random walk trend
+/// let mut rng = sim::stream_rng(1, 0);
+/// let normal = Normal::new(0.0, 1.0).unwrap();
+/// let mut trend = 0.0;
+/// let (mut a, mut b) = (Vec::new(), Vec::new());
+/// for _ in 0..300 {
+///     trend += normal.sample(&mut rng);
+///     a.push(trend + normal.sample(&mut rng));
+///     b.push(2.0 * trend + normal.sample(&mut rng));
+/// }
+/// let m = vecm::fit(&[a, b], 2, Some(1)).unwrap();
+/// // the cointegrating vector is proportional to (2, -1)
+/// assert!((m.beta[0] / m.beta[1] + 2.0).abs() < 0.05);
+/// ```
+pub fn fit<S: AsRef<[f64]>>(y: &[S], p: usize, rank: Option<usize>) -> Result<VecmModel> {
+    let j = johansen(y, p)?;
+    let y = to_columns(y, p)?;
+    let k = y.len();
+    let n = y[0].len();
+    let rank = rank.unwrap_or(j.rank);
+    if rank > k {
+        anyhow::bail!("Cointegration rank cannot exceed the number of series");
+    }
+
+    // beta: the first `rank` eigenvectors, alpha = S01 beta
+    let mut beta = vec![0.0; k * rank];
+    for i in 0..k {
+        for c in 0..rank {
+            beta[i * rank + c] = j.eigenvectors[i * k + c];
+        }
+    }
+    let mut alpha = vec![0.0; k * rank];
+    for i in 0..k {
+        for c in 0..rank {
+            alpha[i * rank + c] = (0..k).map(|l| j.s01[i * k + l] * beta[l * rank + c]).sum();
+        }
+    }
+    let mut pi = vec![0.0; k * k];
+    for i in 0..k {
+        for l in 0..k {
+            pi[i * k + l] = (0..rank)
+                .map(|c| alpha[i * rank + c] * beta[l * rank + c])
+                .sum();
+        }
+    }
+
+    // short-run parameters by least squares given pi
+    let reg = regressors(&y, p);
+    let mut intercept = vec![0.0; k];
+    let mut gamma = vec![vec![0.0; k * k]; p - 1];
+    let mut residuals = Vec::with_capacity(k);
+    for r in 0..k {
+        let target: Vec<f64> = (0..n - p)
+            .map(|t| reg.z0[r][t] - (0..k).map(|l| pi[r * k + l] * reg.z1[l][t]).sum::<f64>())
+            .collect();
+        let coef = linalg::lstsq(&reg.z2, reg.d, &target)?;
+        intercept[r] = coef[0];
+        for (i, g) in gamma.iter_mut().enumerate() {
+            let o = 1 + i * k;
+            g[r * k..(r + 1) * k].copy_from_slice(&coef[o..o + k]);
+        }
+        residuals.push(
+            target
+                .iter()
+                .zip(reg.z2.chunks(reg.d))
+                .map(|(v, row)| v - row.iter().zip(&coef).map(|(a, b)| a * b).sum::<f64>())
+                .collect::<Vec<f64>>(),
+        );
+    }
+    let sigma = moments(&residuals, &residuals);
+    let y_tail = (n - p..n)
+        .map(|t| y.iter().map(|s| s[t]).collect())
+        .collect();
+
+    Ok(VecmModel {
+        k,
+        p,
+        rank,
+        alpha,
+        beta,
+        pi,
+        gamma,
+        intercept,
+        sigma,
+        residuals,
+        y_tail,
+    })
+}
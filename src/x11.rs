@@ -0,0 +1,342 @@
+//! X-11-style seasonal adjustment by iterated moving averages.
+//!
+//! The procedure follows the B and C tables of the Census X-11 method (Shiskin et al.,
+//! 1967; Ladiray and Quenneville, 2001):
+//!
+//! 1. A centered `2 x period` moving average gives a first trend estimate.
+//! 2. Seasonal factors are estimated from the detrended series (SI ratios) by a `3 x 3`
+//!    moving average over the same season of each year. Extreme SI values are
+//!    downweighted with a 5-year moving standard deviation of the irregular and
+//!    replaced before the factors are re-estimated and normalized.
+//! 3. A Henderson moving average of the preliminary adjusted series gives an improved
+//!    trend, from which the final seasonal factors are estimated with a `3 x 5`
+//!    moving average and the same extreme-value replacement.
+//!
+//! Moving averages are truncated and renormalized at the ends of the series instead of
+//! using X-11's asymmetric end filters or ARIMA extensions.
+
+use anyhow::Result;
+
+#[cfg(feature = "chrono")]
+use crate::timeseries::TimeSeries;
+
+/// Extreme values with an irregular of at most `LOWER_SIGMA` standard deviations get
+/// full weight.
+pub const LOWER_SIGMA: f64 = 1.5;
+
+/// Extreme values with an irregular of at least `UPPER_SIGMA` standard deviations get
+/// zero weight and are fully replaced.
+pub const UPPER_SIGMA: f64 = 2.5;
+
+/// Decomposition model of a seasonal adjustment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// `x = trend + seasonal + irregular`.
+    Additive,
+    /// `x = trend * seasonal * irregular`, for positive series.
+    Multiplicative,
+}
+
+/// Result of an X-11 seasonal adjustment. All components have the length of the
+/// series.
+#[derive(Debug, Clone)]
+pub struct X11Decomposition {
+    /// Decomposition model.
+    pub mode: Mode,
+    /// Seasonal period.
+    pub period: usize,
+    /// Final trend-cycle estimate.
+    pub trend: Vec<f64>,
+    /// Seasonal factors, i.e. differences (additive) or ratios (multiplicative).
+    pub seasonal: Vec<f64>,
+    /// Irregular component of the seasonally adjusted series.
+    pub irregular: Vec<f64>,
+    /// Seasonally adjusted series.
+    pub adjusted: Vec<f64>,
+    /// Weights of the SI values in the final seasonal estimation, from 0 for extreme
+    /// values that were replaced to 1 for values that were used as they are.
+    pub weights: Vec<f64>,
+}
+
+impl X11Decomposition {
+    /// Seasonal factors of the last complete year, ordered by season of the
+    /// observations they apply to, e.g. to adjust the next year of a series.
+    pub fn last_factors(&self) -> Vec<f64> {
+        let n = self.seasonal.len();
+        self.seasonal[n - self.period..].to_vec()
+    }
+}
+
+/// Seasonally adjust a series with the X-11 method.
+///
+/// # Arguments
+///
+/// * `&x` - Vector of the timeseries. Needs at least three full periods, and positive
+///   values for `Mode::Multiplicative`.
+/// * `period` - Seasonal period, at least 2, e.g. 12 for monthly data.
+/// * `mode` - Additive or multiplicative decomposition.
+///
+/// # Returns
+///
+/// * Trend, seasonal factors, irregular, adjusted series and extreme-value weights.
+///
+/// # Example
+///
+/// ```
+/// use arima::x11;
+/// let pattern = [2.0, -1.0, 0.5, -1.5];
+/// let x: Vec<f64> = (0..40).map(|t| 10.0 + 0.1 * t as f64 + pattern[t % 4]).collect();
+/// let d = x11::adjust(&x, 4, x11::Mode::Additive).unwrap();
+/// for (s, p) in d.last_factors().iter().zip(&pattern) {
+///     assert!((s - p).abs() < 0.05);
+/// }
+/// ```
+pub fn adjust(x: &[f64], period: usize, mode: Mode) -> Result<X11Decomposition> {
+    if period < 2 {
+        anyhow::bail!("Seasonal period must be at least 2");
+    }
+    if x.len() < 3 * period {
+        anyhow::bail!(
+            "Need at least three full periods ({} values), got {}",
+            3 * period,
+            x.len()
+        );
+    }
+    if x.iter().any(|v| !v.is_finite()) {
+        anyhow::bail!("Series must not contain missing or infinite values");
+    }
+    if mode == Mode::Multiplicative && x.iter().any(|v| *v <= 0.0) {
+        anyhow::bail!("Multiplicative adjustment needs positive values");
+    }
+
+    // stage 1: preliminary trend and seasonal factors
+    let trend = centered_ma(x, period);
+    let si = remove(x, &trend, mode);
+    let (seasonal, _) = seasonal_factors(&si, period, mode, &[1.0, 2.0, 3.0, 2.0, 1.0]);
+
+    // stage 2: Henderson trend of the preliminary adjusted series, final factors
+    let length = henderson_length(period);
+    let trend = henderson(&remove(x, &seasonal, mode), length);
+    let si = remove(x, &trend, mode);
+    let (seasonal, weights) =
+        seasonal_factors(&si, period, mode, &[1.0, 2.0, 3.0, 3.0, 3.0, 2.0, 1.0]);
+
+    let adjusted = remove(x, &seasonal, mode);
+    let trend = henderson(&adjusted, length);
+    let irregular = remove(&adjusted, &trend, mode);
+    Ok(X11Decomposition {
+        mode,
+        period,
+        trend,
+        seasonal,
+        irregular,
+        adjusted,
+        weights,
+    })
+}
+
+/// Same as `adjust`, with the seasonal period implied by the sampling frequency of the
+/// series, see `TimeSeries::seasonal_period`, e.g. 12 for monthly and 4 for quarterly
+/// data. Fails if the series has gaps or its frequency has no seasonal period. Only
+/// enabled with feature `chrono`.
+#[cfg(feature = "chrono")]
+pub fn adjust_series(x: &TimeSeries<f64>, mode: Mode) -> Result<X11Decomposition> {
+    adjust(x.regular_values()?, x.seasonal_period(), mode)
+}
+
+/// Weights of the symmetric Henderson moving average of odd length `length`.
+///
+/// # Example
+///
+/// ```
+/// use arima::x11;
+/// let w = x11::henderson_weights(5);
+/// assert!((w[2] - 0.559441).abs() < 1.0e-6);
+/// assert!((w.iter().sum::<f64>() - 1.0).abs() < 1.0e-12);
+/// ```
+pub fn henderson_weights(length: usize) -> Vec<f64> {
+    let m = (length / 2) as f64;
+    let n = m + 2.0;
+    let denom =
+        8.0 * n * (n * n - 1.0) * (4.0 * n * n - 1.0) * (4.0 * n * n - 9.0) * (4.0 * n * n - 25.0);
+    (0..2 * (length / 2) + 1)
+        .map(|i| {
+            let j = i as f64 - m;
+            let j2 = j * j;
+            315.0
+                * ((n - 1.0).powi(2) - j2)
+                * (n * n - j2)
+                * ((n + 1.0).powi(2) - j2)
+                * (3.0 * n * n - 16.0 - 11.0 * j2)
+                / denom
+        })
+        .collect()
+}
+
+/// Henderson filter length used for the trend: the smallest odd length longer than a
+/// period, at least 5, e.g. 13 for monthly and 5 for quarterly data.
+fn henderson_length(period: usize) -> usize {
+    let length = if period % 2 == 0 {
+        period + 1
+    } else {
+        period + 2
+    };
+    length.max(5)
+}
+
+/// Remove a component: `x - c` or `x / c`.
+fn remove(x: &[f64], c: &[f64], mode: Mode) -> Vec<f64> {
+    x.iter()
+        .zip(c)
+        .map(|(x, c)| match mode {
+            Mode::Additive => x - c,
+            Mode::Multiplicative => x / c,
+        })
+        .collect()
+}
+
+/// Apply a symmetric filter, truncating and renormalizing its weights at the ends.
+fn filter(x: &[f64], weights: &[f64]) -> Vec<f64> {
+    let half = weights.len() / 2;
+    (0..x.len())
+        .map(|t| {
+            let lo = half.saturating_sub(t);
+            let hi = weights.len().min(x.len() + half - t);
+            let mut sum = 0.0;
+            let mut total = 0.0;
+            for (k, w) in weights.iter().enumerate().take(hi).skip(lo) {
+                sum += w * x[t + k - half];
+                total += w;
+            }
+            sum / total
+        })
+        .collect()
+}
+
+/// Centered moving average over one period (`2 x period` for even periods). The ends
+/// repeat the nearest value that has a complete window.
+fn centered_ma(x: &[f64], period: usize) -> Vec<f64> {
+    let half = period / 2;
+    let mut weights = vec![1.0; 2 * half + 1];
+    if period % 2 == 0 {
+        weights[0] = 0.5;
+        weights[2 * half] = 0.5;
+    }
+    let total: f64 = weights.iter().sum();
+    let n = x.len();
+    let mut ma = vec![0.0; n];
+    for t in half..n - half {
+        ma[t] = weights
+            .iter()
+            .enumerate()
+            .map(|(k, w)| w * x[t + k - half])
+            .sum::<f64>()
+            / total;
+    }
+    for t in 0..half {
+        ma[t] = ma[half];
+        ma[n - 1 - t] = ma[n - 1 - half];
+    }
+    ma
+}
+
+fn henderson(x: &[f64], length: usize) -> Vec<f64> {
+    filter(x, &henderson_weights(length))
+}
+
+/// Apply a seasonal filter to each season separately.
+fn seasonal_ma(si: &[f64], period: usize, weights: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; si.len()];
+    for season in 0..period {
+        let values: Vec<f64> = si.iter().skip(season).step_by(period).copied().collect();
+        for (k, v) in filter(&values, weights).into_iter().enumerate() {
+            out[season + k * period] = v;
+        }
+    }
+    out
+}
+
+/// Center seasonal factors so that they sum to zero (additive) or average to one
+/// (multiplicative) over each period.
+fn normalize(seasonal: &[f64], period: usize, mode: Mode) -> Vec<f64> {
+    remove(seasonal, &centered_ma(seasonal, period), mode)
+}
+
+/// Estimate normalized seasonal factors from SI values with extreme-value replacement.
+/// Returns the factors and the weights of the SI values.
+fn seasonal_factors(
+    si: &[f64],
+    period: usize,
+    mode: Mode,
+    weights: &[f64],
+) -> (Vec<f64>, Vec<f64>) {
+    let preliminary = normalize(&seasonal_ma(si, period, weights), period, mode);
+    let irregular: Vec<f64> = remove(si, &preliminary, mode)
+        .iter()
+        .map(|v| match mode {
+            Mode::Additive => *v,
+            Mode::Multiplicative => v - 1.0,
+        })
+        .collect();
+    let extreme = extreme_weights(&irregular, period);
+    let replaced = replace_extremes(si, &extreme, period);
+    let seasonal = normalize(&seasonal_ma(&replaced, period, weights), period, mode);
+    (seasonal, extreme)
+}
+
+/// Weights of the irregular values from their size relative to a 5-year moving
+/// standard deviation, which is computed twice, the second time without the values
+/// beyond `UPPER_SIGMA` standard deviations.
+fn extreme_weights(irregular: &[f64], period: usize) -> Vec<f64> {
+    let n = irregular.len();
+    let years = n.div_ceil(period);
+    let mut weights = vec![1.0; n];
+    for year in 0..years {
+        let lo = year.saturating_sub(2).min(years.saturating_sub(5));
+        let window = &irregular[lo * period..n.min((lo + 5) * period)];
+        let rms = |limit: f64| {
+            let kept: Vec<f64> = window
+                .iter()
+                .filter(|v| v.abs() <= limit)
+                .copied()
+                .collect();
+            (kept.iter().map(|v| v * v).sum::<f64>() / kept.len().max(1) as f64).sqrt()
+        };
+        let sigma = rms(f64::INFINITY);
+        let sigma = rms(UPPER_SIGMA * sigma);
+        if sigma <= 0.0 || sigma.is_nan() {
+            continue;
+        }
+        for t in year * period..n.min((year + 1) * period) {
+            let z = irregular[t].abs() / sigma;
+            weights[t] = ((UPPER_SIGMA - z) / (UPPER_SIGMA - LOWER_SIGMA)).clamp(0.0, 1.0);
+        }
+    }
+    weights
+}
+
+/// Replace downweighted SI values by a weighted average of the value and the mean of
+/// the two nearest full-weight values of the same season before and after it.
+fn replace_extremes(si: &[f64], weights: &[f64], period: usize) -> Vec<f64> {
+    let mut out = si.to_vec();
+    for t in 0..si.len() {
+        if weights[t] >= 1.0 {
+            continue;
+        }
+        let full = |s: &usize| weights[*s] >= 1.0;
+        let before = (0..t / period).map(|k| t - (k + 1) * period);
+        let after = (t + period..si.len()).step_by(period);
+        let neighbours: Vec<f64> = before
+            .filter(full)
+            .take(2)
+            .chain(after.filter(full).take(2))
+            .map(|s| si[s])
+            .collect();
+        if neighbours.is_empty() {
+            continue;
+        }
+        let replacement = neighbours.iter().sum::<f64>() / neighbours.len() as f64;
+        out[t] = weights[t] * si[t] + (1.0 - weights[t]) * replacement;
+    }
+    out
+}
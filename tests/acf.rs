@@ -307,4 +307,132 @@ mod test_acf {
             assert_lt!((pacf_real[i] - pacf_calc[i] as f64).abs(), 1.0e-7);
         }
     }
+
+    #[test]
+    fn acf_into_reuses_buffer_f64() {
+        let x = AR3;
+        let mut buf = Vec::new();
+
+        arima::acf::acf_into(&x, Some(4), true, &mut buf).unwrap();
+        arima::acf::acf_into(&x, None, false, &mut buf).unwrap();
+
+        assert_eq!(buf.len(), AR3_COR.len());
+        for i in 0..AR3_COR.len() {
+            assert_lt!((AR3_COR[i] - buf[i]).abs(), 1.0e-7);
+        }
+    }
+
+    #[test]
+    fn ar_into_matches_ar_f64() {
+        let mut ws = arima::acf::ArWorkspace::new();
+        for order in [5, 3, 1] {
+            let (ar_calc, var_calc) = arima::acf::ar(&AR3, Some(order)).unwrap();
+            let var = arima::acf::ar_into(&AR3, Some(order), &mut ws).unwrap();
+
+            assert_eq!(ws.coef(), &ar_calc[..]);
+            assert_lt!((var - var_calc).abs(), 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn acf_long_series_matches_naive_f64() {
+        // length is neither a multiple of the accumulator width nor of the block size
+        let x: Vec<f64> = (0..5003).map(|i| ((i * 37 % 101) as f64).sin()).collect();
+        let n = x.len() as f64;
+        let mean = x.iter().sum::<f64>() / n;
+
+        let acf_calc = arima::acf::acf(&x, Some(300), true).unwrap();
+        let lags: Vec<usize> = (0..301).collect();
+        assert_eq!(acf_calc, arima::acf::acf_lags(&x, &lags, true).unwrap());
+
+        for (t, c) in acf_calc.iter().enumerate() {
+            let mut naive = 0.0;
+            for i in 0..x.len() - t {
+                naive += (x[i] - mean) * (x[i + t] - mean);
+            }
+            assert_lt!((naive / n - c).abs(), 1.0e-12);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn acf_par_matches_acf_f64() {
+        let x: Vec<f64> = (0..5000).map(|i| ((i * 37 % 101) as f64).sin()).collect();
+        for &covariance in &[true, false] {
+            let seq = arima::acf::acf(&x, Some(200), covariance).unwrap();
+            let par = arima::acf::acf_par(&x, Some(200), covariance).unwrap();
+            assert_eq!(seq, par);
+        }
+    }
+
+    #[test]
+    fn online_acf_matches_acf_f64() {
+        let mut online = arima::acf::OnlineAcf::new(6);
+        for (i, &x) in AR3.iter().enumerate() {
+            online.push(x);
+            assert_eq!(online.len(), i + 1);
+
+            let calc = online.acf(true).unwrap();
+            let real = arima::acf::acf(&AR3[..i + 1], Some(6), true).unwrap();
+            assert_eq!(calc.len(), real.len());
+            for k in 0..real.len() {
+                assert_lt!((calc[k] - real[k]).abs(), 1.0e-8);
+            }
+        }
+
+        let cor = online.acf(false).unwrap();
+        for k in 0..cor.len() {
+            assert_lt!((cor[k] - AR3_COR[k]).abs(), 1.0e-7);
+        }
+    }
+
+    #[test]
+    fn acf_lags_f64() {
+        let lags = [14, 0, 1, 7, 19];
+
+        let cor = arima::acf::acf_lags(&AR3, &lags, false).unwrap();
+        let cov = arima::acf::acf_lags(&AR3, &lags, true).unwrap();
+
+        for (i, &lag) in lags.iter().enumerate() {
+            assert_lt!((cor[i] - AR3_COR[lag]).abs(), 1.0e-7);
+            assert_lt!((cov[i] - AR3_COV[lag]).abs(), 1.0e-7);
+        }
+        assert!(arima::acf::acf_lags(&AR3, &[20], false).is_err());
+    }
+
+    #[test]
+    fn ar_batch_matches_ar_f64() {
+        const ORDER: usize = 3;
+        let windows: Vec<&[f64]> = AR3.windows(8).collect();
+
+        let mut ws = arima::acf::ArBatchWorkspace::new();
+        arima::acf::ar_batch(&windows, ORDER, &mut ws).unwrap();
+        assert_eq!(ws.len(), windows.len());
+
+        for (s, w) in windows.iter().enumerate() {
+            let (phi, var) = arima::acf::ar(w, Some(ORDER)).unwrap();
+            assert_eq!(ws.coef(s), &phi[..]);
+            assert_eq!(ws.var()[s], var);
+        }
+
+        let short: Vec<&[f64]> = vec![&AR3[..3]];
+        assert!(arima::acf::ar_batch(&short, ORDER, &mut ws).is_err());
+    }
+
+    #[test]
+    fn ar_coef_rho_f64() {
+        let ar_real = [0.4499776844, -0.249432051, 0.0135795645];
+        let ar_calc = arima::acf::ar_coef_rho(&AR3_COR, Some(3)).unwrap();
+
+        assert_eq!(ar_real.len(), ar_calc.len());
+        for i in 0..ar_real.len() {
+            assert_lt!((ar_real[i] - ar_calc[i]).abs(), 1.0e-7);
+        }
+    }
+
+    #[test]
+    fn ar_coef_rho_not_positive_definite() {
+        assert!(arima::acf::ar_coef_rho(&[1.0, 1.5], None).is_err());
+        assert!(arima::acf::ar_coef_rho(&[1.0, 1.0, 1.0], None).is_err());
+    }
 }
@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod test_benchmark {
+    use arima::benchmark::{self, Baseline, Drift, Forecaster, Mean, Naive, SeasonalNaive};
+    use arima::ets::{self, ErrorType, EtsSpec, SeasonType, TrendType};
+    use arima::smoothing::{self, Seasonal};
+    use arima::structural::{self, Trend};
+    use arima::tbats::{self, TbatsSpec};
+    use arima::{model, sim, theta, util};
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    fn ar1(n: usize, phi: f64, seed: u64) -> Vec<f64> {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let x = sim::arima_sim(
+            n,
+            Some(&[phi]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(seed, 0),
+        )
+        .unwrap();
+        x.iter().map(|v| 20.0 + v).collect()
+    }
+
+    #[test]
+    fn test_point_forecasts() {
+        let x = [2.0, 4.0, 3.0, 5.0, 6.0];
+        assert_eq!(Naive::fit(&x).unwrap().forecast(2), vec![6.0, 6.0]);
+        assert_eq!(Mean::fit(&x).unwrap().forecast(1), vec![4.0]);
+        assert_eq!(Drift::fit(&x).unwrap().forecast(2), vec![7.0, 8.0]);
+        assert_eq!(
+            SeasonalNaive::fit(&x, 2).unwrap().forecast(3),
+            vec![5.0, 6.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn test_forecast_variances() {
+        let x = [2.0, 4.0, 3.0, 5.0, 6.0];
+        // residuals 2, -1, 2, 1
+        let naive = Naive::fit(&x).unwrap();
+        assert_lt!((naive.sigma2 - 2.5).abs(), 1.0e-12);
+        assert_eq!(naive.forecast_variance(2), vec![2.5, 5.0]);
+
+        // residuals 1, 1, 3
+        let seasonal = SeasonalNaive::fit(&x, 2).unwrap();
+        assert_lt!((seasonal.sigma2 - 11.0 / 3.0).abs(), 1.0e-12);
+        let v = seasonal.forecast_variance(5);
+        assert_eq!(v[1], v[0]);
+        assert_eq!(v[2], 2.0 * v[0]);
+        assert_eq!(v[4], 3.0 * v[0]);
+
+        // residuals 1, -2, 1, 0 with one estimated slope
+        let drift = Drift::fit(&x).unwrap();
+        assert_lt!((drift.sigma2 - 2.0).abs(), 1.0e-12);
+        assert_lt!(
+            (drift.forecast_variance(5)[4] - 5.0 * 2.0 * 2.0).abs(),
+            1.0e-12
+        );
+
+        let mean = Mean::fit(&x).unwrap();
+        assert_lt!((mean.sigma2 - 2.5).abs(), 1.0e-12);
+        assert_lt!((mean.forecast_variance(1)[0] - 3.0).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn test_intervals() {
+        let x = ar1(200, 0.0, 11);
+        let mean = Mean::fit(&x).unwrap();
+        let (lower, upper) = mean.forecast_intervals(3, 0.95).unwrap();
+        let sd = mean.forecast_variance(1)[0].sqrt();
+        assert_lt!((upper[0] - mean.mean - 1.959964 * sd).abs(), 1.0e-5);
+        assert_lt!((mean.mean - lower[2] - 1.959964 * sd).abs(), 1.0e-5);
+        assert!(mean.forecast_intervals(3, 1.0).is_err());
+
+        // the trait is implemented by fitted models as well
+        let m = model::fit(&x, 1, 0, 0).unwrap();
+        let f: &dyn Forecaster = &m;
+        let (lower, upper) = f.forecast_intervals(2, 0.8).unwrap();
+        let point = m.forecast(2);
+        let var = m.forecast_variance(2);
+        assert_lt!(
+            (upper[1] - point[1] - 1.281552 * var[1].sqrt()).abs(),
+            1.0e-5
+        );
+        assert_lt!(lower[0], point[0]);
+    }
+
+    /// Positive monthly series with a seasonal pattern.
+    fn seasonal(n: usize, seed: u64) -> Vec<f64> {
+        ar1(n, 0.3, seed)
+            .iter()
+            .enumerate()
+            .map(|(t, v)| v + 4.0 * (2.0 * std::f64::consts::PI * t as f64 / 12.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_model_forecasters() {
+        let x = seasonal(120, 13);
+        let ses = smoothing::ses(&x, None).unwrap();
+        let hw =
+            smoothing::holt_winters(&x, 12, Seasonal::Multiplicative, None, None, None).unwrap();
+        let spec = EtsSpec {
+            error: ErrorType::Multiplicative,
+            trend: TrendType::Damped,
+            season: SeasonType::Additive,
+        };
+        let ets = ets::fit(&x, spec, 12).unwrap();
+        let theta = theta::fit(&x, None).unwrap();
+        let tbats = tbats::auto_tbats(&x, &[12.0]).unwrap();
+        let uc = structural::fit(&x, Trend::LocalLinearTrend).unwrap();
+        let models: Vec<&dyn Forecaster> = vec![&ses, &hw, &ets, &theta, &tbats, &uc];
+        for f in models {
+            let point = f.forecast(24);
+            let var = f.forecast_variance(24);
+            assert_eq!(var.len(), 24);
+            assert_lt!(0.0, var[0]);
+            // variances may follow the seasonal pattern, but grow from year to year
+            for i in 0..12 {
+                assert_lt!(var[i], var[i + 12]);
+            }
+            let (lower, upper) = f.forecast_intervals(24, 0.9).unwrap();
+            for i in 0..24 {
+                assert_lt!(lower[i], point[i]);
+                assert_lt!(point[i], upper[i]);
+            }
+            assert!(f.forecast_intervals(2, 0.0).is_err());
+        }
+        assert_eq!(
+            ses.forecast_variance(1)[0],
+            ses.sse / ses.fitted.len() as f64
+        );
+        assert_eq!(Forecaster::forecast_variance(&uc, 5), uc.forecast(5).1);
+        assert_eq!(Forecaster::forecast_variance(&theta, 1), vec![theta.sigma2]);
+    }
+
+    #[test]
+    fn test_ets_variances_match_simulation() {
+        let x = seasonal(120, 14);
+        for (error, trend, season) in [
+            (ErrorType::Additive, TrendType::Damped, SeasonType::Additive),
+            (
+                ErrorType::Multiplicative,
+                TrendType::Additive,
+                SeasonType::None,
+            ),
+            (
+                ErrorType::Multiplicative,
+                TrendType::None,
+                SeasonType::Additive,
+            ),
+            (
+                ErrorType::Additive,
+                TrendType::None,
+                SeasonType::Multiplicative,
+            ),
+        ] {
+            let spec = EtsSpec {
+                error,
+                trend,
+                season,
+            };
+            let m = ets::fit(&x, spec, 12).unwrap();
+            let mut rng = sim::stream_rng(15, 0);
+            let paths: Vec<Vec<f64>> = (0..4000).map(|_| m.simulate(15, &mut rng)).collect();
+            let var = m.forecast_variance(15);
+            for (i, v) in var.iter().enumerate() {
+                let values: Vec<f64> = paths.iter().map(|p| p[i]).collect();
+                let mean = values.iter().sum::<f64>() / 4000.0;
+                let sample = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 3999.0;
+                assert_lt!((sample / v - 1.0).abs(), 0.1, "{} at step {}", spec, i + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tbats_box_cox_intervals() {
+        let x: Vec<f64> = seasonal(120, 16).iter().map(|v| v.exp() / 1.0e8).collect();
+        let spec = TbatsSpec {
+            lambda: Some(0.0),
+            trend: false,
+            damped: false,
+            seasons: vec![(12.0, 2)],
+            ar: 0,
+            ma: 0,
+        };
+        let m = tbats::fit(&x, &spec).unwrap();
+        let f: &dyn Forecaster = &m;
+        let (lower, upper) = f.forecast_intervals(6, 0.95).unwrap();
+        let point = f.forecast(6);
+        let var = f.forecast_variance(6);
+        let sd = m.sigma2.sqrt();
+        // log-normal intervals around the median
+        assert_lt!(
+            (lower[0] - point[0] * (-1.959964 * sd).exp()).abs(),
+            1.0e-5 * point[0]
+        );
+        assert_lt!(
+            (upper[0] - point[0] * (1.959964 * sd).exp()).abs(),
+            1.0e-5 * point[0]
+        );
+        assert_lt!(
+            (var[0] - m.sigma2 * point[0].powi(2)).abs(),
+            1.0e-9 * var[0]
+        );
+        for i in 0..6 {
+            assert_lt!(0.0, lower[i]);
+            assert_lt!(point[i], upper[i]);
+        }
+        let logs = util::box_cox(&point, 0.0);
+        assert_lt!((logs[0] - (lower[0] * upper[0]).sqrt().ln()).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let mut rng = sim::stream_rng(12, 0);
+        let normal = Normal::new(0.0, 0.5).unwrap();
+        let x: Vec<f64> = (0..216)
+            .map(|t| {
+                let angle = 2.0 * std::f64::consts::PI * t as f64 / 12.0;
+                20.0 + 5.0 * angle.sin() + normal.sample(&mut rng)
+            })
+            .collect();
+        let (train, test) = x.split_at(204);
+        let m = model::fit(train, 1, 0, 0).unwrap();
+        let b = benchmark::benchmark(&m, train, test, Some(12)).unwrap();
+        let methods: Vec<Baseline> = b.baselines.iter().map(|(b, _)| *b).collect();
+        assert_eq!(
+            methods,
+            vec![
+                Baseline::Naive,
+                Baseline::SeasonalNaive,
+                Baseline::Drift,
+                Baseline::Mean
+            ]
+        );
+        // over a full season the seasonal naive forecast beats the AR(1) model
+        let seasonal = b.baselines[1].1;
+        assert_lt!(seasonal.rmse, b.model.rmse);
+        assert_lt!(seasonal.rmse, b.baselines[3].1.rmse);
+        assert!(b.not_beaten().contains(&Baseline::SeasonalNaive));
+        assert!(!b.beats_all());
+
+        // without a period the seasonal naive method is left out
+        let b = benchmark::benchmark(&m, train, test, None).unwrap();
+        assert_eq!(b.baselines.len(), 3);
+        assert!(Baseline::SeasonalNaive.fit(train, None).is_err());
+        assert!(benchmark::benchmark(&m, train, &[], None).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Naive::fit(&[1.0]).is_err());
+        assert!(Drift::fit(&[1.0, 2.0]).is_err());
+        assert!(SeasonalNaive::fit(&[1.0, 2.0, 3.0], 3).is_err());
+        assert!(SeasonalNaive::fit(&[1.0, 2.0, 3.0], 0).is_err());
+        assert!(Mean::fit(&[1.0, f64::NAN]).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod test_bootstrap {
+    use arima::{bootstrap, sim};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    fn ar1(n: usize, phi: f64, seed: u64) -> Vec<f64> {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        sim::arima_sim(
+            n,
+            Some(&[phi]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(seed, 0),
+        )
+        .unwrap()
+    }
+
+    fn bootstrap_variance_of_mean(x: &[f64], scheme: bootstrap::Scheme) -> f64 {
+        let mut rng = sim::stream_rng(101, 1);
+        let means: Vec<f64> = (0..2000)
+            .map(|_| {
+                let y = bootstrap::resample(x, scheme, &mut rng).unwrap();
+                y.iter().sum::<f64>() / y.len() as f64
+            })
+            .collect();
+        let m = means.iter().sum::<f64>() / means.len() as f64;
+        means.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (means.len() - 1) as f64
+    }
+
+    #[test]
+    fn block_length_grows_with_dependence() {
+        let noise = bootstrap::block_length(&ar1(1000, 0.0, 102)).unwrap();
+        let persistent = bootstrap::block_length(&ar1(1000, 0.8, 102)).unwrap();
+        assert_lt!(noise.stationary, 3.0);
+        assert_gt!(persistent.stationary, 8.0);
+        assert_gt!(persistent.moving, 8);
+        assert_lt!(persistent.stationary, 3.0 * 1000_f64.sqrt());
+        assert!(bootstrap::block_length(&[1.0; 20]).is_err());
+        assert!(bootstrap::block_length(&[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn variance_of_mean() {
+        // long-run variance of an AR(1) with phi = 0.5 is 1 / (1 - 0.5)^2 = 4
+        let n = 1000;
+        let x = ar1(n, 0.5, 103);
+        let target = 4.0 / n as f64;
+        let b = bootstrap::block_length(&x).unwrap();
+        for scheme in [b.moving_block(), b.stationary()] {
+            let v = bootstrap_variance_of_mean(&x, scheme);
+            assert_lt!((v / target - 1.0).abs(), 0.4);
+        }
+        // resampling single observations ignores the dependence
+        let iid = bootstrap_variance_of_mean(&x, bootstrap::Scheme::MovingBlock(1));
+        assert_lt!(iid / target, 0.5);
+    }
+
+    #[test]
+    fn stationary_block_lengths() {
+        let mut rng = sim::stream_rng(104, 0);
+        let idx =
+            bootstrap::resample_indices(100_000, bootstrap::Scheme::Stationary(5.0), &mut rng)
+                .unwrap();
+        let breaks = idx
+            .windows(2)
+            .filter(|w| w[1] != (w[0] + 1) % 100_000)
+            .count();
+        let mean = idx.len() as f64 / (breaks + 1) as f64;
+        assert_lt!((mean - 5.0).abs(), 0.2);
+    }
+
+    #[test]
+    fn invalid_schemes() {
+        let mut rng = sim::stream_rng(105, 0);
+        let x = [1.0, 2.0, 3.0];
+        assert!(bootstrap::resample(&x, bootstrap::Scheme::MovingBlock(0), &mut rng).is_err());
+        assert!(bootstrap::resample(&x, bootstrap::Scheme::MovingBlock(4), &mut rng).is_err());
+        assert!(bootstrap::resample(&x, bootstrap::Scheme::Stationary(0.5), &mut rng).is_err());
+        assert!(bootstrap::resample(&[], bootstrap::Scheme::Stationary(1.0), &mut rng).is_err());
+        let y = bootstrap::resample(&x, bootstrap::Scheme::MovingBlock(3), &mut rng).unwrap();
+        assert_eq!(y, x);
+    }
+}
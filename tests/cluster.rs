@@ -0,0 +1,301 @@
+#[cfg(test)]
+mod test_cluster {
+    use arima::{cluster, sim};
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    fn ar1(n: usize, phi: f64, stream: u64) -> Vec<f64> {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        sim::arima_sim(
+            n,
+            Some(&[phi]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(121, stream),
+        )
+        .unwrap()
+    }
+
+    /// Ten persistent and ten alternating series, interleaved.
+    fn panel() -> Vec<Vec<f64>> {
+        (0..20)
+            .map(|i| ar1(300, if i % 2 == 0 { 0.8 } else { -0.6 }, i))
+            .collect()
+    }
+
+    #[test]
+    fn features() {
+        let x = ar1(300, 0.8, 0);
+        assert_eq!(
+            cluster::features(&x, cluster::Features::Acf(5))
+                .unwrap()
+                .len(),
+            5
+        );
+        let both = cluster::features(&x, cluster::Features::AcfPacf(5)).unwrap();
+        assert_eq!(both.len(), 10);
+        // the first partial autocorrelation equals the first autocorrelation
+        assert_lt!((both[0] - both[5]).abs(), 1.0e-10);
+        assert!(cluster::features(&x, cluster::Features::Pacf(0)).is_err());
+    }
+
+    #[test]
+    fn distance_matrix() {
+        let d = cluster::distance_matrix(&panel(), cluster::Features::AcfPacf(4)).unwrap();
+        assert_eq!(d.len(), 20);
+        for (i, row) in d.iter().enumerate() {
+            assert_eq!(row[i], 0.0);
+            for (j, v) in row.iter().enumerate() {
+                assert_eq!(*v, d[j][i]);
+            }
+        }
+        #[cfg(feature = "rayon")]
+        assert_eq!(
+            cluster::distance_matrix_par(&panel(), cluster::Features::AcfPacf(4)).unwrap(),
+            d
+        );
+    }
+
+    #[test]
+    fn recovers_groups() {
+        let d = cluster::distance_matrix(&panel(), cluster::Features::Acf(5)).unwrap();
+        let expected: Vec<usize> = (0..20).map(|i| i % 2).collect();
+        for linkage in [
+            cluster::Linkage::Single,
+            cluster::Linkage::Complete,
+            cluster::Linkage::Average,
+        ] {
+            assert_eq!(cluster::hierarchical(&d, 2, linkage).unwrap(), expected);
+        }
+
+        let km = cluster::k_medoids(&d, 2).unwrap();
+        assert_eq!(km.medoids.len(), 2);
+        for (i, label) in km.labels.iter().enumerate() {
+            assert_eq!(*label, km.labels[i % 2]);
+        }
+        assert_eq!(km.labels[km.medoids[0]], 0);
+        assert_eq!(km.labels[km.medoids[1]], 1);
+
+        // one cluster per series
+        let labels = cluster::hierarchical(&d, 20, cluster::Linkage::Single).unwrap();
+        assert_eq!(labels, (0..20).collect::<Vec<_>>());
+        assert_eq!(cluster::k_medoids(&d, 20).unwrap().cost, 0.0);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let d = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        assert!(cluster::hierarchical(&d, 3, cluster::Linkage::Single).is_err());
+        assert!(cluster::k_medoids(&d, 0).is_err());
+        assert!(cluster::k_medoids(&[vec![0.0, 1.0]], 1).is_err());
+        assert!(cluster::k_medoids(&[vec![f64::NAN]], 1).is_err());
+    }
+
+    /// Random symmetric distance matrix with zero diagonal.
+    fn random_distances(n: usize, stream: u64) -> Vec<Vec<f64>> {
+        let points: Vec<Vec<f64>> = (0..n)
+            .map(|i| ar1(2, 0.0, 100 * stream + i as u64))
+            .collect();
+        let mut d = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in 0..n {
+                d[i][j] = points[i]
+                    .iter()
+                    .zip(&points[j])
+                    .map(|(a, b)| (a - b).abs())
+                    .sum();
+            }
+        }
+        d
+    }
+
+    /// PAM recomputing the total distance of every candidate swap.
+    fn k_medoids_naive(d: &[Vec<f64>], k: usize) -> f64 {
+        let n = d.len();
+        let cost = |medoids: &[usize]| -> f64 {
+            (0..n)
+                .map(|i| {
+                    medoids
+                        .iter()
+                        .map(|m| d[i][*m])
+                        .fold(f64::INFINITY, f64::min)
+                })
+                .sum()
+        };
+        let mut medoids = Vec::new();
+        while medoids.len() < k {
+            let mut best = (0, f64::INFINITY);
+            let candidates: Vec<usize> = (0..n).filter(|c| !medoids.contains(c)).collect();
+            for c in candidates {
+                medoids.push(c);
+                if cost(&medoids) < best.1 {
+                    best = (c, cost(&medoids));
+                }
+                medoids.pop();
+            }
+            medoids.push(best.0);
+        }
+        let mut total = cost(&medoids);
+        loop {
+            let mut best = (0, 0, total);
+            let candidates: Vec<usize> = (0..n).filter(|c| !medoids.contains(c)).collect();
+            for m in 0..k {
+                for c in candidates.iter().copied() {
+                    let old = medoids[m];
+                    medoids[m] = c;
+                    if cost(&medoids) < best.2 {
+                        best = (m, c, cost(&medoids));
+                    }
+                    medoids[m] = old;
+                }
+            }
+            if best.2 < total {
+                medoids[best.0] = best.1;
+                total = best.2;
+            } else {
+                return total;
+            }
+        }
+    }
+
+    /// Agglomerative clustering recomputing the linkage of every pair of clusters.
+    fn hierarchical_naive(d: &[Vec<f64>], k: usize, linkage: cluster::Linkage) -> Vec<usize> {
+        let mut clusters: Vec<Vec<usize>> = (0..d.len()).map(|i| vec![i]).collect();
+        while clusters.len() > k {
+            let mut best = (0, 1, f64::INFINITY);
+            for a in 0..clusters.len() {
+                for b in a + 1..clusters.len() {
+                    let pairs = clusters[a]
+                        .iter()
+                        .flat_map(|i| clusters[b].iter().map(move |j| d[*i][*j]));
+                    let dist = match linkage {
+                        cluster::Linkage::Single => pairs.fold(f64::INFINITY, f64::min),
+                        cluster::Linkage::Complete => pairs.fold(f64::NEG_INFINITY, f64::max),
+                        cluster::Linkage::Average => {
+                            pairs.sum::<f64>() / (clusters[a].len() * clusters[b].len()) as f64
+                        }
+                    };
+                    if dist < best.2 {
+                        best = (a, b, dist);
+                    }
+                }
+            }
+            let merged = clusters.remove(best.1);
+            clusters[best.0].extend(merged);
+        }
+        let mut labels = vec![0; d.len()];
+        clusters.sort_by_key(|c| *c.iter().min().unwrap());
+        for (label, members) in clusters.iter().enumerate() {
+            for i in members {
+                labels[*i] = label;
+            }
+        }
+        labels
+    }
+
+    #[test]
+    fn matches_naive_algorithms() {
+        for stream in 0..5 {
+            let d = random_distances(30, stream);
+            for k in [1, 2, 3, 5, 8] {
+                let km = cluster::k_medoids(&d, k).unwrap();
+                // medoid sets may differ between equally good swaps, e.g. in clusters of
+                // two series, but not the cost
+                let cost = k_medoids_naive(&d, k);
+                assert_lt!((km.cost - cost).abs(), 1.0e-9);
+                let total: f64 = (0..d.len())
+                    .map(|i| {
+                        km.medoids
+                            .iter()
+                            .map(|m| d[i][*m])
+                            .fold(f64::INFINITY, f64::min)
+                    })
+                    .sum();
+                assert_lt!((km.cost - total).abs(), 1.0e-9);
+                for (i, label) in km.labels.iter().enumerate() {
+                    let nearest = km
+                        .medoids
+                        .iter()
+                        .map(|m| d[i][*m])
+                        .fold(f64::INFINITY, f64::min);
+                    assert_eq!(d[i][km.medoids[*label]], nearest);
+                }
+                for linkage in [
+                    cluster::Linkage::Single,
+                    cluster::Linkage::Complete,
+                    cluster::Linkage::Average,
+                ] {
+                    assert_eq!(
+                        cluster::hierarchical(&d, k, linkage).unwrap(),
+                        hierarchical_naive(&d, k, linkage)
+                    );
+                }
+            }
+        }
+    }
+
+    /// Full dynamic programming table without a band.
+    fn dtw_naive(a: &[f64], b: &[f64]) -> f64 {
+        let mut d = vec![vec![f64::INFINITY; b.len() + 1]; a.len() + 1];
+        d[0][0] = 0.0;
+        for i in 1..a.len() + 1 {
+            for j in 1..b.len() + 1 {
+                d[i][j] =
+                    (a[i - 1] - b[j - 1]).abs() + d[i - 1][j - 1].min(d[i - 1][j]).min(d[i][j - 1]);
+            }
+        }
+        d[a.len()][b.len()]
+    }
+
+    #[test]
+    fn dtw() {
+        let a = ar1(40, 0.5, 30);
+        let b = ar1(35, 0.5, 31);
+        let full = cluster::dtw(&a, &b, None).unwrap();
+        assert_lt!((full - dtw_naive(&a, &b)).abs(), 1.0e-10);
+        assert_lt!((full - cluster::dtw(&b, &a, None).unwrap()).abs(), 1.0e-10);
+
+        // narrower bands allow fewer alignments
+        let mut last = full;
+        for window in (5..40).rev() {
+            let d = cluster::dtw(&a, &b, Some(window)).unwrap();
+            assert!(d >= last - 1.0e-10);
+            last = d;
+        }
+        assert!(cluster::dtw(&a, &b, Some(4)).is_err());
+        assert!(cluster::dtw(&a, &[], None).is_err());
+
+        // without warping the distance is the sum of absolute differences
+        assert_eq!(cluster::dtw(&a, &a, Some(0)).unwrap(), 0.0);
+        let c = ar1(40, 0.5, 32);
+        let pointwise: f64 = a.iter().zip(&c).map(|(u, v)| (u - v).abs()).sum();
+        assert_lt!(
+            (cluster::dtw(&a, &c, Some(0)).unwrap() - pointwise).abs(),
+            1.0e-10
+        );
+    }
+
+    #[test]
+    fn dtw_clusters_shifted_patterns() {
+        // bumps and dips at different positions
+        let shape = |center: f64, sign: f64| -> Vec<f64> {
+            (0..50)
+                .map(|t| sign * (-(t as f64 - center).powi(2) / 8.0).exp())
+                .collect()
+        };
+        let series = vec![
+            shape(15.0, 1.0),
+            shape(20.0, -1.0),
+            shape(25.0, 1.0),
+            shape(30.0, -1.0),
+            shape(35.0, 1.0),
+        ];
+        let d = cluster::dtw_matrix(&series, Some(25)).unwrap();
+        assert_eq!(d[1][1], 0.0);
+        assert_eq!(d[0][2], d[2][0]);
+        assert_lt!(d[0][4], d[0][1]);
+        let labels = cluster::hierarchical(&d, 2, cluster::Linkage::Complete).unwrap();
+        assert_eq!(labels, vec![0, 1, 0, 1, 0]);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test_count {
+    use arima::count::{self, CountDistribution};
+    use arima::sim;
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Gamma, Poisson};
+
+    // INGARCH(1, 1) with omega = 1, alpha = 0.3, beta = 0.4, stationary mean 3.33
+    fn simulate(n: usize, size: Option<f64>) -> Vec<f64> {
+        let mut rng = sim::stream_rng(41, 0);
+        let mut y = 0.0;
+        let mut lambda = 10.0 / 3.0;
+        let mut x = Vec::with_capacity(n);
+        for t in 0..n + 100 {
+            lambda = 1.0 + 0.3 * y + 0.4 * lambda;
+            let mean = match size {
+                Some(s) => Gamma::new(s, lambda / s).unwrap().sample(&mut rng),
+                None => lambda,
+            };
+            y = Poisson::new(mean).unwrap().sample(&mut rng);
+            if t >= 100 {
+                x.push(y);
+            }
+        }
+        x
+    }
+
+    #[test]
+    fn test_poisson() {
+        let x = simulate(2000, None);
+        let m = count::fit(&x, 1, 1, CountDistribution::Poisson).unwrap();
+        assert_lt!((m.alpha[0] - 0.3).abs(), 0.08);
+        assert_lt!((m.beta[0] - 0.4).abs(), 0.15);
+        let mean = m.omega / (1.0 - m.alpha[0] - m.beta[0]);
+        assert_lt!((mean - 10.0 / 3.0).abs(), 0.3);
+        assert_eq!(m.intensity.len(), 2000);
+    }
+
+    #[test]
+    fn test_negative_binomial() {
+        let x = simulate(2000, Some(2.0));
+        let nb = count::fit(&x, 1, 1, CountDistribution::NegativeBinomial).unwrap();
+        let poisson = count::fit(&x, 1, 1, CountDistribution::Poisson).unwrap();
+        assert_lt!((nb.size.unwrap() - 2.0).abs(), 0.5);
+        assert_lt!(nb.aic(), poisson.aic());
+        // Pearson residuals have unit variance under the right distribution
+        let var = nb.residuals.iter().map(|r| r * r).sum::<f64>() / nb.n as f64;
+        assert_lt!((var - 1.0).abs(), 0.2);
+    }
+
+    #[test]
+    fn test_simulated_intervals() {
+        let x = simulate(500, None);
+        let m = count::fit(&x, 1, 0, CountDistribution::Poisson).unwrap();
+        let mut rng = sim::stream_rng(42, 0);
+        let (lower, upper) = m.simulated_intervals(5, 0.9, 2000, &mut rng).unwrap();
+        let f = m.forecast(5);
+        for i in 0..5 {
+            assert!(lower[i] <= f[i] && f[i] <= upper[i]);
+            assert_eq!(lower[i].fract(), 0.0);
+        }
+        let pmf = m.forecast_pmf(50);
+        let mean: f64 = pmf.iter().enumerate().map(|(k, p)| k as f64 * p).sum();
+        assert_lt!((mean - f[0]).abs(), 1.0e-6);
+        assert_gt!(pmf[0], 0.0);
+    }
+
+    #[test]
+    fn test_invalid_counts() {
+        assert!(count::fit(&[1.0, 2.5, 3.0, 1.0, 0.0], 1, 0, CountDistribution::Poisson).is_err());
+        assert!(count::fit(
+            &[1.0, -1.0, 3.0, 1.0, 0.0],
+            1,
+            0,
+            CountDistribution::Poisson
+        )
+        .is_err());
+    }
+}
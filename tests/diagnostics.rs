@@ -0,0 +1,210 @@
+#[cfg(test)]
+mod test_diagnostics {
+    use arima::{acf, diagnostics, model, sim};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    fn ar1(n: usize, phi: f64) -> Vec<f64> {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        sim::arima_sim(
+            n,
+            Some(&[phi]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(71, 0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ljung_box() {
+        let x = ar1(200, 0.0);
+        let lb = diagnostics::ljung_box(&x, 2, 0).unwrap();
+        let rho = acf::acf(&x, Some(2), false).unwrap();
+        let n = 200.0;
+        let q = n * (n + 2.0) * (rho[1] * rho[1] / (n - 1.0) + rho[2] * rho[2] / (n - 2.0));
+        assert_lt!((lb.statistic - q).abs(), 1.0e-9);
+        // the chi-squared distribution with 2 degrees of freedom is exponential
+        assert_lt!((lb.p_value - (-0.5 * q).exp()).abs(), 1.0e-9);
+        assert!(diagnostics::ljung_box(&x, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_white_noise_residuals() {
+        let x = ar1(500, 0.6);
+        let m = model::fit(&x, 1, 0, 0).unwrap();
+        let d = diagnostics::diagnostics(&m).unwrap();
+        assert_eq!(d.standardized.len(), 499);
+        let var = d.standardized.iter().map(|r| r * r).sum::<f64>() / 499.0;
+        assert_lt!((var - 1.0).abs(), 1.0e-9);
+        assert_eq!(d.ljung_box.len(), diagnostics::DIAGNOSTICS_LAGS);
+        assert_eq!(d.ljung_box[9].df, 9);
+        for lb in d.ljung_box.iter().skip(1) {
+            assert_gt!(lb.p_value, 0.01);
+        }
+    }
+
+    #[test]
+    fn test_misspecified_model() {
+        let x = ar1(500, 0.6);
+        let m = model::fit(&x, 0, 0, 0).unwrap();
+        let d = diagnostics::diagnostics_lags(&m, 5).unwrap();
+        assert_eq!(d.acf.len(), 6);
+        assert!(d.significant_lags().contains(&1));
+        for lb in &d.ljung_box {
+            assert_lt!(lb.p_value, 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_normality() {
+        let x = ar1(1000, 0.0);
+        let report = diagnostics::normality(&x).unwrap();
+        assert_lt!(report.skewness.abs(), 0.2);
+        assert_lt!(report.excess_kurtosis.abs(), 0.4);
+        assert_gt!(report.p_value, 0.01);
+        // sample and theoretical quantiles agree for normal data
+        for (theoretical, sample) in report.qq.iter().skip(50).take(900) {
+            assert_lt!((theoretical - sample).abs(), 0.2);
+        }
+        // the median plotting position is zero
+        let x = [3.0, 1.0, 2.0];
+        let report = diagnostics::normality(&x).unwrap();
+        assert_lt!(report.qq[1].0.abs(), 1.0e-12);
+        assert_lt!((report.qq[2].0 - 0.869_423_773).abs(), 1.0e-6);
+    }
+
+    #[test]
+    fn test_normality_heavy_tails() {
+        // squared normal noise is strongly skewed
+        let x: Vec<f64> = ar1(500, 0.0).iter().map(|v| v * v).collect();
+        let report = diagnostics::normality(&x).unwrap();
+        assert_gt!(report.skewness, 1.0);
+        assert_lt!(report.p_value, 1.0e-6);
+        assert!(diagnostics::normality(&[1.0, 1.0, 1.0]).is_err());
+        for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.0e100] {
+            assert!(diagnostics::normality(&[1.0, v, 2.0, 0.5]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_mcleod_li() {
+        // ARCH(1) errors are uncorrelated, but their squares are not
+        let mut rng = sim::stream_rng(72, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut e: f64 = 0.0;
+        let x: Vec<f64> = (0..1000)
+            .map(|_| {
+                e = (0.2 + 0.7 * e * e).sqrt() * normal.sample(&mut rng);
+                e
+            })
+            .collect();
+        let ml = diagnostics::mcleod_li(&x, 10).unwrap();
+        assert_eq!(ml.df, 10);
+        assert_lt!(ml.p_value, 1.0e-6);
+
+        let ml = diagnostics::mcleod_li(&ar1(1000, 0.0), 10).unwrap();
+        assert_gt!(ml.p_value, 0.01);
+    }
+
+    #[test]
+    fn test_out_of_sample() {
+        let x = ar1(700, 0.6);
+        let m = model::fit(&x[..400], 1, 0, 0).unwrap();
+        let report = diagnostics::out_of_sample(&m, &x[400..], 3, &[0.8, 0.95]).unwrap();
+        assert_eq!(report.horizons.len(), 3);
+        assert_eq!(report.horizons[2].horizon, 3);
+        assert_eq!(report.horizons[2].errors.len(), 298);
+
+        // one-step errors are the residuals with the fitted coefficients
+        let coef = [m.intercept, m.phi[0]];
+        let full = model::FittedModel::from_coef(&x, &coef, 1, 0, 0).unwrap();
+        for (e, r) in report.horizons[0].errors.iter().zip(&full.residuals[400..]) {
+            assert_lt!((e - r).abs(), 1.0e-9);
+        }
+
+        for horizon in &report.horizons {
+            assert_lt!((horizon.coverage[0] - 0.8).abs(), 0.08);
+            assert_lt!((horizon.coverage[1] - 0.95).abs(), 0.05);
+        }
+        assert_eq!(report.pit.len(), 300);
+        assert_eq!(report.pit_histogram.iter().sum::<usize>(), 300);
+        assert_gt!(report.pit_p_value, 0.01);
+
+        assert!(diagnostics::out_of_sample(&m, &x[400..], 0, &[0.8]).is_err());
+        assert!(diagnostics::out_of_sample(&m, &x[400..], 3, &[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_influence() {
+        let mut x = ar1(200, 0.5);
+        x[120] += 8.0;
+        let m = model::fit(&x, 1, 0, 0).unwrap();
+        let infl = diagnostics::influence(&m).unwrap();
+        assert_eq!(infl.index.len(), 199);
+        assert_eq!(infl.coef_change[0].len(), 2);
+        // the outlier enters the residuals at 120 and, as a lagged value, at 121
+        let top = infl.most_influential(2);
+        assert!(top.contains(&120) || top.contains(&121));
+        for ld in &infl.likelihood_displacement {
+            assert_gt!(*ld, -1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_diebold_mariano() {
+        let x = ar1(400, 0.0);
+        let e1: Vec<f64> = x[..200].to_vec();
+        let e2: Vec<f64> = x[200..].to_vec();
+        let dm = diagnostics::diebold_mariano(&e1, &e2, 1, diagnostics::Loss::Squared).unwrap();
+        assert_eq!(dm.df, 199);
+
+        // one-step statistic from its definition
+        let d: Vec<f64> = e1.iter().zip(&e2).map(|(a, b)| a * a - b * b).collect();
+        let n = d.len() as f64;
+        let mean = d.iter().sum::<f64>() / n;
+        let var = d.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let expected = ((n - 1.0) / n).sqrt() * mean / (var / n).sqrt();
+        assert_lt!((dm.statistic - expected).abs(), 1.0e-10);
+        assert_lt!(
+            (dm.p_value - 2.0 * dm.p_value_less.min(1.0 - dm.p_value_less)).abs(),
+            1.0e-10
+        );
+        // independent errors of the same distribution
+        assert_gt!(dm.p_value, 0.01);
+
+        // swapping the errors flips the statistic
+        let swapped =
+            diagnostics::diebold_mariano(&e2, &e1, 1, diagnostics::Loss::Squared).unwrap();
+        assert_lt!((swapped.statistic + dm.statistic).abs(), 1.0e-12);
+
+        let scaled: Vec<f64> = e2.iter().map(|e| 2.0 * e).collect();
+        let dm =
+            diagnostics::diebold_mariano(&e1, &scaled, 3, diagnostics::Loss::Absolute).unwrap();
+        assert_lt!(dm.p_value_less, 0.001);
+
+        assert!(diagnostics::diebold_mariano(&e1, &e1, 1, diagnostics::Loss::Squared).is_err());
+        assert!(
+            diagnostics::diebold_mariano(&e1, &e2[1..], 1, diagnostics::Loss::Squared).is_err()
+        );
+        assert!(diagnostics::diebold_mariano(&e1, &e2, 0, diagnostics::Loss::Squared).is_err());
+    }
+
+    #[test]
+    fn test_compare_forecasts() {
+        let x = ar1(600, 0.8);
+        let ar = model::fit(&x[..400], 1, 0, 0).unwrap();
+        let mean = model::fit(&x[..400], 0, 0, 0).unwrap();
+        let dm =
+            diagnostics::compare_forecasts(&ar, &mean, &x[400..], 1, diagnostics::Loss::Squared)
+                .unwrap();
+        assert_lt!(dm.mean_differential, 0.0);
+        assert_lt!(dm.p_value_less, 0.01);
+        let dm =
+            diagnostics::compare_forecasts(&ar, &mean, &x[400..], 2, diagnostics::Loss::Squared)
+                .unwrap();
+        assert_eq!(dm.horizon, 2);
+        assert_eq!(dm.df, 198);
+    }
+}
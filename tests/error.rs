@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod test_error {
+    use arima::acf;
+    use arima::error::{condition_threshold, ConditioningError, ConditioningKind};
+
+    #[test]
+    fn ar_coef_rho_conditioning() {
+        // rho_2 = 1 makes the Toeplitz matrix of rho_0..rho_2 singular
+        let rho = [1.0_f64, 0.5, 1.0, 0.2];
+        assert!(acf::ar_coef_rho(&rho, Some(1)).is_ok());
+        let err = acf::ar_coef_rho(&rho, Some(3)).unwrap_err();
+        let cond = err.downcast_ref::<ConditioningError>().unwrap();
+        assert_eq!(cond.kind, ConditioningKind::NotPositiveDefinite);
+        assert_eq!(cond.order, 3);
+        assert_eq!(cond.info, None);
+        assert!(cond.condition.is_infinite());
+        assert!(err
+            .to_string()
+            .starts_with("Matrix is not positive-definite at order 3"));
+    }
+
+    #[test]
+    fn ar_coef_rho_condition_estimate() {
+        // AR(1) auto-correlations: the inverse of the Toeplitz matrix is tridiagonal,
+        // so the 1-norm condition number is (1 + phi)^3 / (1 - phi) for order 4
+        let phi = 0.9_f64;
+        let rho: Vec<f64> = (0..5).map(|k| phi.powi(k)).collect();
+        let solution = acf::ar_coef_rho_checked(&rho, Some(4)).unwrap();
+        let expected = (1.0 + phi).powi(3) / (1.0 - phi);
+        assert!((solution.condition - expected).abs() < 1.0e-9 * expected);
+        assert!(solution.warning.is_none());
+        assert!((solution.coef[0] - phi).abs() < 1.0e-12);
+    }
+
+    #[test]
+    fn ill_conditioned_warning() {
+        assert!((condition_threshold::<f64>() - 6.7108864e7).abs() < 1.0);
+        assert!((condition_threshold::<f32>() - 2896.3093).abs() < 1.0e-3);
+
+        // condition number (1 + phi) / (1 - phi) of AR(1) auto-correlations at order 2
+        for &(phi, warn) in [(0.999_f64, false), (1.0 - 1.0e-8, true)].iter() {
+            let rho = [1.0, phi, phi * phi];
+            let solution = acf::ar_coef_rho_checked(&rho, Some(2)).unwrap();
+            let expected = (1.0 + phi) / (1.0 - phi);
+            assert!((solution.condition / expected - 1.0).abs() < 1.0e-6);
+            assert_eq!(solution.warning.is_some(), warn);
+            if let Some(warning) = solution.warning {
+                assert_eq!(warning.kind, ConditioningKind::IllConditioned);
+                assert_eq!(warning.order, 2);
+                assert!(warning
+                    .to_string()
+                    .starts_with("Matrix is ill-conditioned at order 2"));
+            }
+            // the unchecked variant still returns the coefficients
+            assert_eq!(acf::ar_coef_rho(&rho, Some(2)).unwrap(), solution.coef);
+        }
+    }
+
+    #[test]
+    fn indefinite_minor_has_finite_condition() {
+        let rho = [1.0_f64, 0.9, -0.9];
+        let err = acf::ar_coef_rho(&rho, Some(2)).unwrap_err();
+        let cond = err.downcast_ref::<ConditioningError>().unwrap();
+        assert_eq!(cond.kind, ConditioningKind::NotPositiveDefinite);
+        assert_eq!(cond.order, 3);
+        assert!(cond.condition.is_finite() && cond.condition > 1.0);
+    }
+
+    #[cfg(feature = "lapack")]
+    #[test]
+    fn ar_lapack_rho_info() {
+        let rho = [1.0_f64, 1.0, 0.5];
+        let err = acf::ar_lapack_rho(&rho, Some(2)).unwrap_err();
+        let cond = err.downcast_ref::<ConditioningError>().unwrap();
+        assert_eq!(cond.info, Some(2));
+        assert_eq!(cond.order, 2);
+    }
+
+    #[cfg(feature = "lapack")]
+    #[test]
+    fn ar_lapack_ls_collinear() {
+        let x = [1.0_f32; 8];
+        let err = acf::ar_lapack_ls(&x, 2).unwrap_err();
+        let cond = err.downcast_ref::<ConditioningError>().unwrap();
+        assert_eq!(cond.kind, ConditioningKind::Singular);
+        assert!(cond.info.unwrap() > 0);
+    }
+
+    #[cfg(feature = "lapack")]
+    #[test]
+    #[should_panic]
+    fn lapack_short_slices() {
+        use arima::acf::LapackFloat;
+        let mut a = [1.0_f64; 3];
+        let mut b = [1.0_f64; 2];
+        f64::posv(b'L', 2, &mut a, &mut b);
+    }
+}
@@ -155,4 +155,27 @@ mod test_estimate {
         assert_lt!((coef[2] - 1.0564438).abs(), 1.0e-2); // MA 1
         assert_lt!((coef[3] - 1.5102864).abs(), 1.0e-2); // MA 2
     }
+
+    #[test]
+    fn fit_reproducible() {
+        let a = arima::estimate::fit(&AR3, 1, 0, 1).unwrap();
+        let b = arima::estimate::fit(&AR3, 1, 0, 1).unwrap();
+
+        let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<u64>>();
+        assert_eq!(bits(&a), bits(&b));
+    }
+
+    #[test]
+    fn css_matches_residuals_f64() {
+        let (y, _mean) = arima::util::center(&AR3);
+        let phi = [0.48359302];
+        let theta = [1.05643909, 1.51029256];
+
+        let residuals =
+            arima::estimate::residuals(&y, -23.64706, Some(&phi), Some(&theta)).unwrap();
+        let expected: f64 = residuals.iter().map(|r| r * r).sum();
+        let css = arima::estimate::css(&y, -23.64706, Some(&phi), Some(&theta)).unwrap();
+
+        assert_lt!((css - expected).abs(), 1.0e-9);
+    }
 }
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod test_ets {
+    use arima::ets::{self, ErrorType, EtsSpec, SeasonType, TrendType};
+    use arima::sim;
+    use more_asserts::{assert_ge, assert_gt, assert_lt};
+
+    fn seasonal_series(n: usize) -> Vec<f64> {
+        let pattern = [1.3, 0.8, 1.1, 0.9, 1.0, 0.9];
+        let mut rng = sim::stream_rng(7, 0);
+        let noise: Vec<f64> = (0..n)
+            .map(|_| rand::Rng::gen_range(&mut rng, -0.02..0.02))
+            .collect();
+        (0..n)
+            .map(|i| (100.0 + 0.5 * i as f64) * pattern[i % 6] * (1.0 + noise[i]))
+            .collect()
+    }
+
+    #[test]
+    fn spec_display() {
+        let spec = EtsSpec {
+            error: ErrorType::Multiplicative,
+            trend: TrendType::Damped,
+            season: SeasonType::Additive,
+        };
+        assert_eq!(spec.to_string(), "ETS(M,Ad,A)");
+    }
+
+    #[test]
+    fn fit_all_specs() {
+        let x = seasonal_series(60);
+        for &error in [ErrorType::Additive, ErrorType::Multiplicative].iter() {
+            for &trend in [TrendType::None, TrendType::Additive, TrendType::Damped].iter() {
+                for &season in [
+                    SeasonType::None,
+                    SeasonType::Additive,
+                    SeasonType::Multiplicative,
+                ]
+                .iter()
+                {
+                    let spec = EtsSpec {
+                        error,
+                        trend,
+                        season,
+                    };
+                    let m = ets::fit(&x, spec, 6).unwrap();
+                    assert_eq!(m.residuals.len(), x.len());
+                    assert!(m.loglik.is_finite());
+                    assert_gt!(m.alpha, 0.0);
+                    assert_lt!(m.alpha, 1.0);
+                    assert_eq!(m.beta.is_some(), trend != TrendType::None);
+                    assert_eq!(m.gamma.is_some(), season != SeasonType::None);
+                    if let Some(phi) = m.phi {
+                        assert_ge!(phi, 0.8);
+                        assert_lt!(phi, 0.98);
+                    }
+                    assert_eq!(m.forecast(12).len(), 12);
+                    // alpha, initial level and variance, plus trend and seasonal terms
+                    let k = 3
+                        + 2 * (trend != TrendType::None) as usize
+                        + (trend == TrendType::Damped) as usize
+                        + 6 * (season != SeasonType::None) as usize;
+                    assert_eq!(m.n_params(), k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn auto_ets_selects_seasonal_trend() {
+        let x = seasonal_series(72);
+        let m = ets::auto_ets(&x, 6).unwrap();
+        assert_eq!(m.spec.season, SeasonType::Multiplicative);
+        assert_ne!(m.spec.trend, TrendType::None);
+
+        let f = m.forecast(6);
+        let pattern = [1.3, 0.8, 1.1, 0.9, 1.0, 0.9];
+        for (h, v) in f.iter().enumerate() {
+            let i = 72 + h;
+            let truth = (100.0 + 0.5 * i as f64) * pattern[i % 6];
+            assert_lt!((v - truth).abs() / truth, 0.05);
+        }
+    }
+
+    #[test]
+    fn auto_ets_non_positive() {
+        // multiplicative components must not be considered
+        let x = [0.5, -1.0, 0.3, 0.8, -0.2, 0.1, 0.4, -0.6, 0.2, 0.0];
+        let m = ets::auto_ets(&x, 1).unwrap();
+        assert_eq!(m.spec.error, ErrorType::Additive);
+        assert_eq!(m.spec.season, SeasonType::None);
+    }
+
+    #[test]
+    fn intervals_widen() {
+        let x = seasonal_series(48);
+        let m = ets::auto_ets(&x, 6).unwrap();
+        let mut rng = sim::stream_rng(3, 0);
+        let (lower, upper) = m.simulated_intervals(12, 0.95, 1000, &mut rng).unwrap();
+        let f = m.forecast(12);
+        for i in 0..12 {
+            assert_lt!(lower[i], f[i]);
+            assert_gt!(upper[i], f[i]);
+        }
+        // compare horizons in the same season
+        assert_gt!(upper[6] - lower[6], upper[0] - lower[0]);
+        assert!(m.simulated_intervals(2, 1.5, 10, &mut rng).is_err());
+    }
+
+    #[test]
+    fn invalid_input() {
+        let spec = EtsSpec {
+            error: ErrorType::Multiplicative,
+            trend: TrendType::None,
+            season: SeasonType::None,
+        };
+        assert!(ets::fit(&[1.0, -1.0, 2.0, 3.0], spec, 1).is_err());
+        let spec = EtsSpec {
+            error: ErrorType::Additive,
+            trend: TrendType::None,
+            season: SeasonType::Additive,
+        };
+        assert!(ets::fit(&[1.0; 10], spec, 6).is_err());
+    }
+}
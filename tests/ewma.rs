@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod test_ewma {
+    use arima::{ewma, sim};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    #[test]
+    fn test_mean_and_variance_match_weighted_sums() {
+        let x = [2.0, 4.0, 3.0, 7.0, 5.0, 6.0, 1.0, 3.0];
+        let alpha = 0.25;
+        let mut e = ewma::Ewma::new(alpha).unwrap();
+        e.extend(x.iter().copied());
+        assert_eq!(e.len(), x.len());
+
+        // weights (1 - alpha)^(n - 1) for the first value, alpha (1 - alpha)^(n - 1 - t)
+        // for the others
+        let n = x.len();
+        let w: Vec<f64> = (0..n)
+            .map(|t| {
+                if t == 0 {
+                    (1.0f64 - alpha).powi(n as i32 - 1)
+                } else {
+                    alpha * (1.0f64 - alpha).powi((n - 1 - t) as i32)
+                }
+            })
+            .collect();
+        let mean: f64 = w.iter().zip(&x).map(|(w, x)| w * x).sum();
+        let variance: f64 = w.iter().zip(&x).map(|(w, x)| w * (x - mean).powi(2)).sum();
+        assert_lt!((e.mean().unwrap() - mean).abs(), 1.0e-12);
+        assert_lt!((e.variance().unwrap() - variance).abs(), 1.0e-12);
+        assert_lt!((e.std_dev().unwrap() - variance.sqrt()).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn test_empty_and_single_value() {
+        let mut e = ewma::Ewma::new(0.5).unwrap();
+        assert!(e.is_empty());
+        assert_eq!(e.mean(), None);
+        e.push(3.0);
+        assert_eq!(e.mean(), Some(3.0));
+        assert_eq!(e.variance(), None);
+        assert_eq!(e.score(4.0), None);
+    }
+
+    #[test]
+    fn test_halflife() {
+        let e = ewma::Ewma::from_halflife(5.0f64).unwrap();
+        assert_lt!(((1.0 - e.alpha()).powi(5) - 0.5).abs(), 1.0e-12);
+        assert!(ewma::Ewma::from_halflife(0.0).is_err());
+    }
+
+    #[test]
+    fn test_invalid_alpha() {
+        assert!(ewma::Ewma::new(0.0).is_err());
+        assert!(ewma::Ewma::new(1.5).is_err());
+        assert!(ewma::ewma(&[1.0, 2.0], f64::NAN).is_err());
+        assert!(ewma::Ewma::new(1.0).is_ok());
+    }
+
+    #[test]
+    fn test_tracks_level_shift() {
+        let mut x = vec![0.0f64; 50];
+        x.extend(vec![10.0; 50]);
+        let m = ewma::ewma(&x, 0.2).unwrap();
+        assert_eq!(m.len(), x.len());
+        assert_eq!(m[49], 0.0);
+        assert_lt!((m[99] - 10.0).abs(), 1.0e-3);
+    }
+
+    #[test]
+    fn test_anomaly_scores() {
+        let mut rng = sim::stream_rng(42, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut x: Vec<f64> = (0..500).map(|_| normal.sample(&mut rng)).collect();
+        x[300] += 10.0;
+        let s = ewma::anomaly_scores(&x, 0.05).unwrap();
+        assert_eq!(s.len(), x.len());
+        assert!(s[0].is_nan() && s[1].is_nan());
+        assert_gt!(s[300], 5.0);
+        // after burn-in, scores of the noise are roughly standard normal
+        let rest: Vec<f64> = s[50..300].to_vec();
+        let mean = rest.iter().sum::<f64>() / rest.len() as f64;
+        let var = rest.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / rest.len() as f64;
+        assert_lt!(mean.abs(), 0.3);
+        assert_lt!((var - 1.0).abs(), 0.35);
+        assert_lt!(rest.iter().filter(|v| v.abs() > 3.0).count(), 5);
+    }
+
+    #[test]
+    fn test_f32() {
+        let m = ewma::ewma(&[1.0f32, 2.0, 3.0], 0.5).unwrap();
+        assert_eq!(m, vec![1.0f32, 1.5, 2.25]);
+    }
+}
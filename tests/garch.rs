@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod test_garch {
+    use arima::garch::{self, GarchKind, GarchSpec, Innovations};
+    use arima::sim;
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal, StudentT};
+
+    // returns with mean 0.1 and GJR-GARCH(1, 1) variance; gamma = 0 gives a GARCH(1, 1)
+    fn simulate(n: usize, gamma: f64, nu: Option<f64>) -> Vec<f64> {
+        let mut rng = sim::stream_rng(51, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut e: f64 = 0.0;
+        let mut s2 = 1.0;
+        let mut x = Vec::with_capacity(n);
+        for t in 0..n + 100 {
+            let neg = if e < 0.0 { e * e } else { 0.0 };
+            s2 = 0.1 + 0.1 * e * e + gamma * neg + 0.8 * s2;
+            let z = match nu {
+                Some(nu) => StudentT::new(nu).unwrap().sample(&mut rng) * ((nu - 2.0) / nu).sqrt(),
+                None => normal.sample(&mut rng),
+            };
+            e = s2.sqrt() * z;
+            if t >= 100 {
+                x.push(0.1 + e);
+            }
+        }
+        x
+    }
+
+    fn spec(kind: GarchKind, innovations: Innovations) -> GarchSpec {
+        GarchSpec {
+            kind,
+            p: 1,
+            q: 1,
+            innovations,
+        }
+    }
+
+    #[test]
+    fn test_garch() {
+        let x = simulate(3000, 0.0, None);
+        let m = garch::fit(&x, &spec(GarchKind::Garch, Innovations::Normal)).unwrap();
+        assert_lt!((m.mu - 0.1).abs(), 0.05);
+        assert_lt!((m.alpha[0] - 0.1).abs(), 0.04);
+        assert_lt!((m.beta[0] - 0.8).abs(), 0.08);
+        assert!(m.gamma.is_empty());
+        assert_eq!(m.n_params(), 4);
+    }
+
+    #[test]
+    fn test_gjr() {
+        let x = simulate(3000, 0.1, None);
+        let gjr = garch::fit(&x, &spec(GarchKind::Gjr, Innovations::Normal)).unwrap();
+        let plain = garch::fit(&x, &spec(GarchKind::Garch, Innovations::Normal)).unwrap();
+        assert_lt!((gjr.gamma[0] - 0.1).abs(), 0.06);
+        assert_lt!(gjr.aic(), plain.aic());
+    }
+
+    #[test]
+    fn test_egarch() {
+        let x = simulate(3000, 0.1, None);
+        let m = garch::fit(&x, &spec(GarchKind::Egarch, Innovations::Normal)).unwrap();
+        // negative shocks raise the volatility
+        assert_lt!(m.gamma[0], 0.0);
+        assert_gt!(m.alpha[0], 0.0);
+        assert_gt!(m.beta[0], 0.5);
+    }
+
+    #[test]
+    fn test_student_t() {
+        let x = simulate(3000, 0.0, Some(5.0));
+        let t = garch::fit(&x, &spec(GarchKind::Garch, Innovations::StudentT)).unwrap();
+        let normal = garch::fit(&x, &spec(GarchKind::Garch, Innovations::Normal)).unwrap();
+        let nu = t.nu.unwrap();
+        assert_gt!(nu, 3.5);
+        assert_lt!(nu, 8.0);
+        assert_lt!(t.aic(), normal.aic());
+    }
+
+    #[test]
+    fn test_value_at_risk() {
+        let x = simulate(1000, 0.0, None);
+        let m = garch::fit(&x, &spec(GarchKind::Garch, Innovations::Normal)).unwrap();
+        let s2 = m.forecast_variance(3);
+        let var = m.value_at_risk(3, 0.99).unwrap();
+        for (v, s2) in var.iter().zip(&s2) {
+            assert_lt!((v - (2.326_347_874 * s2.sqrt() - m.mu)).abs(), 1.0e-6);
+        }
+        assert!(m.value_at_risk(1, 1.0).is_err());
+
+        // heavier tails give a larger value at risk at high levels
+        let t = garch::fit(&x, &spec(GarchKind::Garch, Innovations::StudentT)).unwrap();
+        let q = t.value_at_risk(1, 0.999).unwrap()[0] - -t.mu;
+        let q_normal = 3.090_232_306 * t.forecast_variance(1)[0].sqrt();
+        assert_gt!(q, q_normal);
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "json")]
+
+#[cfg(test)]
+mod test_json {
+    use arima::json::{self, SarimaxParams};
+    use arima::model::FittedModel;
+    use more_asserts::assert_lt;
+
+    const X: [f64; 12] = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+
+    #[test]
+    fn import_statsmodels() {
+        // layout written by statsmodels for an ARIMA(2, 1, 1) without trend
+        let s = r#"{
+            "order": [2, 1, 1],
+            "seasonal_order": [0, 0, 0, 0],
+            "trend": null,
+            "param_names": ["ar.L1", "ar.L2", "ma.L1", "sigma2"],
+            "params": [0.4, -0.2, 0.3, 0.05]
+        }"#;
+        let m = json::from_statsmodels(s, &X).unwrap();
+        assert_eq!((m.ar, m.d, m.ma), (2, 1, 1));
+        assert_eq!(m.intercept, 0.0);
+        assert_eq!(m.phi, vec![0.4, -0.2]);
+        assert_eq!(m.theta, vec![0.3]);
+        assert_eq!(m.sigma2, 0.05);
+        let expected = FittedModel::from_coef(&X, &[0.0, 0.4, -0.2, 0.3], 2, 1, 1).unwrap();
+        assert_eq!(m.forecast(3), expected.forecast(3));
+    }
+
+    #[test]
+    fn import_const_and_missing_lags() {
+        let params = SarimaxParams {
+            order: (3, 0, 0),
+            seasonal_order: (0, 0, 0, 0),
+            trend: Some("c".to_string()),
+            param_names: vec![
+                "const".into(),
+                "ar.L1".into(),
+                "ar.L3".into(),
+                "sigma2".into(),
+            ],
+            params: vec![2.0, 0.5, 0.1, 1.0],
+        };
+        let (coef, sigma2) = params.coefficients().unwrap();
+        assert_lt!((coef[0] - 2.0 * 0.4).abs(), 1.0e-12);
+        assert_eq!(&coef[1..], &[0.5, 0.0, 0.1]);
+        assert_eq!(sigma2, 1.0);
+    }
+
+    #[test]
+    fn invalid_params() {
+        let mut params = SarimaxParams::from_model(
+            &FittedModel::from_coef(&X, &[0.1, 0.5, 0.2], 1, 0, 1).unwrap(),
+        );
+        assert!(params.coefficients().is_ok());
+
+        let mut seasonal = params.clone();
+        seasonal.seasonal_order = (1, 0, 0, 12);
+        assert!(seasonal.coefficients().is_err());
+
+        let mut lag = params.clone();
+        lag.param_names[1] = "ar.L2".to_string();
+        assert!(lag.coefficients().is_err());
+
+        params.param_names.pop();
+        assert!(params.coefficients().is_err());
+    }
+
+    #[test]
+    fn roundtrip() {
+        let m = FittedModel::from_coef(&X, &[0.1, 0.5, 0.2], 1, 1, 1).unwrap();
+        let s = json::to_statsmodels(&m).unwrap();
+        let imported = json::from_statsmodels(&s, &X).unwrap();
+        assert_eq!(imported.intercept, m.intercept);
+        assert_eq!(imported.theta, m.theta);
+        assert_eq!(imported.residuals, m.residuals);
+        assert_eq!(imported.forecast(4), m.forecast(4));
+    }
+}
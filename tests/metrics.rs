@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod test_metrics {
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    #[test]
+    fn accuracy_f64() {
+        let actual = [2.0, 4.0, 5.0, 10.0];
+        let predicted = [1.0, 5.0, 5.0, 8.0];
+        let acc = arima::metrics::accuracy(&actual, &predicted).unwrap();
+
+        assert_lt!((acc.me - 0.5).abs(), 1.0e-12);
+        assert_lt!((acc.rmse - 1.5_f64.sqrt()).abs(), 1.0e-12);
+        assert_lt!((acc.mae - 1.0).abs(), 1.0e-12);
+        assert_lt!((acc.mpe - 11.25).abs(), 1.0e-12);
+        assert_lt!((acc.mape - 23.75).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn accuracy_zero_actual() {
+        let acc = arima::metrics::accuracy(&[0.0, 1.0], &[1.0, 1.0]).unwrap();
+        assert!(acc.mape.is_nan());
+        assert_lt!((acc.mae - 0.5).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn accuracy_length_mismatch() {
+        assert!(arima::metrics::accuracy(&[1.0, 2.0], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn crps_gaussian_value() {
+        // 2 phi(0) - 1 / sqrt(pi)
+        let crps = arima::metrics::crps_gaussian(&[0.0], &[0.0], &[1.0]).unwrap();
+        assert_lt!((crps - 0.233695).abs(), 1.0e-6);
+        // scales with the standard deviation
+        let crps2 = arima::metrics::crps_gaussian(&[0.0], &[0.0], &[2.0]).unwrap();
+        assert_lt!((crps2 - 2.0 * crps).abs(), 1.0e-12);
+        assert!(arima::metrics::crps_gaussian(&[0.0], &[0.0], &[0.0]).is_err());
+    }
+
+    #[test]
+    fn crps_sample_matches_gaussian() {
+        let mut rng = arima::sim::stream_rng(91, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let actual = [-1.0, 0.3, 2.0];
+        let samples: Vec<Vec<f64>> = actual
+            .iter()
+            .map(|_| (0..20000).map(|_| normal.sample(&mut rng)).collect())
+            .collect();
+        let sample = arima::metrics::crps_sample(&actual, &samples).unwrap();
+        let exact = arima::metrics::crps_gaussian(&actual, &[0.0; 3], &[1.0; 3]).unwrap();
+        assert_lt!((sample - exact).abs(), 0.01);
+        assert!(arima::metrics::crps_sample(&actual, &samples[..2]).is_err());
+    }
+
+    #[test]
+    fn quantile_loss_f64() {
+        let actual = [1.0, 2.0, 3.0];
+        let median = arima::metrics::quantile_loss(&actual, &[2.0, 2.0, 2.0], 0.5).unwrap();
+        assert_lt!((median - 1.0 / 3.0).abs(), 1.0e-12);
+        // under-prediction costs tau, over-prediction 1 - tau
+        let low = arima::metrics::quantile_loss(&[1.0], &[0.0], 0.1).unwrap();
+        let high = arima::metrics::quantile_loss(&[1.0], &[2.0], 0.1).unwrap();
+        assert_lt!((low - 0.1).abs(), 1.0e-12);
+        assert_lt!((high - 0.9).abs(), 1.0e-12);
+        assert!(arima::metrics::quantile_loss(&actual, &actual, 1.0).is_err());
+    }
+
+    #[test]
+    fn interval_coverage_backtest() {
+        let mut rng = arima::sim::stream_rng(92, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let levels = [0.8, 0.95];
+        let z = [1.281552, 1.959964];
+        // 500 origins with three-step forecasts of white noise
+        let actual: Vec<Vec<f64>> = (0..500)
+            .map(|_| (0..3).map(|_| normal.sample(&mut rng)).collect())
+            .collect();
+        let bounds = |scale: f64| -> Vec<Vec<(Vec<f64>, Vec<f64>)>> {
+            actual
+                .iter()
+                .map(|_| {
+                    z.iter()
+                        .map(|z| (vec![-scale * z; 3], vec![scale * z; 3]))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let cov = arima::metrics::interval_coverage(&levels, &actual, &bounds(1.0)).unwrap();
+        assert_eq!(cov.len(), 6);
+        assert_eq!((cov[4].level, cov[4].horizon, cov[4].count), (0.95, 2, 500));
+        for c in &cov {
+            assert_lt!((c.coverage - c.level).abs(), 0.05);
+            assert_lt!((c.below + c.above + c.coverage - 1.0).abs(), 1.0e-12);
+        }
+        assert_lt!((cov[0].mean_width - 2.0 * z[0]).abs(), 1.0e-12);
+
+        // too narrow intervals are detected
+        let narrow = arima::metrics::interval_coverage(&levels, &actual, &bounds(0.5)).unwrap();
+        for c in &narrow {
+            assert_lt!(c.coverage, c.level - 0.2);
+        }
+
+        assert!(arima::metrics::interval_coverage(&[0.8], &actual, &bounds(1.0)).is_err());
+        assert!(arima::metrics::interval_coverage(&levels, &actual[..2], &bounds(1.0)).is_err());
+    }
+}
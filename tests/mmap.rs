@@ -0,0 +1,60 @@
+#![cfg(feature = "mmap")]
+
+#[cfg(test)]
+mod test_mmap {
+    use arima::mmap::{self, MmapSeries};
+    use more_asserts::assert_lt;
+
+    fn write_series(name: &str, values: &[f64]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn mmap_acf_f64() {
+        let values: Vec<f64> = (0..1000).map(|i| ((i * 13 % 29) as f64).cos()).collect();
+        let path = write_series("arima_test_mmap_acf.bin", &values);
+
+        let x = MmapSeries::open(&path).unwrap();
+        assert_eq!(x.len(), values.len());
+
+        let expected = arima::acf::acf(&values, Some(10), false).unwrap();
+        let calc = arima::acf::acf(&x, Some(10), false).unwrap();
+        assert_eq!(expected, calc);
+    }
+
+    #[test]
+    fn mmap_chunked_diff_and_css_f64() {
+        let values: Vec<f64> = (0..1001).map(|i| ((i * 7 % 31) as f64).sin()).collect();
+        let path = write_series("arima_test_mmap_chunks.bin", &values);
+        let x = MmapSeries::open(&path).unwrap();
+
+        let mut diffed = Vec::new();
+        for chunk in x.chunks(64, 2).unwrap() {
+            diffed.extend(arima::util::diff(chunk, 2));
+        }
+        assert_eq!(diffed, arima::util::diff(&values, 2));
+
+        let phi = [0.5, -0.2];
+        let mut css = 0.0;
+        for chunk in x.chunks(100, phi.len()).unwrap() {
+            css += arima::estimate::css(chunk, 0.1, Some(&phi), None).unwrap();
+        }
+        let total = arima::estimate::css(&values, 0.1, Some(&phi), None).unwrap();
+        assert_lt!((css - total).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn mmap_rejects_partial_values() {
+        let path = std::env::temp_dir().join("arima_test_mmap_partial.bin");
+        std::fs::write(&path, [0u8; 12]).unwrap();
+        assert!(MmapSeries::open(&path).is_err());
+    }
+
+    #[test]
+    fn chunks_overlap_must_be_smaller() {
+        assert!(mmap::chunks(&[1.0, 2.0], 2, 2).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod test_model {
+    use more_asserts::assert_lt;
+
+    const AR3: [f64; 20] = [
+        149.8228533548,
+        86.8388399871,
+        42.3116899484,
+        76.6796578536,
+        60.3665347774,
+        66.7733563129,
+        -5.1144504108,
+        14.0294086329,
+        76.2517878809,
+        121.2898170491,
+        74.65663878,
+        69.9331198692,
+        46.7476543397,
+        26.2225173663,
+        -32.0638217183,
+        2.8335240789,
+        31.5182582874,
+        76.4827451823,
+        36.6122657518,
+        -33.430444607,
+    ];
+
+    #[test]
+    fn fit_model_ar2() {
+        let m = arima::model::fit(&AR3, 2, 0, 0).unwrap();
+        let coef = arima::estimate::fit(&AR3, 2, 0, 0).unwrap();
+
+        assert_eq!(m.n_used(), 18);
+        assert_eq!(m.n_params(), 4);
+        assert_lt!((m.intercept - coef[0]).abs(), 1.0e-12);
+        assert_lt!((m.sigma2 - m.css() / 18.0).abs(), 1.0e-9);
+
+        // the information criteria follow from the log-likelihood
+        let ll = m.loglik();
+        assert_lt!((m.aic() - (-2.0 * ll + 8.0)).abs(), 1.0e-9);
+        assert_lt!((m.aicc() - (m.aic() + 40.0 / 13.0)).abs(), 1.0e-9);
+        assert_lt!((m.bic() - (-2.0 * ll + 18.0_f64.ln() * 4.0)).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn fitted_values() {
+        let m = arima::model::fit(&AR3, 1, 1, 0).unwrap();
+        let fitted = m.fitted();
+        assert_eq!(fitted.len(), AR3.len() - 2);
+
+        // one-step prediction of the level is the last level plus the predicted difference
+        let pred = AR3[1] + m.intercept + m.phi[0] * (AR3[1] - AR3[0]);
+        assert_lt!((fitted[0] - pred).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn compare_ranks_by_aicc() {
+        let models = vec![
+            arima::model::fit(&AR3, 3, 0, 0).unwrap(),
+            arima::model::fit(&AR3, 1, 0, 0).unwrap(),
+            arima::model::fit(&AR3, 2, 0, 0).unwrap(),
+        ];
+        let table = arima::model::compare(&models).unwrap();
+
+        assert_eq!(table.len(), 3);
+        for w in table.windows(2) {
+            assert!(w[0].aicc <= w[1].aicc);
+        }
+        for row in &table {
+            assert_eq!(row.order.0, models[row.index].ar);
+        }
+
+        // all rows use the residuals after the first three values
+        let ar1 = table.iter().find(|row| row.order.0 == 1).unwrap();
+        let residuals = &models[1].residuals[3..];
+        let n = residuals.len() as f64;
+        let sigma2 = residuals.iter().map(|r| r * r).sum::<f64>() / n;
+        let loglik = -0.5 * n * ((2.0 * std::f64::consts::PI).ln() + 1.0 + sigma2.ln());
+        assert_lt!((ar1.loglik - loglik).abs(), 1.0e-9);
+
+        // different series or orders of differencing are rejected
+        let other = arima::model::fit(&AR3[1..], 1, 0, 0).unwrap();
+        assert!(arima::model::compare(&[models[0].clone(), other]).is_err());
+        let differenced = arima::model::fit(&AR3, 1, 1, 0).unwrap();
+        assert!(arima::model::compare(&[models[0].clone(), differenced]).is_err());
+    }
+
+    #[test]
+    fn standardized_residuals_exact_variances() {
+        let m = arima::model::FittedModel::from_coef(&AR3, &[0.0, 0.5, 0.6], 1, 0, 1).unwrap();
+        let z = m.standardized_residuals();
+        assert_eq!(z.len(), AR3.len() - 1);
+        // the innovations converge to the residuals and their variances to sigma2
+        let sd = m.sigma2.sqrt();
+        let last = z.len() - 1;
+        assert_lt!(
+            (z[last] - m.residuals[last + 1] / sd).abs(),
+            1.0e-3 * z[last].abs()
+        );
+
+        // explosive AR part: residuals divided by the constant standard deviation
+        let m = arima::model::FittedModel::from_coef(&AR3, &[0.0, 1.1], 1, 0, 0).unwrap();
+        let sd = m.sigma2.sqrt();
+        for (z, r) in m.standardized_residuals().iter().zip(&m.residuals[1..]) {
+            assert_lt!((z - r / sd).abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn from_coef_wrong_length() {
+        assert!(arima::model::FittedModel::from_coef(&AR3, &[1.0, 0.5], 2, 0, 0).is_err());
+    }
+
+    #[test]
+    fn roots() {
+        // 1 - 1.2 z + 0.72 z^2 has complex roots with modulus 1/sqrt(0.72) and
+        // frequency 1/8
+        let roots = arima::model::Roots::new(&[1.2, -0.72], &[]);
+        assert_eq!(roots.ar.len(), 2);
+        for r in &roots.ar {
+            assert_lt!((r.modulus - 1.0 / 0.72_f64.sqrt()).abs(), 1.0e-9);
+            assert_lt!((r.frequency - 0.125).abs(), 1.0e-9);
+        }
+        assert!(roots.stationary);
+        assert!(!roots.near_unit_ar);
+        assert!(roots.near_cancellation.is_empty());
+    }
+
+    #[test]
+    fn roots_flags() {
+        // near unit root and an MA root cancelling the other AR root
+        let roots = arima::model::Roots::new(&[1.49, -0.49], &[-0.5]);
+        assert!(roots.near_unit_ar);
+        assert!(!roots.near_unit_ma);
+        assert_eq!(roots.near_cancellation.len(), 1);
+        let (i, j) = roots.near_cancellation[0];
+        assert_lt!((roots.ar[i].re - 2.0).abs(), 0.05);
+        assert_lt!((roots.ma[j].re - 2.0).abs(), 1.0e-9);
+
+        // non-invertible MA part
+        let roots = arima::model::Roots::new(&[], &[2.0]);
+        assert!(!roots.invertible);
+        assert!(roots.stationary);
+    }
+
+    #[test]
+    fn ic_table() {
+        let table = arima::model::ic_table(&AR3, &[1, 2, 3], 0, &[0, 1]);
+        assert_eq!(table.aicc.len(), 3);
+        // all models condition on the first three values
+        let m = arima::model::fit(&AR3[1..], 2, 0, 1).unwrap();
+        assert_lt!((table.aicc[1][1] - m.aicc()).abs(), 1.0e-9);
+        let m = arima::model::fit(&AR3, 3, 0, 0).unwrap();
+        assert_lt!((table.aicc[2][0] - m.aicc()).abs(), 1.0e-9);
+        assert_eq!(
+            m.n_used(),
+            arima::model::fit(&AR3[2..], 1, 0, 0).unwrap().n_used()
+        );
+
+        let (p, q, aicc) = table.best().unwrap();
+        for row in &table.aicc {
+            for v in row {
+                assert!(v.is_nan() || aicc <= *v);
+            }
+        }
+        assert!(table.ar.contains(&p) && table.ma.contains(&q));
+    }
+
+    #[test]
+    fn forecast_variance() {
+        // ARMA(1, 1): psi_1 = phi + theta, psi_2 = phi psi_1
+        let m = arima::model::FittedModel::from_coef(&AR3, &[20.0, 0.5, 0.3], 1, 0, 1).unwrap();
+        let var = m.forecast_variance(3);
+        let expected = [1.0, 1.0 + 0.64, 1.0 + 0.64 + 0.16];
+        for (v, e) in var.iter().zip(&expected) {
+            assert_lt!((v - e * m.sigma2).abs(), 1.0e-9);
+        }
+
+        // ARIMA(1, 1, 0): psi weights of (1 - 0.5 B)(1 - B) are 1, 1.5, 1.75
+        let m = arima::model::FittedModel::from_coef(&AR3, &[0.0, 0.5], 1, 1, 0).unwrap();
+        let var = m.forecast_variance(3);
+        assert_lt!((var[2] - (1.0 + 2.25 + 3.0625) * m.sigma2).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn psi_weights() {
+        // AR(2): psi_j = phi_1 psi_{j-1} + phi_2 psi_{j-2}
+        let psi = arima::model::psi_weights(&[0.5, 0.3], &[], 4);
+        let expected = [1.0, 0.5, 0.55, 0.425, 0.3775];
+        for (p, e) in psi.iter().zip(&expected) {
+            assert_lt!((p - e).abs(), 1.0e-12);
+        }
+
+        // MA(2): the weights are the MA coefficients
+        assert_eq!(
+            arima::model::psi_weights(&[], &[0.4, -0.2], 3),
+            vec![1.0, 0.4, -0.2, 0.0]
+        );
+
+        // ARIMA(0, 1, 1): psi_j = 1 + theta for j >= 1
+        let m = arima::model::FittedModel::from_coef(&AR3, &[0.0, -0.6], 0, 1, 1).unwrap();
+        for p in &m.psi_weights(5)[1..] {
+            assert_lt!((p - 0.4).abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn pi_weights() {
+        // ARMA(1, 1): pi_j = (phi + theta) (-theta)^(j - 1)
+        let pi = arima::model::pi_weights(&[0.5], &[0.3], 4);
+        for (j, p) in pi.iter().enumerate() {
+            assert_lt!((p - 0.8 * (-0.3_f64).powi(j as i32)).abs(), 1.0e-12);
+        }
+
+        // a non-invertible MA part gives growing weights
+        let pi = arima::model::pi_weights(&[], &[1.5], 10);
+        assert!(pi[9].abs() > pi[0].abs());
+
+        // ARIMA(0, 1, 1) is exponential smoothing: pi_j = (1 + theta) (-theta)^(j - 1)
+        let m = arima::model::FittedModel::from_coef(&AR3, &[0.0, -0.6], 0, 1, 1).unwrap();
+        let pi = m.pi_weights(3);
+        assert_lt!((pi[0] - 0.4).abs(), 1.0e-12);
+        assert_lt!((pi[2] - 0.4 * 0.36).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn from_r() {
+        let m = arima::model::FittedModel::from_r(&AR3, &[0.8, -0.5, 0.3], 0, &[], 20.0, 1600.0)
+            .unwrap();
+        assert_lt!((m.intercept - 20.0 * 0.4).abs(), 1.0e-12);
+        assert_eq!(m.sigma2, 1600.0);
+        // forecasts revert to the mean
+        let f = m.forecast(200);
+        assert_lt!((f[199] - 20.0).abs(), 1.0e-6);
+
+        let m = arima::model::FittedModel::from_r(&AR3, &[], 1, &[-0.4], 0.0, 1.0).unwrap();
+        assert_eq!((m.ar, m.d, m.ma), (0, 1, 1));
+
+        let from_r = arima::model::FittedModel::from_r;
+        assert!(from_r(&AR3, &[], 0, &[1.5], 0.0, 1.0).is_err());
+        assert!(from_r(&AR3, &[0.5], 1, &[], 2.0, 1.0).is_err());
+        assert!(from_r(&AR3, &[0.5], 0, &[], 2.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn forecast_contributions() {
+        let m =
+            arima::model::FittedModel::from_coef(&AR3, &[0.8, 0.4, -0.3, 0.5], 2, 1, 1).unwrap();
+        let c = m.forecast_contributions(10);
+        let f = m.forecast(10);
+        assert!(c.regressors.is_empty());
+        for (total, f) in c.total().iter().zip(&f) {
+            assert_lt!((total - f).abs(), 1.0e-9);
+        }
+
+        // the drift accumulates: 0.8 / (1 - 0.4 + 0.3) per step in the long run
+        let drift = m.forecast_contributions(40).intercept;
+        let slope = drift[39] - drift[38];
+        assert_lt!((slope - 0.8 / 0.9).abs(), 1.0e-6);
+        // the MA term contributes from the last residual on
+        let w1 = AR3[19] - AR3[18];
+        let w2 = AR3[18] - AR3[17];
+        assert_lt!((c.ma[0] - 0.5 * m.residuals.last().unwrap()).abs(), 1.0e-9);
+        assert_lt!((c.ar[0] - (AR3[19] + 0.4 * w1 - 0.3 * w2)).abs(), 1.0e-9);
+
+        // a random walk forecast is the last observation
+        let rw = arima::model::FittedModel::from_coef(&AR3, &[0.0], 0, 1, 0).unwrap();
+        let c = rw.forecast_contributions(3);
+        assert_eq!(c.ar, vec![AR3[19]; 3]);
+        assert_eq!(c.intercept, vec![0.0; 3]);
+        assert_eq!(c.ma, vec![0.0; 3]);
+    }
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "parquet")]
+
+#[cfg(test)]
+mod test_parquet {
+    use arima::parquet::{read_parquet, read_parquet_bytes};
+    use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    /// Parquet file with an optional double column "value", a millisecond timestamp
+    /// column "ts", a nanosecond timestamp column "ts_ns" and a string column "name".
+    fn write_file() -> Vec<u8> {
+        let schema = Arc::new(
+            parse_message_type(
+                "message schema {
+                    OPTIONAL DOUBLE value;
+                    REQUIRED INT64 ts (TIMESTAMP(MILLIS, true));
+                    REQUIRED INT64 ts_ns (TIMESTAMP(NANOS, true));
+                    REQUIRED BYTE_ARRAY name (UTF8);
+                }",
+            )
+            .unwrap(),
+        );
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(Vec::new(), schema, props).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column().unwrap() {
+            match index {
+                0 => {
+                    column
+                        .typed::<DoubleType>()
+                        .write_batch(&[1.5, 2.5], Some(&[1, 0, 1]), None)
+                        .unwrap();
+                }
+                1 => {
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&[1_000, 2_000, 3_000], None, None)
+                        .unwrap();
+                }
+                2 => {
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(&[1_000_000, 2_000_000, 3_000_000], None, None)
+                        .unwrap();
+                }
+                _ => {
+                    let names: Vec<ByteArray> = vec!["a".into(), "b".into(), "c".into()];
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&names, None, None)
+                        .unwrap();
+                }
+            }
+            column.close().unwrap();
+            index += 1;
+        }
+        row_group.close().unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn read_values_and_timestamps() {
+        let data = write_file();
+        let series = read_parquet_bytes(data.clone(), "value", Some("ts")).unwrap();
+        assert_eq!(series.values.len(), 3);
+        assert_eq!(series.values[0], 1.5);
+        assert!(series.values[1].is_nan());
+        assert_eq!(series.values[2], 2.5);
+        assert_eq!(
+            series.timestamps,
+            Some(vec![1_000_000, 2_000_000, 3_000_000])
+        );
+
+        let series = read_parquet_bytes(data, "value", Some("ts_ns")).unwrap();
+        assert_eq!(series.timestamps, Some(vec![1_000, 2_000, 3_000]));
+    }
+
+    #[test]
+    fn read_file() {
+        let path = std::env::temp_dir().join("arima_test_parquet.parquet");
+        std::fs::write(&path, write_file()).unwrap();
+        let series = read_parquet(&path, "value", None).unwrap();
+        assert_eq!(series.values.len(), 3);
+        assert_eq!(series.timestamps, None);
+        // timestamps are not numeric values
+        assert!(read_parquet(&path, "ts", None).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_columns() {
+        let data = write_file();
+        assert!(read_parquet_bytes(data.clone(), "missing", None).is_err());
+        assert!(read_parquet_bytes(data.clone(), "name", None).is_err());
+        assert!(read_parquet_bytes(data, "value", Some("name")).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod test_persist {
+    use arima::{model, persist};
+
+    const X: [f64; 12] = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+
+    #[test]
+    fn roundtrip_file() {
+        let m = model::fit(&X, 1, 1, 1).unwrap();
+        let path = std::env::temp_dir().join("arima_test_persist.bin");
+        persist::save(&path, &m).unwrap();
+        let loaded = persist::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((loaded.ar, loaded.d, loaded.ma), (1, 1, 1));
+        assert_eq!(loaded.phi, m.phi);
+        assert_eq!(loaded.theta, m.theta);
+        assert_eq!(loaded.sigma2, m.sigma2);
+        assert_eq!(loaded.residuals, m.residuals);
+        assert_eq!(loaded.x, m.x);
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let m = model::fit(&X, 1, 0, 0).unwrap();
+        let mut bytes = persist::to_bytes(&m);
+        // a field added by a newer version of the format
+        bytes.extend_from_slice(&999_u16.to_le_bytes());
+        bytes.extend_from_slice(&3_u32.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3]);
+        let loaded = persist::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.phi, m.phi);
+    }
+
+    #[test]
+    fn invalid_files() {
+        let m = model::fit(&X, 1, 0, 0).unwrap();
+        let bytes = persist::to_bytes(&m);
+
+        assert!(persist::from_bytes(b"not a model").is_err());
+
+        let mut future = bytes.clone();
+        future[8..10].copy_from_slice(&(persist::FORMAT_VERSION + 1).to_le_bytes());
+        assert!(persist::from_bytes(&future).is_err());
+
+        assert!(persist::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+        // header only, all fields missing
+        assert!(persist::from_bytes(&bytes[..10]).is_err());
+
+        // more AR lags than observations
+        let short = model::FittedModel {
+            ar: 3,
+            phi: vec![0.1; 3],
+            residuals: vec![0.0; 3],
+            x: X[..3].to_vec(),
+            ..m
+        };
+        assert!(persist::from_bytes(&persist::to_bytes(&short)).is_err());
+    }
+}
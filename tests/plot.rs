@@ -0,0 +1,48 @@
+#![cfg(feature = "plot")]
+
+#[cfg(test)]
+mod test_plot {
+    use arima::plot::{self, Band};
+
+    const X: [f64; 12] = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+
+    #[test]
+    fn correlograms() {
+        let png = std::env::temp_dir().join("arima_test_pacf.png");
+        plot::pacf_plot(&png, &X, 4).unwrap();
+        let bytes = std::fs::read(&png).unwrap();
+        assert_eq!(&bytes[1..4], b"PNG");
+        std::fs::remove_file(&png).unwrap();
+
+        let svg = std::env::temp_dir().join("arima_test_acf.SVG");
+        plot::acf_plot(&svg, &X, 4).unwrap();
+        let text = std::fs::read_to_string(&svg).unwrap();
+        assert!(text.contains("ACF"));
+        std::fs::remove_file(&svg).unwrap();
+    }
+
+    #[test]
+    fn fan_chart() {
+        let path = std::env::temp_dir().join("arima_test_fan.svg");
+        let band = Band {
+            level: 0.9,
+            lower: vec![0.5, 0.4],
+            upper: vec![1.5, 1.6],
+        };
+        plot::fan_chart(&path, &X, &[1.0, 1.0], std::slice::from_ref(&band)).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("<polygon"));
+        std::fs::remove_file(&path).unwrap();
+
+        // bounds must match the forecast horizon
+        assert!(plot::fan_chart(&path, &X, &[1.0], std::slice::from_ref(&band)).is_err());
+
+        // levels must be valid probabilities
+        for &level in [f64::NAN, 0.0, 1.5].iter() {
+            let bad = Band {
+                level,
+                ..band.clone()
+            };
+            assert!(plot::fan_chart(&path, &X, &[1.0, 1.0], &[band.clone(), bad]).is_err());
+        }
+    }
+}
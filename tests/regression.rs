@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod test_regression {
+    use arima::regression;
+    use arima::sim;
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+    use std::f64::consts::PI;
+
+    // weekly data with a yearly season of two harmonics and AR(1) errors
+    fn simulate(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(31, 0);
+        let normal = Normal::new(0.0, 0.3).unwrap();
+        let mut e = 0.0;
+        (0..n)
+            .map(|t| {
+                e = 0.6 * e + normal.sample(&mut rng);
+                let w = 2.0 * PI * t as f64 / 52.18;
+                10.0 + 2.0 * w.sin() - 1.0 * (2.0 * w).cos() + e
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fourier() {
+        let f = regression::fourier(3, 12.0, 2, 5);
+        assert_eq!(f.len(), 4);
+        let w = 2.0 * PI * 5.0 / 12.0;
+        assert_lt!((f[0][0] - w.sin()).abs(), 1.0e-12);
+        assert_lt!((f[3][0] - (2.0 * w).cos()).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn test_harmonic_regression() {
+        let x = simulate(400);
+        let m = regression::harmonic_regression(&x, &[(52.18, 2)], 1, 0).unwrap();
+        assert_eq!(m.coef.len(), 5);
+        assert_lt!((m.coef[0] - 10.0).abs(), 0.3);
+        assert_lt!((m.coef[1] - 2.0).abs(), 0.3);
+        assert_lt!((m.coef[4] + 1.0).abs(), 0.3);
+        assert_lt!((m.errors.phi[0] - 0.6).abs(), 0.15);
+    }
+
+    #[test]
+    fn test_auto_harmonic() {
+        let x = simulate(400);
+        let m = regression::auto_harmonic(&x, &[52.18], 1, 0).unwrap();
+        assert_eq!(m.seasons[0].1, 2);
+
+        // forecasts follow the seasonal pattern
+        let f = m.forecast(52);
+        assert_eq!(f.len(), 52);
+        for (h, v) in f.iter().enumerate().skip(10) {
+            let w = 2.0 * PI * (400 + h) as f64 / 52.18;
+            let expected = 10.0 + 2.0 * w.sin() - (2.0 * w).cos();
+            assert_lt!((v - expected).abs(), 0.5);
+        }
+    }
+
+    #[test]
+    fn test_invalid_harmonics() {
+        let x = simulate(100);
+        assert!(regression::harmonic_regression(&x, &[(4.0, 2)], 0, 0).is_err());
+        assert!(regression::auto_harmonic(&x, &[1.5], 0, 0).is_err());
+    }
+
+    // an AR(1) regressor that leads the series by `delay` steps, and an unrelated one
+    fn distributed_lag(n: usize, delay: isize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let mut rng = sim::stream_rng(32, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut ar1 = |phi: f64| {
+            sim::arima_sim(
+                n,
+                Some(&[phi]),
+                None,
+                0,
+                &|mut rng| normal.sample(&mut rng),
+                &mut rng,
+            )
+            .unwrap()
+        };
+        let z = ar1(0.8);
+        let unrelated = ar1(0.8);
+        let e = ar1(0.5);
+        let x = (0..n)
+            .map(|t| {
+                let s = t as isize - delay;
+                let effect = if s >= 0 && s < n as isize {
+                    1.5 * z[s as usize]
+                } else {
+                    0.0
+                };
+                5.0 + effect + e[t]
+            })
+            .collect();
+        (x, z, unrelated)
+    }
+
+    #[test]
+    fn test_select_lags_prewhitened() {
+        let (x, z, unrelated) = distributed_lag(300, 4);
+        let s = regression::select_lags(
+            &x,
+            &[z, unrelated],
+            3,
+            8,
+            regression::LagMethod::Prewhitened,
+        )
+        .unwrap();
+        assert_eq!(s.len(), 2);
+        assert_eq!(s[0].shift, Some(4));
+        assert_eq!(s[0].significant[0], 4);
+        assert_eq!(s[0].scores.len(), 12);
+        assert_eq!(s[0].scores[0].0, -3);
+        let strongest = |sel: &regression::LagSelection| {
+            sel.scores.iter().map(|(_, r)| r.abs()).fold(0.0, f64::max)
+        };
+        assert_gt!(strongest(&s[0]), 0.5);
+        assert_lt!(strongest(&s[1]), 0.25);
+    }
+
+    #[test]
+    fn test_select_lags_aicc() {
+        // the series leads the regressor
+        let (x, z, _) = distributed_lag(300, -2);
+        let s = regression::select_lags(&x, &[z], 3, 3, regression::LagMethod::Aicc(1, 0)).unwrap();
+        assert_eq!(s[0].shift, Some(-2));
+        let best = s[0]
+            .scores
+            .iter()
+            .min_by(|u, v| u.1.partial_cmp(&v.1).unwrap())
+            .unwrap();
+        assert_eq!(best.0, -2);
+    }
+
+    #[test]
+    fn test_select_lags_invalid() {
+        let (x, z, _) = distributed_lag(50, 1);
+        let method = regression::LagMethod::Prewhitened;
+        assert!(regression::select_lags(&x, &[z[..40].to_vec()], 2, 2, method).is_err());
+        assert!(regression::select_lags(&x, &[vec![1.0; 50]], 2, 2, method).is_err());
+        assert!(regression::select_lags(&x, &[z], 30, 30, method).is_err());
+    }
+
+    #[test]
+    fn test_shifted() {
+        let z = [1.0, 2.0, 3.0, 4.0];
+        let lead = regression::shifted(&z, -2);
+        assert_eq!(&lead[..2], &[3.0, 4.0]);
+        assert!(lead[2].is_nan() && lead[3].is_nan());
+        assert_eq!(regression::shifted(&z, 0), z.to_vec());
+    }
+
+    #[test]
+    fn test_harmonic_forecast_contributions() {
+        let x = simulate(400);
+        let m = regression::harmonic_regression(&x, &[(52.18, 2), (13.0, 1)], 1, 0).unwrap();
+        let c = m.forecast_contributions(20);
+        assert_eq!(c.regressors.len(), 2);
+        for (total, f) in c.total().iter().zip(m.forecast(20)) {
+            assert_lt!((total - f).abs(), 1.0e-9);
+        }
+        // the yearly season dominates the regressors
+        let w = 2.0 * PI * 400.0 / 52.18;
+        assert_lt!(
+            (c.regressors[0][0] - (2.0 * w.sin() - (2.0 * w).cos())).abs(),
+            0.3
+        );
+        assert_lt!(c.regressors[1][0].abs(), 0.3);
+        assert!(c.ma.iter().all(|v| *v == 0.0));
+    }
+}
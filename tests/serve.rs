@@ -0,0 +1,240 @@
+#![cfg(feature = "serve")]
+
+#[cfg(test)]
+mod test_serve {
+    use arima::serve::{ForecastResponse, ModelInfo, Server, MAX_BODY, MAX_HEADERS, MAX_LINE};
+    use arima::{json, model, persist};
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    const X: [f64; 12] = [1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2, 1.0, 0.9];
+
+    fn server() -> Server {
+        let mut server = Server::new();
+        server.insert("demo", model::fit(&X, 1, 1, 0).unwrap());
+        server
+    }
+
+    #[test]
+    fn list_models() {
+        let r = server().handle("GET", "/models", b"");
+        assert_eq!(r.status, 200);
+        let v: serde_json::Value = serde_json::from_str(&r.body).unwrap();
+        let models: Vec<ModelInfo> = serde_json::from_value(v["models"].clone()).unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].name, "demo");
+        assert_eq!(models[0].order, (1, 1, 0));
+        assert_eq!(models[0].n, X.len());
+    }
+
+    #[test]
+    fn forecast_matches_model() {
+        let mut server = server();
+        let m = model::fit(&X, 1, 1, 0).unwrap();
+        let r = server.handle("GET", "/models/demo/forecast?h=4&level=0.9", b"");
+        assert_eq!(r.status, 200);
+        let f: ForecastResponse = serde_json::from_str(&r.body).unwrap();
+        assert_eq!(f.level, 0.9);
+        let forecast = m.forecast(4);
+        let variance = m.forecast_variance(4);
+        for i in 0..4 {
+            assert!((f.forecast[i] - forecast[i]).abs() < 1e-12);
+            assert!((f.variance[i] - variance[i]).abs() < 1e-12);
+            let half = 1.6448536269514722 * f.variance[i].sqrt();
+            assert!((f.upper[i] - f.forecast[i] - half).abs() < 1e-9);
+            assert!((f.forecast[i] - f.lower[i] - half).abs() < 1e-9);
+        }
+
+        // defaults
+        let r = server.handle("GET", "/models/demo/forecast", b"");
+        let f: ForecastResponse = serde_json::from_str(&r.body).unwrap();
+        assert_eq!(f.forecast.len(), 1);
+        assert_eq!(f.level, 0.95);
+    }
+
+    #[test]
+    fn observations_update_forecasts() {
+        let mut server = server();
+        let r = server.handle(
+            "POST",
+            "/models/demo/observations",
+            br#"{"values": [1.1, 1.3]}"#,
+        );
+        assert_eq!(r.status, 200);
+        let info: ModelInfo = serde_json::from_str(&r.body).unwrap();
+        assert_eq!(info.n, X.len() + 2);
+
+        // same coefficients and variance, forecasts from the new observations
+        let m = model::fit(&X, 1, 1, 0).unwrap();
+        let mut x = X.to_vec();
+        x.extend_from_slice(&[1.1, 1.3]);
+        let mut coef = vec![m.intercept];
+        coef.extend_from_slice(&m.phi);
+        let expected = model::FittedModel::from_coef(&x, &coef, 1, 1, 0).unwrap();
+        let updated = server.model("demo").unwrap();
+        assert_eq!(updated.phi, m.phi);
+        assert_eq!(updated.sigma2, m.sigma2);
+        assert_eq!(updated.forecast(3), expected.forecast(3));
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let mut server = server();
+        let status = |server: &mut Server, method: &str, target: &str, body: &[u8]| {
+            let r = server.handle(method, target, body);
+            let v: serde_json::Value = serde_json::from_str(&r.body).unwrap();
+            assert!(v["error"].is_string());
+            r.status
+        };
+        let cases: [(&str, &str, &[u8], u16); 10] = [
+            ("GET", "/", b"", 404),
+            ("GET", "/models/other/forecast", b"", 404),
+            ("POST", "/models/demo/forecast", b"", 405),
+            ("GET", "/models/demo/observations", b"", 405),
+            ("GET", "/models/demo/forecast?h=0", b"", 400),
+            ("GET", "/models/demo/forecast?h=x", b"", 400),
+            ("GET", "/models/demo/forecast?level=1.5", b"", 400),
+            ("GET", "/models/demo/forecast?k=1", b"", 400),
+            ("POST", "/models/demo/observations", b"[1]", 400),
+            (
+                "POST",
+                "/models/demo/observations",
+                br#"{"values": [1.0, null]}"#,
+                400,
+            ),
+        ];
+        for (method, target, body, expected) in cases.iter() {
+            assert_eq!(status(&mut server, method, target, body), *expected);
+        }
+        let r = server.handle("GET", "/models", b"");
+        assert!(r.body.contains(&format!(r#""n":{}"#, X.len())));
+    }
+
+    #[test]
+    fn bounded_state() {
+        // ARIMA(2, 1, 1) with many appended observations keeps only the last ones and
+        // forecasts as if the whole series were kept
+        let m = model::fit(&X, 2, 1, 1).unwrap();
+        let mut server = Server::new();
+        server.insert("demo", m.clone());
+        let mut x = X.to_vec();
+        for t in 0..200 {
+            let v = 1.0 + 0.3 * (t as f64 * 0.7).sin();
+            server.observe("demo", &[v]).unwrap();
+            x.push(v);
+        }
+        let mut coef = vec![m.intercept];
+        coef.extend_from_slice(&m.phi);
+        coef.extend_from_slice(&m.theta);
+        let expected = model::FittedModel::from_coef(&x, &coef, 2, 1, 1).unwrap();
+        let served = server.model("demo").unwrap();
+        assert_eq!(served.x.len(), 4);
+        assert_eq!(served.residuals.len(), 3);
+        for (a, b) in served.forecast(5).iter().zip(expected.forecast(5)) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn load_saved_model() {
+        let m = model::fit(&X, 1, 0, 1).unwrap();
+        let path = std::env::temp_dir().join("arima_test_serve.bin");
+        persist::save(&path, &m).unwrap();
+        let mut server = Server::new();
+        let loaded = server.load("saved", &path);
+        std::fs::remove_file(&path).unwrap();
+        loaded.unwrap();
+        assert_eq!(server.model("saved").unwrap().forecast(2), m.forecast(2));
+    }
+
+    #[test]
+    fn load_parameters() {
+        let m = model::fit(&X, 1, 0, 1).unwrap();
+        let path = std::env::temp_dir().join("arima_test_serve_params.json");
+        std::fs::write(&path, json::to_statsmodels(&m).unwrap()).unwrap();
+        let mut server = Server::new();
+        let loaded = server.load("params", &path);
+        let invalid = server.load("a/b", &path);
+        std::fs::remove_file(&path).unwrap();
+        loaded.unwrap();
+        assert!(invalid.is_err());
+
+        // no observations yet
+        let r = server.handle("GET", "/models/params/forecast", b"");
+        assert_eq!(r.status, 400);
+        server.observe("params", &X).unwrap();
+        let forecast = server.model("params").unwrap().forecast(3);
+        for (a, b) in forecast.iter().zip(m.forecast(3)) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn http_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || server().run_listener(listener));
+
+        let request = |text: String| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(text.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let body = r#"{"values": [1.0]}"#;
+        let response = request(format!(
+            "POST /models/demo/observations HTTP/1.1\r\nHost: localhost\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        ));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(&format!(r#""n":{}}}"#, X.len() + 1)));
+
+        let response = request("GET /models/demo/forecast?h=2 HTTP/1.1\r\n\r\n".to_string());
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+        let f: ForecastResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(f.forecast.len(), 2);
+
+        let response = request("GET /nothing HTTP/1.1\r\n\r\n".to_string());
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn http_limits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || server().run_listener(listener));
+
+        let request = |text: String| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.write_all(text.as_bytes()).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let long = "a".repeat(MAX_LINE + 1);
+        let response = request(format!("GET /{} HTTP/1.1\r\n\r\n", long));
+        assert!(response.starts_with("HTTP/1.1 414 "));
+        let response = request(format!("GET /models HTTP/1.1\r\nX: {}\r\n\r\n", long));
+        assert!(response.starts_with("HTTP/1.1 431 "));
+        let headers = "X: 1\r\n".repeat(MAX_HEADERS + 1);
+        let response = request(format!("GET /models HTTP/1.1\r\n{}\r\n", headers));
+        assert!(response.starts_with("HTTP/1.1 431 "));
+        let response = request(
+            "POST /models/demo/observations HTTP/1.1\r\nContent-Length: x\r\n\r\n".to_string(),
+        );
+        assert!(response.starts_with("HTTP/1.1 400 "));
+        let response = request(format!(
+            "POST /models/demo/observations HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY + 1
+        ));
+        assert!(response.starts_with("HTTP/1.1 413 "));
+
+        // the server still answers afterwards
+        let response = request("GET /models HTTP/1.1\r\n\r\n".to_string());
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod test_signal {
+    use arima::model::FittedModel;
+    use arima::{model, signal, sim};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    fn random_walk_plus_noise(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(71, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut level = 10.0;
+        (0..n)
+            .map(|_| {
+                level += 0.4 * normal.sample(&mut rng);
+                level + normal.sample(&mut rng)
+            })
+            .collect()
+    }
+
+    fn components_add_up(x: &[f64], s: &signal::SignalExtraction) {
+        for (t, xt) in x.iter().enumerate() {
+            let sum = s.trend.estimate[t]
+                + s.seasonal.estimate[t]
+                + s.transitory.estimate[t]
+                + s.irregular.estimate[t];
+            assert_lt!((sum - xt).abs(), 1.0e-8);
+            assert_lt!(
+                (s.adjusted[t] + s.seasonal.estimate[t] - x[t]).abs(),
+                1.0e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_ima() {
+        let x = random_walk_plus_noise(300);
+        let theta = -0.6;
+        let m = FittedModel::from_coef(&x, &[0.0, theta], 0, 1, 1).unwrap();
+        let s = signal::extract(&m, None).unwrap();
+        components_add_up(&x, &s);
+
+        // trend (1 - B) T_t = (1 + B) a_t with variance (1 + theta)^2 / 4, and white noise
+        // irregular with variance (1 - theta)^2 / 4, relative to the innovation variance
+        let trend = s.trend.model.as_ref().unwrap();
+        assert_eq!(trend.phi.len(), 1);
+        assert_lt!((trend.phi[0] - 1.0).abs(), 1.0e-9);
+        assert_lt!((trend.theta[0] - 1.0).abs(), 1.0e-6);
+        let ratio = trend.sigma2 / m.sigma2;
+        assert_lt!((ratio - (1.0_f64 + theta).powi(2) / 4.0).abs(), 1.0e-6);
+        let irregular = s.irregular.model.as_ref().unwrap();
+        let ratio = irregular.sigma2 / m.sigma2;
+        assert_lt!((ratio - (1.0_f64 - theta).powi(2) / 4.0).abs(), 1.0e-6);
+        assert!(s.seasonal.model.is_none());
+        assert!(s.transitory.model.is_none());
+        assert!(s.seasonal.estimate.iter().all(|v| *v == 0.0));
+
+        // uncertainty is largest at the ends of the series
+        assert_gt!(s.trend.se[299], 1.2 * s.trend.se[150]);
+        assert_gt!(s.trend.se[150], 0.0);
+    }
+
+    #[test]
+    fn test_wiener_kolmogorov_filter() {
+        // in the middle of a long series the smoother applies the symmetric filter
+        // (1 + theta)^2 / 4 (1 + B)(1 + F) / ((1 + theta B)(1 + theta F))
+        let x = random_walk_plus_noise(400);
+        let theta: f64 = -0.6;
+        let m = FittedModel::from_coef(&x, &[0.0, theta], 0, 1, 1).unwrap();
+        let s = signal::extract(&m, None).unwrap();
+        let a = |k: i32| (-theta).powi(k.abs()) / (1.0 - theta * theta);
+        let c = (1.0 + theta).powi(2) / 4.0;
+        let t = 200;
+        let filtered: f64 = (-60..61)
+            .map(|k| c * (a(k - 1) + 2.0 * a(k) + a(k + 1)) * x[(t as i32 + k) as usize])
+            .sum();
+        assert_lt!((s.trend.estimate[t] - filtered).abs(), 1.0e-6);
+    }
+
+    #[test]
+    fn test_seasonal_allocation() {
+        let mut rng = sim::stream_rng(72, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let x: Vec<f64> = (0..120)
+            .map(|t| {
+                0.05 * t as f64 + 2.0 * [1.0, -0.5, 0.5, -1.0][t % 4] + normal.sample(&mut rng)
+            })
+            .collect();
+        // (1 - 0.6561 B^4)(1 - B): roots of modulus 1 / 0.9 at frequencies 0, 1/4 and 1/2
+        let m = FittedModel::from_coef(&x, &[0.0, 0.0, 0.0, 0.0, 0.6561], 4, 1, 0).unwrap();
+        let s = signal::extract(&m, Some(4)).unwrap();
+        components_add_up(&x, &s);
+        // (1 - B)(1 - 0.9 B) and (1 + 0.9 B)(1 + 0.81 B^2)
+        let trend = s.trend.model.as_ref().unwrap();
+        assert_eq!(trend.phi.len(), 2);
+        assert_lt!((trend.phi[0] - 1.9).abs(), 1.0e-6);
+        assert_lt!((trend.phi[1] + 0.9).abs(), 1.0e-6);
+        let seasonal = s.seasonal.model.as_ref().unwrap();
+        assert_eq!(seasonal.phi.len(), 3);
+        assert_lt!((seasonal.phi[0] + 0.9).abs(), 1.0e-6);
+        assert_gt!(s.seasonal.se[60], 0.0);
+        assert!(s.transitory.model.is_none());
+
+        // without a period, the seasonal roots are transitory
+        let s = signal::extract(&m, None).unwrap();
+        components_add_up(&x, &s);
+        assert!(s.seasonal.model.is_none());
+        assert_eq!(s.transitory.model.as_ref().unwrap().phi.len(), 3);
+    }
+
+    #[test]
+    fn test_stationary_with_mean() {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let x: Vec<f64> = sim::arima_sim(
+            200,
+            Some(&[0.8]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(73, 0),
+        )
+        .unwrap()
+        .iter()
+        .map(|v| 5.0 + v)
+        .collect();
+        let m = model::fit(&x, 1, 0, 1).unwrap();
+        let s = signal::extract(&m, None).unwrap();
+        components_add_up(&x, &s);
+        let mean = s.trend.estimate.iter().sum::<f64>() / 200.0;
+        let x_mean = x.iter().sum::<f64>() / 200.0;
+        assert_lt!((mean - x_mean).abs(), 0.5);
+        // a stationary trend has bounded uncertainty everywhere
+        assert_lt!(s.trend.se[0], 2.0 * s.trend.se[100]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let x = random_walk_plus_noise(50);
+        let m = model::fit(&x, 0, 1, 1).unwrap();
+        assert!(signal::extract(&m, Some(1)).is_err());
+    }
+}
@@ -45,4 +45,31 @@ mod test_sim {
 
         assert!(pacf - 0.9 < 0.05);
     }
+
+    #[test]
+    fn sim_streams_independent_of_threads() {
+        let normal = Normal::new(0.0, 2.0).unwrap();
+        let simulate = |stream: u64| {
+            arima::sim::arima_sim(
+                50,
+                Some(&[0.5]),
+                Some(&[0.3]),
+                0,
+                &|mut rng| normal.sample(&mut rng),
+                &mut arima::sim::stream_rng(7, stream),
+            )
+            .unwrap()
+        };
+
+        let sequential: Vec<Vec<f64>> = (0..4).map(simulate).collect();
+        let threaded: Vec<Vec<f64>> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4).rev().map(|i| s.spawn(move || simulate(i))).collect();
+            let mut out: Vec<Vec<f64>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+            out.reverse();
+            out
+        });
+
+        assert_eq!(sequential, threaded);
+        assert_ne!(sequential[0], sequential[1]);
+    }
 }
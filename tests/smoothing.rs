@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod test_smoothing {
+    use arima::smoothing::{self, Seasonal};
+    use more_asserts::assert_lt;
+
+    #[test]
+    fn ses_fixed_alpha() {
+        let x = [1.0, 2.0, 3.0];
+        let m = smoothing::ses(&x, Some(0.5)).unwrap();
+        // l0 = 1, l1 = 1.5, l2 = 2.25
+        assert_eq!(m.fitted, vec![1.0, 1.5]);
+        assert_lt!((m.level - 2.25).abs(), 1.0e-12);
+        assert_lt!((m.sse - 3.25).abs(), 1.0e-12);
+        assert_eq!(m.forecast(2), vec![2.25, 2.25]);
+    }
+
+    #[test]
+    fn ses_estimated_alpha() {
+        // a constant level with noise should give a small alpha
+        let x = [
+            5.1, 4.9, 5.2, 4.8, 5.0, 5.1, 4.9, 5.0, 5.2, 4.8, 5.1, 4.9, 5.0, 5.1, 4.9, 5.0,
+        ];
+        let m = smoothing::ses(&x, None).unwrap();
+        assert_lt!(m.alpha, 0.5);
+        let fixed = smoothing::ses(&x, Some(0.9)).unwrap();
+        assert_lt!(m.sse, fixed.sse);
+    }
+
+    #[test]
+    fn holt_linear() {
+        let x: Vec<f64> = (0..20).map(|i| 2.0 + 0.5 * i as f64).collect();
+        let m = smoothing::holt(&x, None, None).unwrap();
+        assert_lt!(m.sse, 1.0e-12);
+        let f = m.forecast(3);
+        assert_lt!((f[2] - 13.0).abs(), 1.0e-9);
+    }
+
+    #[test]
+    fn holt_winters_additive() {
+        let pattern = [3.0, -1.0, 0.5, -2.5];
+        let x: Vec<f64> = (0..32)
+            .map(|i| 20.0 + 0.25 * i as f64 + pattern[i % 4])
+            .collect();
+        let m = smoothing::holt_winters(&x, 4, Seasonal::Additive, None, None, None).unwrap();
+        let f = m.forecast(6);
+        for (h, v) in f.iter().enumerate() {
+            let i = 32 + h;
+            assert_lt!(
+                (v - (20.0 + 0.25 * i as f64 + pattern[i % 4])).abs(),
+                1.0e-3
+            );
+        }
+    }
+
+    #[test]
+    fn holt_winters_multiplicative() {
+        let pattern = [1.2, 0.8, 1.1, 0.9];
+        let x: Vec<f64> = (0..32).map(|i| 50.0 * pattern[i % 4]).collect();
+        let m = smoothing::holt_winters(&x, 4, Seasonal::Multiplicative, None, None, None).unwrap();
+        let f = m.forecast(4);
+        for (h, v) in f.iter().enumerate() {
+            assert_lt!((v - 50.0 * pattern[(32 + h) % 4]).abs(), 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(smoothing::ses(&[1.0], None).is_err());
+        assert!(smoothing::ses(&[1.0, 2.0], Some(1.5)).is_err());
+        assert!(
+            smoothing::holt_winters(&[1.0; 7], 4, Seasonal::Additive, None, None, None).is_err()
+        );
+        assert!(
+            smoothing::holt_winters(&[0.0; 8], 4, Seasonal::Multiplicative, None, None, None)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod test_spectrum {
+    use arima::spectrum;
+    use more_asserts::assert_lt;
+    use num::complex::Complex64;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn arma_spectrum_integrates_to_variance() {
+        // ARMA(1, 1) variance: sigma2 (1 + 2 phi theta + theta^2) / (1 - phi^2)
+        let (phi, theta, sigma2) = (0.6, 0.3, 1.5);
+        let n = 2000;
+        let freqs: Vec<f64> = (0..n).map(|k| (k as f64 + 0.5) / n as f64 - 0.5).collect();
+        let f = spectrum::arma_spectrum(&[phi], &[theta], sigma2, &freqs);
+        let integral: f64 = f.iter().sum::<f64>() / n as f64;
+        let variance = sigma2 * (1.0 + 2.0 * phi * theta + theta * theta) / (1.0 - phi * phi);
+        assert_lt!((integral - variance).abs(), 1.0e-6);
+
+        // white noise has a flat spectrum
+        for v in spectrum::arma_spectrum(&[], &[], 2.0, &[0.0, 0.1, 0.4]) {
+            assert_lt!((v - 2.0).abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn acgf_on_unit_circle() {
+        let (phi, theta) = ([0.5, -0.2], [0.4]);
+        for v in &[0.05, 0.2, 0.35] {
+            let z = Complex64::from_polar(1.0, -2.0 * PI * v);
+            let g = spectrum::acgf(&phi, &theta, 1.3, z);
+            let f = spectrum::arma_spectrum(&phi, &theta, 1.3, &[*v]);
+            assert_lt!((g.re - f[0]).abs(), 1.0e-12);
+            assert_lt!(g.im.abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn periodogram_parseval() {
+        // for odd n, 2 / n times the sum of the ordinates is the sample variance
+        let x = [1.0, 3.0, -2.0, 0.5, 4.0, 2.5, -1.0, 0.0, 1.5];
+        let (freqs, pgram) = spectrum::periodogram(&x).unwrap();
+        assert_eq!(freqs, spectrum::fourier_frequencies(9));
+        let mean = x.iter().sum::<f64>() / 9.0;
+        let var = x.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 9.0;
+        assert_lt!((2.0 * pgram.iter().sum::<f64>() / 9.0 - var).abs(), 1.0e-12);
+        assert!(spectrum::periodogram(&[1.0]).is_err());
+    }
+
+    #[test]
+    fn periodogram_matches_direct_dft() {
+        // powers of two, other even and odd lengths and a prime
+        for n in [2, 3, 8, 12, 97, 128, 250] {
+            let x: Vec<f64> = (0..n)
+                .map(|t| (0.37 * t as f64).sin() + 0.01 * (t * t % 7) as f64)
+                .collect();
+            let mean = x.iter().sum::<f64>() / n as f64;
+            let (freqs, pgram) = spectrum::periodogram(&x).unwrap();
+            assert_eq!(pgram.len(), n / 2);
+            for (v, p) in freqs.iter().zip(&pgram) {
+                let z: Complex64 = x
+                    .iter()
+                    .enumerate()
+                    .map(|(t, xt)| {
+                        (xt - mean) * Complex64::from_polar(1.0, -2.0 * PI * v * t as f64)
+                    })
+                    .sum();
+                assert_lt!((p - z.norm_sqr() / n as f64).abs(), 1.0e-10);
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test_statespace {
+    use arima::sim;
+    use arima::statespace::StateSpaceBuilder;
+    use arima::structural::{self, Trend};
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    fn random_walk(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(61, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut mu = 5.0;
+        (0..n)
+            .map(|_| {
+                mu += 0.5 * normal.sample(&mut rng);
+                mu + normal.sample(&mut rng)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_local_level_matches_structural() {
+        let x = random_walk(200);
+        let fitted = structural::fit(&x, Trend::LocalLevel).unwrap();
+        let model = StateSpaceBuilder::new(1)
+            .observation(&[1.0])
+            .observation_variance(fitted.irregular_var)
+            .state_covariance(&[fitted.level_var])
+            .build()
+            .unwrap();
+        assert_lt!((model.loglik(&x) - fitted.loglik).abs(), 1.0e-6);
+        let smoothed = model.smooth(&x);
+        for (a, level) in smoothed.a.iter().zip(&fitted.level) {
+            assert_lt!((a[0] - level).abs(), 1.0e-6);
+        }
+        let (mean, var) = model.forecast(&x, 3);
+        let (fitted_mean, fitted_var) = fitted.forecast(3);
+        assert_lt!((mean[2] - fitted_mean[2]).abs(), 1.0e-6);
+        assert_lt!((var[2] - fitted_var[2]).abs(), 1.0e-6);
+    }
+
+    #[test]
+    fn test_selection_matrix() {
+        // local linear trend with a single disturbance entering the level and the slope
+        let model = StateSpaceBuilder::new(2)
+            .transition(&[1.0, 1.0, 0.0, 1.0])
+            .observation(&[1.0, 0.0])
+            .observation_variance(0.1)
+            .selection(&[1.0, 0.5], 1)
+            .state_covariance(&[2.0])
+            .initial_state(&[0.0, 0.0], &[0.0, 0.0, 0.0, 0.0])
+            .build()
+            .unwrap();
+        let (_, var) = model.forecast(&[], 2);
+        // Var(y_1) = H, Var(y_2) = H + Q
+        assert_lt!((var[0] - 0.1).abs(), 1.0e-12);
+        assert_lt!((var[1] - 2.1).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn test_time_varying_regression() {
+        // y_t = b x_t + e_t with a constant coefficient b as the state
+        let mut rng = sim::stream_rng(62, 0);
+        let normal = Normal::new(0.0, 0.1).unwrap();
+        let x: Vec<f64> = (0..50).map(|t| (t as f64 * 0.7).sin() + 1.5).collect();
+        let y: Vec<f64> = x
+            .iter()
+            .map(|v| 2.0 * v + normal.sample(&mut rng))
+            .collect();
+        let model = StateSpaceBuilder::new(1)
+            .time_varying_observation(x.iter().map(|v| vec![*v]).collect())
+            .observation_variance(0.01)
+            .state_covariance(&[0.0])
+            .build()
+            .unwrap();
+        let smoothed = model.smooth(&y);
+        // the smoothed state is the least squares estimate
+        let ols = x.iter().zip(&y).map(|(a, b)| a * b).sum::<f64>()
+            / x.iter().map(|a| a * a).sum::<f64>();
+        for a in &smoothed.a {
+            assert_lt!((a[0] - ols).abs(), 1.0e-6);
+        }
+        assert_lt!((ols - 2.0).abs(), 0.05);
+    }
+
+    #[test]
+    fn test_invalid_dimensions() {
+        assert!(StateSpaceBuilder::new(2)
+            .transition(&[1.0])
+            .observation(&[1.0, 0.0])
+            .observation_variance(1.0)
+            .state_covariance(&[1.0, 0.0, 0.0, 1.0])
+            .build()
+            .is_err());
+        assert!(StateSpaceBuilder::new(1)
+            .observation_variance(1.0)
+            .state_covariance(&[1.0])
+            .build()
+            .is_err());
+    }
+}
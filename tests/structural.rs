@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test_structural {
+    use arima::sim;
+    use arima::structural::{self, Seasonal, Trend, UcSpec};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    // local level model with irregular variance 1 and level variance 0.25
+    fn local_level(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(5, 0);
+        let irregular = Normal::new(0.0, 1.0).unwrap();
+        let level = Normal::new(0.0, 0.5).unwrap();
+        let mut mu = 10.0;
+        (0..n)
+            .map(|_| {
+                let y = mu + irregular.sample(&mut rng);
+                mu += level.sample(&mut rng);
+                y
+            })
+            .collect()
+    }
+
+    #[test]
+    fn local_level_variances() {
+        let x = local_level(1000);
+        let m = structural::fit(&x, Trend::LocalLevel).unwrap();
+        assert_lt!((m.irregular_var - 1.0).abs(), 0.25);
+        assert_lt!((m.level_var - 0.25).abs(), 0.1);
+        assert!(m.slope.is_none());
+        assert_eq!(m.level.len(), x.len());
+        assert_eq!(m.n_used, x.len() - 1);
+    }
+
+    #[test]
+    fn local_level_missing_values() {
+        let mut x = local_level(200);
+        x[50] = f64::NAN;
+        x[51] = f64::NAN;
+        let m = structural::fit(&x, Trend::LocalLevel).unwrap();
+        assert!(m.innovations[50].is_nan());
+        // the smoother interpolates between the neighbours
+        assert!(m.level[50].is_finite());
+        assert_gt!(m.level_se2[50], m.level_se2[40]);
+        assert_eq!(m.n_used, x.len() - 3);
+    }
+
+    #[test]
+    fn local_linear_trend() {
+        let mut rng = sim::stream_rng(9, 0);
+        let noise = Normal::new(0.0, 0.5).unwrap();
+        let x: Vec<f64> = (0..100)
+            .map(|t| 3.0 + 0.7 * t as f64 + noise.sample(&mut rng))
+            .collect();
+        let m = structural::fit(&x, Trend::LocalLinearTrend).unwrap();
+        let slope = m.slope.as_ref().unwrap();
+        for s in slope.iter().skip(5) {
+            assert_lt!((s - 0.7).abs(), 0.1);
+        }
+        let (mean, var) = m.forecast(10);
+        assert_lt!((mean[9] - (3.0 + 0.7 * 109.0)).abs(), 2.0);
+        assert_gt!(var[9], var[0]);
+
+        let ll = structural::fit(&x, Trend::LocalLevel).unwrap();
+        assert_lt!(m.aic(), ll.aic());
+    }
+
+    fn seasonal_series(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(13, 0);
+        let noise = Normal::new(0.0, 0.3).unwrap();
+        let pattern = [2.0, -1.0, 0.5, 1.5, -2.0, -1.0];
+        (0..n)
+            .map(|t| 20.0 + 0.1 * t as f64 + pattern[t % 6] + noise.sample(&mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn dummy_and_trigonometric_seasonal() {
+        let x = seasonal_series(120);
+        let pattern = [2.0, -1.0, 0.5, 1.5, -2.0, -1.0];
+        for &seasonal in [Seasonal::Dummy(6), Seasonal::Trigonometric(6)].iter() {
+            let spec = UcSpec {
+                trend: Trend::LocalLinearTrend,
+                seasonal: Some(seasonal),
+                cycle: None,
+            };
+            let m = structural::fit_uc(&x, &spec).unwrap();
+            let s = m.seasonal.as_ref().unwrap();
+            for t in 60..120 {
+                assert_lt!((s[t] - pattern[t % 6]).abs(), 0.5);
+            }
+            assert!(m.seasonal_var.is_some());
+            assert!(m.cycle.is_none());
+
+            let (mean, _) = m.forecast(6);
+            for (h, v) in mean.iter().enumerate() {
+                let t = 120 + h;
+                assert_lt!((v - (20.0 + 0.1 * t as f64 + pattern[t % 6])).abs(), 1.0);
+            }
+            assert_eq!(m.n_params(), 4);
+        }
+    }
+
+    #[test]
+    fn stochastic_cycle() {
+        // damped cycle with period 20 on top of a constant level
+        let mut rng = sim::stream_rng(17, 0);
+        let kappa = Normal::new(0.0, 0.5).unwrap();
+        let irregular = Normal::new(0.0, 0.2).unwrap();
+        let lambda = 2.0 * std::f64::consts::PI / 20.0;
+        let (sin, cos) = lambda.sin_cos();
+        let (mut psi, mut psi_star) = (0.0, 0.0);
+        let x: Vec<f64> = (0..400)
+            .map(|_| {
+                let y = 50.0 + psi + irregular.sample(&mut rng);
+                let next = 0.95 * (cos * psi + sin * psi_star) + kappa.sample(&mut rng);
+                psi_star = 0.95 * (-sin * psi + cos * psi_star) + kappa.sample(&mut rng);
+                psi = next;
+                y
+            })
+            .collect();
+        let spec = UcSpec {
+            trend: Trend::LocalLevel,
+            seasonal: None,
+            cycle: Some(16.0),
+        };
+        let m = structural::fit_uc(&x, &spec).unwrap();
+        assert_lt!((m.cycle_period.unwrap() - 20.0).abs(), 4.0);
+        assert_gt!(m.cycle_rho.unwrap(), 0.8);
+        assert_eq!(m.cycle.as_ref().unwrap().len(), x.len());
+        assert_eq!(m.n_params(), 5);
+    }
+
+    #[test]
+    fn too_short() {
+        assert!(structural::fit(&[1.0, 2.0, 3.0], Trend::LocalLinearTrend).is_err());
+        assert!(structural::fit(&[1.0, f64::NAN, 2.0], Trend::LocalLevel).is_err());
+        let spec = UcSpec {
+            trend: Trend::LocalLevel,
+            seasonal: Some(Seasonal::Dummy(12)),
+            cycle: None,
+        };
+        assert!(structural::fit_uc(&[1.0; 12], &spec).is_err());
+    }
+
+    #[test]
+    fn standardized_residuals() {
+        let mut x = local_level(300);
+        x[100] = f64::NAN;
+        let m = structural::fit(&x, Trend::LocalLevel).unwrap();
+        let z = m.standardized_residuals();
+        // the diffuse first observation and the missing value are excluded
+        assert!(z[0].is_nan());
+        assert!(z[100].is_nan());
+        // early innovation variances are larger while the level is uncertain
+        assert_gt!(m.innovation_var[1], m.innovation_var[200]);
+        let used: Vec<f64> = z.iter().copied().filter(|v| !v.is_nan()).collect();
+        assert_eq!(used.len(), m.n_used);
+        let var = used.iter().map(|v| v * v).sum::<f64>() / used.len() as f64;
+        assert_lt!((var - 1.0).abs(), 0.15);
+    }
+}
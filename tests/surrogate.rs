@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod test_surrogate {
+    use arima::{sim, spectrum, surrogate};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    fn ar1(n: usize, phi: f64, seed: u64) -> Vec<f64> {
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        sim::arima_sim(
+            n,
+            Some(&[phi]),
+            None,
+            0,
+            &|mut rng| normal.sample(&mut rng),
+            &mut sim::stream_rng(seed, 0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn phase_randomization_keeps_periodogram() {
+        for n in [101, 128] {
+            let x = ar1(n, 0.7, 111);
+            let mut rng = sim::stream_rng(111, 1);
+            let y =
+                surrogate::surrogate(&x, surrogate::Method::PhaseRandomization, &mut rng).unwrap();
+            assert_eq!(y.len(), n);
+            let (_, px) = spectrum::periodogram(&x).unwrap();
+            let (_, py) = spectrum::periodogram(&y).unwrap();
+            for (a, b) in px.iter().zip(&py) {
+                assert_lt!((a - b).abs(), 1.0e-8);
+            }
+            let mean = |v: &[f64]| v.iter().sum::<f64>() / v.len() as f64;
+            assert_lt!((mean(&x) - mean(&y)).abs(), 1.0e-10);
+            assert_gt!(
+                x.iter().zip(&y).map(|(a, b)| (a - b).abs()).sum::<f64>(),
+                1.0
+            );
+        }
+    }
+
+    #[test]
+    fn aaft_keeps_distribution() {
+        // skewed marginal distribution
+        let x: Vec<f64> = ar1(200, 0.6, 112).iter().map(|v| v.exp()).collect();
+        let s = surrogate::Surrogates::new(&x, surrogate::Method::Aaft).unwrap();
+        let mut rng = sim::stream_rng(112, 1);
+        let mut sorted = x.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for _ in 0..3 {
+            let mut y = s.draw(&mut rng);
+            y.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert_eq!(y, sorted);
+        }
+    }
+
+    #[test]
+    fn nonlinearity_test() {
+        let statistic = |y: &[f64]| surrogate::time_reversal_asymmetry(y, 1);
+        let mut rng = sim::stream_rng(113, 0);
+
+        let linear = ar1(256, 0.6, 113);
+        let test = surrogate::surrogate_test(
+            &linear,
+            surrogate::Method::PhaseRandomization,
+            99,
+            statistic,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(test.surrogates.len(), 99);
+        assert_gt!(test.p_value, 0.05);
+
+        // slow noisy rises and sudden falls
+        let mut normal_rng = sim::stream_rng(114, 0);
+        let normal = Normal::new(0.0, 0.5).unwrap();
+        let mut v = 0.0;
+        let nonlinear: Vec<f64> = (0..256)
+            .map(|_| {
+                v = if v > 5.0 { 0.0 } else { v + 1.0 } + normal.sample(&mut normal_rng);
+                v
+            })
+            .collect();
+        let test =
+            surrogate::surrogate_test(&nonlinear, surrogate::Method::Aaft, 99, statistic, &mut rng)
+                .unwrap();
+        assert_lt!(test.p_value, 0.05);
+
+        assert!(surrogate::surrogate_test(
+            &linear,
+            surrogate::Method::Aaft,
+            0,
+            statistic,
+            &mut rng
+        )
+        .is_err());
+        assert!(surrogate::Surrogates::new(&[1.0, 2.0], surrogate::Method::Aaft).is_err());
+    }
+
+    #[test]
+    fn time_reversal_asymmetry() {
+        // rises by one, falls by three
+        let x = [0.0, 1.0, 2.0, 3.0, 0.0, 1.0, 2.0, 3.0, 0.0];
+        assert_lt!(surrogate::time_reversal_asymmetry(&x, 1), 0.0);
+        let reversed: Vec<f64> = x.iter().rev().copied().collect();
+        assert_gt!(surrogate::time_reversal_asymmetry(&reversed, 1), 0.0);
+        assert!(surrogate::time_reversal_asymmetry(&x, 0).is_nan());
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod test_tbats {
+    use arima::sim;
+    use arima::tbats::{self, TbatsSpec};
+    use more_asserts::{assert_gt, assert_lt};
+    use rand::Rng;
+    use std::f64::consts::PI;
+
+    // hourly-like series with a daily (24) and a non-integer weekly-like (33.5) cycle
+    fn two_seasons(n: usize) -> Vec<f64> {
+        let mut rng = sim::stream_rng(11, 0);
+        (0..n)
+            .map(|t| {
+                let t = t as f64;
+                50.0 + 0.05 * t
+                    + 5.0 * (2.0 * PI * t / 24.0).sin()
+                    + 2.0 * (4.0 * PI * t / 24.0).cos()
+                    + 3.0 * (2.0 * PI * t / 33.5).cos()
+                    + rng.gen_range(-0.3..0.3)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fit_multiple_seasons() {
+        let x = two_seasons(200);
+        let spec = TbatsSpec {
+            lambda: None,
+            trend: true,
+            damped: false,
+            seasons: vec![(24.0, 2), (33.5, 1)],
+            ar: 0,
+            ma: 0,
+        };
+        let m = tbats::fit(&x, &spec).unwrap();
+        assert_eq!(m.gamma.len(), 2);
+        assert_eq!(m.residuals.len(), 200);
+        // noise is uniform on (-0.3, 0.3) with variance 0.03
+        assert_lt!(m.sigma2, 0.1);
+
+        let f = m.forecast(24);
+        let truth = two_seasons(224);
+        for (h, v) in f.iter().enumerate() {
+            assert_lt!((v - truth[200 + h]).abs(), 0.6);
+        }
+    }
+
+    #[test]
+    fn box_cox_and_arma() {
+        let x: Vec<f64> = two_seasons(150).iter().map(|v| v.exp() / 1.0e20).collect();
+        let spec = TbatsSpec {
+            lambda: Some(0.0),
+            trend: false,
+            damped: false,
+            seasons: vec![(24.0, 1)],
+            ar: 1,
+            ma: 1,
+        };
+        let m = tbats::fit(&x, &spec).unwrap();
+        assert_eq!(m.ar.len(), 1);
+        assert_eq!(m.ma.len(), 1);
+        assert!(m.forecast(5).iter().all(|v| *v > 0.0));
+        assert!(m.loglik.is_finite());
+    }
+
+    #[test]
+    fn auto_tbats_adds_harmonics() {
+        let x = two_seasons(240);
+        let m = tbats::auto_tbats(&x, &[24.0, 33.5]).unwrap();
+        assert_gt!(m.spec.seasons[0].1, 1);
+        assert!(m.spec.trend);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let x = two_seasons(100);
+        let mut spec = TbatsSpec {
+            lambda: None,
+            trend: false,
+            damped: false,
+            seasons: vec![(4.0, 2)],
+            ar: 0,
+            ma: 0,
+        };
+        assert!(tbats::fit(&x, &spec).is_err());
+        spec.seasons = vec![(24.0, 1)];
+        spec.lambda = Some(0.0);
+        assert!(tbats::fit(&[1.0, -1.0, 2.0], &spec).is_err());
+        assert!(tbats::auto_tbats(&x, &[2.0]).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod test_theta {
+    use arima::theta;
+    use more_asserts::{assert_ge, assert_lt};
+
+    const AR3: [f64; 20] = [
+        149.8228533548,
+        86.8388399871,
+        42.3116899484,
+        76.6796578536,
+        60.3665347774,
+        66.7733563129,
+        -5.1144504108,
+        14.0294086329,
+        76.2517878809,
+        121.2898170491,
+        74.65663878,
+        69.9331198692,
+        46.7476543397,
+        26.2225173663,
+        -32.0638217183,
+        2.8335240789,
+        31.5182582874,
+        76.4827451823,
+        36.6122657518,
+        -33.430444607,
+    ];
+
+    #[test]
+    fn classic_theta_drift() {
+        // the classic method adds half the regression slope per step
+        let m = theta::fit(&AR3, Some(2.0)).unwrap();
+        let f = m.forecast(5);
+        for i in 1..5 {
+            assert_lt!((f[i] - f[i - 1] - m.b / 2.0).abs(), 1.0e-12);
+        }
+    }
+
+    #[test]
+    fn theta_one_is_ses() {
+        let m = theta::fit(&AR3, Some(1.0)).unwrap();
+        let f = m.forecast(3);
+        assert_lt!((f[0] - m.level).abs(), 1.0e-12);
+        assert_lt!((f[2] - m.level).abs(), 1.0e-12);
+    }
+
+    #[test]
+    fn optimized_theta() {
+        let m = theta::fit(&AR3, None).unwrap();
+        assert_ge!(m.theta, 1.0);
+        assert_eq!(m.residuals.len(), AR3.len());
+        let classic = theta::fit(&AR3, Some(2.0)).unwrap();
+        assert_lt!(m.sigma2, classic.sigma2 + 1.0e-9);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(theta::fit(&[1.0, 2.0], None).is_err());
+        assert!(theta::fit(&AR3, Some(0.5)).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+#![cfg(feature = "chrono")]
+
+#[cfg(test)]
+mod test_timeseries {
+    use arima::timeseries::{self, Frequency, Gap, TimeSeries};
+    use arima::{benchmark, ets, model, regression, signal, tbats, x11};
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta};
+
+    fn day(m: u32, d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, m, d)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn gaps_and_fill() {
+        let index = vec![day(1, 1), day(1, 2), day(1, 5), day(1, 6), day(1, 8)];
+        let x =
+            TimeSeries::from_timestamps(vec![1.0, 2.0, 3.0, 4.0, 5.0], index, Frequency::daily())
+                .unwrap();
+        assert_eq!(
+            x.gaps(),
+            vec![
+                Gap {
+                    after: 1,
+                    missing: 2
+                },
+                Gap {
+                    after: 3,
+                    missing: 1
+                }
+            ]
+        );
+
+        let filled: TimeSeries<f64> = x.fill_gaps();
+        assert_eq!(filled.len(), 8);
+        assert!(filled.gaps().is_empty());
+        assert!(filled[2].is_nan() && filled[3].is_nan() && filled[6].is_nan());
+        assert_eq!(filled[4], 3.0);
+        assert_eq!(filled.index()[7], day(1, 8));
+    }
+
+    #[test]
+    fn invalid_timestamps() {
+        // off the daily grid
+        let off_grid = vec![day(1, 1), day(1, 2) + chrono::TimeDelta::hours(12)];
+        assert!(TimeSeries::from_timestamps(vec![1.0, 2.0], off_grid, Frequency::daily()).is_err());
+        // not increasing
+        let unordered = vec![day(1, 3), day(1, 2)];
+        assert!(
+            TimeSeries::from_timestamps(vec![1.0, 2.0], unordered, Frequency::daily()).is_err()
+        );
+        // duplicate
+        let duplicate = vec![day(1, 2), day(1, 2)];
+        assert!(
+            TimeSeries::from_timestamps(vec![1.0, 2.0], duplicate, Frequency::daily()).is_err()
+        );
+        // length mismatch
+        assert!(TimeSeries::from_timestamps(
+            vec![1.0],
+            vec![day(1, 1), day(1, 2)],
+            Frequency::daily()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn invalid_frequencies() {
+        let invalid = [
+            Frequency::Fixed(TimeDelta::zero()),
+            Frequency::Fixed(TimeDelta::days(-1)),
+            Frequency::Fixed(TimeDelta::days(365 * 300)),
+            Frequency::Months(0),
+        ];
+        for frequency in invalid.iter() {
+            assert!(TimeSeries::new(vec![1.0, 2.0], day(1, 1), *frequency).is_err());
+            let index = vec![day(1, 1), day(1, 2)];
+            assert!(TimeSeries::from_timestamps(vec![1.0, 2.0], index, *frequency).is_err());
+        }
+    }
+
+    #[test]
+    fn monthly_index_keeps_anchor() {
+        // month ends do not drift to the 28th/29th after February
+        let x = TimeSeries::new(vec![0.0; 13], day(1, 31), Frequency::monthly()).unwrap();
+        assert_eq!(x.index()[1], day(2, 29));
+        assert_eq!(x.index()[2], day(3, 31));
+        assert_eq!(x.index()[3], day(4, 30));
+        let index = x.index().to_vec();
+        let y = TimeSeries::from_timestamps(vec![0.0; 13], index, Frequency::monthly()).unwrap();
+        assert!(y.gaps().is_empty());
+    }
+
+    #[test]
+    fn align_intersects() {
+        let x = TimeSeries::from_timestamps(
+            vec![1.0, 2.0, 3.0],
+            vec![day(1, 1), day(1, 3), day(1, 4)],
+            Frequency::daily(),
+        )
+        .unwrap();
+        let y = TimeSeries::new(vec![10, 20, 30], day(1, 2), Frequency::daily()).unwrap();
+        let (a, b) = x.align(&y).unwrap();
+        assert_eq!(a.values(), &[2.0, 3.0]);
+        assert_eq!(b.values(), &[20, 30]);
+        assert_eq!(a.index(), &[day(1, 3), day(1, 4)]);
+
+        let z = TimeSeries::new(vec![1.0], day(1, 1), Frequency::weekly()).unwrap();
+        assert!(x.align(&z).is_err());
+    }
+
+    #[test]
+    fn dated_forecast() {
+        let x = TimeSeries::new(
+            vec![1.0, 1.2, 1.4, 1.6, 1.4, 1.2, 1.0, 1.1, 1.3, 1.2],
+            day(1, 1),
+            Frequency::hourly(),
+        )
+        .unwrap();
+        let m = model::fit(&x, 1, 0, 0).unwrap();
+        let forecast = m.forecast(3);
+        let dates = x.forecast_index(3);
+        assert_eq!(forecast.len(), dates.len());
+        assert_eq!(dates[0], day(1, 1) + chrono::TimeDelta::hours(10));
+        assert_eq!(dates[2], day(1, 1) + chrono::TimeDelta::hours(12));
+    }
+
+    #[test]
+    fn infer_fixed_frequencies() {
+        let start = day(3, 1);
+        for (delta, expected) in [
+            (
+                TimeDelta::minutes(15),
+                Frequency::Fixed(TimeDelta::minutes(15)),
+            ),
+            (TimeDelta::hours(1), Frequency::hourly()),
+            (TimeDelta::days(1), Frequency::daily()),
+            (TimeDelta::weeks(1), Frequency::weekly()),
+            (TimeDelta::days(28), Frequency::Fixed(TimeDelta::days(28))),
+        ] {
+            let index: Vec<NaiveDateTime> = (0..30).map(|k| start + delta * k).collect();
+            let f = timeseries::infer_frequency(&index).unwrap();
+            assert_eq!(f.frequency, expected);
+            assert!(f.is_regular());
+        }
+    }
+
+    #[test]
+    fn infer_calendar_frequencies() {
+        // month ends, clamped to the last day of shorter months
+        let index: Vec<NaiveDateTime> = (0..24)
+            .map(|k| Frequency::monthly().step(day(1, 31), k).unwrap())
+            .collect();
+        let f = timeseries::infer_frequency(&index).unwrap();
+        assert_eq!(f.frequency, Frequency::monthly());
+        assert!(f.is_regular());
+
+        let index: Vec<NaiveDateTime> = (0..8)
+            .map(|k| Frequency::yearly().step(day(2, 29), k).unwrap())
+            .collect();
+        let f = timeseries::infer_frequency(&index).unwrap();
+        assert_eq!(f.frequency, Frequency::yearly());
+        assert_eq!(f.frequency.seasonal_period(), 1);
+    }
+
+    #[test]
+    fn infer_irregularities() {
+        // business days: weekends are gaps, and one timestamp at noon
+        let mut index: Vec<NaiveDateTime> = (1..29)
+            .map(|d| day(4, d))
+            .filter(|t| t.weekday().number_from_monday() <= 5)
+            .collect();
+        index.insert(4, day(4, 4) + TimeDelta::hours(12));
+        let f = timeseries::infer_frequency(&index).unwrap();
+        assert_eq!(f.frequency, Frequency::daily());
+        assert_eq!(f.off_grid, vec![4]);
+        assert_eq!(f.gaps.len(), 3);
+        assert!(f.gaps.iter().all(|g| g.missing == 2));
+        assert!(!f.is_regular());
+        assert!(TimeSeries::from_timestamps_inferred(vec![0.0; index.len()], index).is_err());
+
+        assert!(timeseries::infer_frequency(&[day(1, 1)]).is_err());
+        assert!(timeseries::infer_frequency(&[day(1, 2), day(1, 1)]).is_err());
+    }
+
+    #[test]
+    fn seasonal_periods() {
+        assert_eq!(Frequency::daily().seasonal_periods(), vec![7.0, 365.25]);
+        assert_eq!(Frequency::daily().seasonal_period(), 7);
+        assert_eq!(Frequency::weekly().seasonal_period(), 52);
+        assert_eq!(Frequency::monthly().seasonal_period(), 12);
+        assert_eq!(
+            Frequency::Fixed(TimeDelta::minutes(30)).seasonal_periods(),
+            vec![2.0, 48.0, 336.0, 17532.0]
+        );
+        // divides neither a day nor a week
+        let periods = Frequency::Fixed(TimeDelta::hours(5)).seasonal_periods();
+        assert_eq!(periods.len(), 1);
+        assert!((periods[0] - 8766.0 / 5.0).abs() < 1.0e-9);
+        assert!(Frequency::Months(5).seasonal_periods().is_empty());
+
+        let index: Vec<NaiveDateTime> = (0..24)
+            .map(|k| Frequency::monthly().step(day(1, 1), k).unwrap())
+            .collect();
+        let x = TimeSeries::from_timestamps_inferred(vec![1.0; 24], index).unwrap();
+        assert_eq!(x.frequency(), Frequency::monthly());
+        assert_eq!(x.seasonal_period(), 12);
+        assert_eq!(x.seasonal_periods(), vec![12.0]);
+    }
+
+    #[test]
+    fn seasonal_entry_points() {
+        let pattern = [2.0, -1.0, 0.5, -1.5];
+        let values: Vec<f64> = (0..40)
+            .map(|t| 10.0 + 0.1 * t as f64 + pattern[t % 4] + 0.05 * (t as f64 * 1.3).sin())
+            .collect();
+        let x = TimeSeries::new(values.clone(), day(1, 1), Frequency::quarterly()).unwrap();
+
+        let m = ets::auto_ets_series(&x).unwrap();
+        assert_eq!(m.period, 4);
+        let d = x11::adjust_series(&x, x11::Mode::Additive).unwrap();
+        assert_eq!(
+            d.seasonal,
+            x11::adjust(&values, 4, x11::Mode::Additive)
+                .unwrap()
+                .seasonal
+        );
+        let h = regression::auto_harmonic_series(&x, 0, 0).unwrap();
+        assert_eq!(h.seasons[0].0, 4.0);
+        let t = tbats::auto_tbats_series(&x).unwrap();
+        assert_eq!(t.spec.seasons.len(), 1);
+
+        let train =
+            TimeSeries::new(values[..32].to_vec(), day(1, 1), Frequency::quarterly()).unwrap();
+        let fit = model::fit(&train, 1, 0, 0).unwrap();
+        let b = benchmark::benchmark_series(&fit, &train, &values[32..]).unwrap();
+        assert!(b
+            .baselines
+            .iter()
+            .any(|(m, _)| *m == benchmark::Baseline::SeasonalNaive));
+        let s = signal::extract_series(&fit, &train).unwrap();
+        assert_eq!(s.trend.estimate.len(), 32);
+        assert!(signal::extract_series(&fit, &x).is_err());
+
+        // yearly data has no seasonal period
+        let yearly = TimeSeries::new(values.clone(), day(1, 1), Frequency::yearly()).unwrap();
+        assert_eq!(ets::auto_ets_series(&yearly).unwrap().period, 1);
+        assert!(x11::adjust_series(&yearly, x11::Mode::Additive).is_err());
+        let b = benchmark::benchmark_series(&fit, &yearly, &values[32..]).unwrap();
+        assert_eq!(b.baselines.len(), 3);
+
+        // gaps are rejected
+        let index: Vec<NaiveDateTime> = (0..41)
+            .filter(|k| *k != 20)
+            .map(|k| Frequency::quarterly().step(day(1, 1), k).unwrap())
+            .collect();
+        let gappy = TimeSeries::from_timestamps(values, index, Frequency::quarterly()).unwrap();
+        assert!(gappy.regular_values().is_err());
+        assert!(ets::auto_ets_series(&gappy).is_err());
+        assert!(x11::adjust_series(&gappy, x11::Mode::Additive).is_err());
+        assert!(tbats::auto_tbats_series(&gappy).is_err());
+    }
+}
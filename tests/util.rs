@@ -25,7 +25,7 @@ mod test_util {
 
     #[test]
     fn diff_1_f64() {
-        let x = [
+        let x: [f64; 10] = [
             4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
             8.6400979, 0.7207072, 6.6751565,
         ];
@@ -45,13 +45,13 @@ mod test_util {
         assert_eq!(x_diff.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_diff[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_diff[i] - y[i]).abs(), 1.0e-7);
         }
     }
 
     #[test]
     fn diff_2_f64() {
-        let x = [
+        let x: [f64; 10] = [
             4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
             8.6400979, 0.7207072, 6.6751565,
         ];
@@ -70,13 +70,13 @@ mod test_util {
         assert_eq!(x_diff.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_diff[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_diff[i] - y[i]).abs(), 1.0e-7);
         }
     }
 
     #[test]
     fn diff_log_f64() {
-        let x = [
+        let x: [f64; 10] = [
             9.9902684, 4.3772393, 1.8550282, 9.7252195, 2.8445105, 0.2348111, 7.6587723, 8.9285881,
             7.6012410, 3.6073980,
         ];
@@ -89,7 +89,7 @@ mod test_util {
         assert_eq!(x_diff.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_diff[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_diff[i] - y[i]).abs(), 1.0e-7);
         }
     }
 
@@ -105,7 +105,7 @@ mod test_util {
 
     #[test]
     fn cumsum_f64() {
-        let x = [
+        let x: [f64; 10] = [
             4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
             8.6400979, 0.7207072, 6.6751565,
         ];
@@ -126,7 +126,7 @@ mod test_util {
         assert_eq!(x_cumsum.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_cumsum[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_cumsum[i] - y[i]).abs(), 1.0e-7);
         }
     }
 
@@ -157,7 +157,7 @@ mod test_util {
 
     #[test]
     fn diffinv_1_f64() {
-        let x = [
+        let x: [f64; 10] = [
             4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
             8.6400979, 0.7207072, 6.6751565,
         ];
@@ -179,20 +179,20 @@ mod test_util {
         assert_eq!(x_diffinv.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_diffinv[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_diffinv[i] - y[i]).abs(), 1.0e-7);
         }
 
         // check backwards
         let z = arima::util::diff(&x_diffinv, 1);
 
         for i in 0..z.len() {
-            assert_lt!((z[i] - x[i] as f64).abs(), 1.0e-7);
+            assert_lt!((z[i] - x[i]).abs(), 1.0e-7);
         }
     }
 
     #[test]
     fn diffinv_2_f64() {
-        let x = [
+        let x: [f64; 10] = [
             4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
             8.6400979, 0.7207072, 6.6751565,
         ];
@@ -215,7 +215,52 @@ mod test_util {
         assert_eq!(x_diffinv.len(), y.len());
 
         for i in 0..y.len() {
-            assert_lt!((x_diffinv[i] - y[i] as f64).abs(), 1.0e-7);
+            assert_lt!((x_diffinv[i] - y[i]).abs(), 1.0e-7);
+        }
+    }
+
+    #[test]
+    fn mean_iter_i32() {
+        assert_eq!(arima::util::mean_iter(vec![2, 3, 4, 5, 6]), Some(4));
+    }
+
+    #[test]
+    fn mean_iter_invalid_count() {
+        assert_eq!(arima::util::mean_iter(Vec::<i32>::new()), None);
+        assert_eq!(arima::util::mean_iter(Vec::<f64>::new()), None);
+        // 200 is not representable as i8
+        assert_eq!(arima::util::mean_iter(vec![0_i8; 200]), None);
+        assert_eq!(arima::util::mean_iter(vec![1_i8; 100]), Some(1));
+    }
+
+    #[test]
+    fn mean_iter_f32() {
+        let m = arima::util::mean_iter((0..1000).map(|v| v as f32)).unwrap();
+        assert_lt!((m - 499.5).abs(), 1.0e-3);
+    }
+
+    #[test]
+    fn mean_var_iter_f64() {
+        let x: [f64; 10] = [
+            4.1341055, 4.5212322, -9.1234667, -1.3249472, -8.9102578, -7.5955399, -1.8054393,
+            8.6400979, 0.7207072, 6.6751565,
+        ];
+        let (m, v) = arima::util::mean_var_iter(x.iter().copied());
+        let cov0 = arima::acf::acf(&x, Some(0), true).unwrap()[0];
+
+        assert_lt!((m - arima::util::mean(&x)).abs(), 1.0e-12);
+        assert_lt!((v - cov0).abs(), 1.0e-12);
+
+        let (m, v) = arima::util::mean_var_iter(std::iter::empty::<f64>());
+        assert!(m.is_nan() && v.is_nan());
+    }
+
+    #[test]
+    fn diff_iter_i32() {
+        let x = [-4, -9, 20, 23, -18, 6];
+        for d in 0..4 {
+            let y: Vec<i32> = arima::util::diff_iter(x.iter().copied(), d).collect();
+            assert_eq!(y, arima::util::diff(&x, d));
         }
     }
 }
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test_varma {
+    use arima::sim;
+    use arima::varma;
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    // bivariate VARMA(1, 1) with known coefficients
+    const A: [f64; 4] = [0.5, 0.1, -0.2, 0.4];
+    const M: [f64; 4] = [0.3, 0.0, 0.1, -0.2];
+
+    fn simulate(n: usize) -> Vec<Vec<f64>> {
+        let mut rng = sim::stream_rng(21, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut y = vec![vec![0.0; n]; 2];
+        let mut e_prev = [0.0, 0.0];
+        let mut y_prev = [0.0, 0.0];
+        for t in 0..n + 100 {
+            let e = [normal.sample(&mut rng), normal.sample(&mut rng)];
+            let mut cur = [1.0, -0.5];
+            for r in 0..2 {
+                for s in 0..2 {
+                    cur[r] += A[r * 2 + s] * y_prev[s] + M[r * 2 + s] * e_prev[s];
+                }
+                cur[r] += e[r];
+            }
+            if t >= 100 {
+                y[0][t - 100] = cur[0];
+                y[1][t - 100] = cur[1];
+            }
+            y_prev = cur;
+            e_prev = e;
+        }
+        y
+    }
+
+    #[test]
+    fn var_least_squares() {
+        let y = simulate(3000);
+        let m = varma::fit(&y, 1, 0).unwrap();
+        assert_eq!(m.iterations, 0);
+        assert!(m.converged);
+        assert!(m.ma.is_empty());
+        // a VAR(1) fitted to VARMA data is biased, but the residual variance is close
+        assert_lt!((m.sigma[0] - 1.0).abs(), 0.2);
+    }
+
+    #[test]
+    fn varma_coefficients() {
+        let y = simulate(5000);
+        let m = varma::fit(&y, 1, 1).unwrap();
+        assert!(m.converged);
+        assert_lt!(m.iterations, varma::VARMA_MAX_ITERATIONS);
+        for i in 0..4 {
+            assert_lt!((m.ar[0][i] - A[i]).abs(), 0.1);
+            assert_lt!((m.ma[0][i] - M[i]).abs(), 0.1);
+        }
+        assert_lt!((m.sigma[0] - 1.0).abs(), 0.1);
+        assert_lt!(m.sigma[1].abs(), 0.1);
+        assert_lt!((m.sigma[3] - 1.0).abs(), 0.1);
+
+        // long-run forecasts converge to the process mean (I - A)^-1 c
+        let f = m.forecast(200);
+        let det = (1.0 - A[0]) * (1.0 - A[3]) - A[1] * A[2];
+        let mean0 = ((1.0 - A[3]) * 1.0 + A[1] * -0.5) / det;
+        assert_lt!((f[0][199] - mean0).abs(), 0.3);
+
+        let var = varma::fit(&y, 1, 0).unwrap();
+        assert_lt!(m.aic(), var.aic());
+    }
+
+    #[test]
+    fn invalid_input() {
+        let empty: [Vec<f64>; 0] = [];
+        assert!(varma::fit(&empty, 1, 0).is_err());
+        assert!(varma::fit(&[vec![1.0, 2.0, 3.0], vec![1.0, 2.0]], 1, 0).is_err());
+        assert!(varma::fit(&[vec![1.0, 2.0, 3.0], vec![1.0, 2.0, 3.0]], 1, 1).is_err());
+    }
+}
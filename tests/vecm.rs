@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod test_vecm {
+    use arima::sim;
+    use arima::vecm;
+    use more_asserts::{assert_gt, assert_lt};
+    use rand_distr::{Distribution, Normal};
+
+    // dy_t = alpha beta' y_{t-1} + e_t with alpha = (-0.2, 0.1), beta = (1, -0.5)
+    fn simulate(n: usize) -> Vec<Vec<f64>> {
+        let mut rng = sim::stream_rng(31, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let mut y = vec![Vec::with_capacity(n), Vec::with_capacity(n)];
+        let (mut a, mut b) = (0.0, 0.0);
+        for _ in 0..n {
+            let ect = a - 0.5 * b;
+            a += -0.2 * ect + normal.sample(&mut rng);
+            b += 0.1 * ect + normal.sample(&mut rng);
+            y[0].push(a);
+            y[1].push(b);
+        }
+        y
+    }
+
+    #[test]
+    fn johansen_rank() {
+        let y = simulate(1000);
+        let j = vecm::johansen(&y, 1).unwrap();
+        assert_eq!(j.rank, 1);
+        assert_gt!(j.trace[0], j.trace_crit[0]);
+        assert_lt!(j.trace[1], j.trace_crit[1]);
+        assert_gt!(j.eigenvalues[0], j.eigenvalues[1]);
+        assert_eq!(j.max_eig[1], j.trace[1]);
+
+        // three independent random walks are not cointegrated
+        let mut rng = sim::stream_rng(32, 0);
+        let normal = Normal::new(0.0, 1.0).unwrap();
+        let walks: Vec<Vec<f64>> = (0..3)
+            .map(|_| {
+                let mut s = 0.0;
+                (0..500)
+                    .map(|_| {
+                        s += normal.sample(&mut rng);
+                        s
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(vecm::johansen(&walks, 2).unwrap().rank, 0);
+    }
+
+    #[test]
+    fn vecm_long_run_matrix() {
+        let y = simulate(2000);
+        let m = vecm::fit(&y, 1, None).unwrap();
+        assert_eq!(m.rank, 1);
+        assert!(m.gamma.is_empty());
+        let pi = [-0.2, 0.1, 0.1, -0.05];
+        for (estimate, truth) in m.pi.iter().zip(&pi) {
+            assert_lt!((estimate - truth).abs(), 0.05);
+        }
+        assert_lt!((m.sigma[0] - 1.0).abs(), 0.1);
+    }
+
+    #[test]
+    fn var_representation_and_forecast() {
+        let y = simulate(500);
+        let m = vecm::fit(&y, 3, Some(1)).unwrap();
+        assert_eq!(m.gamma.len(), 2);
+        let a = m.var_coefficients();
+        assert_eq!(a.len(), 3);
+        // the VAR coefficients sum to I + pi
+        for i in 0..2 {
+            for j in 0..2 {
+                let sum: f64 = a.iter().map(|ai| ai[i * 2 + j]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 } + m.pi[i * 2 + j];
+                assert_lt!((sum - expected).abs(), 1.0e-12);
+            }
+        }
+        let f = m.forecast(100);
+        // forecasts stay close to the long-run equilibrium a = 0.5 b
+        assert_lt!((f[0][99] - 0.5 * f[1][99]).abs(), 2.0);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let y = simulate(100);
+        assert!(vecm::fit(&y, 0, None).is_err());
+        assert!(vecm::fit(&y, 1, Some(3)).is_err());
+        assert!(vecm::johansen(&[vec![1.0, 2.0, 3.0]], 2).is_err());
+    }
+}
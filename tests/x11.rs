@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test_x11 {
+    use arima::{sim, x11};
+    use more_asserts::assert_lt;
+    use rand_distr::{Distribution, Normal};
+
+    const PATTERN: [f64; 12] = [
+        -3.0, -2.0, -0.5, 1.0, 2.0, 3.5, 4.0, 2.5, 0.5, -1.5, -2.5, -3.0,
+    ];
+
+    fn monthly(seed: u64) -> Vec<f64> {
+        let mut rng = sim::stream_rng(seed, 0);
+        let noise = Normal::new(0.0, 0.3).unwrap();
+        (0..120)
+            .map(|t| 50.0 + 0.2 * t as f64 + PATTERN[t % 12] + noise.sample(&mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn additive_recovers_pattern() {
+        let x = monthly(81);
+        let d = x11::adjust(&x, 12, x11::Mode::Additive).unwrap();
+        assert_eq!(d.adjusted.len(), x.len());
+        for t in 12..108 {
+            assert_lt!((d.seasonal[t] - PATTERN[t % 12]).abs(), 0.4);
+            assert_lt!((d.adjusted[t] - d.trend[t] - d.irregular[t]).abs(), 1.0e-9);
+            assert_lt!((d.trend[t] - 50.0 - 0.2 * t as f64).abs(), 0.4);
+        }
+        // the factors of a year sum to about zero
+        let year: f64 = d.seasonal[48..60].iter().sum();
+        assert_lt!(year.abs(), 0.1);
+    }
+
+    #[test]
+    fn extreme_value_is_replaced() {
+        let mut x = monthly(82);
+        x[65] += 15.0;
+        let d = x11::adjust(&x, 12, x11::Mode::Additive).unwrap();
+        assert_eq!(d.weights[65], 0.0);
+        assert_lt!((d.seasonal[65] - PATTERN[65 % 12]).abs(), 0.5);
+        // the outlier stays in the adjusted series as an irregular
+        assert_lt!(10.0, d.irregular[65]);
+        let full = d.weights.iter().filter(|w| **w == 1.0).count();
+        assert_lt!(100, full);
+    }
+
+    #[test]
+    fn multiplicative_factors() {
+        let mut rng = sim::stream_rng(83, 0);
+        let noise = Normal::new(0.0, 0.01).unwrap();
+        let factors = [0.8, 1.1, 1.3, 0.8];
+        let x: Vec<f64> = (0..48)
+            .map(|t| (100.0 + 2.0 * t as f64) * factors[t % 4] * (1.0 + noise.sample(&mut rng)))
+            .collect();
+        let d = x11::adjust(&x, 4, x11::Mode::Multiplicative).unwrap();
+        for (s, f) in d.last_factors().iter().zip(&factors) {
+            assert_lt!((s - f).abs(), 0.03);
+        }
+        for (t, v) in x.iter().enumerate() {
+            assert_lt!((d.seasonal[t] * d.adjusted[t] - v).abs(), 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let x = monthly(84);
+        assert!(x11::adjust(&x[..30], 12, x11::Mode::Additive).is_err());
+        assert!(x11::adjust(&x, 1, x11::Mode::Additive).is_err());
+        let mut y = x.clone();
+        y[3] = -1.0;
+        assert!(x11::adjust(&y, 12, x11::Mode::Multiplicative).is_err());
+        y[3] = f64::NAN;
+        assert!(x11::adjust(&y, 12, x11::Mode::Additive).is_err());
+    }
+
+    #[test]
+    fn henderson_weights() {
+        let w = x11::henderson_weights(13);
+        assert_eq!(w.len(), 13);
+        assert_lt!((w.iter().sum::<f64>() - 1.0).abs(), 1.0e-12);
+        assert_lt!((w[6] - 0.240058).abs(), 1.0e-6);
+        assert_lt!((w[0] + 0.019350).abs(), 1.0e-6);
+    }
+}